@@ -0,0 +1,350 @@
+use crate::{ConnectionsSnapshot, ControllerConnection, CoreState, now_unix_seconds};
+use chrono::DateTime;
+use std::cmp::Reverse;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Closed connections kept for inspection, newest first.
+const CLOSED_HISTORY_LEN: usize = 200;
+
+/// A connection of the kernel, open or recently closed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrackedConnection {
+    pub connection: ControllerConnection,
+    /// Unix seconds, parsed from the kernel's start time.
+    pub started_at: Option<u64>,
+    /// Unix seconds; when it was first seen gone for closed connections.
+    pub closed_at: Option<u64>,
+}
+
+impl TrackedConnection {
+    fn new(connection: ControllerConnection) -> Self {
+        let started_at = DateTime::parse_from_rfc3339(&connection.start)
+            .ok()
+            .and_then(|start| u64::try_from(start.timestamp()).ok());
+        Self {
+            connection,
+            started_at,
+            closed_at: None,
+        }
+    }
+
+    /// Host name, or the destination IP when the kernel saw none, with the
+    /// port.
+    pub fn target(&self) -> String {
+        let metadata = &self.connection.metadata;
+        let host = if metadata.host.is_empty() {
+            metadata.destination_ip.as_str()
+        } else {
+            metadata.host.as_str()
+        };
+        if metadata.destination_port.is_empty() {
+            host.to_string()
+        } else if host.contains(':') {
+            format!("[{host}]:{}", metadata.destination_port)
+        } else {
+            format!("{host}:{}", metadata.destination_port)
+        }
+    }
+
+    /// Seconds the connection has been open, or was open once closed.
+    pub fn duration_secs(&self, now: u64) -> Option<u64> {
+        let end = self.closed_at.unwrap_or(now);
+        Some(end.saturating_sub(self.started_at?))
+    }
+
+    /// Case-insensitive search over target, process, rule and chain.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_ascii_lowercase();
+        if query.is_empty() {
+            return true;
+        }
+        let connection = &self.connection;
+        let metadata = &connection.metadata;
+        [
+            self.target().as_str(),
+            metadata.process.as_str(),
+            metadata.source_ip.as_str(),
+            metadata.network.as_str(),
+            connection.rule.as_str(),
+            connection.rule_payload.as_str(),
+        ]
+        .into_iter()
+        .chain(connection.chains.iter().map(String::as_str))
+        .any(|field| field.to_ascii_lowercase().contains(&query))
+    }
+}
+
+/// Orders of the connections list.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConnectionSort {
+    #[default]
+    Newest,
+    Download,
+    Upload,
+    Host,
+}
+
+impl ConnectionSort {
+    pub const ALL: [ConnectionSort; 4] = [
+        ConnectionSort::Newest,
+        ConnectionSort::Download,
+        ConnectionSort::Upload,
+        ConnectionSort::Host,
+    ];
+
+    pub fn sort(self, connections: &mut [TrackedConnection]) {
+        match self {
+            Self::Newest => connections.sort_by_key(|tracked| Reverse(tracked.started_at)),
+            Self::Download => {
+                connections.sort_by_key(|tracked| Reverse(tracked.connection.download))
+            }
+            Self::Upload => connections.sort_by_key(|tracked| Reverse(tracked.connection.upload)),
+            Self::Host => connections.sort_by_cached_key(TrackedConnection::target),
+        }
+    }
+}
+
+/// Connections of the kernel, see [`crate::Core::connections`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConnectionsView {
+    /// Whether the last poll of the controller succeeded.
+    pub connected: bool,
+    pub active: Vec<TrackedConnection>,
+    /// Newest first.
+    pub closed: Vec<TrackedConnection>,
+    pub upload_total: u64,
+    pub download_total: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ConnectionTracker {
+    active: Vec<TrackedConnection>,
+    closed: VecDeque<TrackedConnection>,
+    upload_total: u64,
+    download_total: u64,
+    connected: bool,
+}
+
+impl ConnectionTracker {
+    fn update(&mut self, snapshot: ConnectionsSnapshot, now: u64) {
+        let open = snapshot
+            .connections
+            .iter()
+            .map(|connection| connection.id.as_str())
+            .collect::<HashSet<_>>();
+        let gone = std::mem::take(&mut self.active)
+            .into_iter()
+            .filter(|tracked| !open.contains(tracked.connection.id.as_str()))
+            .collect();
+        self.retire(gone, now);
+
+        self.active = snapshot
+            .connections
+            .into_iter()
+            .map(TrackedConnection::new)
+            .collect();
+        self.upload_total = snapshot.upload_total;
+        self.download_total = snapshot.download_total;
+        self.connected = true;
+    }
+
+    /// Moves `id`, or every connection for `None`, to the closed history.
+    pub fn mark_closed(&mut self, id: Option<&str>, now: u64) {
+        let (closed, active) = std::mem::take(&mut self.active)
+            .into_iter()
+            .partition::<Vec<_>, _>(|tracked| id.is_none_or(|id| tracked.connection.id == id));
+        self.active = active;
+        self.retire(closed, now);
+    }
+
+    fn disconnect(&mut self, now: u64) {
+        self.mark_closed(None, now);
+        self.connected = false;
+    }
+
+    fn retire(&mut self, connections: Vec<TrackedConnection>, now: u64) {
+        for mut tracked in connections {
+            tracked.closed_at = Some(now);
+            if self.closed.len() == CLOSED_HISTORY_LEN {
+                self.closed.pop_back();
+            }
+            self.closed.push_front(tracked);
+        }
+    }
+
+    pub fn snapshot(&self) -> ConnectionsView {
+        ConnectionsView {
+            connected: self.connected,
+            active: self.active.clone(),
+            closed: self.closed.iter().cloned().collect(),
+            upload_total: self.upload_total,
+            download_total: self.download_total,
+        }
+    }
+}
+
+/// Polls the controller's connections until the owning [`crate::Core`] is
+/// dropped.
+pub(crate) fn spawn(inner: Weak<Mutex<CoreState>>) {
+    let spawned = thread::Builder::new()
+        .name("linkpad-connections".to_string())
+        .spawn(move || {
+            info!("connection tracker started");
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                let controller = {
+                    let Some(inner) = inner.upgrade() else {
+                        break;
+                    };
+                    let state = inner.lock().expect("core state poisoned");
+                    state.controller.clone()
+                };
+                let snapshot = controller.map(|controller| controller.connections());
+
+                let Some(inner) = inner.upgrade() else {
+                    break;
+                };
+                let mut state = inner.lock().expect("core state poisoned");
+                let tracker = &mut state.connections;
+                match snapshot {
+                    Some(Ok(snapshot)) => tracker.update(snapshot, now_unix_seconds()),
+                    Some(Err(error)) => {
+                        if tracker.connected {
+                            warn!("connections poll failed: {error}");
+                        }
+                        tracker.disconnect(now_unix_seconds());
+                    }
+                    None => tracker.disconnect(now_unix_seconds()),
+                }
+            }
+            info!("connection tracker stopped");
+        });
+    if let Err(error) = spawned {
+        warn!("failed to start connection tracker: {error}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConnectionMetadata;
+
+    fn connection(id: &str, host: &str, download: u64, start: &str) -> ControllerConnection {
+        ControllerConnection {
+            id: id.to_string(),
+            metadata: ConnectionMetadata {
+                host: host.to_string(),
+                destination_ip: "93.184.216.34".to_string(),
+                destination_port: "443".to_string(),
+                process: "curl".to_string(),
+                ..Default::default()
+            },
+            download,
+            start: start.to_string(),
+            chains: vec!["node-a".to_string(), "Proxy".to_string()],
+            rule: "DomainSuffix".to_string(),
+            rule_payload: "example.com".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// 2026-01-01T00:00:00Z.
+    const START: u64 = 1_767_225_600;
+
+    fn snapshot(connections: Vec<ControllerConnection>) -> ConnectionsSnapshot {
+        ConnectionsSnapshot {
+            connections,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn moves_vanished_connections_to_history() {
+        let mut tracker = ConnectionTracker::default();
+        tracker.update(
+            snapshot(vec![
+                connection("a", "example.com", 10, "2026-01-01T00:00:00Z"),
+                connection("b", "", 20, "2026-01-01T00:00:05Z"),
+            ]),
+            START + 10,
+        );
+        tracker.update(
+            snapshot(vec![connection(
+                "a",
+                "example.com",
+                30,
+                "2026-01-01T00:00:00Z",
+            )]),
+            START + 15,
+        );
+
+        let view = tracker.snapshot();
+        assert!(view.connected);
+        assert_eq!(view.active[0].connection.download, 30);
+        assert_eq!(view.closed.len(), 1);
+        assert_eq!(view.closed[0].connection.id, "b");
+        assert_eq!(view.closed[0].target(), "93.184.216.34:443");
+        assert_eq!(view.closed[0].duration_secs(u64::MAX), Some(10));
+        assert_eq!(view.active[0].duration_secs(START + 20), Some(20));
+
+        tracker.mark_closed(Some("a"), START + 20);
+        tracker.disconnect(START + 25);
+        let view = tracker.snapshot();
+        assert!(!view.connected && view.active.is_empty());
+        assert_eq!(
+            view.closed
+                .iter()
+                .map(|tracked| (tracked.connection.id.as_str(), tracked.closed_at))
+                .collect::<Vec<_>>(),
+            vec![("a", Some(START + 20)), ("b", Some(START + 15))]
+        );
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut tracker = ConnectionTracker::default();
+        for index in 0..CLOSED_HISTORY_LEN + 5 {
+            let id = index.to_string();
+            tracker.update(snapshot(vec![connection(&id, "a.test", 0, "")]), 0);
+        }
+        tracker.disconnect(1);
+        let view = tracker.snapshot();
+        assert_eq!(view.closed.len(), CLOSED_HISTORY_LEN);
+        assert_eq!(
+            view.closed[0].connection.id,
+            (CLOSED_HISTORY_LEN + 4).to_string()
+        );
+    }
+
+    #[test]
+    fn filters_and_sorts() {
+        let mut connections = vec![
+            TrackedConnection::new(connection("1", "b.test", 5, "2026-01-01T00:00:00Z")),
+            TrackedConnection::new(connection("2", "a.test", 50, "2026-01-01T00:01:00Z")),
+            TrackedConnection::new(connection("3", "c.test", 1, "2026-01-01T00:00:30Z")),
+        ];
+        assert!(connections[0].matches("B.TEST"));
+        assert!(connections[0].matches("curl"));
+        assert!(connections[0].matches("node-a"));
+        assert!(connections[0].matches("  "));
+        assert!(!connections[0].matches("firefox"));
+
+        let ids = |connections: &[TrackedConnection]| {
+            connections
+                .iter()
+                .map(|tracked| tracked.connection.id.clone())
+                .collect::<Vec<_>>()
+        };
+        ConnectionSort::Newest.sort(&mut connections);
+        assert_eq!(ids(&connections), ["2", "3", "1"]);
+        ConnectionSort::Download.sort(&mut connections);
+        assert_eq!(ids(&connections), ["2", "1", "3"]);
+        ConnectionSort::Host.sort(&mut connections);
+        assert_eq!(ids(&connections), ["2", "1", "3"]);
+    }
+}
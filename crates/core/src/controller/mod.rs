@@ -0,0 +1,710 @@
+//! Client for the mihomo external controller (RESTful API).
+
+mod types;
+
+use crate::{CoreError, CoreResult};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use reqwest::Method;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::CONTENT_TYPE;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{BufRead, BufReader};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use types::{
+    ConfigSource, DelayResponse, GroupsResponse, ProvidersResponse, ProxiesResponse, RulesResponse,
+    SelectProxyRequest,
+};
+pub use types::{
+    ConnectionMetadata, ConnectionsSnapshot, ControllerConfigs, ControllerConfigsPatch,
+    ControllerConnection, ControllerMemory, ControllerProxy, ControllerRule, ControllerTraffic,
+    ControllerVersion, DelayHistory, DnsAnswer, DnsQueryResponse, DnsQuestion, ProxyProviderInfo,
+    RuleProviderInfo,
+};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Extra time on top of a delay test's own timeout for the kernel to answer.
+const DELAY_REQUEST_MARGIN: Duration = Duration::from_secs(4);
+
+pub type ControllerResult<T> = Result<T, ControllerError>;
+
+/// Failure of a controller request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ControllerError {
+    /// The request could not be sent or the response not read.
+    Transport(String),
+    /// The controller answered with a non-success status.
+    Status { status: u16, body: String },
+    /// A body could not be encoded, or the response did not have the
+    /// expected shape.
+    Decode(String),
+}
+
+impl fmt::Display for ControllerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(msg) => write!(f, "controller unreachable: {msg}"),
+            Self::Status { status, body } if body.trim().is_empty() => {
+                write!(f, "controller request failed: {status}")
+            }
+            Self::Status { status, body } => {
+                write!(f, "controller request failed: {status} {}", body.trim())
+            }
+            Self::Decode(msg) => write!(f, "invalid controller response: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ControllerError {}
+
+/// Typed access to one controller endpoint. Cloning is cheap and clones share
+/// the underlying connection pool.
+#[derive(Clone, Debug)]
+pub struct ControllerClient {
+    base_url: String,
+    secret: Option<String>,
+    http: Client,
+    /// Same as `http` without the overall timeout, for endpoints that never
+    /// finish their response.
+    stream_http: Client,
+}
+
+impl ControllerClient {
+    /// `address` is the `external-controller` value, with or without a scheme.
+    pub fn new(address: &str, secret: Option<String>) -> CoreResult<Self> {
+        let http = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|error| CoreError::Network(error.to_string()))?;
+        let stream_http = Client::builder()
+            .connect_timeout(REQUEST_TIMEOUT)
+            .timeout(None)
+            .build()
+            .map_err(|error| CoreError::Network(error.to_string()))?;
+        Ok(Self {
+            base_url: normalize_base_url(address)?,
+            secret: secret.filter(|secret| !secret.trim().is_empty()),
+            http,
+            stream_http,
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn version(&self) -> ControllerResult<ControllerVersion> {
+        self.get_json("/version")
+    }
+
+    pub fn configs(&self) -> ControllerResult<ControllerConfigs> {
+        self.get_json("/configs")
+    }
+
+    pub fn patch_configs(&self, patch: &ControllerConfigsPatch) -> ControllerResult<()> {
+        let request = with_json(self.request(Method::PATCH, "/configs"), patch)?;
+        self.send_empty(request)
+    }
+
+    /// Makes the kernel load `path`, or its current config file when empty.
+    pub fn reload_configs(&self, path: &str, force: bool) -> ControllerResult<()> {
+        let request = self
+            .request(Method::PUT, "/configs")
+            .query(&[("force", force)]);
+        let request = with_json(request, &ConfigSource { path, payload: "" })?;
+        self.send_empty(request)
+    }
+
+    /// Mode of the running kernel.
+    pub fn mode(&self) -> ControllerResult<crate::ProxyMode> {
+        let configs = self.configs()?;
+        configs
+            .proxy_mode()
+            .ok_or_else(|| ControllerError::Decode(format!("invalid mode `{}`", configs.mode)))
+    }
+
+    /// Every proxy and group by name.
+    pub fn proxies(&self) -> ControllerResult<BTreeMap<String, ControllerProxy>> {
+        self.get_json::<ProxiesResponse>("/proxies")
+            .map(|response| response.proxies)
+    }
+
+    pub fn proxy(&self, name: &str) -> ControllerResult<ControllerProxy> {
+        self.get_json(&format!("/proxies/{}", encode(name)))
+    }
+
+    /// Current selection of every group whose selection is one of its members.
+    pub fn group_selections(&self) -> ControllerResult<BTreeMap<String, String>> {
+        Ok(self
+            .proxies()?
+            .into_iter()
+            .filter_map(|(group, proxy)| {
+                let now = proxy.now.trim();
+                let valid =
+                    !now.is_empty() && (proxy.all.is_empty() || proxy.all.iter().any(|m| m == now));
+                valid.then(|| (group, now.to_string()))
+            })
+            .collect())
+    }
+
+    pub fn select_proxy(&self, group: &str, proxy: &str) -> ControllerResult<()> {
+        let request = with_json(
+            self.request(Method::PUT, &format!("/proxies/{}", encode(group))),
+            &SelectProxyRequest { name: proxy },
+        )?;
+        self.send_empty(request)
+    }
+
+    /// Latency of `proxy` in milliseconds, `None` when the test timed out.
+    pub fn proxy_delay(
+        &self,
+        proxy: &str,
+        url: &str,
+        timeout_ms: u32,
+    ) -> ControllerResult<Option<u32>> {
+        let request = self
+            .request(Method::GET, &format!("/proxies/{}/delay", encode(proxy)))
+            .query(&[("url", url), ("timeout", &timeout_ms.to_string())])
+            .timeout(Duration::from_millis(u64::from(timeout_ms)) + DELAY_REQUEST_MARGIN);
+        let response: DelayResponse = self.send_json(request)?;
+        if response.delay < 0 {
+            return Ok(None);
+        }
+        u32::try_from(response.delay)
+            .map(Some)
+            .map_err(|_| ControllerError::Decode(format!("invalid delay {}", response.delay)))
+    }
+
+    /// Proxy groups in the order of the config.
+    pub fn groups(&self) -> ControllerResult<Vec<ControllerProxy>> {
+        self.get_json::<GroupsResponse>("/group")
+            .map(|response| response.proxies)
+    }
+
+    /// Tests every member of `group`, returning the latency of those that
+    /// answered.
+    pub fn group_delay(
+        &self,
+        group: &str,
+        url: &str,
+        timeout_ms: u32,
+    ) -> ControllerResult<BTreeMap<String, u32>> {
+        let request = self
+            .request(Method::GET, &format!("/group/{}/delay", encode(group)))
+            .query(&[("url", url), ("timeout", &timeout_ms.to_string())])
+            .timeout(Duration::from_millis(u64::from(timeout_ms)) + DELAY_REQUEST_MARGIN);
+        self.send_json(request)
+    }
+
+    pub fn proxy_providers(&self) -> ControllerResult<BTreeMap<String, ProxyProviderInfo>> {
+        self.get_json::<ProvidersResponse<_>>("/providers/proxies")
+            .map(|response| response.providers)
+    }
+
+    pub fn update_proxy_provider(&self, name: &str) -> ControllerResult<()> {
+        let path = format!("/providers/proxies/{}", encode(name));
+        self.send_empty(self.request(Method::PUT, &path))
+    }
+
+    pub fn rule_providers(&self) -> ControllerResult<BTreeMap<String, RuleProviderInfo>> {
+        self.get_json::<ProvidersResponse<_>>("/providers/rules")
+            .map(|response| response.providers)
+    }
+
+    pub fn update_rule_provider(&self, name: &str) -> ControllerResult<()> {
+        let path = format!("/providers/rules/{}", encode(name));
+        self.send_empty(self.request(Method::PUT, &path))
+    }
+
+    pub fn rules(&self) -> ControllerResult<Vec<ControllerRule>> {
+        self.get_json::<RulesResponse>("/rules")
+            .map(|response| response.rules)
+    }
+
+    pub fn connections(&self) -> ControllerResult<ConnectionsSnapshot> {
+        self.get_json("/connections")
+    }
+
+    pub fn close_connection(&self, id: &str) -> ControllerResult<()> {
+        let path = format!("/connections/{}", encode(id));
+        self.send_empty(self.request(Method::DELETE, &path))
+    }
+
+    pub fn close_all_connections(&self) -> ControllerResult<()> {
+        self.send_empty(self.request(Method::DELETE, "/connections"))
+    }
+
+    /// Resolves `name` through the kernel's DNS; `record_type` is e.g. `A`.
+    pub fn dns_query(&self, name: &str, record_type: &str) -> ControllerResult<DnsQueryResponse> {
+        let request = self
+            .request(Method::GET, "/dns/query")
+            .query(&[("name", name), ("type", record_type)]);
+        self.send_json(request)
+    }
+
+    /// Restarts the kernel process in place with its current config.
+    pub fn restart(&self) -> ControllerResult<()> {
+        let request = with_json(
+            self.request(Method::POST, "/restart"),
+            &ConfigSource::default(),
+        )?;
+        self.send_empty(request)
+    }
+
+    /// Upload and download rates, one sample per second.
+    pub fn traffic(&self) -> ControllerResult<ControllerStream<ControllerTraffic>> {
+        self.open_stream("/traffic")
+    }
+
+    /// Memory used by the kernel, one sample per second.
+    pub fn memory(&self) -> ControllerResult<ControllerStream<ControllerMemory>> {
+        self.open_stream("/memory")
+    }
+
+    fn open_stream<T: DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> ControllerResult<ControllerStream<T>> {
+        let request = self.authorize(self.stream_http.get(format!("{}{path}", self.base_url)));
+        let response = request
+            .send()
+            .map_err(|error| ControllerError::Transport(error.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ControllerError::Status {
+                status: status.as_u16(),
+                body: response.text().unwrap_or_default(),
+            });
+        }
+        Ok(ControllerStream {
+            reader: BufReader::new(response),
+            item: PhantomData,
+        })
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.authorize(
+            self.http
+                .request(method, format!("{}{path}", self.base_url)),
+        )
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match self.secret.as_ref() {
+            Some(secret) => request.bearer_auth(secret),
+            None => request,
+        }
+    }
+
+    fn get_json<T: DeserializeOwned>(&self, path: &str) -> ControllerResult<T> {
+        self.send_json(self.request(Method::GET, path))
+    }
+
+    fn send_json<T: DeserializeOwned>(&self, request: RequestBuilder) -> ControllerResult<T> {
+        let body = self.send(request)?;
+        serde_json::from_str(&body).map_err(|error| ControllerError::Decode(error.to_string()))
+    }
+
+    fn send_empty(&self, request: RequestBuilder) -> ControllerResult<()> {
+        self.send(request).map(|_| ())
+    }
+
+    fn send(&self, request: RequestBuilder) -> ControllerResult<String> {
+        let response = request
+            .send()
+            .map_err(|error| ControllerError::Transport(error.to_string()))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .map_err(|error| ControllerError::Transport(error.to_string()))?;
+        if !status.is_success() {
+            return Err(ControllerError::Status {
+                status: status.as_u16(),
+                body,
+            });
+        }
+        Ok(body)
+    }
+}
+
+/// Items of a streaming endpoint, which sends one JSON object per line.
+/// Iteration ends when the kernel closes the connection.
+pub struct ControllerStream<T> {
+    reader: BufReader<Response>,
+    item: PhantomData<T>,
+}
+
+impl<T> fmt::Debug for ControllerStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ControllerStream").finish_non_exhaustive()
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for ControllerStream<T> {
+    type Item = ControllerResult<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) if line.trim().is_empty() => continue,
+                Ok(_) => {
+                    return Some(
+                        serde_json::from_str(line.trim())
+                            .map_err(|error| ControllerError::Decode(error.to_string())),
+                    );
+                }
+                Err(error) => return Some(Err(ControllerError::Transport(error.to_string()))),
+            }
+        }
+    }
+}
+
+fn with_json<T: Serialize>(request: RequestBuilder, body: &T) -> ControllerResult<RequestBuilder> {
+    let body =
+        serde_json::to_string(body).map_err(|error| ControllerError::Decode(error.to_string()))?;
+    Ok(request.header(CONTENT_TYPE, "application/json").body(body))
+}
+
+/// Everything but RFC 3986 unreserved characters.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+fn encode(segment: &str) -> String {
+    utf8_percent_encode(segment, PATH_SEGMENT).to_string()
+}
+
+fn normalize_base_url(raw: &str) -> CoreResult<String> {
+    let trimmed = raw.trim().trim_end_matches('/');
+    if trimmed.is_empty() {
+        return Err(CoreError::InvalidConfig(
+            "external-controller is empty".to_string(),
+        ));
+    }
+    let with_scheme = if trimmed.contains("://") {
+        trimmed.to_string()
+    } else {
+        format!("http://{trimmed}")
+    };
+    let parsed = url::Url::parse(&with_scheme).map_err(|error| {
+        CoreError::InvalidConfig(format!("invalid external-controller: {error}"))
+    })?;
+    let mut normalized = parsed.to_string();
+    if normalized.ends_with('/') {
+        normalized.pop();
+    }
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProxyMode;
+    use crate::test_support::StubServer;
+
+    fn client(server: &StubServer) -> ControllerClient {
+        ControllerClient::new(&server.url(), Some("s3cret".to_string())).expect("client")
+    }
+
+    #[test]
+    fn reads_version_and_configs() {
+        let server = StubServer::start();
+        server
+            .route("GET", "/version", 200, r#"{"meta":true,"version":"v1.19.2"}"#)
+            .route(
+                "GET",
+                "/configs",
+                200,
+                r#"{"port":0,"mixed-port":7890,"allow-lan":false,"mode":"Rule","log-level":"info","ipv6":false,"tun":{"enable":false}}"#,
+            );
+        let client = client(&server);
+
+        let version = client.version().expect("version");
+        assert!(version.meta);
+        assert_eq!(version.version, "v1.19.2");
+        assert_eq!(
+            server.last_request().header("authorization"),
+            Some("Bearer s3cret")
+        );
+
+        let configs = client.configs().expect("configs");
+        assert_eq!(configs.mixed_port, 7890);
+        assert_eq!(client.mode().expect("mode"), ProxyMode::Rule);
+
+        server.route("GET", "/configs", 200, r#"{"mode":"global"}"#);
+        assert_eq!(client.mode().expect("mode"), ProxyMode::Global);
+        server.route("GET", "/configs", 200, r#"{"mode":"invalid"}"#);
+        let error = client.mode().expect_err("invalid mode");
+        assert!(error.to_string().contains("invalid mode"));
+    }
+
+    #[test]
+    fn updates_and_reloads_configs() {
+        let server = StubServer::start();
+        server
+            .route("PATCH", "/configs", 204, "")
+            .route("PUT", "/configs", 204, "");
+        let client = client(&server);
+
+        client
+            .patch_configs(&ControllerConfigsPatch {
+                mode: Some(ProxyMode::Direct),
+                ..Default::default()
+            })
+            .expect("patch configs");
+        assert_eq!(server.last_request().body, r#"{"mode":"direct"}"#);
+
+        client
+            .reload_configs("/tmp/runtime.yaml", true)
+            .expect("reload configs");
+        let request = server.last_request();
+        assert_eq!(request.path, "/configs?force=true");
+        assert_eq!(request.body, r#"{"path":"/tmp/runtime.yaml","payload":""}"#);
+    }
+
+    #[test]
+    fn reads_and_selects_proxies() {
+        let server = StubServer::start();
+        server.route(
+            "GET",
+            "/proxies",
+            200,
+            r#"{"proxies":{
+                "GLOBAL":{"name":"GLOBAL","type":"Selector","now":"DIRECT","all":["DIRECT","Proxy"]},
+                "MyGroup":{"name":"MyGroup","type":"Selector","now":"Proxy A","all":["Proxy A","Proxy B"]},
+                "NoMembers":{"now":"X","all":[]},
+                "Stale":{"now":"Gone","all":["A"]},
+                "NoNow":{"all":["A","B"]},
+                "node":{"name":"node","type":"Shadowsocks","udp":true,"history":[{"time":"t","delay":88}]}
+            }}"#,
+        );
+        server.route("PUT", "/proxies/My%20Group", 204, "");
+        let client = client(&server);
+
+        let proxies = client.proxies().expect("proxies");
+        assert_eq!(proxies["node"].history[0].delay, 88);
+        assert!(proxies["node"].udp);
+
+        let selections = client.group_selections().expect("selections");
+        assert_eq!(selections.get("GLOBAL"), Some(&"DIRECT".to_string()));
+        assert_eq!(selections.get("MyGroup"), Some(&"Proxy A".to_string()));
+        assert_eq!(selections.get("NoMembers"), Some(&"X".to_string()));
+        assert!(!selections.contains_key("Stale"));
+        assert!(!selections.contains_key("NoNow"));
+
+        client
+            .select_proxy("My Group", "Node \"1\"")
+            .expect("select proxy");
+        assert_eq!(server.last_request().body, r#"{"name":"Node \"1\""}"#);
+    }
+
+    #[test]
+    fn measures_delays() {
+        let server = StubServer::start();
+        server
+            .route("GET", "/proxies/a/delay", 200, r#"{"delay":123}"#)
+            .route("GET", "/proxies/b/delay", 200, r#"{"delay":-1}"#)
+            .route("GET", "/proxies/c/delay", 200, r#"{"delay":"bad"}"#)
+            .route("GET", "/proxies/d/delay", 504, r#"{"message":"Timeout"}"#)
+            .route("GET", "/group/g/delay", 200, r#"{"a":120,"b":300}"#);
+        let client = client(&server);
+
+        assert_eq!(client.proxy_delay("a", "http://x/", 100), Ok(Some(123)));
+        assert!(
+            server
+                .last_request()
+                .path
+                .starts_with("/proxies/a/delay?url=http%3A%2F%2Fx%2F&timeout=100")
+        );
+        assert_eq!(client.proxy_delay("b", "http://x/", 100), Ok(None));
+        assert!(matches!(
+            client.proxy_delay("c", "http://x/", 100),
+            Err(ControllerError::Decode(_))
+        ));
+        assert_eq!(
+            client.proxy_delay("d", "http://x/", 100),
+            Err(ControllerError::Status {
+                status: 504,
+                body: r#"{"message":"Timeout"}"#.to_string()
+            })
+        );
+
+        let delays = client
+            .group_delay("g", "http://x/", 100)
+            .expect("group delay");
+        assert_eq!(delays.get("b"), Some(&300));
+    }
+
+    #[test]
+    fn reads_groups_providers_and_rules() {
+        let server = StubServer::start();
+        server
+            .route(
+                "GET",
+                "/group",
+                200,
+                r#"{"proxies":[{"name":"Auto","type":"URLTest","now":"a","all":["a","b"]}]}"#,
+            )
+            .route(
+                "GET",
+                "/providers/proxies",
+                200,
+                r#"{"providers":{"sub":{"name":"sub","type":"Proxy","vehicleType":"HTTP","proxies":null,"updatedAt":"2026-01-01T00:00:00Z"}}}"#,
+            )
+            .route("PUT", "/providers/proxies/sub", 204, "")
+            .route(
+                "GET",
+                "/providers/rules",
+                200,
+                r#"{"providers":{"ads":{"name":"ads","behavior":"Domain","format":"YamlRule","ruleCount":42,"type":"Rule","vehicleType":"HTTP"}}}"#,
+            )
+            .route("PUT", "/providers/rules/ads", 204, "")
+            .route(
+                "GET",
+                "/rules",
+                200,
+                r#"{"rules":[{"type":"DomainSuffix","payload":"example.com","proxy":"DIRECT","size":-1}]}"#,
+            );
+        let client = client(&server);
+
+        assert_eq!(client.groups().expect("groups")[0].all, vec!["a", "b"]);
+        let providers = client.proxy_providers().expect("proxy providers");
+        assert_eq!(providers["sub"].vehicle_type, "HTTP");
+        assert!(providers["sub"].proxies.is_empty());
+        client
+            .update_proxy_provider("sub")
+            .expect("update provider");
+        assert_eq!(
+            client.rule_providers().expect("rule providers")["ads"].rule_count,
+            42
+        );
+        client.update_rule_provider("ads").expect("update provider");
+        assert_eq!(client.rules().expect("rules")[0].proxy, "DIRECT");
+    }
+
+    #[test]
+    fn manages_connections_dns_and_restart() {
+        let server = StubServer::start();
+        server
+            .route(
+                "GET",
+                "/connections",
+                200,
+                r#"{"downloadTotal":10,"uploadTotal":5,"memory":1024,"connections":[{
+                    "id":"c-1","upload":1,"download":2,"start":"2026-01-01T00:00:00Z",
+                    "chains":["node","Proxy"],"rule":"Match","rulePayload":"",
+                    "metadata":{"network":"tcp","type":"HTTP","sourceIP":"127.0.0.1","destinationIP":"1.1.1.1",
+                    "sourcePort":"50000","destinationPort":"443","host":"example.com","process":"curl"}}]}"#,
+            )
+            .route("DELETE", "/connections/c-1", 204, "")
+            .route("DELETE", "/connections", 204, "")
+            .route(
+                "GET",
+                "/dns/query",
+                200,
+                r#"{"Status":0,"Question":[{"Name":"example.com.","Qtype":1,"Qclass":1}],"Answer":[{"name":"example.com.","type":1,"TTL":60,"data":"93.184.216.34"}]}"#,
+            )
+            .route("POST", "/restart", 200, r#"{"status":"ok"}"#);
+        let client = client(&server);
+
+        let snapshot = client.connections().expect("connections");
+        assert_eq!(snapshot.memory, 1024);
+        let connection = &snapshot.connections[0];
+        assert_eq!(connection.metadata.host, "example.com");
+        assert_eq!(connection.metadata.destination_port, "443");
+        assert_eq!(connection.chains, vec!["node", "Proxy"]);
+        client.close_connection("c-1").expect("close connection");
+        client.close_all_connections().expect("close all");
+
+        let answer = client.dns_query("example.com", "A").expect("dns query");
+        assert_eq!(
+            server.last_request().path,
+            "/dns/query?name=example.com&type=A"
+        );
+        assert_eq!(answer.question[0].qtype, 1);
+        assert_eq!(answer.answer[0].data, "93.184.216.34");
+
+        client.restart().expect("restart");
+        assert_eq!(server.last_request().body, r#"{"path":"","payload":""}"#);
+    }
+
+    #[test]
+    fn streams_traffic_and_memory() {
+        let server = StubServer::start();
+        server
+            .route(
+                "GET",
+                "/traffic",
+                200,
+                "{\"up\":10,\"down\":20}\n\n{\"up\":30,\"down\":40}\n",
+            )
+            .route(
+                "GET",
+                "/memory",
+                200,
+                "{\"inuse\":1024,\"oslimit\":0}\nnot json\n",
+            );
+        let client = client(&server);
+
+        let ticks = client
+            .traffic()
+            .expect("traffic stream")
+            .collect::<ControllerResult<Vec<_>>>()
+            .expect("traffic ticks");
+        assert_eq!(
+            ticks,
+            vec![
+                ControllerTraffic { up: 10, down: 20 },
+                ControllerTraffic { up: 30, down: 40 },
+            ]
+        );
+        assert_eq!(
+            server.last_request().header("authorization"),
+            Some("Bearer s3cret")
+        );
+
+        let mut memory = client.memory().expect("memory stream");
+        assert_eq!(memory.next().unwrap().expect("memory").inuse, 1024);
+        assert!(matches!(
+            memory.next(),
+            Some(Err(ControllerError::Decode(_)))
+        ));
+        assert!(memory.next().is_none());
+
+        server.route("GET", "/traffic", 401, "unauthorized");
+        assert!(matches!(
+            client.traffic(),
+            Err(ControllerError::Status { status: 401, .. })
+        ));
+    }
+
+    #[test]
+    fn reports_unreachable_controller() {
+        let server = StubServer::start();
+        let url = server.url();
+        drop(server);
+        std::thread::sleep(Duration::from_millis(50));
+        let client = ControllerClient::new(&url, None).expect("client");
+        assert!(matches!(
+            client.version(),
+            Err(ControllerError::Transport(_))
+        ));
+
+        assert_eq!(
+            ControllerClient::new("127.0.0.1:9097/", None)
+                .expect("client")
+                .base_url(),
+            "http://127.0.0.1:9097"
+        );
+        assert!(ControllerClient::new(" ", None).is_err());
+    }
+}
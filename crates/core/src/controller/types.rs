@@ -0,0 +1,269 @@
+//! Request and response bodies of the mihomo external controller API.
+
+use crate::{ProxyMode, parse_proxy_mode, proxy_mode_name};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// `GET /version`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct ControllerVersion {
+    #[serde(default)]
+    pub version: String,
+    /// `true` for mihomo (Clash.Meta) kernels.
+    #[serde(default)]
+    pub meta: bool,
+}
+
+/// `GET /configs`, the settings the running kernel uses.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ControllerConfigs {
+    pub port: u16,
+    pub socks_port: u16,
+    pub mixed_port: u16,
+    pub allow_lan: bool,
+    pub bind_address: String,
+    /// Raw mode name, see [`ControllerConfigs::proxy_mode`].
+    pub mode: String,
+    pub log_level: String,
+    pub ipv6: bool,
+}
+
+impl ControllerConfigs {
+    pub fn proxy_mode(&self) -> Option<ProxyMode> {
+        parse_proxy_mode(&self.mode)
+    }
+}
+
+/// `PATCH /configs`; only the fields that are set are changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ControllerConfigsPatch {
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_mode"
+    )]
+    pub mode: Option<ProxyMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_lan: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipv6: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mixed_port: Option<u16>,
+}
+
+fn serialize_mode<S: Serializer>(
+    mode: &Option<ProxyMode>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match mode {
+        Some(mode) => serializer.serialize_str(proxy_mode_name(mode)),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Body of `PUT /configs` and `POST /restart`. An empty `path` and
+/// `payload` make the kernel reuse the file it was started with.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub(crate) struct ConfigSource<'a> {
+    pub path: &'a str,
+    pub payload: &'a str,
+}
+
+/// A proxy, or a proxy group when `all` is not empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ControllerProxy {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Current selection of a group.
+    pub now: String,
+    /// Members of a group.
+    pub all: Vec<String>,
+    pub history: Vec<DelayHistory>,
+    pub udp: bool,
+    pub alive: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct DelayHistory {
+    pub time: String,
+    pub delay: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ProxiesResponse {
+    #[serde(default)]
+    pub proxies: BTreeMap<String, ControllerProxy>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GroupsResponse {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub proxies: Vec<ControllerProxy>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct SelectProxyRequest<'a> {
+    pub name: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct DelayResponse {
+    pub delay: i64,
+}
+
+/// `GET /providers/proxies`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProxyProviderInfo {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub vehicle_type: String,
+    #[serde(deserialize_with = "null_as_default")]
+    pub proxies: Vec<ControllerProxy>,
+    pub updated_at: Option<String>,
+}
+
+/// `GET /providers/rules`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RuleProviderInfo {
+    pub name: String,
+    pub behavior: String,
+    pub format: String,
+    pub vehicle_type: String,
+    pub rule_count: u64,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ProvidersResponse<T> {
+    #[serde(default = "BTreeMap::new")]
+    pub providers: BTreeMap<String, T>,
+}
+
+/// `GET /rules`, the rules as loaded by the kernel.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ControllerRule {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub payload: String,
+    /// Target policy.
+    pub proxy: String,
+    /// Entry count for `RULE-SET` and `GEOSITE` rules, `-1` otherwise.
+    pub size: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RulesResponse {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub rules: Vec<ControllerRule>,
+}
+
+/// `GET /connections`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ConnectionsSnapshot {
+    pub download_total: u64,
+    pub upload_total: u64,
+    #[serde(deserialize_with = "null_as_default")]
+    pub connections: Vec<ControllerConnection>,
+    pub memory: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ControllerConnection {
+    pub id: String,
+    pub metadata: ConnectionMetadata,
+    pub upload: u64,
+    pub download: u64,
+    /// RFC 3339 start time.
+    pub start: String,
+    /// Proxies used, from the outermost to the group the rule selected.
+    #[serde(deserialize_with = "null_as_default")]
+    pub chains: Vec<String>,
+    pub rule: String,
+    pub rule_payload: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ConnectionMetadata {
+    pub network: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(rename = "sourceIP")]
+    pub source_ip: String,
+    #[serde(rename = "destinationIP")]
+    pub destination_ip: String,
+    pub source_port: String,
+    pub destination_port: String,
+    pub host: String,
+    pub dns_mode: String,
+    pub process: String,
+    pub process_path: String,
+}
+
+/// `GET /dns/query`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct DnsQueryResponse {
+    /// DNS response code, `0` on success.
+    pub status: u16,
+    #[serde(deserialize_with = "null_as_default")]
+    pub question: Vec<DnsQuestion>,
+    #[serde(deserialize_with = "null_as_default")]
+    pub answer: Vec<DnsAnswer>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct DnsQuestion {
+    pub name: String,
+    pub qtype: u16,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct DnsAnswer {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub record_type: u16,
+    #[serde(rename = "TTL")]
+    pub ttl: u32,
+    pub data: String,
+}
+
+/// A line of `GET /traffic`, in bytes per second.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ControllerTraffic {
+    pub up: u64,
+    pub down: u64,
+}
+
+/// A line of `GET /memory`, in bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ControllerMemory {
+    pub inuse: u64,
+    /// `0` when the OS sets no limit.
+    pub oslimit: u64,
+}
+
+/// mihomo encodes empty lists as `null`.
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
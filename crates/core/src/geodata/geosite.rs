@@ -0,0 +1,231 @@
+//! Reader for v2ray `geosite.dat` files.
+//!
+//! The file is a protobuf `GeoSiteList { repeated GeoSite entry = 1; }` where
+//! `GeoSite { string country_code = 1; repeated Domain domain = 2; }` and
+//! `Domain { Type type = 1; string value = 2; }`.
+
+const DOMAIN_PLAIN: u64 = 0;
+const DOMAIN_REGEX: u64 = 1;
+const DOMAIN_SUFFIX: u64 = 2;
+const DOMAIN_FULL: u64 = 3;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum SiteDomain {
+    Keyword(String),
+    Suffix(String),
+    Full(String),
+    /// Regex entries are kept so callers can tell an unsupported entry from a
+    /// miss.
+    Regex(String),
+}
+
+impl SiteDomain {
+    /// Whether `host` (lowercase) matches; `None` for regex entries.
+    pub fn matches(&self, host: &str) -> Option<bool> {
+        match self {
+            Self::Keyword(value) => Some(host.contains(value.as_str())),
+            Self::Suffix(value) => Some(
+                host == value
+                    || host
+                        .strip_suffix(value.as_str())
+                        .is_some_and(|rest| rest.ends_with('.')),
+            ),
+            Self::Full(value) => Some(host == value),
+            Self::Regex(_) => None,
+        }
+    }
+}
+
+/// Number of lists in the file; fails when it is not a geosite file.
+pub(crate) fn count_sites(bytes: &[u8]) -> Result<usize, String> {
+    let mut reader = Reader::new(bytes);
+    let mut count = 0;
+    while let Some((field, wire)) = reader.key()? {
+        if field == 1 && wire == 2 {
+            reader.bytes()?;
+            count += 1;
+        } else {
+            reader.skip(wire)?;
+        }
+    }
+    Ok(count)
+}
+
+/// Domains listed under `code` (case-insensitive), or `None` when the file
+/// has no such list.
+pub(crate) fn load_site(bytes: &[u8], code: &str) -> Result<Option<Vec<SiteDomain>>, String> {
+    let mut reader = Reader::new(bytes);
+    while let Some((field, wire)) = reader.key()? {
+        if field != 1 || wire != 2 {
+            reader.skip(wire)?;
+            continue;
+        }
+        let entry = reader.bytes()?;
+        let mut site = Reader::new(entry);
+        let mut entry_code = None;
+        let mut domains = Vec::new();
+        while let Some((field, wire)) = site.key()? {
+            match (field, wire) {
+                (1, 2) => {
+                    let value = String::from_utf8_lossy(site.bytes()?);
+                    if !value.eq_ignore_ascii_case(code) {
+                        break;
+                    }
+                    entry_code = Some(());
+                }
+                (2, 2) => domains.push(site.bytes()?),
+                _ => site.skip(wire)?,
+            }
+        }
+        if entry_code.is_none() {
+            continue;
+        }
+        return domains
+            .into_iter()
+            .map(parse_domain)
+            .collect::<Result<Vec<_>, _>>()
+            .map(|domains| Some(domains.into_iter().flatten().collect()));
+    }
+    Ok(None)
+}
+
+fn parse_domain(bytes: &[u8]) -> Result<Option<SiteDomain>, String> {
+    let mut reader = Reader::new(bytes);
+    let mut kind = DOMAIN_PLAIN;
+    let mut value = String::new();
+    while let Some((field, wire)) = reader.key()? {
+        match (field, wire) {
+            (1, 0) => kind = reader.varint()?,
+            (2, 2) => value = String::from_utf8_lossy(reader.bytes()?).to_ascii_lowercase(),
+            _ => reader.skip(wire)?,
+        }
+    }
+    Ok(match kind {
+        DOMAIN_PLAIN => Some(SiteDomain::Keyword(value)),
+        DOMAIN_REGEX => Some(SiteDomain::Regex(value)),
+        DOMAIN_SUFFIX => Some(SiteDomain::Suffix(value)),
+        DOMAIN_FULL => Some(SiteDomain::Full(value)),
+        _ => None,
+    })
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    cursor: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, cursor: 0 }
+    }
+
+    fn key(&mut self) -> Result<Option<(u64, u64)>, String> {
+        if self.cursor >= self.bytes.len() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        Ok(Some((key >> 3, key & 0x7)))
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self
+                .bytes
+                .get(self.cursor)
+                .ok_or_else(|| "geosite data truncated".to_string())?;
+            self.cursor += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("geosite varint too long".to_string())
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], String> {
+        let len = self.varint()? as usize;
+        let end = self
+            .cursor
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| "geosite data truncated".to_string())?;
+        let bytes = &self.bytes[self.cursor..end];
+        self.cursor = end;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, wire: u64) -> Result<(), String> {
+        match wire {
+            0 => self.varint().map(|_| ()),
+            1 => self.advance(8),
+            2 => self.bytes().map(|_| ()),
+            5 => self.advance(4),
+            other => Err(format!("unsupported protobuf wire type {other}")),
+        }
+    }
+
+    fn advance(&mut self, len: usize) -> Result<(), String> {
+        if self.cursor + len > self.bytes.len() {
+            return Err("geosite data truncated".to_string());
+        }
+        self.cursor += len;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn field(out: &mut Vec<u8>, number: u8, payload: &[u8]) {
+        out.push((number << 3) | 2);
+        out.push(payload.len() as u8);
+        out.extend_from_slice(payload);
+    }
+
+    /// Encodes a `geosite.dat` holding one list of `(type, value)` domains.
+    pub(crate) fn build_geosite(code: &str, domains: &[(u8, &str)]) -> Vec<u8> {
+        let mut site = Vec::new();
+        field(&mut site, 1, code.as_bytes());
+        for (kind, value) in domains {
+            let mut domain = vec![0x08, *kind];
+            field(&mut domain, 2, value.as_bytes());
+            field(&mut site, 2, &domain);
+        }
+        let mut other_site = Vec::new();
+        field(&mut other_site, 1, b"OTHER");
+        let mut file = Vec::new();
+        field(&mut file, 1, &other_site);
+        field(&mut file, 1, &site);
+        file
+    }
+
+    #[test]
+    fn loads_domains_for_code() {
+        let bytes = build_geosite(
+            "GOOGLE",
+            &[
+                (2, "google.com"),
+                (3, "g.co"),
+                (0, "youtube"),
+                (1, "^goo+gle$"),
+            ],
+        );
+        let domains = load_site(&bytes, "google")
+            .expect("geosite should decode")
+            .expect("list should exist");
+        assert_eq!(domains.len(), 4);
+
+        let matches = |host: &str| domains.iter().filter_map(|d| d.matches(host)).any(|m| m);
+        assert!(matches("www.google.com"));
+        assert!(matches("google.com"));
+        assert!(!matches("notgoogle.com"));
+        assert!(matches("g.co"));
+        assert!(!matches("www.g.co"));
+        assert!(matches("m.youtube.com"));
+
+        assert_eq!(load_site(&bytes, "cn").expect("decode"), None);
+        assert!(load_site(&[0x0a, 0x05, 0x01], "cn").is_err());
+    }
+}
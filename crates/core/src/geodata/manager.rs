@@ -0,0 +1,348 @@
+//! Keeps the geodata files in the runtime dir present and current, so mihomo
+//! never has to download them itself while starting.
+
+use super::geosite;
+use super::mmdb::Mmdb;
+use crate::{
+    Core, CoreError, CoreEvent, CoreResult, CoreState, LINKPAD_HTTP_USER_AGENT, now_unix_seconds,
+};
+use chrono::DateTime;
+use reqwest::blocking::Client;
+use reqwest::header::USER_AGENT;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const UPDATER_TICK: Duration = Duration::from_secs(10 * 60);
+/// Delay before a file whose update failed is tried again.
+const FAILED_UPDATE_RETRY_SECS: u64 = 30 * 60;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A geodata file mihomo reads from its runtime dir.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GeoDataKind {
+    /// Country database used by `GEOIP` rules.
+    GeoIp,
+    /// Domain lists used by `GEOSITE` rules.
+    GeoSite,
+    /// Autonomous system database used by `IP-ASN` rules.
+    Asn,
+}
+
+impl GeoDataKind {
+    pub const ALL: [GeoDataKind; 3] = [Self::GeoIp, Self::GeoSite, Self::Asn];
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::GeoIp => "geoip.metadb",
+            Self::GeoSite => "geosite.dat",
+            Self::Asn => "GeoLite2-ASN.mmdb",
+        }
+    }
+
+    /// Key of the file's download URL under mihomo's `geox-url`.
+    pub(crate) fn geox_key(self) -> &'static str {
+        match self {
+            Self::GeoIp => "mmdb",
+            Self::GeoSite => "geosite",
+            Self::Asn => "asn",
+        }
+    }
+
+    fn default_url(self) -> String {
+        format!(
+            "https://github.com/MetaCubeX/meta-rules-dat/releases/download/latest/{}",
+            self.file_name()
+        )
+    }
+}
+
+/// Where geodata files are downloaded from and how often.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeoDataSettings {
+    pub geoip_url: String,
+    pub geosite_url: String,
+    pub asn_url: String,
+    /// Age after which a file is downloaded again; `None` only fills in
+    /// missing files.
+    pub update_interval_hours: Option<u32>,
+}
+
+impl Default for GeoDataSettings {
+    fn default() -> Self {
+        Self {
+            geoip_url: GeoDataKind::GeoIp.default_url(),
+            geosite_url: GeoDataKind::GeoSite.default_url(),
+            asn_url: GeoDataKind::Asn.default_url(),
+            update_interval_hours: Some(24),
+        }
+    }
+}
+
+impl GeoDataSettings {
+    pub fn url(&self, kind: GeoDataKind) -> &str {
+        match kind {
+            GeoDataKind::GeoIp => &self.geoip_url,
+            GeoDataKind::GeoSite => &self.geosite_url,
+            GeoDataKind::Asn => &self.asn_url,
+        }
+    }
+}
+
+/// State of one geodata file in the runtime dir.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeoDataStatus {
+    pub kind: GeoDataKind,
+    pub path: PathBuf,
+    pub present: bool,
+    pub size: u64,
+    /// Build date of MaxMind databases; for `geosite.dat`, which carries no
+    /// version, the start of its SHA-256 digest.
+    pub version: Option<String>,
+    /// Last modification, in Unix seconds.
+    pub updated_at: Option<u64>,
+    pub age_secs: Option<u64>,
+}
+
+pub(crate) fn status(dir: &Path, kind: GeoDataKind, now: u64) -> GeoDataStatus {
+    let path = file_path(dir, kind);
+    let metadata = fs::metadata(&path).ok().filter(|meta| meta.is_file());
+    let updated_at = metadata
+        .as_ref()
+        .and_then(|meta| meta.modified().ok())
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|since| since.as_secs());
+    let version = metadata
+        .is_some()
+        .then(|| fs::read(&path).ok())
+        .flatten()
+        .and_then(|bytes| file_version(kind, bytes));
+    GeoDataStatus {
+        kind,
+        present: metadata.is_some(),
+        size: metadata.map_or(0, |meta| meta.len()),
+        version,
+        updated_at,
+        age_secs: updated_at.map(|updated_at| now.saturating_sub(updated_at)),
+        path,
+    }
+}
+
+/// Whether `status` calls for a download: the file is missing, or older than
+/// the update interval.
+pub(crate) fn is_update_due(status: &GeoDataStatus, interval_hours: Option<u32>) -> bool {
+    if !status.present {
+        return true;
+    }
+    match (interval_hours, status.age_secs) {
+        (Some(hours), Some(age)) => age >= u64::from(hours) * 3600,
+        _ => false,
+    }
+}
+
+/// Downloads `kind` from `url` into `dir`. Returns whether the file changed.
+pub(crate) fn update(dir: &Path, kind: GeoDataKind, url: &str) -> CoreResult<bool> {
+    let client = Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|error| CoreError::Network(error.to_string()))?;
+    let response = client
+        .get(url)
+        .header(USER_AGENT, LINKPAD_HTTP_USER_AGENT)
+        .send()
+        .map_err(|error| CoreError::Network(error.to_string()))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(CoreError::Network(format!(
+            "failed to download {} from `{url}`: {status}",
+            kind.file_name()
+        )));
+    }
+    let bytes = response
+        .bytes()
+        .map_err(|error| CoreError::Network(error.to_string()))?;
+    install(dir, kind, &bytes)
+}
+
+/// Copies a geodata file from `source` into `dir`. Returns whether the file
+/// changed.
+pub(crate) fn import(dir: &Path, kind: GeoDataKind, source: &Path) -> CoreResult<bool> {
+    let bytes = fs::read(source).map_err(|error| {
+        CoreError::InvalidConfig(format!("failed to read `{}`: {error}", source.display()))
+    })?;
+    install(dir, kind, &bytes)
+}
+
+/// Checks `bytes` really is a `kind` file, then swaps it in.
+fn install(dir: &Path, kind: GeoDataKind, bytes: &[u8]) -> CoreResult<bool> {
+    validate(kind, bytes)?;
+    let path = file_path(dir, kind);
+    if fs::read(&path).is_ok_and(|existing| existing == bytes) {
+        // Touch the file so its age restarts and the updater leaves it be.
+        let _ = fs::File::options()
+            .append(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(std::time::SystemTime::now()));
+        return Ok(false);
+    }
+    fs::create_dir_all(dir).map_err(|error| CoreError::InvalidConfig(error.to_string()))?;
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, bytes).map_err(|error| CoreError::InvalidConfig(error.to_string()))?;
+    fs::rename(&temp_path, &path).map_err(|error| {
+        CoreError::InvalidConfig(format!("failed to replace `{}`: {error}", path.display()))
+    })?;
+    info!("geodata updated: {}", path.display());
+    Ok(true)
+}
+
+fn validate(kind: GeoDataKind, bytes: &[u8]) -> CoreResult<()> {
+    let result = match kind {
+        GeoDataKind::GeoIp | GeoDataKind::Asn => Mmdb::from_bytes(bytes.to_vec()).map(|_| ()),
+        GeoDataKind::GeoSite => geosite::count_sites(bytes).and_then(|count| {
+            if count == 0 {
+                Err("no domain lists".to_string())
+            } else {
+                Ok(())
+            }
+        }),
+    };
+    result.map_err(|error| {
+        CoreError::InvalidConfig(format!("not a valid {}: {error}", kind.file_name()))
+    })
+}
+
+fn file_version(kind: GeoDataKind, bytes: Vec<u8>) -> Option<String> {
+    match kind {
+        GeoDataKind::GeoIp | GeoDataKind::Asn => {
+            let epoch = Mmdb::from_bytes(bytes).ok()?.build_epoch()?;
+            let built = DateTime::from_timestamp(i64::try_from(epoch).ok()?, 0)?;
+            Some(built.format("%Y-%m-%d").to_string())
+        }
+        GeoDataKind::GeoSite => {
+            let digest = format!("{:x}", Sha256::digest(&bytes));
+            Some(digest[..12].to_string())
+        }
+    }
+}
+
+/// Path of `kind` in `dir`, reusing a file that only differs in case since
+/// mihomo matches these names case-insensitively.
+fn file_path(dir: &Path, kind: GeoDataKind) -> PathBuf {
+    super::find_file(dir, kind.file_name()).unwrap_or_else(|| dir.join(kind.file_name()))
+}
+
+/// Runs the update loop until the owning [`Core`] is dropped. Missing files
+/// are fetched right away, before the kernel would try on its own.
+pub(crate) fn spawn(inner: Weak<Mutex<CoreState>>) {
+    let spawned = thread::Builder::new()
+        .name("linkpad-geodata-updater".to_string())
+        .spawn(move || {
+            info!("geodata updater started");
+            let mut failed_at = HashMap::new();
+            loop {
+                let Some(inner) = inner.upgrade() else {
+                    break;
+                };
+                run_due_updates(&Core { inner }, &mut failed_at);
+                thread::sleep(UPDATER_TICK);
+            }
+            info!("geodata updater stopped");
+        });
+    if let Err(error) = spawned {
+        warn!("failed to start geodata updater: {error}");
+    }
+}
+
+fn run_due_updates(core: &Core, failed_at: &mut HashMap<GeoDataKind, u64>) {
+    let now = now_unix_seconds();
+    let interval_hours = core.config().geodata.update_interval_hours;
+    for status in core.geodata_status() {
+        let retry_allowed = failed_at
+            .get(&status.kind)
+            .is_none_or(|failed| now >= failed.saturating_add(FAILED_UPDATE_RETRY_SECS));
+        if !retry_allowed || !is_update_due(&status, interval_hours) {
+            continue;
+        }
+        info!("scheduled geodata update: {}", status.kind.file_name());
+        let event = match core.update_geodata(status.kind) {
+            Ok(updated) => {
+                failed_at.remove(&status.kind);
+                CoreEvent::GeoDataUpdated {
+                    kind: status.kind,
+                    version: updated.version,
+                }
+            }
+            Err(error) => {
+                warn!(
+                    "scheduled geodata update failed: {}: {error}",
+                    status.kind.file_name()
+                );
+                failed_at.insert(status.kind, now);
+                CoreEvent::GeoDataUpdateFailed {
+                    kind: status.kind,
+                    error: error.to_string(),
+                }
+            }
+        };
+        core.push_event(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geodata::{geosite, mmdb};
+    use crate::test_support::StubServer;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("linkpad-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create test dir");
+        dir
+    }
+
+    #[test]
+    fn updates_and_imports_validated_files() {
+        let dir = test_dir("geodata-manager");
+        let missing = status(&dir, GeoDataKind::GeoSite, 1_000);
+        assert!(!missing.present);
+        assert!(is_update_due(&missing, None));
+
+        let server = StubServer::start();
+        let geosite = geosite::tests::build_geosite("CN", &[(2, "example.cn")]);
+        server.route_bytes("GET", "/geosite.dat", 200, &geosite);
+        server.route("GET", "/broken.dat", 200, "<html>rate limited</html>");
+        let url = format!("{}/geosite.dat", server.url());
+
+        assert!(update(&dir, GeoDataKind::GeoSite, &url).expect("download geosite"));
+        assert!(!update(&dir, GeoDataKind::GeoSite, &url).expect("unchanged geosite"));
+        let broken = format!("{}/broken.dat", server.url());
+        assert!(update(&dir, GeoDataKind::GeoSite, &broken).is_err());
+        let current = status(&dir, GeoDataKind::GeoSite, now_unix_seconds());
+        assert!(current.present);
+        assert_eq!(current.size, geosite.len() as u64);
+        assert_eq!(current.version.as_deref().map(str::len), Some(12));
+        assert!(!is_update_due(&current, Some(24)));
+        assert!(is_update_due(
+            &GeoDataStatus {
+                age_secs: Some(24 * 3600),
+                ..current
+            },
+            Some(24)
+        ));
+
+        let source = dir.join("download.mmdb");
+        fs::write(&source, mmdb::tests::build_country_db(1, "AU")).expect("write mmdb");
+        assert!(import(&dir, GeoDataKind::GeoIp, &source).expect("import mmdb"));
+        assert!(dir.join("geoip.metadb").is_file());
+        assert!(import(&dir, GeoDataKind::Asn, &dir.join("geosite.dat")).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
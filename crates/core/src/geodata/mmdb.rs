@@ -0,0 +1,371 @@
+//! Minimal reader for MaxMind DB files such as mihomo's `Country.mmdb`.
+//!
+//! Only what a country lookup needs is implemented: walking the search tree
+//! and decoding the data section into [`MmdbValue`].
+
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+const METADATA_MARKER: &[u8] = b"\xAB\xCD\xEFMaxMind.com";
+const DATA_SECTION_SEPARATOR: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum MmdbValue {
+    String(String),
+    Double(f64),
+    Bytes(Vec<u8>),
+    Uint(u128),
+    Int(i32),
+    Map(BTreeMap<String, MmdbValue>),
+    Array(Vec<MmdbValue>),
+    Bool(bool),
+}
+
+impl MmdbValue {
+    fn get(&self, key: &str) -> Option<&MmdbValue> {
+        match self {
+            Self::Map(map) => map.get(key),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_uint(&self) -> Option<u128> {
+        match self {
+            Self::Uint(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Mmdb {
+    bytes: Vec<u8>,
+    node_count: usize,
+    record_size: usize,
+    ip_version: u16,
+    data_start: usize,
+    /// When the database was built, in Unix seconds.
+    build_epoch: Option<u64>,
+}
+
+impl Mmdb {
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, String> {
+        let marker = bytes
+            .windows(METADATA_MARKER.len())
+            .rposition(|window| window == METADATA_MARKER)
+            .ok_or_else(|| "missing mmdb metadata".to_string())?;
+        let metadata_start = marker + METADATA_MARKER.len();
+        let (metadata, _) = decode(&bytes[metadata_start..], 0, 0)?;
+
+        let field = |name: &str| {
+            metadata
+                .get(name)
+                .and_then(MmdbValue::as_uint)
+                .ok_or_else(|| format!("mmdb metadata has no `{name}`"))
+        };
+        let node_count = field("node_count")? as usize;
+        let record_size = field("record_size")? as usize;
+        let ip_version = field("ip_version")? as u16;
+        if !matches!(record_size, 24 | 28 | 32) {
+            return Err(format!("unsupported mmdb record size {record_size}"));
+        }
+
+        let data_start = node_count
+            .checked_mul(record_size / 4)
+            .and_then(|tree_size| tree_size.checked_add(DATA_SECTION_SEPARATOR))
+            .filter(|&data_start| data_start <= marker)
+            .ok_or_else(|| "mmdb search tree exceeds file size".to_string())?;
+        let build_epoch = metadata
+            .get("build_epoch")
+            .and_then(MmdbValue::as_uint)
+            .and_then(|epoch| u64::try_from(epoch).ok());
+        Ok(Self {
+            bytes,
+            node_count,
+            record_size,
+            ip_version,
+            data_start,
+            build_epoch,
+        })
+    }
+
+    pub fn build_epoch(&self) -> Option<u64> {
+        self.build_epoch
+    }
+
+    /// ISO country code stored for `ip`. Besides MaxMind's layout this accepts
+    /// databases that store the code as a plain string or a list of strings.
+    pub fn country_code(&self, ip: IpAddr) -> Option<String> {
+        let value = self.lookup(ip)?;
+        let code = match &value {
+            MmdbValue::String(code) => Some(code.as_str()),
+            MmdbValue::Array(items) => items.first().and_then(MmdbValue::as_str),
+            MmdbValue::Map(_) => ["country", "registered_country", "represented_country"]
+                .into_iter()
+                .find_map(|key| value.get(key)?.get("iso_code")?.as_str()),
+            _ => None,
+        }?;
+        Some(code.to_ascii_uppercase())
+    }
+
+    pub fn lookup(&self, ip: IpAddr) -> Option<MmdbValue> {
+        let (bits, bit_count) = match ip {
+            IpAddr::V4(v4) => (u128::from(u32::from(v4)), 32),
+            IpAddr::V6(v6) => {
+                if self.ip_version == 4 {
+                    return None;
+                }
+                (u128::from(v6), 128)
+            }
+        };
+
+        let mut node = 0;
+        // IPv4 addresses live under `::/96` in IPv6 trees.
+        if self.ip_version == 6 && bit_count == 32 {
+            for _ in 0..96 {
+                if node >= self.node_count {
+                    break;
+                }
+                node = self.read_record(node, 0)?;
+            }
+        }
+        for index in 0..bit_count {
+            if node >= self.node_count {
+                break;
+            }
+            let bit = ((bits >> (bit_count - 1 - index)) & 1) as usize;
+            node = self.read_record(node, bit)?;
+        }
+
+        // Records between the node count and the end of the separator point
+        // nowhere; only a corrupt tree has them.
+        let offset = node.checked_sub(self.node_count + DATA_SECTION_SEPARATOR)?;
+        let data = &self.bytes[self.data_start..];
+        decode(data, offset, 0).ok().map(|(value, _)| value)
+    }
+
+    fn read_record(&self, node: usize, bit: usize) -> Option<usize> {
+        let node_bytes = self.record_size / 4;
+        let base = node * node_bytes;
+        let bytes = self.bytes.get(base..base + node_bytes)?;
+        let value = match (self.record_size, bit) {
+            (24, 0) => be_uint(&bytes[0..3]),
+            (24, _) => be_uint(&bytes[3..6]),
+            (28, 0) => (usize::from(bytes[3] >> 4) << 24) | be_uint(&bytes[0..3]),
+            (28, _) => (usize::from(bytes[3] & 0x0f) << 24) | be_uint(&bytes[4..7]),
+            (_, 0) => be_uint(&bytes[0..4]),
+            (_, _) => be_uint(&bytes[4..8]),
+        };
+        Some(value)
+    }
+}
+
+fn be_uint(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .fold(0usize, |value, byte| (value << 8) | usize::from(*byte))
+}
+
+/// Decodes the value at `offset` of `data`, returning it with the offset just
+/// past it. `depth` guards against pointer loops in corrupt files.
+fn decode(data: &[u8], offset: usize, depth: usize) -> Result<(MmdbValue, usize), String> {
+    if depth > 32 {
+        return Err("mmdb data nested too deeply".to_string());
+    }
+    let byte = |at: usize| {
+        data.get(at)
+            .copied()
+            .ok_or_else(|| "mmdb data truncated".to_string())
+    };
+    let slice = |from: usize, len: usize| {
+        data.get(from..from + len)
+            .ok_or_else(|| "mmdb data truncated".to_string())
+    };
+
+    let control = byte(offset)?;
+    let mut cursor = offset + 1;
+    let mut kind = control >> 5;
+
+    if kind == 1 {
+        let size = usize::from((control >> 3) & 0x3);
+        let low = usize::from(control & 0x7);
+        let extra = slice(cursor, size + 1)?;
+        let pointer = match size {
+            0 => (low << 8) | be_uint(extra),
+            1 => ((low << 16) | be_uint(extra)) + 2048,
+            2 => ((low << 24) | be_uint(extra)) + 526_336,
+            _ => be_uint(extra),
+        };
+        let (value, _) = decode(data, pointer, depth + 1)?;
+        return Ok((value, cursor + size + 1));
+    }
+
+    if kind == 0 {
+        kind = 7 + byte(cursor)?;
+        cursor += 1;
+    }
+
+    let mut size = usize::from(control & 0x1f);
+    match size {
+        29 => {
+            size = 29 + usize::from(byte(cursor)?);
+            cursor += 1;
+        }
+        30 => {
+            size = 285 + be_uint(slice(cursor, 2)?);
+            cursor += 2;
+        }
+        31 => {
+            size = 65_821 + be_uint(slice(cursor, 3)?);
+            cursor += 3;
+        }
+        _ => {}
+    }
+
+    match kind {
+        2 => {
+            let text = String::from_utf8_lossy(slice(cursor, size)?).into_owned();
+            Ok((MmdbValue::String(text), cursor + size))
+        }
+        3 => {
+            let raw: [u8; 8] = slice(cursor, 8)?.try_into().map_err(|_| "bad double")?;
+            Ok((MmdbValue::Double(f64::from_be_bytes(raw)), cursor + 8))
+        }
+        4 => Ok((
+            MmdbValue::Bytes(slice(cursor, size)?.to_vec()),
+            cursor + size,
+        )),
+        5 | 6 | 9 | 10 => {
+            let value = slice(cursor, size)?
+                .iter()
+                .fold(0u128, |value, byte| (value << 8) | u128::from(*byte));
+            Ok((MmdbValue::Uint(value), cursor + size))
+        }
+        7 => {
+            let mut map = BTreeMap::new();
+            for _ in 0..size {
+                let (key, next) = decode(data, cursor, depth + 1)?;
+                let (value, next) = decode(data, next, depth + 1)?;
+                cursor = next;
+                if let MmdbValue::String(key) = key {
+                    map.insert(key, value);
+                }
+            }
+            Ok((MmdbValue::Map(map), cursor))
+        }
+        8 => {
+            let value = slice(cursor, size)?
+                .iter()
+                .fold(0u32, |value, byte| (value << 8) | u32::from(*byte));
+            Ok((MmdbValue::Int(value as i32), cursor + size))
+        }
+        11 => {
+            let mut items = Vec::with_capacity(size.min(64));
+            for _ in 0..size {
+                let (value, next) = decode(data, cursor, depth + 1)?;
+                cursor = next;
+                items.push(value);
+            }
+            Ok((MmdbValue::Array(items), cursor))
+        }
+        14 => Ok((MmdbValue::Bool(size != 0), cursor)),
+        15 => {
+            let raw: [u8; 4] = slice(cursor, 4)?.try_into().map_err(|_| "bad float")?;
+            Ok((
+                MmdbValue::Double(f64::from(f32::from_be_bytes(raw))),
+                cursor + 4,
+            ))
+        }
+        other => Err(format!("unsupported mmdb data type {other}")),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn encode_string(out: &mut Vec<u8>, value: &str) {
+        out.push((2 << 5) | value.len() as u8);
+        out.extend_from_slice(value.as_bytes());
+    }
+
+    fn encode_map_header(out: &mut Vec<u8>, pairs: u8) {
+        out.push((7 << 5) | pairs);
+    }
+
+    fn encode_uint16(out: &mut Vec<u8>, value: u16) {
+        out.push((5 << 5) | 2);
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// Builds an IPv4 database with 24-bit records that maps `network/8` to
+    /// `{country: {iso_code: code}}`.
+    pub(crate) fn build_country_db(network: u8, code: &str) -> Vec<u8> {
+        let node_count = 8usize;
+        let data_record = node_count + DATA_SECTION_SEPARATOR;
+        let mut tree = Vec::new();
+        for depth in 0..8 {
+            let bit = (network >> (7 - depth)) & 1;
+            let next = if depth == 7 { data_record } else { depth + 1 };
+            let (left, right) = if bit == 0 {
+                (next, node_count)
+            } else {
+                (node_count, next)
+            };
+            tree.extend_from_slice(&(left as u32).to_be_bytes()[1..]);
+            tree.extend_from_slice(&(right as u32).to_be_bytes()[1..]);
+        }
+
+        let mut bytes = tree;
+        bytes.extend_from_slice(&[0; DATA_SECTION_SEPARATOR]);
+        encode_map_header(&mut bytes, 1);
+        encode_string(&mut bytes, "country");
+        encode_map_header(&mut bytes, 1);
+        encode_string(&mut bytes, "iso_code");
+        encode_string(&mut bytes, code);
+
+        bytes.extend_from_slice(METADATA_MARKER);
+        encode_map_header(&mut bytes, 3);
+        encode_string(&mut bytes, "node_count");
+        encode_uint16(&mut bytes, node_count as u16);
+        encode_string(&mut bytes, "record_size");
+        encode_uint16(&mut bytes, 24);
+        encode_string(&mut bytes, "ip_version");
+        encode_uint16(&mut bytes, 4);
+        bytes
+    }
+
+    #[test]
+    fn looks_up_country_codes() {
+        let db = Mmdb::from_bytes(build_country_db(1, "au")).expect("test db should load");
+        assert_eq!(
+            db.country_code("1.2.3.4".parse().unwrap()),
+            Some("AU".to_string())
+        );
+        assert_eq!(db.country_code("2.2.3.4".parse().unwrap()), None);
+        assert_eq!(db.country_code("::1".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn ignores_records_pointing_into_the_separator() {
+        let mut bytes = build_country_db(1, "au");
+        // Last node, right record: repoint it from the data section into the
+        // separator.
+        let record = 7 * 6 + 3;
+        bytes[record..record + 3].copy_from_slice(&(8u32 + 1).to_be_bytes()[1..]);
+        let db = Mmdb::from_bytes(bytes).expect("test db should load");
+        assert_eq!(db.country_code("1.2.3.4".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn rejects_files_without_metadata() {
+        assert!(Mmdb::from_bytes(vec![0; 64]).is_err());
+    }
+}
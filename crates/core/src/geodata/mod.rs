@@ -0,0 +1,176 @@
+//! Access to the geodata files mihomo keeps in its runtime dir.
+
+mod geosite;
+pub(crate) mod manager;
+mod mmdb;
+
+use geosite::SiteDomain;
+use mmdb::Mmdb;
+use std::collections::HashMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::warn;
+
+const COUNTRY_MMDB_FILE: &str = "Country.mmdb";
+/// mihomo's own country database, managed by [`manager`]; used when
+/// `Country.mmdb` is absent.
+const GEOIP_METADB_FILE: &str = "geoip.metadb";
+const GEOSITE_FILE: &str = "GeoSite.dat";
+
+/// Lazily loaded geodata of one runtime dir.
+///
+/// Lookups return `None` when the database is missing or unreadable so
+/// callers can tell "not in the database" apart from "no database".
+#[derive(Debug)]
+pub(crate) struct GeoData {
+    dir: PathBuf,
+    country: Option<Option<Mmdb>>,
+    sites: HashMap<String, Option<Vec<SiteDomain>>>,
+    /// Modification times of the database files when the lookups above were
+    /// loaded, see [`GeoData::reload_if_changed`].
+    loaded_from: Vec<Option<SystemTime>>,
+}
+
+impl GeoData {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            country: None,
+            sites: HashMap::new(),
+            loaded_from: Vec::new(),
+        }
+    }
+
+    /// Drops what was loaded when a database file appeared, changed or went
+    /// away since, so a long-lived instance sees the updater's downloads.
+    pub fn reload_if_changed(&mut self) {
+        let modified = [COUNTRY_MMDB_FILE, GEOIP_METADB_FILE, GEOSITE_FILE]
+            .into_iter()
+            .map(|name| {
+                let path = find_file(&self.dir, name)?;
+                fs::metadata(path).and_then(|meta| meta.modified()).ok()
+            })
+            .collect::<Vec<_>>();
+        if modified != self.loaded_from {
+            self.country = None;
+            self.sites.clear();
+            self.loaded_from = modified;
+        }
+    }
+
+    /// Country code of `ip`; `Some(None)` when the database has no entry.
+    pub fn country(&mut self, ip: IpAddr) -> Option<Option<String>> {
+        if self.country.is_none() {
+            let db = [COUNTRY_MMDB_FILE, GEOIP_METADB_FILE]
+                .into_iter()
+                .find_map(|name| Some((name, self.read(name)?)))
+                .and_then(|(name, bytes)| match Mmdb::from_bytes(bytes) {
+                    Ok(db) => Some(db),
+                    Err(error) => {
+                        warn!("failed to load {name}: {error}");
+                        None
+                    }
+                });
+            self.country = Some(db);
+        }
+        let db = self.country.as_ref()?.as_ref()?;
+        Some(db.country_code(ip))
+    }
+
+    /// Whether `host` is in the GeoSite list `code`; `Some(false)` for
+    /// unknown codes.
+    ///
+    /// Regex entries are not evaluated, so a host listed only through a regex
+    /// is reported as not matching.
+    pub fn site_contains(&mut self, code: &str, host: &str) -> Option<bool> {
+        let key = code.to_ascii_lowercase();
+        if !self.sites.contains_key(&key) {
+            let domains =
+                self.read(GEOSITE_FILE)
+                    .and_then(|bytes| match geosite::load_site(&bytes, &key) {
+                        Ok(domains) => Some(domains.unwrap_or_default()),
+                        Err(error) => {
+                            warn!("failed to load {GEOSITE_FILE}: {error}");
+                            None
+                        }
+                    });
+            self.sites.insert(key.clone(), domains);
+        }
+        let domains = self.sites.get(&key)?.as_ref()?;
+        let host = host.to_ascii_lowercase();
+        Some(
+            domains
+                .iter()
+                .any(|domain| domain.matches(&host) == Some(true)),
+        )
+    }
+
+    /// Reads `name` from the runtime dir.
+    fn read(&self, name: &str) -> Option<Vec<u8>> {
+        let path = find_file(&self.dir, name)?;
+        match fs::read(&path) {
+            Ok(bytes) => Some(bytes),
+            Err(error) => {
+                warn!("failed to read `{}`: {error}", path.display());
+                None
+            }
+        }
+    }
+}
+
+/// Path of `name` in `dir`, matching the file name case-insensitively like
+/// mihomo does.
+fn find_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .and_then(|file| file.to_str())
+                .is_some_and(|file| file.eq_ignore_ascii_case(name))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_missing_databases() {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!("linkpad-geodata-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create test dir");
+
+        let mut geodata = GeoData::new(&dir);
+        geodata.reload_if_changed();
+        let ip = "1.1.1.1".parse().unwrap();
+        assert_eq!(geodata.country(ip), None);
+        assert_eq!(geodata.site_contains("google", "google.com"), None);
+
+        fs::write(
+            dir.join("country.mmdb"),
+            mmdb::tests::build_country_db(1, "AU"),
+        )
+        .expect("write mmdb");
+        fs::write(
+            dir.join("geosite.dat"),
+            geosite::tests::build_geosite("GOOGLE", &[(2, "google.com")]),
+        )
+        .expect("write geosite");
+
+        // The same instance picks the new files up.
+        assert_eq!(geodata.country(ip), None);
+        geodata.reload_if_changed();
+        assert_eq!(geodata.country(ip), Some(Some("AU".to_string())));
+        assert_eq!(
+            geodata.site_contains("google", "www.google.com"),
+            Some(true)
+        );
+        assert_eq!(geodata.site_contains("cn", "www.google.com"), Some(false));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,22 +1,88 @@
 use base64::Engine as _;
 use base64::engine::general_purpose;
 use chrono::{Local, NaiveDateTime};
-use percent_encoding::{NON_ALPHANUMERIC, percent_decode_str, utf8_percent_encode};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::collections::hash_map::DefaultHasher;
 use std::fmt;
+use std::fs;
 use std::hash::{Hash, Hasher};
-use std::sync::{Arc, Mutex};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::{error, info, warn};
 
+mod connection_tracker;
+mod controller;
+mod geodata;
+mod log_tail;
+mod pac;
+mod profile_cache;
+mod profile_override;
+mod proxy_watchdog;
+mod refresh_scheduler;
+mod rule;
+mod rule_matcher;
 mod runtime;
-pub use runtime::{KernelInfo, KernelUpgradeInfo, StartupStatus};
-use runtime::{KernelRuntime, StartupError, StartupManager, SystemProxyError, SystemProxyManager};
+mod share_link;
+mod supervisor;
+#[cfg(test)]
+mod test_support;
+mod traffic_monitor;
+use connection_tracker::ConnectionTracker;
+pub use connection_tracker::{ConnectionSort, ConnectionsView, TrackedConnection};
+pub use controller::{
+    ConnectionMetadata, ConnectionsSnapshot, ControllerClient, ControllerConfigs,
+    ControllerConfigsPatch, ControllerConnection, ControllerError, ControllerMemory,
+    ControllerProxy, ControllerResult, ControllerRule, ControllerStream, ControllerTraffic,
+    ControllerVersion, DelayHistory, DnsAnswer, DnsQueryResponse, DnsQuestion, ProxyProviderInfo,
+    RuleProviderInfo,
+};
+use geodata::GeoData;
+pub use geodata::manager::{GeoDataKind, GeoDataSettings, GeoDataStatus};
+use log_tail::LogBuffer;
+pub use log_tail::{LogEntry, LogLevel};
+use pac::PacServer;
+use profile_cache::ProfileCache;
+use profile_override::{OverrideStore, apply_override};
+use proxy_watchdog::ProxyWatchdog;
+pub use rule::{Rule, RuleDiagnostic, RuleKind};
+use rule_matcher::{MatchQuery, RuleMatcher};
+pub use rule_matcher::{RuleMatch, SkippedRule};
+pub use runtime::{
+    ConfigDiagnostic, KernelChannel, KernelInfo, KernelKind, KernelUpgradeInfo, KernelVersions,
+    ProxyEndpoint, ShellSyntax, StartupStatus, SystemProxyStatus,
+};
+use runtime::{
+    DEFAULT_BYPASS, KernelRuntime, PendingKernel, ReadinessProbe, StartupError, StartupManager,
+    SystemProxyConfig, SystemProxyError, SystemProxyManager,
+};
+use share_link::{ShareLinkProxy, build_subscription_document, decode_share_link};
+use supervisor::Supervisor;
+use traffic_monitor::TrafficHistory;
+pub use traffic_monitor::{TrafficSample, TrafficStats};
 
 pub type CoreResult<T> = Result<T, CoreError>;
 
+/// Lock on the core state that kernel launches give up while the kernel
+/// starts and hand back afterwards.
+type StateGuard<'a> = MutexGuard<'a, CoreState>;
+
+/// User agent of the core's own downloads: kernel releases and geodata.
+const LINKPAD_HTTP_USER_AGENT: &str = "linkpad-core/0.1";
+
+/// Config tests [`Core::validate_switch`] runs before giving up on a state
+/// that keeps changing underneath it.
+const SWITCH_TEST_ATTEMPTS: usize = 3;
+
+/// Profiles and config a change leads to, ready to be committed.
+struct Switch {
+    profiles: Vec<Profile>,
+    config: Config,
+    /// Whether the running kernel has to be brought onto them.
+    apply: bool,
+}
+
 #[derive(Debug)]
 pub enum CoreError {
     AlreadyRunning,
@@ -26,6 +92,19 @@ pub enum CoreError {
     InvalidProfile(String),
     Network(String),
     Parse(String),
+    Controller(ControllerError),
+    /// The kernel's test mode (`-t`) refused the runtime config; the running
+    /// kernel was left as it was.
+    ConfigRejected(Vec<ConfigDiagnostic>),
+    /// Another launch is still waiting for the kernel to become ready.
+    KernelStarting,
+    /// The kernel kept running but its controller or mixed port did not come
+    /// up within `timeout`; it has been stopped again.
+    KernelNotReady {
+        timeout: Duration,
+        /// Last lines of the kernel log, oldest first.
+        log_tail: Vec<String>,
+    },
 }
 
 impl fmt::Display for CoreError {
@@ -33,17 +112,43 @@ impl fmt::Display for CoreError {
         match self {
             CoreError::AlreadyRunning => write!(f, "core already running"),
             CoreError::NotRunning => write!(f, "core not running"),
+            CoreError::KernelStarting => write!(f, "kernel is still starting"),
             CoreError::ProfileNotFound => write!(f, "profile not found"),
             CoreError::InvalidConfig(msg) => write!(f, "invalid config: {msg}"),
             CoreError::InvalidProfile(msg) => write!(f, "invalid profile: {msg}"),
             CoreError::Network(msg) => write!(f, "network error: {msg}"),
             CoreError::Parse(msg) => write!(f, "parse error: {msg}"),
+            CoreError::Controller(error) => write!(f, "{error}"),
+            CoreError::ConfigRejected(diagnostics) => {
+                write!(f, "kernel rejected the config")?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n{diagnostic}")?;
+                }
+                Ok(())
+            }
+            CoreError::KernelNotReady { timeout, log_tail } => {
+                write!(
+                    f,
+                    "kernel did not become ready within {}s",
+                    timeout.as_secs_f32()
+                )?;
+                if !log_tail.is_empty() {
+                    write!(f, "; last log lines:\n{}", log_tail.join("\n"))?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
 impl std::error::Error for CoreError {}
 
+impl From<ControllerError> for CoreError {
+    fn from(error: ControllerError) -> Self {
+        CoreError::Controller(error)
+    }
+}
+
 fn map_startup_error(error: StartupError) -> CoreError {
     CoreError::InvalidConfig(format!("startup manager failed: {error}"))
 }
@@ -62,43 +167,44 @@ struct CoreState {
     running: bool,
     config: Config,
     profiles: Vec<Profile>,
+    profile_cache: ProfileCache,
+    overrides: OverrideStore,
     kernel_runtime: KernelRuntime,
-    system_proxy_manager: SystemProxyManager,
+    /// Shared with the proxy watchdog, which reads the OS settings without
+    /// holding the core lock.
+    system_proxy_manager: Arc<Mutex<SystemProxyManager>>,
     startup_manager: StartupManager,
     system_proxy_enabled: bool,
-    controller: Option<ControllerConfig>,
-}
-
-#[derive(Clone, Debug)]
-struct ControllerConfig {
-    base_url: String,
-    secret: Option<String>,
-}
-
-#[derive(Debug, Deserialize)]
-struct ControllerProxyListResponse {
-    #[serde(default)]
-    proxies: BTreeMap<String, ControllerProxyState>,
-}
-
-#[derive(Debug, Deserialize)]
-struct ControllerProxyState {
-    #[serde(default)]
-    now: String,
-    #[serde(default)]
-    all: Vec<String>,
-}
-
-#[derive(Debug, Deserialize)]
-struct ControllerDelayResponse {
-    #[serde(default)]
-    delay: i64,
-}
-
-#[derive(Debug, Deserialize)]
-struct ControllerRuntimeConfigResponse {
-    #[serde(default)]
-    mode: String,
+    /// What the enabled system proxy was last pointed at.
+    system_proxy_applied: Option<SystemProxyConfig>,
+    pac: PacServer,
+    /// A launch is waiting for the kernel to become ready without the lock.
+    launching: bool,
+    /// [`Core::stop`] was called during that wait; the launch kills its
+    /// kernel instead of keeping it.
+    launch_cancelled: bool,
+    /// Geodata for local rule matching, parsed on first use and kept across
+    /// queries; it reloads itself when the files change.
+    rule_geodata: Option<Arc<Mutex<GeoData>>>,
+    /// Env files for terminals; `None` only in tests built from the default
+    /// state, so they never write next to a real install.
+    shell_proxy: Option<SystemProxyManager>,
+    /// What the env files were last pointed at.
+    shell_proxy_applied: Option<SystemProxyConfig>,
+    proxy_watchdog_started: bool,
+    proxy_watchdog: ProxyWatchdog,
+    controller: Option<ControllerClient>,
+    refresh_scheduler_started: bool,
+    geodata_updater_started: bool,
+    traffic_monitor_started: bool,
+    traffic: TrafficHistory,
+    connection_tracker_started: bool,
+    connections: ConnectionTracker,
+    log_tail_started: bool,
+    logs: LogBuffer,
+    supervisor_started: bool,
+    supervisor: Supervisor,
+    events: Vec<CoreEvent>,
 }
 
 impl Default for Core {
@@ -109,8 +215,18 @@ impl Default for Core {
 
 impl Core {
     pub fn new() -> Self {
+        let mut state = CoreState::default();
+        let runtime_dir = state.kernel_runtime.runtime_dir().to_path_buf();
+        state
+            .system_proxy_manager
+            .lock()
+            .expect("system proxy manager poisoned")
+            .set_snapshot_path(Some(runtime_dir.join(SYSTEM_PROXY_SNAPSHOT_FILE)));
+        let mut shell_proxy = SystemProxyManager::new_shell_env(runtime_dir.join(SHELL_ENV_DIR));
+        shell_proxy.set_snapshot_path(Some(runtime_dir.join(SHELL_PROXY_SNAPSHOT_FILE)));
+        state.shell_proxy = Some(shell_proxy);
         Self {
-            inner: Arc::new(Mutex::new(CoreState::default())),
+            inner: Arc::new(Mutex::new(state)),
         }
     }
 
@@ -122,28 +238,335 @@ impl Core {
             return Err(CoreError::AlreadyRunning);
         }
         state.running = false;
+        state.supervisor.cancel();
+        let (_state, launched) = self.launch(state);
+        launched?;
+        info!("core started");
+        Ok(())
+    }
+
+    /// Builds the runtime config of the active profile and starts the kernel
+    /// with it. The lock is released while the kernel comes up, which can
+    /// take up to [`Config::startup_timeout_secs`], and held again on return.
+    fn launch<'a>(&'a self, mut state: StateGuard<'a>) -> (StateGuard<'a>, CoreResult<()>) {
+        if state.launching {
+            return (state, Err(CoreError::KernelStarting));
+        }
+        let (controller, readiness, mut pending) = match Self::spawn_kernel(&mut state) {
+            Ok(spawned) => spawned,
+            Err(error) => return (state, Err(error)),
+        };
+        state.launching = true;
+        state.launch_cancelled = false;
+        drop(state);
+
+        let ready = pending.wait_until_ready(&readiness);
+        let mut state = self.inner.lock().expect("core state poisoned");
+        state.launching = false;
+        if let Err(error) = ready {
+            return (state, Err(error));
+        }
+        if std::mem::take(&mut state.launch_cancelled) {
+            pending.kill();
+            info!("kernel launch cancelled by stop");
+            return (state, Err(CoreError::NotRunning));
+        }
+        let adopted = state.kernel_runtime.adopt(pending);
+        if adopted.is_ok() {
+            state.running = true;
+            state.controller = Some(controller);
+        }
+        (state, adopted)
+    }
 
+    fn spawn_kernel(
+        state: &mut CoreState,
+    ) -> CoreResult<(ControllerClient, ReadinessProbe, PendingKernel)> {
         let config = state.config.clone();
-        let active_profile = state
-            .profiles
+        let runtime_config = Self::runtime_config_for(state, &state.profiles, &config)?;
+        let controller = controller_client_from_config(&runtime_config)?;
+        let readiness = ReadinessProbe::new(
+            controller.clone(),
+            config.mixed_port,
+            Duration::from_secs(config.startup_timeout_secs),
+        );
+        let pending = state.kernel_runtime.spawn(&runtime_config, config.kernel)?;
+        Ok((controller, readiness, pending))
+    }
+
+    fn runtime_config_for(
+        state: &CoreState,
+        profiles: &[Profile],
+        config: &Config,
+    ) -> CoreResult<String> {
+        let active_profile = profiles
             .iter()
             .find(|profile| profile.active)
-            .cloned()
             .ok_or_else(|| {
-                CoreError::InvalidConfig("no active profile to launch mihomo".to_string())
+                CoreError::InvalidConfig("no active profile to launch the kernel".to_string())
             })?;
-        let runtime_config = build_runtime_config_from_profile(&active_profile, &config)?;
-        let controller = extract_controller_config(&runtime_config)?;
-        state.kernel_runtime.start(&runtime_config)?;
-        state.running = true;
-        state.controller = Some(controller);
-        info!("core started");
+        let profile_yaml = load_profile_document(&state.profile_cache, active_profile)?;
+        let overrides = state.overrides.layers(&active_profile.id)?;
+        build_runtime_config_yaml(&profile_yaml, &overrides, config)
+    }
+
+    /// Builds the profiles and config `change` makes of the current state and,
+    /// while the kernel runs, has it test the runtime config they produce, so
+    /// a change it would refuse is rejected before anything is committed.
+    ///
+    /// The test runs without the lock. Once it is held again `change` is
+    /// rebuilt from the state as it is now, and tested again if the runtime
+    /// config came out different, so the caller commits a [`Switch`] that
+    /// neither lost a concurrent change nor went untested.
+    fn validate_switch<'a>(
+        &'a self,
+        mut state: StateGuard<'a>,
+        change: impl Fn(&CoreState) -> CoreResult<(Vec<Profile>, Config)>,
+    ) -> (StateGuard<'a>, CoreResult<Switch>) {
+        let mut tested: Option<(KernelKind, String)> = None;
+        for _ in 0..SWITCH_TEST_ATTEMPTS {
+            if state.launching {
+                return (state, Err(CoreError::KernelStarting));
+            }
+            let (profiles, config) = match change(&state) {
+                Ok(changed) => changed,
+                Err(error) => return (state, Err(error)),
+            };
+            if !state.running || !state.kernel_runtime.is_running() {
+                let switch = Switch {
+                    profiles,
+                    config,
+                    apply: false,
+                };
+                return (state, Ok(switch));
+            }
+            let runtime_config = match Self::runtime_config_for(&state, &profiles, &config) {
+                Ok(runtime_config) => runtime_config,
+                Err(error) => return (state, Err(error)),
+            };
+            if Self::is_applied(&state, config.kernel, &runtime_config) {
+                let switch = Switch {
+                    profiles,
+                    config,
+                    apply: false,
+                };
+                return (state, Ok(switch));
+            }
+            let candidate = (config.kernel, runtime_config);
+            if tested.as_ref() == Some(&candidate) {
+                let switch = Switch {
+                    profiles,
+                    config,
+                    apply: true,
+                };
+                return (state, Ok(switch));
+            }
+            let tester = match state.kernel_runtime.config_tester(config.kernel) {
+                Ok(tester) => tester,
+                Err(error) => return (state, Err(error)),
+            };
+            drop(state);
+
+            let result = tester.test(&candidate.1);
+            state = self.inner.lock().expect("core state poisoned");
+            if let Err(error) = result {
+                return (state, Err(error));
+            }
+            tested = Some(candidate);
+        }
+        warn!("runtime config kept changing while the kernel tested it");
+        let error = CoreError::InvalidConfig(
+            "the config changed while the kernel tested it; try again".to_string(),
+        );
+        (state, Err(error))
+    }
+
+    /// Brings the running kernel onto the current state. The config is
+    /// reloaded in place, keeping open connections, unless a listener, the
+    /// controller or the kernel itself changed, or the kernel cannot reload;
+    /// then the kernel is restarted. Group selections are restored either way.
+    fn apply_runtime_change<'a>(
+        &'a self,
+        mut state: StateGuard<'a>,
+    ) -> (StateGuard<'a>, CoreResult<()>) {
+        let config = state.config.clone();
+        let runtime_config = match Self::runtime_config_for(&state, &state.profiles, &config) {
+            Ok(runtime_config) => runtime_config,
+            Err(error) => return (state, Err(error)),
+        };
+        if state.controller.is_some() && Self::is_applied(&state, config.kernel, &runtime_config) {
+            return (state, Ok(()));
+        }
+        if let Some(controller) = state.controller.clone() {
+            match controller.group_selections() {
+                Ok(selections) => state.supervisor.record_selections(selections),
+                Err(error) => warn!("failed to read group selections: {error}"),
+            }
+            let runtime = &state.kernel_runtime;
+            let reloadable = runtime.kind() == config.kernel
+                && runtime.controller_dialect().supports_reload()
+                && runtime
+                    .applied_config()
+                    .is_some_and(|applied| !requires_restart(applied, &runtime_config));
+            if reloadable {
+                match state.kernel_runtime.reload(&runtime_config, &controller) {
+                    Ok(()) => {
+                        supervisor::restore_selections(&controller, state.supervisor.selections());
+                        return (state, Ok(()));
+                    }
+                    Err(error) => warn!("hot reload failed, restarting kernel: {error}"),
+                }
+            }
+        }
+
+        info!("restarting kernel with the new runtime config");
+        self.relaunch(state)
+    }
+
+    /// Whether the kernel already runs `runtime_config`, so a change that
+    /// renders to it needs neither a test nor a reload.
+    fn is_applied(state: &CoreState, kind: KernelKind, runtime_config: &str) -> bool {
+        state.kernel_runtime.kind() == kind
+            && state.kernel_runtime.applied_config() == Some(runtime_config)
+    }
+
+    /// Restarts the kernel and restores the recorded group selections.
+    fn relaunch<'a>(&'a self, mut state: StateGuard<'a>) -> (StateGuard<'a>, CoreResult<()>) {
+        if let Err(error) = state.kernel_runtime.stop() {
+            return (state, Err(error));
+        }
+        state.running = false;
+        state.controller = None;
+        let (state, launched) = self.launch(state);
+        if launched.is_ok()
+            && let Some(controller) = &state.controller
+        {
+            supervisor::restore_selections(controller, state.supervisor.selections());
+        }
+        (state, launched)
+    }
+
+    /// Whether the kernel a binary change affects is the one running.
+    fn runs_configured_kernel(state: &CoreState) -> bool {
+        state.running && state.kernel_runtime.kind() == state.config.kernel
+    }
+
+    /// Restarts the kernel on the binary that is now current, recording the
+    /// group selections first.
+    fn relaunch_on_new_binary<'a>(
+        &'a self,
+        mut state: StateGuard<'a>,
+    ) -> (StateGuard<'a>, CoreResult<()>) {
+        if let Some(controller) = state.controller.clone() {
+            match controller.group_selections() {
+                Ok(selections) => state.supervisor.record_selections(selections),
+                Err(error) => warn!("failed to read group selections: {error}"),
+            }
+        }
+        self.relaunch(state)
+    }
+
+    /// System proxy for the current config. In PAC mode the script is rebuilt
+    /// from the active profile and served first, and the URL points at it;
+    /// otherwise the PAC server is stopped.
+    fn system_proxy_for(state: &mut CoreState) -> CoreResult<SystemProxyConfig> {
+        let proxy = system_proxy_config(&state.config);
+        let settings = &state.config.system_proxy;
+        if settings.mode != SystemProxyMode::Pac {
+            state.pac.stop();
+            return Ok(proxy);
+        }
+        // Hosts the manual proxy would bypass go direct in the script too.
+        let direct = settings
+            .pac_direct
+            .iter()
+            .chain(&settings.bypass)
+            .cloned()
+            .collect::<Vec<_>>();
+        let rules = state
+            .profiles
+            .iter()
+            .find(|profile| profile.active)
+            .map(|profile| profile.rules.as_slice())
+            .unwrap_or_default();
+        let script = pac::build_script(rules, &direct, &proxy);
+        let port = settings.pac_port;
+        state.pac.set_script(script);
+        state.pac.listen(port)?;
+        let pac_url = state.pac.url();
+        Ok(proxy.with_pac_url(pac_url))
+    }
+
+    /// Points an enabled system proxy at the current config, when that
+    /// changed its endpoints, bypass list or PAC script.
+    fn sync_system_proxy(state: &mut CoreState) -> CoreResult<()> {
+        if !state.system_proxy_enabled {
+            return Ok(());
+        }
+        let proxy = Self::system_proxy_for(state)?;
+        if state.system_proxy_applied.as_ref() != Some(&proxy) {
+            state
+                .system_proxy_manager
+                .lock()
+                .expect("system proxy manager poisoned")
+                .enable_with_config(&proxy)
+                .map_err(map_system_proxy_error)?;
+            info!(
+                "system proxy updated: http={}, https={}, socks={}, bypass={}, pac={}",
+                proxy.http_port,
+                proxy.https_port,
+                proxy.socks_port,
+                proxy.bypass.len(),
+                proxy.pac_url.as_deref().unwrap_or("-")
+            );
+            state.system_proxy_applied = Some(proxy);
+        }
+        Self::sync_shell_proxy(state);
         Ok(())
     }
 
+    /// Points the terminal env files at the enabled system proxy while
+    /// [`SystemProxySettings::shell_env`] is on, and removes them otherwise.
+    /// Terminals are an extra, so failures are only logged.
+    fn sync_shell_proxy(state: &mut CoreState) {
+        let wanted = state
+            .system_proxy_applied
+            .clone()
+            .filter(|_| state.system_proxy_enabled && state.config.system_proxy.shell_env)
+            .map(|proxy| proxy.with_pac_url(None));
+        if state.shell_proxy_applied == wanted {
+            return;
+        }
+        let Some(manager) = state.shell_proxy.as_mut() else {
+            return;
+        };
+        let result = match &wanted {
+            Some(proxy) => manager.enable_with_config(proxy),
+            None => manager.disable(),
+        };
+        match result {
+            Ok(()) => {
+                info!(
+                    "shell proxy env {}",
+                    if wanted.is_some() {
+                        "written"
+                    } else {
+                        "removed"
+                    }
+                );
+                state.shell_proxy_applied = wanted;
+            }
+            Err(error) => warn!("failed to update shell proxy env: {error}"),
+        }
+    }
+
     pub fn stop(&self) -> CoreResult<()> {
         info!("core stop requested");
         let mut state = self.inner.lock().expect("core state poisoned");
+        state.supervisor.cancel();
+        if state.launching {
+            state.launch_cancelled = true;
+        }
         if !state.running && !state.kernel_runtime.is_running() {
             let _ = state.kernel_runtime.stop();
             warn!("core stop skipped: not running");
@@ -153,9 +576,14 @@ impl Core {
         if state.system_proxy_enabled {
             state
                 .system_proxy_manager
+                .lock()
+                .expect("system proxy manager poisoned")
                 .disable()
                 .map_err(map_system_proxy_error)?;
             state.system_proxy_enabled = false;
+            state.system_proxy_applied = None;
+            state.pac.stop();
+            Self::sync_shell_proxy(&mut state);
         }
         state.kernel_runtime.stop()?;
         state.running = false;
@@ -171,9 +599,10 @@ impl Core {
 
     pub fn is_running(&self) -> bool {
         let mut state = self.inner.lock().expect("core state poisoned");
+        // The system proxy is left alone: the supervisor either brings the
+        // kernel back or disables it.
         if state.running && !state.kernel_runtime.is_running() {
             state.running = false;
-            state.system_proxy_enabled = false;
             state.controller = None;
         }
         state.running
@@ -185,9 +614,91 @@ impl Core {
     }
 
     pub fn update_config(&self, config: Config) -> CoreResult<()> {
+        config.system_proxy.validate(config.mixed_port)?;
+        let state = self.inner.lock().expect("core state poisoned");
+        let (mut state, switch) =
+            self.validate_switch(state, |state| Ok((state.profiles.clone(), config.clone())));
+        let switch = switch?;
+        state.config = switch.config;
+        if switch.apply {
+            let applied;
+            (state, applied) = self.apply_runtime_change(state);
+            applied?;
+        }
+        Self::sync_system_proxy(&mut state)
+    }
+
+    /// Sets the releases [`Core::upgrade_kernel_binary`] installs. Unlike
+    /// [`Core::update_config`], this leaves the running kernel alone.
+    pub fn set_kernel_channel(&self, channel: KernelChannel) {
         let mut state = self.inner.lock().expect("core state poisoned");
-        state.config = config;
-        Ok(())
+        state.config.kernel_channel = channel;
+    }
+
+    /// Sets the mirror base URLs kernel upgrades try before github.com,
+    /// leaving the running kernel alone like [`Core::set_kernel_channel`].
+    pub fn set_kernel_mirrors(&self, mirrors: Vec<String>) {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        state.config.kernel_mirrors = mirrors;
+    }
+
+    /// Sets where and how often geodata files are downloaded. The new URLs
+    /// reach the kernel with its next restart.
+    pub fn set_geodata_settings(&self, settings: GeoDataSettings) {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        state.config.geodata = settings;
+    }
+
+    /// Presence, version and age of each geodata file in the runtime dir.
+    pub fn geodata_status(&self) -> Vec<GeoDataStatus> {
+        let dir = self.runtime_dir();
+        let now = now_unix_seconds();
+        GeoDataKind::ALL
+            .into_iter()
+            .map(|kind| geodata::manager::status(&dir, kind, now))
+            .collect()
+    }
+
+    /// Downloads `kind` from its configured URL. The kernel reads the new
+    /// file when it next starts.
+    pub fn update_geodata(&self, kind: GeoDataKind) -> CoreResult<GeoDataStatus> {
+        let (dir, url) = {
+            let state = self.inner.lock().expect("core state poisoned");
+            let url = state.config.geodata.url(kind).trim().to_string();
+            (state.kernel_runtime.runtime_dir().to_path_buf(), url)
+        };
+        if url.is_empty() {
+            return Err(CoreError::InvalidConfig(format!(
+                "no download URL set for {}",
+                kind.file_name()
+            )));
+        }
+        geodata::manager::update(&dir, kind, &url)?;
+        Ok(geodata::manager::status(&dir, kind, now_unix_seconds()))
+    }
+
+    /// Installs a geodata file downloaded by hand, after checking it is one.
+    pub fn import_geodata_file(&self, kind: GeoDataKind, path: &Path) -> CoreResult<GeoDataStatus> {
+        let dir = self.runtime_dir();
+        geodata::manager::import(&dir, kind, path)?;
+        Ok(geodata::manager::status(&dir, kind, now_unix_seconds()))
+    }
+
+    /// Starts the background thread that downloads missing geodata files and
+    /// renews those older than the update interval. Calling it again is a
+    /// no-op.
+    pub fn start_geodata_updater(&self) {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        if state.geodata_updater_started {
+            return;
+        }
+        state.geodata_updater_started = true;
+        geodata::manager::spawn(Arc::downgrade(&self.inner));
+    }
+
+    fn runtime_dir(&self) -> std::path::PathBuf {
+        let state = self.inner.lock().expect("core state poisoned");
+        state.kernel_runtime.runtime_dir().to_path_buf()
     }
 
     pub fn is_system_proxy_enabled(&self) -> bool {
@@ -195,9 +706,104 @@ impl Core {
         state.system_proxy_enabled
     }
 
+    /// Proxy settings the OS has right now, whoever set them.
+    pub fn system_proxy_status(&self) -> CoreResult<SystemProxyStatus> {
+        let manager = {
+            let state = self.inner.lock().expect("core state poisoned");
+            state.system_proxy_manager.clone()
+        };
+        manager
+            .lock()
+            .expect("system proxy manager poisoned")
+            .status()
+            .map_err(map_system_proxy_error)
+    }
+
+    /// Puts back the OS proxy settings found before an earlier run enabled the
+    /// system proxy and ended without disabling it. Returns whether there was
+    /// anything to restore; call it at launch, before enabling the proxy.
+    pub fn recover_system_proxy(&self) -> CoreResult<bool> {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        if state.system_proxy_enabled {
+            return Ok(false);
+        }
+        let recovered = state
+            .system_proxy_manager
+            .lock()
+            .expect("system proxy manager poisoned")
+            .recover()
+            .map_err(map_system_proxy_error)?;
+        if recovered {
+            info!("system proxy settings restored after an unclean exit");
+        }
+        if let Some(shell_proxy) = state.shell_proxy.as_mut() {
+            match shell_proxy.recover() {
+                Ok(true) => info!("stale shell proxy env removed"),
+                Ok(false) => {}
+                Err(error) => warn!("failed to remove stale shell proxy env: {error}"),
+            }
+        }
+        Ok(recovered)
+    }
+
+    /// Starts the background thread that notices when the OS proxy settings
+    /// stop matching the enabled system proxy, see
+    /// [`CoreEvent::SystemProxyDrifted`]. Calling it again is a no-op.
+    pub fn start_system_proxy_watchdog(&self) {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        if state.proxy_watchdog_started {
+            return;
+        }
+        state.proxy_watchdog_started = true;
+        proxy_watchdog::spawn(Arc::downgrade(&self.inner));
+    }
+
+    /// Sets whether the watchdog puts drifted OS proxy settings back. It
+    /// reads the flag on every check, so nothing else needs to change.
+    pub fn set_proxy_restore_on_drift(&self, on: bool) {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        state.config.system_proxy.restore_on_drift = on;
+    }
+
+    /// Commands that point a terminal at the proxy endpoints of the current
+    /// config, whether or not the system proxy is enabled.
+    pub fn shell_proxy_commands(&self, syntax: ShellSyntax) -> String {
+        let state = self.inner.lock().expect("core state poisoned");
+        syntax.export_commands(&system_proxy_config(&state.config))
+    }
+
+    /// Makes bash, zsh, fish and PowerShell source the env files kept while
+    /// [`SystemProxySettings::shell_env`] is on. Returns the rc files changed.
+    pub fn install_shell_hooks(&self) -> CoreResult<Vec<std::path::PathBuf>> {
+        let dir = self.runtime_dir().join(SHELL_ENV_DIR);
+        let changed = runtime::install_shell_hooks(&dir).map_err(map_system_proxy_error)?;
+        info!("shell proxy hooks installed: {changed:?}");
+        Ok(changed)
+    }
+
+    /// Takes the lines [`Core::install_shell_hooks`] added out of the rc
+    /// files again. Returns the rc files changed.
+    pub fn uninstall_shell_hooks(&self) -> CoreResult<Vec<std::path::PathBuf>> {
+        let changed = runtime::uninstall_shell_hooks().map_err(map_system_proxy_error)?;
+        info!("shell proxy hooks removed: {changed:?}");
+        Ok(changed)
+    }
+
+    /// Turns [`SystemProxySettings::shell_env`] on or off and writes or
+    /// removes the env files to match right away.
+    pub fn set_proxy_shell_env(&self, on: bool) {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        state.config.system_proxy.shell_env = on;
+        Self::sync_shell_proxy(&mut state);
+    }
+
+    pub fn shell_hooks_installed(&self) -> bool {
+        runtime::shell_hooks_installed()
+    }
+
     pub fn kernel_info(&self) -> KernelInfo {
         let state = self.inner.lock().expect("core state poisoned");
-        state.kernel_runtime.kernel_info()
+        state.kernel_runtime.kernel_info(state.config.kernel)
     }
 
     pub fn verify_kernel_binary(&self) -> CoreResult<KernelInfo> {
@@ -209,9 +815,104 @@ impl Core {
         }
     }
 
+    /// Installs the kernel release the configured channel points at, trying
+    /// the configured mirrors before github.com, and switches to it with
+    /// [`Core::switch_kernel_binary`].
     pub fn upgrade_kernel_binary(&self) -> CoreResult<KernelUpgradeInfo> {
         let state = self.inner.lock().expect("core state poisoned");
-        state.kernel_runtime.install_latest_kernel()
+        let kind = state.config.kernel;
+        let current = state.kernel_runtime.kernel_versions(kind).current;
+        let upgrade = state.kernel_runtime.install_kernel(
+            kind,
+            &state.config.kernel_channel,
+            &state.config.kernel_mirrors,
+        )?;
+        self.switch_kernel_binary(state, current, upgrade).1
+    }
+
+    /// Installs a kernel release asset (`.gz` or `.zip`) downloaded by hand,
+    /// for machines that cannot reach GitHub, and switches to it with
+    /// [`Core::switch_kernel_binary`].
+    pub fn install_kernel_from_file(&self, path: &Path) -> CoreResult<KernelUpgradeInfo> {
+        let state = self.inner.lock().expect("core state poisoned");
+        let kind = state.config.kernel;
+        let current = state.kernel_runtime.kernel_versions(kind).current;
+        let upgrade = state.kernel_runtime.install_kernel_from_file(kind, path)?;
+        self.switch_kernel_binary(state, current, upgrade).1
+    }
+
+    /// Restarts a running kernel on a newly installed version. When the new
+    /// binary does not start or pass its readiness check, the previous version
+    /// is restored and the kernel started on it again.
+    fn switch_kernel_binary<'a>(
+        &'a self,
+        mut state: StateGuard<'a>,
+        current: Option<String>,
+        upgrade: KernelUpgradeInfo,
+    ) -> (StateGuard<'a>, CoreResult<KernelUpgradeInfo>) {
+        let kind = state.config.kernel;
+        if current.as_deref() == Some(upgrade.version.as_str()) {
+            info!("kernel {} is already current", upgrade.version);
+            return (state, Ok(upgrade));
+        }
+        info!(
+            "kernel {} installed from {}",
+            upgrade.version, upgrade.asset_name
+        );
+        let restart = Self::runs_configured_kernel(&state);
+        let verified = match state.kernel_runtime.kernel_info(kind) {
+            KernelInfo {
+                binary_path: Some(_),
+                ..
+            } if restart => {
+                let verified;
+                (state, verified) = self.relaunch_on_new_binary(state);
+                verified
+            }
+            KernelInfo {
+                binary_path: Some(_),
+                ..
+            } => Ok(()),
+            info => Err(CoreError::InvalidConfig(info.status)),
+        };
+        let Err(error) = verified else {
+            return (state, Ok(upgrade));
+        };
+
+        error!("kernel {} failed, rolling back: {error}", upgrade.version);
+        let restored = match state.kernel_runtime.rollback_kernel(kind) {
+            Ok(restored) => restored.unwrap_or_else(|| "the previous binary".to_string()),
+            Err(rollback_error) => return (state, Err(rollback_error)),
+        };
+        if restart {
+            let relaunched;
+            (state, relaunched) = self.relaunch_on_new_binary(state);
+            if let Err(restart_error) = relaunched {
+                error!("kernel failed to start after rollback: {restart_error}");
+            }
+        }
+        let failed = CoreError::InvalidConfig(format!(
+            "kernel {} failed and was rolled back to {restored}: {error}",
+            upgrade.version
+        ));
+        (state, Err(failed))
+    }
+
+    /// Switches back to the kernel version used before the last upgrade or
+    /// rollback, restarting a running kernel on it.
+    pub fn rollback_kernel_binary(&self) -> CoreResult<Option<String>> {
+        let state = self.inner.lock().expect("core state poisoned");
+        let kind = state.config.kernel;
+        let restored = state.kernel_runtime.rollback_kernel(kind)?;
+        if Self::runs_configured_kernel(&state) {
+            self.relaunch_on_new_binary(state).1?;
+        }
+        Ok(restored)
+    }
+
+    pub fn kernel_versions(&self) -> KernelVersions {
+        let state = self.inner.lock().expect("core state poisoned");
+        state.kernel_runtime.kernel_versions(state.config.kernel)
     }
 
     pub fn restart_kernel_runtime(&self) -> CoreResult<()> {
@@ -241,14 +942,13 @@ impl Core {
 
     pub fn enable_system_proxy(&self) -> CoreResult<()> {
         info!("enable system proxy requested");
-        let port = {
+        {
             let state = self.inner.lock().expect("core state poisoned");
             if state.system_proxy_enabled {
                 info!("system proxy already enabled");
                 return Ok(());
             }
-            state.config.mixed_port
-        };
+        }
 
         let started_here = if self.is_running() {
             false
@@ -258,19 +958,39 @@ impl Core {
         };
 
         let mut state = self.inner.lock().expect("core state poisoned");
-        match state.system_proxy_manager.enable("127.0.0.1", port) {
-            Ok(()) => {
+        let result = Self::system_proxy_for(&mut state).and_then(|proxy| {
+            state
+                .system_proxy_manager
+                .lock()
+                .expect("system proxy manager poisoned")
+                .enable_with_config(&proxy)
+                .map_err(map_system_proxy_error)?;
+            Ok(proxy)
+        });
+        match result {
+            Ok(proxy) => {
                 state.system_proxy_enabled = true;
-                info!("system proxy enabled on 127.0.0.1:{port}");
+                info!(
+                    "system proxy enabled on {}: http={}, https={}, socks={}, pac={}",
+                    proxy.host,
+                    proxy.http_port,
+                    proxy.https_port,
+                    proxy.socks_port,
+                    proxy.pac_url.as_deref().unwrap_or("-")
+                );
+                state.system_proxy_applied = Some(proxy);
+                state.proxy_watchdog = ProxyWatchdog::default();
+                Self::sync_shell_proxy(&mut state);
                 Ok(())
             }
             Err(error) => {
                 error!("enable system proxy failed: {error}");
+                state.pac.stop();
                 if started_here {
                     let _ = state.kernel_runtime.stop();
                     state.running = false;
                 }
-                Err(map_system_proxy_error(error))
+                Err(error)
             }
         }
     }
@@ -281,16 +1001,25 @@ impl Core {
         if state.system_proxy_enabled {
             state
                 .system_proxy_manager
+                .lock()
+                .expect("system proxy manager poisoned")
                 .disable()
                 .map_err(map_system_proxy_error)?;
             state.system_proxy_enabled = false;
+            state.system_proxy_applied = None;
+            state.pac.stop();
+            Self::sync_shell_proxy(&mut state);
             info!("system proxy disabled");
         }
 
+        state.supervisor.cancel();
         if state.running {
             state.kernel_runtime.stop()?;
             state.running = false;
             info!("kernel stopped after disabling system proxy");
+        } else {
+            // A crash seen but not yet handled must not bring the kernel back.
+            let _ = state.kernel_runtime.take_unexpected_exit();
         }
         state.controller = None;
         Ok(())
@@ -319,12 +1048,7 @@ impl Core {
             })?
         };
 
-        fetch_proxy_delay_from_controller(
-            &controller,
-            proxy_name,
-            DEFAULT_DELAY_TEST_URL,
-            DEFAULT_DELAY_TIMEOUT_MS,
-        )
+        probe_delay(&controller, proxy_name)
     }
 
     pub fn probe_proxy_delays(
@@ -351,7 +1075,7 @@ impl Core {
             })?
         };
 
-        fetch_proxy_delays_from_controller(&controller, proxy_names)
+        probe_delays(&controller, proxy_names)
     }
 
     pub fn select_proxy(&self, group_name: &str, proxy_name: &str) -> CoreResult<()> {
@@ -372,7 +1096,10 @@ impl Core {
             })?
         };
 
-        send_proxy_selection_request(&controller, group_name, proxy_name)
+        controller.select_proxy(group_name, proxy_name)?;
+        let mut state = self.inner.lock().expect("core state poisoned");
+        state.supervisor.record_selection(group_name, proxy_name);
+        Ok(())
     }
 
     pub fn current_proxy_group_selections(&self) -> CoreResult<BTreeMap<String, String>> {
@@ -388,7 +1115,7 @@ impl Core {
             })?
         };
 
-        fetch_proxy_selection_map_from_controller(&controller)
+        Ok(controller.group_selections()?)
     }
 
     pub fn set_mode(&self, mode: ProxyMode) -> CoreResult<()> {
@@ -415,11 +1142,15 @@ impl Core {
             return Ok(());
         };
 
-        if let Err(error) = send_mode_update_request(&controller, mode) {
+        let patch = ControllerConfigsPatch {
+            mode: Some(mode),
+            ..Default::default()
+        };
+        if let Err(error) = controller.patch_configs(&patch) {
             let mut state = self.inner.lock().expect("core state poisoned");
             state.config.mode = previous_mode;
             error!("set mode failed: {error}");
-            return Err(error);
+            return Err(error.into());
         }
 
         info!("set mode succeeded: {:?}", mode);
@@ -442,7 +1173,7 @@ impl Core {
             return Ok(fallback_mode);
         };
 
-        let mode = fetch_mode_from_controller(&controller)?;
+        let mode = controller.mode()?;
         let mut state = self.inner.lock().expect("core state poisoned");
         state.config.mode = mode;
         Ok(mode)
@@ -450,15 +1181,79 @@ impl Core {
 
     pub fn import_profile_url(&self, source_url: &str, set_active: bool) -> CoreResult<Profile> {
         info!("import profile requested");
-        let content = fetch_profile_content(source_url)?;
-        let parsed = parse_profile_yaml(source_url, &content)?;
+        let fetched = fetch_profile_content(source_url)?;
+        self.store_imported_profile(
+            ProfileSourceKind::Remote,
+            source_url,
+            None,
+            fetched,
+            set_active,
+        )
+    }
+
+    pub fn import_profile_file(&self, path: &Path, set_active: bool) -> CoreResult<Profile> {
+        info!("import profile file requested: {}", path.display());
+        let path = fs::canonicalize(path).map_err(|error| {
+            CoreError::InvalidProfile(format!("failed to read `{}`: {error}", path.display()))
+        })?;
+        let content = read_profile_file(&path)?;
+        self.store_imported_profile(
+            ProfileSourceKind::LocalFile,
+            &path.to_string_lossy(),
+            profile_name_from_path(&path),
+            FetchedProfile::local(content),
+            set_active,
+        )
+    }
+
+    pub fn import_profile_text(
+        &self,
+        name: &str,
+        content: &str,
+        set_active: bool,
+    ) -> CoreResult<Profile> {
+        info!("import profile text requested");
+        if content.trim().is_empty() {
+            return Err(CoreError::InvalidProfile(
+                "profile content is empty".to_string(),
+            ));
+        }
+        let name = name.trim();
+        self.store_imported_profile(
+            ProfileSourceKind::Inline,
+            "",
+            (!name.is_empty()).then(|| name.to_string()),
+            FetchedProfile::local(content.to_string()),
+            set_active,
+        )
+    }
+
+    fn store_imported_profile(
+        &self,
+        source_kind: ProfileSourceKind,
+        source_url: &str,
+        name: Option<String>,
+        fetched: FetchedProfile,
+        set_active: bool,
+    ) -> CoreResult<Profile> {
+        let mut parsed = parse_profile_yaml(source_url, &fetched.content)?;
+        let refresh = fetched.refresh_state(None);
+        let raw_yaml = parsed.generated_yaml.take().unwrap_or(fetched.content);
 
         let mut state = self.inner.lock().expect("core state poisoned");
+        let id_seed = if source_url.is_empty() {
+            raw_yaml.as_str()
+        } else {
+            source_url
+        };
         let mut profile = Profile {
-            id: build_profile_id(source_url),
-            name: parsed.name,
+            id: build_profile_id(id_seed),
+            name: name.unwrap_or(parsed.name),
             source_url: source_url.to_string(),
+            source_kind,
             updated_at: current_local_timestamp(),
+            subscription: fetched.userinfo,
+            refresh,
             node_count: parsed.node_count,
             group_count: parsed.group_count,
             rule_count: parsed.rule_count,
@@ -466,19 +1261,36 @@ impl Core {
             proxy_groups: parsed.proxy_groups,
             proxy_nodes: parsed.proxy_nodes,
             rules: parsed.rules,
-            raw_yaml: content,
+            rule_diagnostics: parsed.rule_diagnostics,
+            raw_yaml,
         };
 
-        if let Some(index) = state
-            .profiles
-            .iter()
-            .position(|item| item.source_url == source_url)
-        {
+        // Pasted profiles have no source to match against, so each paste is new.
+        let existing_index = if source_kind == ProfileSourceKind::Inline {
+            None
+        } else {
+            state
+                .profiles
+                .iter()
+                .position(|item| item.source_kind == source_kind && item.source_url == source_url)
+        };
+        if let Some(index) = existing_index {
             profile.id = state.profiles[index].id.clone();
+            profile.refresh.interval_override_hours =
+                state.profiles[index].refresh.interval_override_hours;
             state.profiles[index] = profile.clone();
         } else {
+            let base_id = profile.id.clone();
+            let mut suffix = 2;
+            while state.profiles.iter().any(|item| item.id == profile.id) {
+                profile.id = format!("{base_id}-{suffix}");
+                suffix += 1;
+            }
             state.profiles.insert(0, profile.clone());
         }
+        if let Err(error) = state.profile_cache.store(&profile.id, &profile.raw_yaml) {
+            warn!("failed to cache profile {}: {error}", profile.id);
+        }
 
         if set_active || state.profiles.iter().all(|item| !item.active) {
             for item in &mut state.profiles {
@@ -500,6 +1312,12 @@ impl Core {
 
     pub fn refresh_profile(&self, id: &str) -> CoreResult<Profile> {
         info!("refresh profile requested: id={id}");
+        self.refresh_profile_checked(id).map(|(profile, _)| profile)
+    }
+
+    /// Refreshes a profile from its source and reports whether the document
+    /// changed. Remote sources are asked conditionally when a copy is loaded.
+    fn refresh_profile_checked(&self, id: &str) -> CoreResult<(Profile, bool)> {
         let existing = {
             let state = self.inner.lock().expect("core state poisoned");
             state
@@ -510,21 +1328,59 @@ impl Core {
                 .ok_or(CoreError::ProfileNotFound)?
         };
 
-        let content = fetch_profile_content(&existing.source_url)?;
-        let parsed = parse_profile_yaml(&existing.source_url, &content)?;
-
-        let mut state = self.inner.lock().expect("core state poisoned");
-        let index = state
-            .profiles
-            .iter()
-            .position(|profile| profile.id == id)
-            .ok_or(CoreError::ProfileNotFound)?;
+        let fetched = match existing.source_kind {
+            ProfileSourceKind::Remote => {
+                let previous = if existing.raw_yaml.trim().is_empty() {
+                    ProfileRefreshState::default()
+                } else {
+                    existing.refresh.clone()
+                };
+                match fetch_profile_content_if_modified(&existing.source_url, &previous)? {
+                    FetchOutcome::Modified(fetched) => fetched,
+                    FetchOutcome::NotModified => {
+                        let mut state = self.inner.lock().expect("core state poisoned");
+                        let profile = state
+                            .profiles
+                            .iter_mut()
+                            .find(|profile| profile.id == id)
+                            .ok_or(CoreError::ProfileNotFound)?;
+                        profile.refresh.last_checked_at = Some(now_unix_seconds());
+                        info!("profile not modified: id={id}");
+                        return Ok((profile.clone(), false));
+                    }
+                }
+            }
+            ProfileSourceKind::LocalFile => {
+                FetchedProfile::local(read_profile_file(Path::new(&existing.source_url))?)
+            }
+            ProfileSourceKind::Inline => {
+                let cache = self
+                    .inner
+                    .lock()
+                    .expect("core state poisoned")
+                    .profile_cache
+                    .clone();
+                FetchedProfile::local(load_profile_document(&cache, &existing)?)
+            }
+        };
+        let mut parsed = parse_profile_yaml(&existing.source_url, &fetched.content)?;
+        let refresh = fetched.refresh_state(existing.refresh.interval_override_hours);
+        let raw_yaml = parsed.generated_yaml.take().unwrap_or(fetched.content);
+        // Only remote profiles take their name from the document; file and
+        // pasted profiles keep the name they were imported with.
+        let name = match existing.source_kind {
+            ProfileSourceKind::Remote => parsed.name,
+            ProfileSourceKind::LocalFile | ProfileSourceKind::Inline => existing.name.clone(),
+        };
 
-        state.profiles[index] = Profile {
+        let refreshed = Profile {
             id: existing.id.clone(),
-            name: parsed.name,
+            name,
             source_url: existing.source_url.clone(),
+            source_kind: existing.source_kind,
             updated_at: current_local_timestamp(),
+            subscription: fetched.userinfo,
+            refresh,
             node_count: parsed.node_count,
             group_count: parsed.group_count,
             rule_count: parsed.rule_count,
@@ -532,8 +1388,36 @@ impl Core {
             proxy_groups: parsed.proxy_groups,
             proxy_nodes: parsed.proxy_nodes,
             rules: parsed.rules,
-            raw_yaml: content,
+            rule_diagnostics: parsed.rule_diagnostics,
+            raw_yaml,
         };
+        let state = self.inner.lock().expect("core state poisoned");
+        let (mut state, switch) = self.validate_switch(state, |state| {
+            let mut profiles = state.profiles.clone();
+            let profile = profiles
+                .iter_mut()
+                .find(|profile| profile.id == id)
+                .ok_or(CoreError::ProfileNotFound)?;
+            *profile = Profile {
+                active: profile.active,
+                ..refreshed.clone()
+            };
+            Ok((profiles, state.config.clone()))
+        });
+        let switch = switch?;
+        state.profiles = switch.profiles;
+        let apply = switch.apply;
+        let index = state
+            .profiles
+            .iter()
+            .position(|profile| profile.id == id)
+            .ok_or(CoreError::ProfileNotFound)?;
+        if let Err(error) = state
+            .profile_cache
+            .store(id, &state.profiles[index].raw_yaml)
+        {
+            warn!("failed to cache profile {id}: {error}");
+        }
         info!(
             "profile refreshed: id={}, name={}, nodes={}, groups={}, rules={}",
             state.profiles[index].id,
@@ -542,8 +1426,14 @@ impl Core {
             state.profiles[index].group_count,
             state.profiles[index].rule_count
         );
+        if apply {
+            let applied;
+            (state, applied) = self.apply_runtime_change(state);
+            applied?;
+        }
+        Self::sync_system_proxy(&mut state)?;
 
-        Ok(state.profiles[index].clone())
+        Ok((state.profiles[index].clone(), true))
     }
 
     pub fn delete_profile(&self, id: &str) -> CoreResult<()> {
@@ -554,12 +1444,14 @@ impl Core {
             .position(|profile| profile.id == id)
             .ok_or(CoreError::ProfileNotFound)?;
 
-        let removed_active = state.profiles[index].active;
-        state.profiles.remove(index);
-        if removed_active {
+        let removed = state.profiles.remove(index);
+        state.profile_cache.remove(&removed.id);
+        state.overrides.remove_profile(&removed.id);
+        if removed.active {
             if let Some(first) = state.profiles.first_mut() {
                 first.active = true;
             }
+            Self::sync_system_proxy(&mut state)?;
         }
         Ok(())
     }
@@ -590,28 +1482,105 @@ impl Core {
             .unwrap_or_default()
     }
 
-    pub fn active_rules(&self) -> Vec<String> {
-        self.active_profile()
-            .map(|profile| profile.rules)
+    /// Rules the kernel runs with: the active profile's, with the override
+    /// layers' `prepend-rules` and `append-rules` in place.
+    pub fn active_rules(&self) -> Vec<Rule> {
+        self.effective_rules()
+            .map(|effective| effective.rules)
             .unwrap_or_default()
     }
 
-    pub fn set_active_profile(&self, id: &str) -> CoreResult<()> {
-        let mut state = self.inner.lock().expect("core state poisoned");
-        let mut found = false;
-        for profile in &mut state.profiles {
-            if profile.id == id {
-                profile.active = true;
-                found = true;
-            } else {
-                profile.active = false;
+    pub fn active_rule_diagnostics(&self) -> Vec<RuleDiagnostic> {
+        self.effective_rules()
+            .map(|effective| effective.diagnostics)
+            .unwrap_or_default()
+    }
+
+    /// Rules of the runtime config for the active profile. When that config
+    /// cannot be built, e.g. an override layer is broken, the profile's own
+    /// rules stand in, as they are what the kernel keeps running with.
+    fn effective_rules(&self) -> Option<EffectiveRules> {
+        let state = self.inner.lock().expect("core state poisoned");
+        let profile = state.profiles.iter().find(|profile| profile.active)?;
+        let effective = Self::runtime_config_for(&state, &state.profiles, &state.config)
+            .and_then(EffectiveRules::parse);
+        match effective {
+            Ok(effective) => Some(effective),
+            Err(error) => {
+                warn!("using profile rules without overrides: {error}");
+                Some(EffectiveRules {
+                    document: profile.raw_yaml.clone(),
+                    rules: profile.rules.clone(),
+                    diagnostics: profile.rule_diagnostics.clone(),
+                    groups: profile.proxy_groups.clone(),
+                })
             }
         }
-        if found {
-            Ok(())
-        } else {
-            Err(CoreError::ProfileNotFound)
+    }
+
+    /// Finds the rule of [`Core::active_rules`] that would handle a connection
+    /// to `host_or_ip`, see the `rule_matcher` module. Returns `None` when no
+    /// rule matches. Resolves the host and asks the kernel for its group
+    /// selections, so call it off the UI thread.
+    pub fn match_rule(
+        &self,
+        host_or_ip: &str,
+        port: Option<u16>,
+        process: Option<&str>,
+    ) -> CoreResult<Option<RuleMatch>> {
+        let query = MatchQuery::new(host_or_ip, port, process)
+            .ok_or_else(|| CoreError::InvalidConfig("host or IP is empty".to_string()))?;
+        let effective = self.effective_rules().ok_or(CoreError::ProfileNotFound)?;
+        let (runtime_dir, geodata) = {
+            let mut state = self.inner.lock().expect("core state poisoned");
+            let runtime_dir = state.kernel_runtime.runtime_dir().to_path_buf();
+            let geodata = state
+                .rule_geodata
+                .get_or_insert_with(|| Arc::new(Mutex::new(GeoData::new(&runtime_dir))))
+                .clone();
+            (runtime_dir, geodata)
+        };
+        let selections = self
+            .current_proxy_group_selections()
+            .unwrap_or_else(|error| {
+                warn!("rule match uses default group members: {error}");
+                BTreeMap::new()
+            });
+
+        let mut geodata = geodata.lock().expect("geodata poisoned");
+        geodata.reload_if_changed();
+        let found = RuleMatcher::new(&query, &effective.document, &runtime_dir, &mut geodata)
+            .find_match(&effective.rules, &effective.groups, &selections);
+        info!(
+            "rule match: host={}, rule={:?}",
+            query.host,
+            found.as_ref().map(|found| found.index)
+        );
+        Ok(found)
+    }
+
+    pub fn set_active_profile(&self, id: &str) -> CoreResult<()> {
+        let state = self.inner.lock().expect("core state poisoned");
+        let (mut state, switch) = self.validate_switch(state, |state| {
+            let mut profiles = state.profiles.clone();
+            let mut found = false;
+            for profile in &mut profiles {
+                profile.active = profile.id == id;
+                found |= profile.active;
+            }
+            if !found {
+                return Err(CoreError::ProfileNotFound);
+            }
+            Ok((profiles, state.config.clone()))
+        });
+        let switch = switch?;
+        state.profiles = switch.profiles;
+        if switch.apply {
+            let applied;
+            (state, applied) = self.apply_runtime_change(state);
+            applied?;
         }
+        Self::sync_system_proxy(&mut state)
     }
 
     pub fn replace_profiles(&self, mut profiles: Vec<Profile>) {
@@ -642,15 +1611,265 @@ impl Core {
         }
 
         let mut state = self.inner.lock().expect("core state poisoned");
+        for profile in &mut profiles {
+            if profile.raw_yaml.trim().is_empty()
+                && let Some(entry) = state.profile_cache.load(&profile.id)
+            {
+                profile.raw_yaml = entry.content;
+            }
+        }
         state.profiles = profiles;
     }
-}
+
+    /// Override merged into every profile, see the `profile_override` module
+    /// for the merge rules.
+    pub fn global_override(&self) -> String {
+        let state = self.inner.lock().expect("core state poisoned");
+        state.overrides.load_global()
+    }
+
+    /// Validates and stores the global override; an empty document clears it.
+    /// Takes effect the next time the kernel starts.
+    pub fn set_global_override(&self, content: &str) -> CoreResult<()> {
+        let state = self.inner.lock().expect("core state poisoned");
+        state.overrides.store_global(content)
+    }
+
+    pub fn profile_override(&self, id: &str) -> CoreResult<String> {
+        let state = self.inner.lock().expect("core state poisoned");
+        state.overrides.load_profile(id)
+    }
+
+    /// Validates and stores the override of one profile; an empty document
+    /// clears it. Takes effect the next time the kernel starts.
+    pub fn set_profile_override(&self, id: &str, content: &str) -> CoreResult<()> {
+        let state = self.inner.lock().expect("core state poisoned");
+        if !state.profiles.iter().any(|profile| profile.id == id) {
+            return Err(CoreError::ProfileNotFound);
+        }
+        state.overrides.store_profile(id, content)
+    }
+
+    /// Directory holding the override documents, for editing them by hand.
+    pub fn override_dir(&self) -> std::path::PathBuf {
+        let state = self.inner.lock().expect("core state poisoned");
+        state.overrides.dir().clone()
+    }
+
+    /// Overrides the automatic refresh interval of a profile. `None` follows
+    /// the provider's `profile-update-interval`, `Some(0)` disables it.
+    pub fn set_profile_update_interval(&self, id: &str, hours: Option<u32>) -> CoreResult<()> {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        let profile = state
+            .profiles
+            .iter_mut()
+            .find(|profile| profile.id == id)
+            .ok_or(CoreError::ProfileNotFound)?;
+        profile.refresh.interval_override_hours = hours;
+        info!("profile update interval set: id={id}, hours={hours:?}");
+        Ok(())
+    }
+
+    /// Starts the background thread that refreshes profiles once their update
+    /// interval has elapsed. Calling it again is a no-op.
+    pub fn start_refresh_scheduler(&self) {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        if state.refresh_scheduler_started {
+            return;
+        }
+        state.refresh_scheduler_started = true;
+        refresh_scheduler::spawn(Arc::downgrade(&self.inner));
+    }
+
+    /// Starts the background threads that follow the kernel's traffic and
+    /// memory streams. Calling it again is a no-op.
+    pub fn start_traffic_monitor(&self) {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        if state.traffic_monitor_started {
+            return;
+        }
+        state.traffic_monitor_started = true;
+        traffic_monitor::spawn(Arc::downgrade(&self.inner));
+    }
+
+    /// Recent traffic rates and memory use of the kernel; empty until
+    /// [`Core::start_traffic_monitor`] has been called.
+    pub fn traffic_stats(&self) -> TrafficStats {
+        let state = self.inner.lock().expect("core state poisoned");
+        state.traffic.snapshot()
+    }
+
+    /// Starts the background thread that polls the kernel's connections and
+    /// keeps a history of closed ones. Calling it again is a no-op.
+    pub fn start_connection_tracker(&self) {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        if state.connection_tracker_started {
+            return;
+        }
+        state.connection_tracker_started = true;
+        connection_tracker::spawn(Arc::downgrade(&self.inner));
+    }
+
+    /// Open and recently closed connections; empty until
+    /// [`Core::start_connection_tracker`] has been called.
+    pub fn connections(&self) -> ConnectionsView {
+        let state = self.inner.lock().expect("core state poisoned");
+        state.connections.snapshot()
+    }
+
+    pub fn close_connection(&self, id: &str) -> CoreResult<()> {
+        info!("close connection requested: id={id}");
+        self.running_controller()?.close_connection(id)?;
+        let mut state = self.inner.lock().expect("core state poisoned");
+        state.connections.mark_closed(Some(id), now_unix_seconds());
+        Ok(())
+    }
+
+    pub fn close_all_connections(&self) -> CoreResult<()> {
+        info!("close all connections requested");
+        self.running_controller()?.close_all_connections()?;
+        let mut state = self.inner.lock().expect("core state poisoned");
+        state.connections.mark_closed(None, now_unix_seconds());
+        Ok(())
+    }
+
+    /// Starts the background thread that tails the kernel log. Calling it
+    /// again is a no-op.
+    pub fn start_log_tail(&self) {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        if state.log_tail_started {
+            return;
+        }
+        state.log_tail_started = true;
+        log_tail::spawn(Arc::downgrade(&self.inner));
+    }
+
+    /// Kernel log entries newer than `seq`, or every buffered entry for
+    /// `None`; empty until [`Core::start_log_tail`] has been called.
+    pub fn kernel_logs_since(&self, seq: Option<u64>) -> Vec<LogEntry> {
+        let state = self.inner.lock().expect("core state poisoned");
+        state.logs.since(seq)
+    }
+
+    pub fn kernel_log_path(&self) -> std::path::PathBuf {
+        let state = self.inner.lock().expect("core state poisoned");
+        state.kernel_runtime.log_path()
+    }
+
+    /// Saves a copy of the kernel log into `dest_dir` and returns its path.
+    pub fn export_kernel_log(&self, dest_dir: &Path) -> CoreResult<std::path::PathBuf> {
+        let log_path = self.kernel_log_path();
+        log_tail::export(&log_path, dest_dir)
+    }
+
+    /// Starts the background thread that restarts the kernel after it exits
+    /// unexpectedly, see [`CoreEvent::KernelExited`]. Calling it again is a
+    /// no-op.
+    pub fn start_supervisor(&self) {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        if state.supervisor_started {
+            return;
+        }
+        state.supervisor_started = true;
+        supervisor::spawn(Arc::downgrade(&self.inner));
+    }
+
+    /// Takes the events produced by background work since the last call.
+    pub fn drain_events(&self) -> Vec<CoreEvent> {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        std::mem::take(&mut state.events)
+    }
+
+    fn running_controller(&self) -> CoreResult<ControllerClient> {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        if !state.running || !state.kernel_runtime.is_running() {
+            return Err(CoreError::NotRunning);
+        }
+        state.controller.clone().ok_or_else(|| {
+            CoreError::InvalidConfig("controller endpoint is not configured".to_string())
+        })
+    }
+
+    fn push_event(&self, event: CoreEvent) {
+        let mut state = self.inner.lock().expect("core state poisoned");
+        state.events.push(event);
+    }
+}
+
+/// Notifications raised by background work in the core, see
+/// [`Core::drain_events`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CoreEvent {
+    ProfileRefreshed {
+        id: String,
+        name: String,
+        /// `false` when the source reported the document as unchanged.
+        changed: bool,
+    },
+    ProfileRefreshFailed {
+        id: String,
+        name: String,
+        error: String,
+    },
+    /// The kernel exited without being stopped; a restart follows.
+    KernelExited {
+        status: String,
+        /// Last lines of the kernel log, oldest first.
+        log_tail: Vec<String>,
+    },
+    KernelRestarted {
+        attempts: u32,
+    },
+    /// Restarting the kernel kept failing and the supervisor gave up.
+    KernelRecoveryFailed {
+        error: String,
+        system_proxy_disabled: bool,
+    },
+    GeoDataUpdated {
+        kind: GeoDataKind,
+        version: Option<String>,
+    },
+    GeoDataUpdateFailed {
+        kind: GeoDataKind,
+        error: String,
+    },
+    /// The OS proxy settings no longer match the enabled system proxy; they
+    /// were set again unless another program keeps changing them or
+    /// [`SystemProxySettings::restore_on_drift`] is off.
+    SystemProxyDrifted {
+        reapplied: bool,
+    },
+}
+
+/// Default of [`Config::startup_timeout_secs`].
+const DEFAULT_STARTUP_TIMEOUT_SECS: u64 = 15;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
     pub mode: ProxyMode,
     pub mixed_port: u16,
     pub allow_lan: bool,
+    /// How long a starting kernel may take until its controller and mixed
+    /// port answer.
+    #[serde(default = "default_startup_timeout_secs")]
+    pub startup_timeout_secs: u64,
+    #[serde(default)]
+    pub kernel: KernelKind,
+    /// Releases a kernel upgrade installs.
+    #[serde(default)]
+    pub kernel_channel: KernelChannel,
+    /// Base URLs standing in for `https://github.com` when downloading kernel
+    /// releases, tried in order before GitHub itself.
+    #[serde(default)]
+    pub kernel_mirrors: Vec<String>,
+    #[serde(default)]
+    pub geodata: GeoDataSettings,
+    #[serde(default)]
+    pub system_proxy: SystemProxySettings,
+}
+
+fn default_startup_timeout_secs() -> u64 {
+    DEFAULT_STARTUP_TIMEOUT_SECS
 }
 
 impl Default for Config {
@@ -659,10 +1878,149 @@ impl Default for Config {
             mode: ProxyMode::Rule,
             mixed_port: 7890,
             allow_lan: false,
+            startup_timeout_secs: DEFAULT_STARTUP_TIMEOUT_SECS,
+            kernel: KernelKind::default(),
+            kernel_channel: KernelChannel::default(),
+            kernel_mirrors: Vec::new(),
+            geodata: GeoDataSettings::default(),
+            system_proxy: SystemProxySettings::default(),
         }
     }
 }
 
+/// Where the settings a system proxy replaced are kept while it is enabled,
+/// under the runtime dir.
+const SYSTEM_PROXY_SNAPSHOT_FILE: &str = "system-proxy-snapshot.json";
+
+/// Directory under the runtime dir with the env files terminals source.
+const SHELL_ENV_DIR: &str = "shell";
+/// Marks env files left by a run that ended without removing them.
+const SHELL_PROXY_SNAPSHOT_FILE: &str = "shell-proxy-snapshot.json";
+
+/// Default of [`SystemProxySettings::pac_port`].
+const DEFAULT_PAC_PORT: u16 = 7899;
+
+/// How the OS is pointed at the kernel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SystemProxyMode {
+    /// Every request goes to the proxy endpoints, except the bypass list.
+    #[default]
+    Global,
+    /// The OS loads a PAC script served by Linkpad, which sends the active
+    /// profile's DIRECT domains straight out. Suits apps that ignore manual
+    /// proxy settings but honour PAC.
+    Pac,
+}
+
+/// Where the system proxy sends each protocol and which hosts it leaves
+/// alone. A dedicated port gets its own kernel listener.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SystemProxySettings {
+    pub mode: SystemProxyMode,
+    /// Host names, `*.` domain wildcards and CIDRs that skip the proxy.
+    pub bypass: Vec<String>,
+    /// HTTP listener (`port`); `None` uses the mixed port.
+    pub http_port: Option<u16>,
+    /// HTTP listener for HTTPS traffic; `None` uses the HTTP endpoint.
+    pub https_port: Option<u16>,
+    /// SOCKS listener (`socks-port`); `None` uses the mixed port.
+    pub socks_port: Option<u16>,
+    /// Loopback port the PAC script is served on.
+    pub pac_port: u16,
+    /// Domains, `*` patterns and IPv4 networks the PAC script sends direct,
+    /// besides the profile's DIRECT rules.
+    pub pac_direct: Vec<String>,
+    /// Set the proxy again when another program changes the OS settings.
+    pub restore_on_drift: bool,
+    /// Also export the proxy to terminals through env files sourced from
+    /// the shell rc files, see [`Core::install_shell_hooks`].
+    pub shell_env: bool,
+}
+
+impl Default for SystemProxySettings {
+    fn default() -> Self {
+        Self {
+            mode: SystemProxyMode::default(),
+            bypass: DEFAULT_BYPASS
+                .iter()
+                .map(|entry| entry.to_string())
+                .collect(),
+            http_port: None,
+            https_port: None,
+            socks_port: None,
+            pac_port: DEFAULT_PAC_PORT,
+            pac_direct: Vec::new(),
+            restore_on_drift: true,
+            shell_env: false,
+        }
+    }
+}
+
+impl SystemProxySettings {
+    /// Rejects dedicated ports that collide with the mixed port or with a
+    /// listener of another kind.
+    fn validate(&self, mixed_port: u16) -> CoreResult<()> {
+        let ports = [
+            ("HTTP", self.http_port),
+            ("HTTPS", self.https_port),
+            ("SOCKS", self.socks_port),
+        ];
+        for (name, port) in ports {
+            match port {
+                Some(0) => {
+                    return Err(CoreError::InvalidConfig(format!(
+                        "{name} port must be between 1 and 65535"
+                    )));
+                }
+                Some(port) if port == mixed_port => {
+                    return Err(CoreError::InvalidConfig(format!(
+                        "{name} port {port} is the mixed port; leave it empty to use the mixed port"
+                    )));
+                }
+                _ => {}
+            }
+        }
+        // HTTPS may share the HTTP listener, but SOCKS needs one of its own.
+        if let Some(socks) = self.socks_port
+            && (self.http_port == Some(socks) || self.https_port == Some(socks))
+        {
+            return Err(CoreError::InvalidConfig(format!(
+                "SOCKS port {socks} is already used for HTTP"
+            )));
+        }
+        if self.mode == SystemProxyMode::Pac {
+            let listeners = [
+                Some(mixed_port),
+                self.http_port,
+                self.https_port,
+                self.socks_port,
+            ];
+            if self.pac_port == 0 || listeners.contains(&Some(self.pac_port)) {
+                return Err(CoreError::InvalidConfig(format!(
+                    "PAC port {} must be free and differ from the proxy ports",
+                    self.pac_port
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// System proxy endpoints on the loopback address for `config`.
+fn system_proxy_config(config: &Config) -> SystemProxyConfig {
+    let settings = &config.system_proxy;
+    let http_port = settings.http_port.unwrap_or(config.mixed_port);
+    SystemProxyConfig {
+        http_port,
+        https_port: settings.https_port.unwrap_or(http_port),
+        socks_port: settings.socks_port.unwrap_or(config.mixed_port),
+        ..SystemProxyConfig::new("127.0.0.1", config.mixed_port)
+    }
+    .with_bypass(settings.bypass.clone())
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ProxyMode {
     Rule,
@@ -674,8 +2032,18 @@ pub enum ProxyMode {
 pub struct Profile {
     pub id: String,
     pub name: String,
+    /// URL for remote profiles, absolute path for local files and empty for
+    /// pasted content.
     pub source_url: String,
+    #[serde(default)]
+    pub source_kind: ProfileSourceKind,
     pub updated_at: String,
+    /// Traffic and expiry reported by the provider's `subscription-userinfo`
+    /// header; `None` for providers that do not send it.
+    #[serde(default)]
+    pub subscription: Option<SubscriptionUserinfo>,
+    #[serde(default)]
+    pub refresh: ProfileRefreshState,
     pub node_count: usize,
     pub group_count: usize,
     pub rule_count: usize,
@@ -684,12 +2052,92 @@ pub struct Profile {
     pub proxy_groups: Vec<ProxyGroup>,
     #[serde(default)]
     pub proxy_nodes: Vec<ProxyNode>,
+    #[serde(default, deserialize_with = "rule::deserialize_rules")]
+    pub rules: Vec<Rule>,
+    /// Rule lines of the document that failed to parse and are not listed in
+    /// `rules`.
     #[serde(default)]
-    pub rules: Vec<String>,
+    pub rule_diagnostics: Vec<RuleDiagnostic>,
     #[serde(default, skip_serializing)]
     pub raw_yaml: String,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileSourceKind {
+    #[default]
+    Remote,
+    LocalFile,
+    Inline,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionUserinfo {
+    pub upload: u64,
+    pub download: u64,
+    /// Traffic quota in bytes; `0` means unlimited.
+    pub total: u64,
+    /// Expiry as unix seconds; `None` means the subscription never expires.
+    pub expire: Option<u64>,
+}
+
+impl SubscriptionUserinfo {
+    pub fn used(&self) -> u64 {
+        self.upload.saturating_add(self.download)
+    }
+
+    /// Fraction of the quota already used, or `None` for unlimited plans.
+    pub fn usage_ratio(&self) -> Option<f64> {
+        if self.total == 0 {
+            return None;
+        }
+        Some((self.used() as f64 / self.total as f64).clamp(0.0, 1.0))
+    }
+
+    /// Local calendar date of the expiry, formatted as `%Y-%m-%d`.
+    pub fn expire_date(&self) -> Option<String> {
+        let expire = i64::try_from(self.expire?).ok()?;
+        let utc = chrono::DateTime::from_timestamp(expire, 0)?;
+        Some(utc.with_timezone(&Local).format("%Y-%m-%d").to_string())
+    }
+
+    /// Seconds until expiry at `now`; negative once the subscription expired.
+    pub fn seconds_until_expiry(&self, now: u64) -> Option<i64> {
+        self.expire
+            .map(|expire| i64::try_from(expire).unwrap_or(i64::MAX) - now as i64)
+    }
+}
+
+/// Automatic refresh schedule and the HTTP validators of the last fetch.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileRefreshState {
+    /// Interval announced by the provider's `profile-update-interval` header.
+    pub provider_interval_hours: Option<u32>,
+    /// Interval chosen by the user; `Some(0)` disables automatic refresh.
+    pub interval_override_hours: Option<u32>,
+    /// Unix seconds of the last successful refresh, including not-modified
+    /// responses.
+    pub last_checked_at: Option<u64>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl ProfileRefreshState {
+    /// Effective interval in hours, or `None` when automatic refresh is off.
+    pub fn interval_hours(&self) -> Option<u32> {
+        self.interval_override_hours
+            .or(self.provider_interval_hours)
+            .filter(|hours| *hours > 0)
+    }
+
+    /// Unix seconds at which the next automatic refresh is due.
+    pub fn next_refresh_at(&self) -> Option<u64> {
+        let interval = u64::from(self.interval_hours()?) * 3600;
+        Some(self.last_checked_at.unwrap_or(0).saturating_add(interval))
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProxyGroup {
     pub name: String,
@@ -713,7 +2161,79 @@ struct ParsedProfile {
     rule_count: usize,
     proxy_groups: Vec<ProxyGroup>,
     proxy_nodes: Vec<ProxyNode>,
-    rules: Vec<String>,
+    rules: Vec<Rule>,
+    rule_diagnostics: Vec<RuleDiagnostic>,
+    /// Clash document generated from share links; `None` when the fetched
+    /// content already was a Clash document.
+    generated_yaml: Option<String>,
+}
+
+/// A profile document together with the metadata its source reported.
+#[derive(Debug)]
+struct FetchedProfile {
+    content: String,
+    userinfo: Option<SubscriptionUserinfo>,
+    update_interval_hours: Option<u32>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl FetchedProfile {
+    fn local(content: String) -> Self {
+        Self {
+            content,
+            userinfo: None,
+            update_interval_hours: None,
+            etag: None,
+            last_modified: None,
+        }
+    }
+
+    /// Refresh state after a successful fetch, keeping the user's override.
+    fn refresh_state(&self, interval_override_hours: Option<u32>) -> ProfileRefreshState {
+        ProfileRefreshState {
+            provider_interval_hours: self.update_interval_hours,
+            interval_override_hours,
+            last_checked_at: Some(now_unix_seconds()),
+            etag: self.etag.clone(),
+            last_modified: self.last_modified.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum FetchOutcome {
+    Modified(FetchedProfile),
+    NotModified,
+}
+
+/// Rules of a runtime config, see [`Core::active_rules`].
+struct EffectiveRules {
+    /// The runtime config, for `rule-providers` and `sub-rules`.
+    document: String,
+    rules: Vec<Rule>,
+    diagnostics: Vec<RuleDiagnostic>,
+    groups: Vec<ProxyGroup>,
+}
+
+impl EffectiveRules {
+    fn parse(document: String) -> CoreResult<Self> {
+        let parsed: RawProfileDoc =
+            serde_yaml::from_str(&document).map_err(|error| CoreError::Parse(error.to_string()))?;
+        let proxy_names = collect_proxy_nodes(&parsed.proxies)
+            .into_iter()
+            .map(|node| node.name)
+            .collect();
+        let groups = collect_proxy_groups(&parsed, proxy_names);
+        let rule_lines = collect_rules(&parsed.rules);
+        let (rules, diagnostics) = Rule::parse_all(rule_lines.iter().map(String::as_str));
+        Ok(Self {
+            document,
+            rules,
+            diagnostics,
+            groups,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -746,29 +2266,37 @@ struct RawProxyGroup {
     proxies: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
-struct SubscriptionNode {
-    name: String,
-    kind: String,
-    udp: bool,
+fn fetch_profile_content(source_url: &str) -> CoreResult<FetchedProfile> {
+    match fetch_profile_content_if_modified(source_url, &ProfileRefreshState::default())? {
+        FetchOutcome::Modified(fetched) => Ok(fetched),
+        FetchOutcome::NotModified => {
+            Err(CoreError::Network("unexpected http status 304".to_string()))
+        }
+    }
 }
 
-fn fetch_profile_content(source_url: &str) -> CoreResult<String> {
+/// Fetches a remote profile, sending the validators of `previous` so an
+/// unchanged document only costs a `304 Not Modified`.
+fn fetch_profile_content_if_modified(
+    source_url: &str,
+    previous: &ProfileRefreshState,
+) -> CoreResult<FetchOutcome> {
     let client = reqwest::blocking::Client::builder()
         .timeout(Duration::from_secs(20))
         .build()
         .map_err(|error| CoreError::Network(error.to_string()))?;
 
-    let mut last_body: Option<String> = None;
+    let mut last_fetched: Option<FetchedProfile> = None;
     let mut last_error: Option<CoreError> = None;
 
     for user_agent in ["linkpad/0.1.2", "clash-verge/2.4.0"] {
-        match fetch_profile_content_once(&client, source_url, user_agent) {
-            Ok(body) => {
-                if looks_like_clash_yaml(&body) {
-                    return Ok(body);
+        match fetch_profile_content_once(&client, source_url, user_agent, previous) {
+            Ok(FetchOutcome::NotModified) => return Ok(FetchOutcome::NotModified),
+            Ok(FetchOutcome::Modified(fetched)) => {
+                if looks_like_clash_yaml(&fetched.content) {
+                    return Ok(FetchOutcome::Modified(fetched));
                 }
-                last_body = Some(body);
+                last_fetched = Some(fetched);
             }
             Err(error) => {
                 last_error = Some(error);
@@ -776,8 +2304,8 @@ fn fetch_profile_content(source_url: &str) -> CoreResult<String> {
         }
     }
 
-    if let Some(body) = last_body {
-        Ok(body)
+    if let Some(fetched) = last_fetched {
+        Ok(FetchOutcome::Modified(fetched))
     } else {
         Err(last_error.unwrap_or_else(|| CoreError::Network("failed to fetch profile".to_string())))
     }
@@ -787,18 +2315,29 @@ fn fetch_profile_content_once(
     client: &reqwest::blocking::Client,
     source_url: &str,
     user_agent: &str,
-) -> CoreResult<String> {
-    let response = client
+    previous: &ProfileRefreshState,
+) -> CoreResult<FetchOutcome> {
+    let mut request = client
         .get(source_url)
         .header(reqwest::header::USER_AGENT, user_agent)
         .header(
             reqwest::header::ACCEPT,
             "application/yaml,text/yaml,text/plain,*/*",
-        )
+        );
+    if let Some(etag) = previous.etag.as_deref() {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = previous.last_modified.as_deref() {
+        request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+    }
+    let response = request
         .send()
         .map_err(|error| CoreError::Network(error.to_string()))?;
 
     let status = response.status();
+    if status == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(FetchOutcome::NotModified);
+    }
     if !status.is_success() {
         return Err(CoreError::Network(format!(
             "http status {}",
@@ -806,9 +2345,90 @@ fn fetch_profile_content_once(
         )));
     }
 
-    response
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let userinfo = header("subscription-userinfo")
+        .as_deref()
+        .and_then(parse_subscription_userinfo);
+    let update_interval_hours = header("profile-update-interval")
+        .as_deref()
+        .and_then(parse_update_interval);
+    let etag = header("etag");
+    let last_modified = header("last-modified");
+    let content = response
         .text()
-        .map_err(|error| CoreError::Network(error.to_string()))
+        .map_err(|error| CoreError::Network(error.to_string()))?;
+    Ok(FetchOutcome::Modified(FetchedProfile {
+        content,
+        userinfo,
+        update_interval_hours,
+        etag,
+        last_modified,
+    }))
+}
+
+/// Parses `profile-update-interval`, an interval in hours.
+fn parse_update_interval(value: &str) -> Option<u32> {
+    let value = value.trim();
+    let hours = value
+        .parse::<u32>()
+        .ok()
+        .or_else(|| value.parse::<f64>().ok().map(|float| float.round() as u32))?;
+    (hours > 0).then_some(hours)
+}
+
+/// Parses `upload=1; download=2; total=3; expire=4`. Unknown keys are ignored
+/// and some providers send the numbers as floats.
+fn parse_subscription_userinfo(header: &str) -> Option<SubscriptionUserinfo> {
+    let mut userinfo = SubscriptionUserinfo::default();
+    let mut matched = false;
+    for part in header.split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        let number = value
+            .parse::<u64>()
+            .ok()
+            .or_else(|| value.parse::<f64>().ok().map(|float| float.max(0.0) as u64));
+        let Some(number) = number else {
+            continue;
+        };
+        match key.trim().to_ascii_lowercase().as_str() {
+            "upload" => userinfo.upload = number,
+            "download" => userinfo.download = number,
+            "total" => userinfo.total = number,
+            "expire" => userinfo.expire = (number > 0).then_some(number),
+            _ => continue,
+        }
+        matched = true;
+    }
+    matched.then_some(userinfo)
+}
+
+fn read_profile_file(path: &Path) -> CoreResult<String> {
+    let content = fs::read_to_string(path).map_err(|error| {
+        CoreError::InvalidProfile(format!("failed to read `{}`: {error}", path.display()))
+    })?;
+    if content.trim().is_empty() {
+        return Err(CoreError::InvalidProfile(format!(
+            "`{}` is empty",
+            path.display()
+        )));
+    }
+    Ok(content)
+}
+
+fn profile_name_from_path(path: &Path) -> Option<String> {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().trim().to_string())
+        .filter(|stem| !stem.is_empty())
 }
 
 fn looks_like_clash_yaml(content: &str) -> bool {
@@ -843,7 +2463,36 @@ fn parse_clash_yaml_profile(source_url: &str, content: &str) -> CoreResult<Parse
         .iter()
         .map(|node| node.name.clone())
         .collect::<Vec<_>>();
+    let groups = collect_proxy_groups(&parsed, all_proxy_names);
+    let name = profile_name_from_source(source_url, &parsed);
+
+    let rule_lines = collect_rules(&parsed.rules);
+    let (rules, rule_diagnostics) = Rule::parse_all(rule_lines.iter().map(String::as_str));
+    for diagnostic in &rule_diagnostics {
+        warn!(
+            "skipping rule #{} `{}`: {}",
+            diagnostic.index + 1,
+            diagnostic.line,
+            diagnostic.message
+        );
+    }
+
+    Ok(ParsedProfile {
+        name,
+        node_count: parsed.proxies.len(),
+        group_count: groups.len(),
+        rule_count: rules.len(),
+        proxy_groups: groups,
+        proxy_nodes,
+        rules,
+        rule_diagnostics,
+        generated_yaml: None,
+    })
+}
 
+/// Proxy groups of a document; one `default` group over every proxy when
+/// it has none.
+fn collect_proxy_groups(parsed: &RawProfileDoc, all_proxy_names: Vec<String>) -> Vec<ProxyGroup> {
     let mut groups: Vec<ProxyGroup> = parsed
         .proxy_groups
         .iter()
@@ -869,19 +2518,7 @@ fn parse_clash_yaml_profile(source_url: &str, content: &str) -> CoreResult<Parse
         });
     }
 
-    let name = profile_name_from_source(source_url, &parsed);
-
-    let rules = collect_rules(&parsed.rules);
-
-    Ok(ParsedProfile {
-        name,
-        node_count: parsed.proxies.len(),
-        group_count: groups.len(),
-        rule_count: rules.len(),
-        proxy_groups: groups,
-        proxy_nodes,
-        rules,
-    })
+    groups
 }
 
 fn parse_subscription_profile(source_url: &str, content: &str) -> CoreResult<ParsedProfile> {
@@ -904,100 +2541,38 @@ fn parse_subscription_profile(source_url: &str, content: &str) -> CoreResult<Par
 }
 
 fn parse_subscription_text(source_url: &str, text: &str) -> Option<ParsedProfile> {
-    let nodes = extract_subscription_nodes(text);
-    if nodes.is_empty() {
+    let proxies = extract_subscription_proxies(text);
+    if proxies.is_empty() {
         return None;
     }
 
-    let mut grouped: BTreeMap<String, Vec<String>> = BTreeMap::new();
-    for node in &nodes {
-        grouped
-            .entry(node.kind.clone())
-            .or_default()
-            .push(node.name.clone());
-    }
-
-    let all_proxies = nodes
-        .iter()
-        .map(|node| node.name.clone())
-        .collect::<Vec<_>>();
-    let proxy_nodes = nodes
-        .iter()
-        .map(|node| ProxyNode {
-            name: node.name.clone(),
-            kind: normalize_kind(&node.kind),
-            udp: node.udp || kind_supports_udp(&node.kind),
-        })
-        .collect::<Vec<_>>();
-
-    let mut groups = Vec::with_capacity(grouped.len() + 1);
-    groups.push(ProxyGroup {
-        name: "All Proxies".to_string(),
-        kind: "select".to_string(),
-        size: nodes.len(),
-        proxies: all_proxies,
-    });
-    for (kind, proxies) in grouped {
-        groups.push(ProxyGroup {
-            name: format!("{} Nodes", kind.to_uppercase()),
-            kind: kind.clone(),
-            size: proxies.len(),
-            proxies,
-        });
-    }
-
-    Some(ParsedProfile {
-        name: profile_name_from_subscription(source_url, &nodes),
-        node_count: nodes.len(),
-        group_count: groups.len(),
-        rule_count: 0,
-        proxy_groups: groups,
-        proxy_nodes,
-        rules: Vec::new(),
-    })
+    let document = match build_subscription_document(&proxies) {
+        Ok(document) => document,
+        Err(error) => {
+            warn!("failed to generate subscription profile: {error}");
+            return None;
+        }
+    };
+    let mut parsed = parse_clash_yaml_profile(source_url, &document).ok()?;
+    parsed.name = profile_name_from_subscription(source_url, &proxies);
+    parsed.generated_yaml = Some(document);
+    Some(parsed)
 }
 
-fn extract_subscription_nodes(text: &str) -> Vec<SubscriptionNode> {
-    let mut nodes = Vec::new();
+fn extract_subscription_proxies(text: &str) -> Vec<ShareLinkProxy> {
+    let mut proxies = Vec::new();
     for raw_line in text.lines() {
         let line = raw_line.trim();
         if line.is_empty() || line.starts_with('#') || !looks_like_proxy_uri(line) {
             continue;
         }
 
-        let kind = line
-            .split("://")
-            .next()
-            .unwrap_or("proxy")
-            .to_ascii_lowercase();
-        let name = parse_subscription_node_name(line)
-            .unwrap_or_else(|| format!("{}-{}", kind.to_uppercase(), nodes.len() + 1));
-        let udp = kind_supports_udp(&kind);
-        nodes.push(SubscriptionNode { name, kind, udp });
-    }
-    nodes
-}
-
-fn parse_subscription_node_name(line: &str) -> Option<String> {
-    if let Ok(url) = url::Url::parse(line) {
-        if let Some(fragment) = url.fragment() {
-            let name = percent_decode_str(fragment).decode_utf8_lossy();
-            let trimmed = name.trim();
-            if !trimmed.is_empty() {
-                return Some(trimmed.to_string());
-            }
-        }
-    }
-
-    if let Some((_, fragment)) = line.split_once('#') {
-        let name = percent_decode_str(fragment).decode_utf8_lossy();
-        let trimmed = name.trim();
-        if !trimmed.is_empty() {
-            return Some(trimmed.to_string());
+        match decode_share_link(line) {
+            Ok(proxy) => proxies.push(proxy),
+            Err(error) => warn!("skipping subscription entry: {error}"),
         }
     }
-
-    None
+    proxies
 }
 
 fn looks_like_proxy_uri(line: &str) -> bool {
@@ -1139,14 +2714,14 @@ fn kind_supports_udp(kind: &str) -> bool {
     )
 }
 
-fn profile_name_from_subscription(source_url: &str, nodes: &[SubscriptionNode]) -> String {
+fn profile_name_from_subscription(source_url: &str, proxies: &[ShareLinkProxy]) -> String {
     if let Ok(url) = url::Url::parse(source_url) {
         if let Some(host) = url.host_str() {
             return host.to_string();
         }
     }
 
-    if let Some(first) = nodes.first() {
+    if let Some(first) = proxies.first() {
         let trimmed = first.name.trim();
         if !trimmed.is_empty() {
             return trimmed.to_string();
@@ -1176,16 +2751,57 @@ fn validate_proxy_selection(
     Ok(())
 }
 
-fn build_runtime_config_from_profile(profile: &Profile, config: &Config) -> CoreResult<String> {
-    let profile_yaml = if profile.raw_yaml.trim().is_empty() {
-        fetch_profile_content(&profile.source_url)?
-    } else {
-        profile.raw_yaml.clone()
+/// Returns the document the kernel should run for `profile`: the in-memory
+/// copy first, then the on-disk cache. Without either, the profile has to be
+/// refreshed first.
+fn load_profile_document(cache: &ProfileCache, profile: &Profile) -> CoreResult<String> {
+    if !profile.raw_yaml.trim().is_empty() {
+        return Ok(profile.raw_yaml.clone());
+    }
+    if let Some(entry) = cache.load(&profile.id) {
+        return Ok(entry.content);
+    }
+
+    // Callers hold the core lock, so there is no fetching here; a refresh
+    // downloads the document and caches it again.
+    warn!("profile {} has no cached content", profile.id);
+    Err(CoreError::InvalidProfile(format!(
+        "profile `{}` has no cached content; refresh it to load it again",
+        profile.name
+    )))
+}
+
+/// Top-level keys mihomo only picks up when it starts: its listeners and the
+/// controller it is managed through.
+const RESTART_REQUIRED_KEYS: [&str; 13] = [
+    "port",
+    "socks-port",
+    "redir-port",
+    "tproxy-port",
+    "mixed-port",
+    "allow-lan",
+    "bind-address",
+    "listeners",
+    "tun",
+    "external-controller",
+    "external-controller-tls",
+    "external-controller-unix",
+    "secret",
+];
+
+/// Whether going from the runtime config `previous` to `next` needs a kernel
+/// restart rather than a reload.
+fn requires_restart(previous: &str, next: &str) -> bool {
+    let parse = |yaml: &str| serde_yaml::from_str::<serde_yaml::Value>(yaml).ok();
+    let (Some(previous), Some(next)) = (parse(previous), parse(next)) else {
+        return true;
     };
-    build_runtime_config_yaml(&profile_yaml, config)
+    RESTART_REQUIRED_KEYS
+        .iter()
+        .any(|key| previous.get(key) != next.get(key))
 }
 
-fn extract_controller_config(runtime_config_yaml: &str) -> CoreResult<ControllerConfig> {
+fn controller_client_from_config(runtime_config_yaml: &str) -> CoreResult<ControllerClient> {
     let root_value: serde_yaml::Value = serde_yaml::from_str(runtime_config_yaml)
         .map_err(|error| CoreError::Parse(error.to_string()))?;
     let root = root_value.as_mapping().ok_or_else(|| {
@@ -1196,200 +2812,29 @@ fn extract_controller_config(runtime_config_yaml: &str) -> CoreResult<Controller
         .get(serde_yaml::Value::String("external-controller".to_string()))
         .and_then(serde_yaml::Value::as_str)
         .unwrap_or("127.0.0.1:9097");
-    let base_url = normalize_controller_url(controller)?;
-
     let secret = root
         .get(serde_yaml::Value::String("secret".to_string()))
         .and_then(serde_yaml::Value::as_str)
-        .map(|value| value.trim().to_string())
-        .filter(|value| !value.is_empty());
-
-    Ok(ControllerConfig { base_url, secret })
-}
-
-fn normalize_controller_url(raw: &str) -> CoreResult<String> {
-    let trimmed = raw.trim().trim_end_matches('/');
-    if trimmed.is_empty() {
-        return Err(CoreError::InvalidConfig(
-            "external-controller is empty".to_string(),
-        ));
-    }
-    let with_scheme = if trimmed.contains("://") {
-        trimmed.to_string()
-    } else {
-        format!("http://{trimmed}")
-    };
-    let parsed = url::Url::parse(&with_scheme).map_err(|error| {
-        CoreError::InvalidConfig(format!("invalid external-controller: {error}"))
-    })?;
-    let mut normalized = parsed.to_string();
-    if normalized.ends_with('/') {
-        normalized.pop();
-    }
-    Ok(normalized)
-}
-
-fn send_proxy_selection_request(
-    controller: &ControllerConfig,
-    group_name: &str,
-    proxy_name: &str,
-) -> CoreResult<()> {
-    let encoded_group = utf8_percent_encode(group_name, NON_ALPHANUMERIC).to_string();
-    let endpoint = format!("{}/proxies/{}", controller.base_url, encoded_group);
-    let body = format!(
-        "{{\"name\":\"{}\"}}",
-        proxy_name.replace('\\', "\\\\").replace('"', "\\\"")
-    );
-
-    let client = reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .map_err(|error| CoreError::Network(error.to_string()))?;
-
-    let mut request = client
-        .put(&endpoint)
-        .header("Content-Type", "application/json")
-        .body(body);
-    if let Some(secret) = controller.secret.as_ref() {
-        request = request.bearer_auth(secret);
-    }
-
-    let response = request
-        .send()
-        .map_err(|error| CoreError::Network(error.to_string()))?;
-    if response.status().is_success() {
-        return Ok(());
-    }
-
-    let status = response.status();
-    let detail = response.text().unwrap_or_default();
-    let detail = detail.trim();
-    if detail.is_empty() {
-        Err(CoreError::InvalidConfig(format!(
-            "controller request failed: {status}"
-        )))
-    } else {
-        Err(CoreError::InvalidConfig(format!(
-            "controller request failed: {status} {detail}"
-        )))
-    }
-}
-
-fn fetch_proxy_selection_map_from_controller(
-    controller: &ControllerConfig,
-) -> CoreResult<BTreeMap<String, String>> {
-    let endpoint = format!("{}/proxies", controller.base_url);
-
-    let client = reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .map_err(|error| CoreError::Network(error.to_string()))?;
-
-    let mut request = client.get(&endpoint);
-    if let Some(secret) = controller.secret.as_ref() {
-        request = request.bearer_auth(secret);
-    }
-
-    let response = request
-        .send()
-        .map_err(|error| CoreError::Network(error.to_string()))?;
-    let status = response.status();
-    let body = response
-        .text()
-        .map_err(|error| CoreError::Network(error.to_string()))?;
-    if !status.is_success() {
-        let detail = body.trim();
-        if detail.is_empty() {
-            return Err(CoreError::InvalidConfig(format!(
-                "controller request failed: {status}"
-            )));
-        }
-        return Err(CoreError::InvalidConfig(format!(
-            "controller request failed: {status} {detail}"
-        )));
-    }
-
-    parse_proxy_selection_map_response(&body)
-}
-
-fn send_mode_update_request(controller: &ControllerConfig, mode: ProxyMode) -> CoreResult<()> {
-    let endpoint = format!("{}/configs", controller.base_url);
-    let body = format!("{{\"mode\":\"{}\"}}", proxy_mode_name(&mode));
+        .map(|value| value.trim().to_string());
 
-    let client = reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .map_err(|error| CoreError::Network(error.to_string()))?;
-
-    let mut request = client
-        .patch(&endpoint)
-        .header("Content-Type", "application/json")
-        .body(body);
-    if let Some(secret) = controller.secret.as_ref() {
-        request = request.bearer_auth(secret);
-    }
-
-    let response = request
-        .send()
-        .map_err(|error| CoreError::Network(error.to_string()))?;
-    if response.status().is_success() {
-        return Ok(());
-    }
-
-    let status = response.status();
-    let detail = response.text().unwrap_or_default();
-    let detail = detail.trim();
-    if detail.is_empty() {
-        Err(CoreError::InvalidConfig(format!(
-            "controller request failed: {status}"
-        )))
-    } else {
-        Err(CoreError::InvalidConfig(format!(
-            "controller request failed: {status} {detail}"
-        )))
-    }
+    ControllerClient::new(controller, secret)
 }
 
-fn fetch_mode_from_controller(controller: &ControllerConfig) -> CoreResult<ProxyMode> {
-    let endpoint = format!("{}/configs", controller.base_url);
-
-    let client = reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .map_err(|error| CoreError::Network(error.to_string()))?;
-
-    let mut request = client.get(&endpoint);
-    if let Some(secret) = controller.secret.as_ref() {
-        request = request.bearer_auth(secret);
-    }
+const DEFAULT_DELAY_TEST_URL: &str = "http://www.gstatic.com/generate_204";
+const DEFAULT_DELAY_TIMEOUT_MS: u32 = 4_000;
 
-    let response = request
-        .send()
-        .map_err(|error| CoreError::Network(error.to_string()))?;
-    let status = response.status();
-    let body = response
-        .text()
-        .map_err(|error| CoreError::Network(error.to_string()))?;
-    if !status.is_success() {
-        let detail = body.trim();
-        if detail.is_empty() {
-            return Err(CoreError::InvalidConfig(format!(
-                "controller request failed: {status}"
-            )));
-        }
-        return Err(CoreError::InvalidConfig(format!(
-            "controller request failed: {status} {detail}"
-        )));
+/// Latency of one proxy; a failed test (the controller answers with an error
+/// status) counts as a timeout.
+fn probe_delay(controller: &ControllerClient, proxy_name: &str) -> CoreResult<Option<u32>> {
+    match controller.proxy_delay(proxy_name, DEFAULT_DELAY_TEST_URL, DEFAULT_DELAY_TIMEOUT_MS) {
+        Ok(delay) => Ok(delay),
+        Err(ControllerError::Status { .. }) => Ok(None),
+        Err(error) => Err(error.into()),
     }
-
-    parse_mode_response(&body)
 }
 
-const DEFAULT_DELAY_TEST_URL: &str = "http://www.gstatic.com/generate_204";
-const DEFAULT_DELAY_TIMEOUT_MS: u32 = 4_000;
-
-fn fetch_proxy_delays_from_controller(
-    controller: &ControllerConfig,
+fn probe_delays(
+    controller: &ControllerClient,
     proxy_names: &[String],
 ) -> CoreResult<BTreeMap<String, Option<u32>>> {
     let mut result = BTreeMap::new();
@@ -1397,12 +2842,7 @@ fn fetch_proxy_delays_from_controller(
     let mut last_error: Option<CoreError> = None;
 
     for proxy_name in proxy_names {
-        match fetch_proxy_delay_from_controller(
-            controller,
-            proxy_name,
-            DEFAULT_DELAY_TEST_URL,
-            DEFAULT_DELAY_TIMEOUT_MS,
-        ) {
+        match probe_delay(controller, proxy_name) {
             Ok(delay) => {
                 if delay.is_some() {
                     success_count += 1;
@@ -1425,89 +2865,21 @@ fn fetch_proxy_delays_from_controller(
     Ok(result)
 }
 
-fn fetch_proxy_delay_from_controller(
-    controller: &ControllerConfig,
-    proxy_name: &str,
-    url: &str,
-    timeout_ms: u32,
-) -> CoreResult<Option<u32>> {
-    let encoded_proxy = utf8_percent_encode(proxy_name, NON_ALPHANUMERIC).to_string();
-    let encoded_url = utf8_percent_encode(url, NON_ALPHANUMERIC).to_string();
-    let endpoint = format!(
-        "{}/proxies/{}/delay?url={}&timeout={}",
-        controller.base_url, encoded_proxy, encoded_url, timeout_ms
-    );
-
-    let client = reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(8))
-        .build()
-        .map_err(|error| CoreError::Network(error.to_string()))?;
-
-    let mut request = client.get(&endpoint);
-    if let Some(secret) = controller.secret.as_ref() {
-        request = request.bearer_auth(secret);
-    }
-
-    let response = request
-        .send()
-        .map_err(|error| CoreError::Network(error.to_string()))?;
-    let status = response.status();
-    let body = response
-        .text()
-        .map_err(|error| CoreError::Network(error.to_string()))?;
-    if !status.is_success() {
-        return Ok(None);
-    }
-
-    parse_delay_response(&body)
-}
-
-fn parse_proxy_selection_map_response(body: &str) -> CoreResult<BTreeMap<String, String>> {
-    let payload: ControllerProxyListResponse =
-        serde_json::from_str(body).map_err(|error| CoreError::Parse(error.to_string()))?;
-
-    let mut selections = BTreeMap::new();
-    for (group_name, state) in payload.proxies {
-        let now = state.now.trim();
-        if now.is_empty() {
-            continue;
-        }
-        if state.all.is_empty() || state.all.iter().any(|item| item == now) {
-            selections.insert(group_name, now.to_string());
-        }
-    }
-    Ok(selections)
-}
-
-fn parse_mode_response(body: &str) -> CoreResult<ProxyMode> {
-    let payload: ControllerRuntimeConfigResponse =
-        serde_json::from_str(body).map_err(|error| CoreError::Parse(error.to_string()))?;
-    parse_proxy_mode(&payload.mode).ok_or_else(|| {
-        CoreError::Parse(format!(
-            "invalid mode in controller response: {}",
-            payload.mode
-        ))
-    })
-}
-
-fn parse_delay_response(body: &str) -> CoreResult<Option<u32>> {
-    let payload: ControllerDelayResponse =
-        serde_json::from_str(body).map_err(|error| CoreError::Parse(error.to_string()))?;
-    if payload.delay < 0 {
-        return Ok(None);
-    }
-
-    let delay = u32::try_from(payload.delay)
-        .map_err(|_| CoreError::Parse(format!("invalid delay value: {}", payload.delay)))?;
-    Ok(Some(delay))
-}
-
-fn build_runtime_config_yaml(profile_yaml: &str, config: &Config) -> CoreResult<String> {
+/// Builds `runtime.yaml`: the profile, then each override layer in order, then
+/// the settings Linkpad owns.
+fn build_runtime_config_yaml(
+    profile_yaml: &str,
+    overrides: &[String],
+    config: &Config,
+) -> CoreResult<String> {
     let mut root_value: serde_yaml::Value =
         serde_yaml::from_str(profile_yaml).map_err(|error| CoreError::Parse(error.to_string()))?;
     let root = root_value.as_mapping_mut().ok_or_else(|| {
         CoreError::InvalidConfig("mihomo config root must be a YAML mapping".to_string())
     })?;
+    for layer in overrides {
+        apply_override(root, layer)?;
+    }
 
     set_mapping_value(
         root,
@@ -1527,10 +2899,92 @@ fn build_runtime_config_yaml(profile_yaml: &str, config: &Config) -> CoreResult<
             serde_yaml::Value::String("127.0.0.1:9097".to_string()),
         );
     }
+    set_geox_urls(root, &config.geodata);
+    set_proxy_listeners(root, config);
 
     serde_yaml::to_string(&root_value).map_err(|error| CoreError::InvalidConfig(error.to_string()))
 }
 
+/// Points mihomo at the same geodata URLs Linkpad updates from, so a file it
+/// has to fetch itself matches, and leaves updating to Linkpad.
+fn set_geox_urls(root: &mut serde_yaml::Mapping, settings: &GeoDataSettings) {
+    let key = serde_yaml::Value::String("geox-url".to_string());
+    let mut urls = match root.remove(&key) {
+        Some(serde_yaml::Value::Mapping(urls)) => urls,
+        _ => serde_yaml::Mapping::new(),
+    };
+    for kind in GeoDataKind::ALL {
+        let url = settings.url(kind).trim();
+        if !url.is_empty() {
+            set_mapping_value(
+                &mut urls,
+                kind.geox_key(),
+                serde_yaml::Value::String(url.to_string()),
+            );
+        }
+    }
+    root.insert(key, serde_yaml::Value::Mapping(urls));
+    set_mapping_value(root, "geo-auto-update", serde_yaml::Value::Bool(false));
+}
+
+/// Name of the listener opened for a dedicated HTTPS port.
+const HTTPS_LISTENER_NAME: &str = "linkpad-https";
+
+/// Opens the listeners dedicated system proxy ports point at.
+fn set_proxy_listeners(root: &mut serde_yaml::Mapping, config: &Config) {
+    let settings = &config.system_proxy;
+    if let Some(port) = settings.http_port {
+        set_mapping_value(root, "port", serde_yaml::Value::Number(port.into()));
+    }
+    if let Some(port) = settings.socks_port {
+        set_mapping_value(root, "socks-port", serde_yaml::Value::Number(port.into()));
+    }
+    let Some(https_port) = settings
+        .https_port
+        .filter(|port| settings.http_port != Some(*port))
+    else {
+        return;
+    };
+    let key = serde_yaml::Value::String("listeners".to_string());
+    let mut listeners = match root.remove(&key) {
+        Some(serde_yaml::Value::Sequence(listeners)) => listeners,
+        _ => Vec::new(),
+    };
+    listeners.retain(|listener| {
+        listener.get("name").and_then(serde_yaml::Value::as_str) != Some(HTTPS_LISTENER_NAME)
+    });
+    let mut listener = serde_yaml::Mapping::new();
+    set_mapping_value(
+        &mut listener,
+        "name",
+        serde_yaml::Value::String(HTTPS_LISTENER_NAME.to_string()),
+    );
+    set_mapping_value(
+        &mut listener,
+        "type",
+        serde_yaml::Value::String("http".to_string()),
+    );
+    set_mapping_value(
+        &mut listener,
+        "listen",
+        serde_yaml::Value::String(
+            if config.allow_lan {
+                "0.0.0.0"
+            } else {
+                "127.0.0.1"
+            }
+            .to_string(),
+        ),
+    );
+    set_mapping_value(
+        &mut listener,
+        "port",
+        serde_yaml::Value::Number(https_port.into()),
+    );
+    listeners.push(serde_yaml::Value::Mapping(listener));
+    root.insert(key, serde_yaml::Value::Sequence(listeners));
+}
+
 fn set_mapping_value(root: &mut serde_yaml::Mapping, key: &str, value: serde_yaml::Value) {
     root.insert(serde_yaml::Value::String(key.to_string()), value);
 }
@@ -1602,7 +3056,149 @@ rules:
         assert_eq!(parsed.group_count, 1);
         assert_eq!(parsed.rule_count, 1);
         assert_eq!(parsed.proxy_groups[0].proxies, vec!["node-1".to_string()]);
-        assert_eq!(parsed.rules, vec!["MATCH,auto".to_string()]);
+        assert_eq!(parsed.rules[0].to_string(), "MATCH,auto");
+        assert_eq!(parsed.rules[0].target, "auto");
+    }
+
+    const NODE_1: &str = "  - { name: \"node-1\", type: ss, server: \"example.com\", port: 443, cipher: aes-128-gcm, password: \"pwd\" }\n";
+    const NODE_2: &str = "  - { name: \"node-2\", type: ss, server: \"example.org\", port: 443, cipher: aes-128-gcm, password: \"pwd\" }\n";
+
+    /// A core whose profile cache lives in a fresh temp dir, also returned.
+    fn core_in_temp_dir(name: &str) -> (Core, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("linkpad-core-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+        let state = CoreState {
+            profile_cache: ProfileCache::new(dir.join("profiles")),
+            overrides: OverrideStore::new(dir.join("overrides")),
+            ..CoreState::default()
+        };
+        let core = Core {
+            inner: Arc::new(Mutex::new(state)),
+        };
+        (core, dir)
+    }
+
+    #[test]
+    fn imports_profile_file_and_rereads_it_on_refresh() {
+        let (core, dir) = core_in_temp_dir("import-file");
+        fs::create_dir_all(dir.join("sub")).expect("create sub dir");
+        let file = dir.join("work.yaml");
+        fs::write(&file, format!("proxies:\n{NODE_1}")).expect("write profile");
+        let canonical = fs::canonicalize(&file).expect("canonical path");
+        let canonical = canonical.to_string_lossy();
+
+        let imported = core
+            .import_profile_file(&dir.join("sub/../work.yaml"), false)
+            .expect("import file");
+        assert_eq!(imported.source_kind, ProfileSourceKind::LocalFile);
+        assert_eq!(imported.source_url, canonical);
+        assert_eq!(imported.name, "work");
+        assert_eq!(imported.node_count, 1);
+        assert!(imported.active);
+        let hash = |id: &str| id.rsplit('-').next().map(str::to_string);
+        assert_eq!(hash(&imported.id), hash(&build_profile_id(&canonical)));
+
+        // The same file through another path updates the profile in place.
+        let again = core
+            .import_profile_file(&file, false)
+            .expect("import again");
+        assert_eq!(again.id, imported.id);
+        assert_eq!(core.profiles().len(), 1);
+
+        fs::write(&file, format!("proxies:\n{NODE_1}{NODE_2}")).expect("rewrite profile");
+        let refreshed = core.refresh_profile(&imported.id).expect("refresh");
+        assert_eq!(refreshed.node_count, 2);
+        assert_eq!(refreshed.name, "work");
+
+        assert!(
+            core.import_profile_file(&dir.join("missing.yaml"), false)
+                .is_err()
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn imports_pasted_profile_text() {
+        let (core, dir) = core_in_temp_dir("import-text");
+        let content = format!("proxies:\n{NODE_1}");
+
+        let unnamed = core
+            .import_profile_text("  ", &content, false)
+            .expect("import text");
+        assert_eq!(unnamed.source_kind, ProfileSourceKind::Inline);
+        assert_eq!(unnamed.name, "node-1");
+        let named = core
+            .import_profile_text("Pasted", &content, false)
+            .expect("import named text");
+        assert_eq!(named.name, "Pasted");
+        // Every paste is a profile of its own.
+        assert_ne!(named.id, unnamed.id);
+        assert_eq!(core.profiles().len(), 2);
+
+        // Refreshing reads the cached copy back.
+        let refreshed = core.refresh_profile(&named.id).expect("refresh");
+        assert_eq!(refreshed.node_count, 1);
+
+        assert!(matches!(
+            core.import_profile_text("Empty", " \n", false),
+            Err(CoreError::InvalidProfile(_))
+        ));
+        assert_eq!(core.profiles().len(), 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn asks_for_a_refresh_when_the_document_is_gone() {
+        let (core, dir) = core_in_temp_dir("document-gone");
+        core.import_profile_text("Gone", &format!("proxies:\n{NODE_1}"), true)
+            .expect("import");
+        let mut state = core.inner.lock().unwrap();
+        state.profiles[0].raw_yaml.clear();
+        state.profile_cache = ProfileCache::new(dir.join("empty"));
+
+        let config = state.config.clone();
+        let Err(CoreError::InvalidProfile(message)) =
+            Core::runtime_config_for(&state, &state.profiles, &config)
+        else {
+            panic!("a missing document should not be fetched");
+        };
+        assert!(message.contains("refresh"));
+        drop(state);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn matches_rules_with_override_layers_applied() {
+        let (core, dir) = core_in_temp_dir("match-overrides");
+        let content = format!(
+            "proxies:\n{NODE_1}rules:\n  - DOMAIN-SUFFIX,example.com,node-1\n  - MATCH,DIRECT\n"
+        );
+        let profile = core
+            .import_profile_text("Rules", &content, true)
+            .expect("import");
+        core.set_profile_override(
+            &profile.id,
+            "prepend-rules:\n  - DOMAIN,www.example.com,REJECT\n",
+        )
+        .expect("store override");
+
+        let rules = core.active_rules();
+        assert_eq!(rules.len(), 3);
+        assert_eq!(rules[0].to_string(), "DOMAIN,www.example.com,REJECT");
+
+        let found = core
+            .match_rule("www.example.com", None, None)
+            .expect("match")
+            .expect("a rule matches");
+        assert_eq!(found.index, 0);
+        assert_eq!(found.policy_chain, vec!["REJECT".to_string()]);
+        let found = core
+            .match_rule("api.example.com", Some(443), None)
+            .expect("match")
+            .expect("a rule matches");
+        assert_eq!(found.index, 1);
+        let _ = fs::remove_dir_all(&dir);
     }
 
     #[test]
@@ -1614,51 +3210,98 @@ rules:
             .expect("should parse base64 subscription");
         assert_eq!(parsed.node_count, 2);
         assert!(parsed.group_count >= 1);
-        assert_eq!(parsed.rule_count, 0);
+        assert_eq!(parsed.rule_count, 1);
         assert_eq!(parsed.proxy_groups[0].proxies.len(), 2);
-        assert!(parsed.rules.is_empty());
+        assert_eq!(parsed.rules[0].to_string(), "MATCH,All Proxies");
+        assert_eq!(parsed.name, "example.com");
+
+        let generated = parsed
+            .generated_yaml
+            .expect("subscription should produce a clash document");
+        let runtime = build_runtime_config_yaml(&generated, &[], &Config::default())
+            .expect("generated document should build a runtime config");
+        assert!(runtime.contains("cipher: aes-128-gcm"));
     }
 
     #[test]
-    fn parses_proxy_selection_map_response() {
-        let body = r#"{
-            "proxies": {
-                "GLOBAL": {"now": "DIRECT", "all": ["DIRECT", "Proxy"]},
-                "MyGroup": {"now": "Proxy A", "all": ["Proxy A", "Proxy B"]},
-                "NoMembers": {"now": "X", "all": []},
-                "NoNow": {"all": ["A", "B"]}
-            }
-        }"#;
-        let map =
-            parse_proxy_selection_map_response(body).expect("should parse controller payload");
-        assert_eq!(map.get("GLOBAL"), Some(&"DIRECT".to_string()));
-        assert_eq!(map.get("MyGroup"), Some(&"Proxy A".to_string()));
-        assert_eq!(map.get("NoMembers"), Some(&"X".to_string()));
-        assert!(!map.contains_key("NoNow"));
+    fn opens_listeners_for_dedicated_proxy_ports() {
+        let mut config = Config::default();
+        config.system_proxy.socks_port = Some(7891);
+        config.system_proxy.https_port = Some(7892);
+        config
+            .system_proxy
+            .validate(config.mixed_port)
+            .expect("valid");
+        let proxy = system_proxy_config(&config);
+        assert_eq!(
+            (proxy.http_port, proxy.https_port, proxy.socks_port),
+            (7890, 7892, 7891)
+        );
+        assert!(proxy.bypass.iter().any(|entry| entry == "192.168.0.0/16"));
+
+        let profile = "listeners:\n  - { name: linkpad-https, type: http, port: 1 }\n";
+        let runtime = build_runtime_config_yaml(profile, &[], &config).expect("runtime config");
+        let value: serde_yaml::Value = serde_yaml::from_str(&runtime).expect("yaml");
+        assert_eq!(value["socks-port"], 7891);
+        assert!(value.get("port").is_none());
+        let listeners = value["listeners"].as_sequence().expect("listeners");
+        assert_eq!(listeners.len(), 1);
+        assert_eq!(listeners[0]["port"], 7892);
+
+        config.system_proxy.http_port = Some(7891);
+        assert!(config.system_proxy.validate(config.mixed_port).is_err());
+        config.system_proxy.http_port = Some(7890);
+        assert!(config.system_proxy.validate(config.mixed_port).is_err());
+
+        config.system_proxy.http_port = None;
+        config.system_proxy.mode = SystemProxyMode::Pac;
+        config.system_proxy.pac_port = 7891;
+        assert!(config.system_proxy.validate(config.mixed_port).is_err());
+        config.system_proxy.pac_port = 7899;
+        assert!(config.system_proxy.validate(config.mixed_port).is_ok());
     }
 
     #[test]
-    fn parses_mode_response() {
-        let mode = parse_mode_response(r#"{"mode":"Rule"}"#).expect("mode should parse");
-        assert_eq!(mode, ProxyMode::Rule);
-
-        let mode = parse_mode_response(r#"{"mode":"global"}"#).expect("mode should parse");
-        assert_eq!(mode, ProxyMode::Global);
-
-        let error = parse_mode_response(r#"{"mode":"invalid"}"#).expect_err("invalid mode");
-        assert!(error.to_string().contains("invalid mode"));
+    fn points_geox_urls_at_the_geodata_settings() {
+        let mut config = Config::default();
+        config.geodata.asn_url = String::new();
+        let profile = "geox-url:\n  asn: https://example.com/asn.mmdb\ngeo-auto-update: true\n";
+        let runtime = build_runtime_config_yaml(profile, &[], &config).expect("runtime config");
+        let value: serde_yaml::Value = serde_yaml::from_str(&runtime).expect("yaml");
+        assert_eq!(value["geox-url"]["asn"], "https://example.com/asn.mmdb");
+        assert_eq!(
+            value["geox-url"]["geosite"],
+            config.geodata.geosite_url.as_str()
+        );
+        assert_eq!(value["geo-auto-update"], false);
     }
 
     #[test]
-    fn parses_delay_response() {
-        let delay = parse_delay_response(r#"{"delay":123}"#).expect("delay should parse");
-        assert_eq!(delay, Some(123));
+    fn parses_subscription_userinfo_header() {
+        let info = parse_subscription_userinfo(
+            "upload=1024; download=2048; total=1073741824; expire=1767225600",
+        )
+        .expect("header should parse");
+        assert_eq!(info.used(), 3072);
+        assert_eq!(info.total, 1_073_741_824);
+        assert_eq!(info.expire, Some(1_767_225_600));
+        assert_eq!(info.seconds_until_expiry(1_767_225_500), Some(100));
+
+        let info = parse_subscription_userinfo("upload=5.0E2;download=500;total=0;expire=0")
+            .expect("float values should parse");
+        assert_eq!(info.used(), 1000);
+        assert_eq!(info.usage_ratio(), None);
+        assert_eq!(info.expire, None);
 
-        let delay = parse_delay_response(r#"{"delay":-1}"#).expect("timeout style should parse");
-        assert_eq!(delay, None);
+        assert!(parse_subscription_userinfo("plan=pro").is_none());
+    }
 
-        let error = parse_delay_response(r#"{"delay":"bad"}"#).expect_err("invalid delay");
-        assert!(error.to_string().contains("parse error"));
+    #[test]
+    fn parses_profile_update_interval_header() {
+        assert_eq!(parse_update_interval("24"), Some(24));
+        assert_eq!(parse_update_interval(" 1.5 "), Some(2));
+        assert_eq!(parse_update_interval("0"), None);
+        assert_eq!(parse_update_interval("daily"), None);
     }
 
     #[cfg(target_os = "macos")]
@@ -1674,7 +3317,7 @@ rules:
         write_script(&bin_dir.join("mihomo"), "#!/bin/sh\nsleep 30\n", 0o755);
         write_script(
             &bin_dir.join("networksetup"),
-            "#!/bin/sh\ncase \"$1\" in\n  -listallnetworkservices)\n    echo \"An asterisk (*) denotes that a network service is disabled.\"\n    echo \"Wi-Fi\"\n    ;;\n  -getwebproxy|-getsecurewebproxy|-getsocksfirewallproxy)\n    echo \"Enabled: No\"\n    echo \"Server:\"\n    echo \"Port: 0\"\n    ;;\n  -setwebproxy|-setsecurewebproxy|-setsocksfirewallproxy|-setwebproxystate|-setsecurewebproxystate|-setsocksfirewallproxystate)\n    exit 0\n    ;;\n  *)\n    echo \"unsupported: $1\" >&2\n    exit 1\n    ;;\nesac\n",
+            "#!/bin/sh\ncase \"$1\" in\n  -listallnetworkservices)\n    echo \"An asterisk (*) denotes that a network service is disabled.\"\n    echo \"Wi-Fi\"\n    ;;\n  -getwebproxy|-getsecurewebproxy|-getsocksfirewallproxy)\n    echo \"Enabled: No\"\n    echo \"Server:\"\n    echo \"Port: 0\"\n    ;;\n  -getproxybypassdomains)\n    echo \"There aren't any bypass domains set on Wi-Fi.\"\n    ;;\n  -getautoproxyurl)\n    echo \"URL: (null)\"\n    echo \"Enabled: No\"\n    ;;\n  -setwebproxy|-setsecurewebproxy|-setsocksfirewallproxy|-setwebproxystate|-setsecurewebproxystate|-setsocksfirewallproxystate|-setproxybypassdomains|-setautoproxyurl|-setautoproxystate)\n    exit 0\n    ;;\n  *)\n    echo \"unsupported: $1\" >&2\n    exit 1\n    ;;\nesac\n",
             0o755,
         );
 
@@ -1684,9 +3327,22 @@ rules:
         // SAFETY: tests update process env to route command lookup to temp stubs.
         unsafe { std::env::set_var("PATH", new_path) };
 
+        // The kernel counts as started once the controller and mixed port
+        // answer, which the stub script cannot do itself.
+        let controller = test_support::StubServer::start();
+        controller.route("GET", "/version", 200, r#"{"version":"v1.19.2"}"#);
+        let listener =
+            std::net::TcpListener::bind(("127.0.0.1", 0)).expect("bind mixed port listener");
+        let mixed_port = listener.local_addr().expect("listener addr").port();
+
         let core = Core::new();
+        core.update_config(Config {
+            mixed_port,
+            ..Config::default()
+        })
+        .expect("config update without a running kernel");
         let raw_yaml = r#"
-mixed-port: 7890
+external-controller: CONTROLLER
 mode: rule
 allow-lan: false
 proxies:
@@ -1695,15 +3351,19 @@ proxy-groups:
   - { name: "auto", type: select, proxies: ["node-1"] }
 rules:
   - MATCH,auto
-"#;
-        let parsed = parse_profile_yaml("https://example.com/sub.yaml", raw_yaml)
+"#
+        .replace("CONTROLLER", controller.url().trim_start_matches("http://"));
+        let parsed = parse_profile_yaml("https://example.com/sub.yaml", &raw_yaml)
             .expect("yaml should parse for e2e test");
 
         core.replace_profiles(vec![Profile {
             id: "p-e2e".to_string(),
             name: "e2e".to_string(),
             source_url: "https://example.com/sub.yaml".to_string(),
+            source_kind: ProfileSourceKind::Remote,
             updated_at: "2026-02-08 00:00:00".to_string(),
+            subscription: None,
+            refresh: ProfileRefreshState::default(),
             node_count: parsed.node_count,
             group_count: parsed.group_count,
             rule_count: parsed.rule_count,
@@ -1711,7 +3371,8 @@ rules:
             proxy_groups: parsed.proxy_groups,
             proxy_nodes: parsed.proxy_nodes,
             rules: parsed.rules,
-            raw_yaml: raw_yaml.to_string(),
+            rule_diagnostics: parsed.rule_diagnostics,
+            raw_yaml,
         }]);
 
         core.enable_system_proxy()
@@ -1749,4 +3410,43 @@ rules:
             unsafe { std::env::set_var("PATH", &self.0) };
         }
     }
+
+    #[test]
+    fn shell_env_follows_enabled_system_proxy() {
+        let dir = std::env::temp_dir().join(format!("linkpad-core-shell-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut state = CoreState {
+            shell_proxy: Some(SystemProxyManager::new_shell_env(dir.clone())),
+            system_proxy_enabled: true,
+            system_proxy_applied: Some(system_proxy_config(&Config::default())),
+            ..CoreState::default()
+        };
+        let env_file = dir.join(ShellSyntax::Posix.file_name());
+
+        Core::sync_shell_proxy(&mut state);
+        assert!(!env_file.exists());
+
+        state.config.system_proxy.shell_env = true;
+        Core::sync_shell_proxy(&mut state);
+        let exports = fs::read_to_string(&env_file).expect("env file");
+        assert!(exports.contains("export http_proxy='http://127.0.0.1:7890'"));
+
+        state.system_proxy_enabled = false;
+        Core::sync_shell_proxy(&mut state);
+        assert!(!env_file.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn restart_needed_only_for_listener_or_controller_changes() {
+        let base =
+            "mixed-port: 7890\nexternal-controller: 127.0.0.1:9097\nrules:\n  - MATCH,DIRECT\n";
+        let rules_changed = base.replace("MATCH,DIRECT", "MATCH,Proxy");
+        let port_changed = base.replace("7890", "7891");
+        let controller_changed = base.replace("9097", "9098");
+        assert!(!requires_restart(base, &rules_changed));
+        assert!(requires_restart(base, &port_changed));
+        assert!(requires_restart(base, &controller_changed));
+        assert!(requires_restart(base, "not: [valid"));
+    }
 }
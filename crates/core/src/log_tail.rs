@@ -0,0 +1,417 @@
+use crate::{CoreError, CoreResult, CoreState};
+use chrono::Local;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How much of an existing log is read when tailing starts.
+const INITIAL_TAIL_BYTES: u64 = 64 * 1024;
+/// Entries kept in memory, oldest dropped first.
+const LOG_BUFFER_LEN: usize = 2000;
+/// Size cap of an exported log.
+const EXPORT_MAX_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Severity of a kernel log line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 4] = [
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warning,
+        LogLevel::Error,
+    ];
+
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.to_ascii_lowercase().as_str() {
+            "debug" | "trace" => Some(Self::Debug),
+            "info" => Some(Self::Info),
+            "warn" | "warning" => Some(Self::Warning),
+            "error" | "fatal" | "panic" => Some(Self::Error),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// A line of the kernel log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogEntry {
+    /// Increases by one per entry, see [`crate::Core::kernel_logs_since`].
+    pub seq: u64,
+    /// Timestamp as printed by the kernel; empty for unstructured output.
+    pub time: String,
+    pub level: LogLevel,
+    pub message: String,
+}
+
+impl LogEntry {
+    /// Whether the entry is at least `min_level` and contains `query`,
+    /// ignoring case.
+    pub fn matches(&self, min_level: LogLevel, query: &str) -> bool {
+        let query = query.trim().to_ascii_lowercase();
+        self.level >= min_level
+            && (query.is_empty() || self.message.to_ascii_lowercase().contains(&query))
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct LogBuffer {
+    entries: VecDeque<LogEntry>,
+    next_seq: u64,
+}
+
+impl LogBuffer {
+    fn push(&mut self, line: &str) {
+        let Some((time, level, message)) = parse_line(line) else {
+            return;
+        };
+        if self.entries.len() == LOG_BUFFER_LEN {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(LogEntry {
+            seq: self.next_seq,
+            time,
+            level,
+            message,
+        });
+        self.next_seq += 1;
+    }
+
+    pub fn since(&self, seq: Option<u64>) -> Vec<LogEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| seq.is_none_or(|seq| entry.seq > seq))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Splits a mihomo line such as
+/// `time="2026-01-01T00:00:00+08:00" level=info msg="[TCP] ..."`, or a
+/// sing-box one as read by [`parse_sing_box_line`].
+///
+/// Lines in any other shape are kept as errors: the kernels only print those
+/// on failures, such as a panic or a config they cannot parse.
+pub(crate) fn parse_line(line: &str) -> Option<(String, LogLevel, String)> {
+    let line = line.trim_end_matches('\r');
+    if line.trim().is_empty() {
+        return None;
+    }
+    let fields = parse_logfmt(line);
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+    };
+    match (
+        field("level").and_then(|level| LogLevel::parse(&level)),
+        field("msg"),
+    ) {
+        (Some(level), Some(message)) => Some((field("time").unwrap_or_default(), level, message)),
+        _ => Some(
+            parse_sing_box_line(line)
+                .unwrap_or_else(|| (String::new(), LogLevel::Error, line.to_string())),
+        ),
+    }
+}
+
+/// Splits a sing-box line such as
+/// `+0800 2026-01-01 08:00:00 INFO [3526 0ms] inbound/mixed[mixed-in]: ...`,
+/// joining zone, date and clock into an RFC 3339 style time.
+fn parse_sing_box_line(line: &str) -> Option<(String, LogLevel, String)> {
+    let mut parts = line.trim().splitn(5, ' ');
+    let zone = parts.next().filter(|zone| zone.starts_with(['+', '-']))?;
+    let date = parts.next()?;
+    let clock = parts.next()?;
+    let level = LogLevel::parse(parts.next()?)?;
+    let message = parts.next().unwrap_or_default().to_string();
+    Some((format!("{date}T{clock}{zone}"), level, message))
+}
+
+fn parse_logfmt(line: &str) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+        let mut key = String::new();
+        while let Some(ch) = chars.next_if(|ch| *ch != '=' && !ch.is_whitespace()) {
+            key.push(ch);
+        }
+        if key.is_empty() || chars.next_if_eq(&'=').is_none() {
+            return fields;
+        }
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(ch) = chars.next() {
+                match ch {
+                    '"' => break,
+                    '\\' => {
+                        if let Some(escaped) = chars.next() {
+                            value.push(match escaped {
+                                'n' => '\n',
+                                't' => '\t',
+                                other => other,
+                            });
+                        }
+                    }
+                    other => value.push(other),
+                }
+            }
+        } else {
+            while let Some(ch) = chars.next_if(|ch| !ch.is_whitespace()) {
+                value.push(ch);
+            }
+        }
+        fields.push((key, value));
+    }
+}
+
+/// Read position in the log file, owned by the tail thread.
+#[derive(Debug, Default)]
+struct TailCursor {
+    offset: Option<u64>,
+    partial: Vec<u8>,
+    /// The first line read after seeking into the middle of the file is
+    /// incomplete and dropped.
+    skip_first_line: bool,
+}
+
+impl TailCursor {
+    /// Returns the complete lines appended since the last call.
+    fn read_new_lines(&mut self, path: &Path) -> std::io::Result<Vec<String>> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+        let len = file.metadata()?.len();
+        let offset = match self.offset {
+            // The file was truncated or replaced; start over.
+            Some(offset) if offset > len => {
+                self.partial.clear();
+                self.skip_first_line = false;
+                0
+            }
+            Some(offset) => offset,
+            None => {
+                let start = len.saturating_sub(INITIAL_TAIL_BYTES);
+                self.skip_first_line = start > 0;
+                start
+            }
+        };
+        file.seek(SeekFrom::Start(offset))?;
+        let mut bytes = Vec::new();
+        file.take(len - offset).read_to_end(&mut bytes)?;
+        self.offset = Some(offset + bytes.len() as u64);
+        self.partial.extend_from_slice(&bytes);
+
+        let Some(last_newline) = self.partial.iter().rposition(|byte| *byte == b'\n') else {
+            return Ok(Vec::new());
+        };
+        let complete = self.partial.drain(..=last_newline).collect::<Vec<_>>();
+        let mut lines = String::from_utf8_lossy(&complete)
+            .lines()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if std::mem::take(&mut self.skip_first_line) && !lines.is_empty() {
+            lines.remove(0);
+        }
+        Ok(lines)
+    }
+}
+
+/// Tails the kernel log into [`CoreState`] until the owning [`crate::Core`]
+/// is dropped.
+///
+/// The file is used rather than the controller's `/logs` stream because it
+/// also has what the kernel printed before it exited, when there is no
+/// controller to ask.
+pub(crate) fn spawn(inner: Weak<Mutex<CoreState>>) {
+    let spawned = thread::Builder::new()
+        .name("linkpad-log-tail".to_string())
+        .spawn(move || {
+            info!("kernel log tail started");
+            let mut cursor = TailCursor::default();
+            let mut last_error = None;
+            loop {
+                let path = {
+                    let Some(inner) = inner.upgrade() else {
+                        break;
+                    };
+                    let state = inner.lock().expect("core state poisoned");
+                    state.kernel_runtime.log_path()
+                };
+                match cursor.read_new_lines(&path) {
+                    Ok(lines) if !lines.is_empty() => {
+                        let Some(inner) = inner.upgrade() else {
+                            break;
+                        };
+                        let mut state = inner.lock().expect("core state poisoned");
+                        for line in &lines {
+                            state.logs.push(line);
+                        }
+                        last_error = None;
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        let message = error.to_string();
+                        if last_error.as_ref() != Some(&message) {
+                            warn!("failed to read `{}`: {message}", path.display());
+                        }
+                        last_error = Some(message);
+                    }
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+            info!("kernel log tail stopped");
+        });
+    if let Err(error) = spawned {
+        warn!("failed to start kernel log tail: {error}");
+    }
+}
+
+/// The last `count` lines of the kernel log at `log_path`, oldest first;
+/// empty when it cannot be read.
+pub(crate) fn last_lines(log_path: &Path, count: usize) -> Vec<String> {
+    let mut cursor = TailCursor::default();
+    let lines = cursor.read_new_lines(log_path).unwrap_or_default();
+    lines[lines.len().saturating_sub(count)..].to_vec()
+}
+
+/// Copies the end of the kernel log at `log_path` into a new timestamped
+/// file in `dest_dir`.
+pub(crate) fn export(log_path: &Path, dest_dir: &Path) -> CoreResult<PathBuf> {
+    let io_error = |error: std::io::Error| CoreError::InvalidConfig(error.to_string());
+    let mut file = File::open(log_path).map_err(|error| {
+        CoreError::InvalidConfig(format!("failed to open `{}`: {error}", log_path.display()))
+    })?;
+    let len = file.metadata().map_err(io_error)?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(EXPORT_MAX_BYTES)))
+        .map_err(io_error)?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(io_error)?;
+
+    fs::create_dir_all(dest_dir).map_err(io_error)?;
+    let target = dest_dir.join(format!(
+        "linkpad-mihomo-{}.log",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::write(&target, bytes).map_err(io_error)?;
+    info!("kernel log exported: {}", target.display());
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("linkpad-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create test dir");
+        dir
+    }
+
+    #[test]
+    fn parses_mihomo_lines() {
+        assert_eq!(
+            parse_line(
+                r#"time="2026-01-01T08:00:00.1+08:00" level=warning msg="[DNS] \"a.test\" timeout""#
+            ),
+            Some((
+                "2026-01-01T08:00:00.1+08:00".to_string(),
+                LogLevel::Warning,
+                "[DNS] \"a.test\" timeout".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_line("panic: runtime error"),
+            Some((
+                String::new(),
+                LogLevel::Error,
+                "panic: runtime error".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_line("+0800 2026-01-01 08:00:00 WARN [3526 0ms] dns: lookup failed"),
+            Some((
+                "2026-01-01T08:00:00+0800".to_string(),
+                LogLevel::Warning,
+                "[3526 0ms] dns: lookup failed".to_string()
+            ))
+        );
+        assert_eq!(parse_line("   "), None);
+    }
+
+    #[test]
+    fn filters_and_keeps_sequence() {
+        let mut buffer = LogBuffer::default();
+        buffer.push(r#"time="t" level=debug msg="dial example.com""#);
+        buffer.push(r#"time="t" level=error msg="Example failed""#);
+        let entries = buffer.since(None);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(buffer.since(Some(0)), entries[1..]);
+        assert!(entries[0].matches(LogLevel::Debug, "EXAMPLE"));
+        assert!(!entries[0].matches(LogLevel::Info, ""));
+        assert!(entries[1].matches(LogLevel::Warning, "failed"));
+        assert!(!entries[1].matches(LogLevel::Debug, "timeout"));
+    }
+
+    #[test]
+    fn tails_appended_and_truncated_files() {
+        let dir = temp_dir("log-tail");
+        let path = dir.join("mihomo.log");
+        let mut cursor = TailCursor::default();
+        assert!(
+            cursor
+                .read_new_lines(&path)
+                .expect("missing file")
+                .is_empty()
+        );
+
+        fs::write(&path, "first\nsecond\npart").expect("write log");
+        assert_eq!(cursor.read_new_lines(&path).unwrap(), ["first", "second"]);
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"ial\nthird\n").unwrap();
+        assert_eq!(cursor.read_new_lines(&path).unwrap(), ["partial", "third"]);
+        assert!(cursor.read_new_lines(&path).unwrap().is_empty());
+
+        fs::write(&path, "new\n").expect("truncate log");
+        assert_eq!(cursor.read_new_lines(&path).unwrap(), ["new"]);
+
+        let large = "x".repeat(INITIAL_TAIL_BYTES as usize) + "\nlast\n";
+        fs::write(&path, &large).expect("write large log");
+        let mut cursor = TailCursor::default();
+        assert_eq!(cursor.read_new_lines(&path).unwrap(), ["last"]);
+        assert_eq!(last_lines(&path, 5), ["last"]);
+        assert!(last_lines(&dir.join("missing.log"), 5).is_empty());
+
+        let exported = export(&path, &dir.join("out")).expect("export log");
+        assert_eq!(fs::read_to_string(exported).unwrap(), large);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
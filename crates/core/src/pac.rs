@@ -0,0 +1,339 @@
+//! PAC script for the system proxy's PAC mode, and the loopback HTTP server
+//! the OS fetches it from.
+
+use crate::rule::parse_cidr;
+use crate::{CoreError, CoreResult, Rule, RuleKind, SystemProxyConfig};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const PAC_PATH: &str = "/proxy.pac";
+/// How often the listener checks whether it was stopped while idle.
+const ACCEPT_POLL: Duration = Duration::from_millis(200);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+/// Builds `FindProxyForURL` for `proxy`. `direct` entries are checked first,
+/// then the profile's domain and IPv4 rules in their order, so a DIRECT rule
+/// only wins where no earlier proxy rule matches. Rules a PAC script cannot
+/// express are left out; the kernel still applies them to what it receives.
+pub(crate) fn build_script(rules: &[Rule], direct: &[String], proxy: &SystemProxyConfig) -> String {
+    let proxy_value = format!(
+        "PROXY {host}:{}; SOCKS5 {host}:{}",
+        proxy.http_port,
+        proxy.socks_port,
+        host = proxy.host
+    );
+    let mut script = String::new();
+    script.push_str(&format!("var PROXY = {};\n", js_string(&proxy_value)));
+    script.push_str("var DIRECT = \"DIRECT\";\n\n");
+    script.push_str(
+        "function suffix(host, domain) {\n  \
+         return host === domain || host.slice(-domain.length - 1) === \".\" + domain;\n}\n\n",
+    );
+    script.push_str("function FindProxyForURL(url, host) {\n");
+    script.push_str("  host = host.toLowerCase();\n");
+    script.push_str("  var ip = /^\\d+\\.\\d+\\.\\d+\\.\\d+$/.test(host);\n");
+
+    for entry in direct {
+        if let Some(condition) = entry_condition(entry) {
+            script.push_str(&format!("  if ({condition}) return DIRECT;\n"));
+        }
+    }
+    let mut fallback = "PROXY";
+    for rule in rules {
+        let policy = if rule.target.eq_ignore_ascii_case("DIRECT") {
+            "DIRECT"
+        } else {
+            "PROXY"
+        };
+        if rule.kind == RuleKind::Match {
+            fallback = policy;
+            break;
+        }
+        if let Some(condition) = rule_condition(rule) {
+            script.push_str(&format!("  if ({condition}) return {policy};\n"));
+        }
+    }
+    script.push_str(&format!("  return {fallback};\n}}\n"));
+    script
+}
+
+/// Condition for a bypass or user entry: an IPv4 network, a `*` pattern, an
+/// address, or a domain matched with its subdomains.
+fn entry_condition(entry: &str) -> Option<String> {
+    let entry = entry.trim().to_ascii_lowercase();
+    if entry.is_empty() {
+        return None;
+    }
+    if entry.contains('/') {
+        return ipv4_condition(&entry);
+    }
+    if let Some(domain) = entry.strip_prefix("*.").or_else(|| entry.strip_prefix('.')) {
+        return Some(format!("suffix(host, {})", js_string(domain)));
+    }
+    if entry.contains('*') {
+        return Some(format!("shExpMatch(host, {})", js_string(&entry)));
+    }
+    if entry.parse::<IpAddr>().is_ok() {
+        return Some(format!("host === {}", js_string(&entry)));
+    }
+    Some(format!("suffix(host, {})", js_string(&entry)))
+}
+
+fn rule_condition(rule: &Rule) -> Option<String> {
+    let payload = rule.payload.trim().to_ascii_lowercase();
+    match rule.kind {
+        RuleKind::Domain => Some(format!("host === {}", js_string(&payload))),
+        RuleKind::DomainSuffix => Some(format!("suffix(host, {})", js_string(&payload))),
+        RuleKind::DomainKeyword => Some(format!("host.indexOf({}) !== -1", js_string(&payload))),
+        RuleKind::IpCidr => ipv4_condition(&payload),
+        _ => None,
+    }
+}
+
+/// Tests literal IPv4 hosts only, since `isInNet` would resolve host names.
+fn ipv4_condition(value: &str) -> Option<String> {
+    let (IpAddr::V4(address), prefix) = parse_cidr(value).ok()? else {
+        return None;
+    };
+    let mask = match prefix {
+        0 => 0,
+        prefix => u32::MAX << (32 - u32::from(prefix)),
+    };
+    let network = Ipv4Addr::from(u32::from(address) & mask);
+    Some(format!(
+        "ip && isInNet(host, \"{network}\", \"{}\")",
+        Ipv4Addr::from(mask)
+    ))
+}
+
+fn js_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+/// Serves the current script on the loopback address. The URL carries a
+/// revision that changes with the script, so the OS fetches it again instead
+/// of keeping a cached copy.
+#[derive(Debug, Default)]
+pub(crate) struct PacServer {
+    script: Arc<Mutex<String>>,
+    revision: u64,
+    listener: Option<Listener>,
+}
+
+#[derive(Debug)]
+struct Listener {
+    /// Port asked for; `port` is where it actually listens.
+    requested: u16,
+    port: u16,
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl PacServer {
+    pub(crate) fn set_script(&mut self, script: String) {
+        let mut current = self.script.lock().expect("pac script poisoned");
+        if *current != script {
+            *current = script;
+            self.revision += 1;
+        }
+    }
+
+    /// Listens on `port`, leaving a port used before.
+    pub(crate) fn listen(&mut self, port: u16) -> CoreResult<()> {
+        if self
+            .listener
+            .as_ref()
+            .is_some_and(|listener| listener.requested == port)
+        {
+            return Ok(());
+        }
+        self.listener = None;
+        let socket = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .and_then(|socket| {
+                socket.set_nonblocking(true)?;
+                Ok(socket)
+            })
+            .map_err(|error| {
+                CoreError::InvalidConfig(format!(
+                    "failed to serve the PAC script on port {port}: {error}"
+                ))
+            })?;
+        let bound = socket
+            .local_addr()
+            .map(|address| address.port())
+            .unwrap_or(port);
+        let stop = Arc::new(AtomicBool::new(false));
+        let script = Arc::clone(&self.script);
+        let stopped = Arc::clone(&stop);
+        thread::Builder::new()
+            .name("linkpad-pac".to_string())
+            .spawn(move || serve(socket, script, stopped))
+            .map_err(|error| {
+                CoreError::InvalidConfig(format!("failed to start the PAC server: {error}"))
+            })?;
+        info!("PAC script served on 127.0.0.1:{bound}");
+        self.listener = Some(Listener {
+            requested: port,
+            port: bound,
+            stop,
+        });
+        Ok(())
+    }
+
+    pub(crate) fn stop(&mut self) {
+        if self.listener.take().is_some() {
+            info!("PAC server stopped");
+        }
+    }
+
+    pub(crate) fn url(&self) -> Option<String> {
+        self.listener.as_ref().map(|listener| {
+            format!(
+                "http://127.0.0.1:{}{PAC_PATH}?v={}",
+                listener.port, self.revision
+            )
+        })
+    }
+}
+
+fn serve(socket: TcpListener, script: Arc<Mutex<String>>, stop: Arc<AtomicBool>) {
+    while !stop.load(Ordering::Relaxed) {
+        match socket.accept() {
+            Ok((stream, _)) => {
+                if let Err(error) = respond(stream, &script) {
+                    warn!("PAC request failed: {error}");
+                }
+            }
+            Err(error) if error.kind() == ErrorKind::WouldBlock => thread::sleep(ACCEPT_POLL),
+            Err(error) => {
+                warn!("PAC server accept failed: {error}");
+                thread::sleep(ACCEPT_POLL);
+            }
+        }
+    }
+}
+
+fn respond(mut stream: TcpStream, script: &Mutex<String>) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut head = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") && head.len() < MAX_REQUEST_HEAD {
+        let read = stream.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buffer[..read]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut request = head.lines().next().unwrap_or_default().split_whitespace();
+    let method = request.next().unwrap_or_default();
+    let path = request.next().unwrap_or_default();
+    let path = path.split_once('?').map_or(path, |(path, _)| path);
+
+    let (status, body) = if !matches!(method, "GET" | "HEAD") {
+        ("405 Method Not Allowed", String::new())
+    } else if path == PAC_PATH {
+        let script = script.lock().expect("pac script poisoned").clone();
+        ("200 OK", script)
+    } else {
+        ("404 Not Found", String::new())
+    };
+    let mut response = format!(
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: application/x-ns-proxy-autoconfig\r\n\
+         Content-Length: {}\r\n\
+         Cache-Control: no-cache\r\n\
+         Connection: close\r\n\r\n",
+        body.len()
+    );
+    if method != "HEAD" {
+        response.push_str(&body);
+    }
+    stream.write_all(response.as_bytes())?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_script_from_direct_rules_and_serves_it() {
+        let lines = [
+            "DOMAIN-SUFFIX,ads.example.com,REJECT",
+            "DOMAIN-SUFFIX,example.com,DIRECT",
+            "DOMAIN-KEYWORD,intranet,direct",
+            "GEOSITE,cn,DIRECT",
+            "IP-CIDR,192.168.1.7/16,DIRECT,no-resolve",
+            "MATCH,Proxy",
+        ];
+        let (rules, diagnostics) = Rule::parse_all(lines);
+        assert!(diagnostics.is_empty());
+        let proxy = SystemProxyConfig {
+            socks_port: 7891,
+            ..SystemProxyConfig::new("127.0.0.1", 7890)
+        };
+        let direct = ["*.Corp.example".to_string(), "10.0.0.0/8".to_string()];
+        let script = build_script(&rules, &direct, &proxy);
+
+        assert!(script.contains("var PROXY = \"PROXY 127.0.0.1:7890; SOCKS5 127.0.0.1:7891\";"));
+        let checks = [
+            "if (suffix(host, \"corp.example\")) return DIRECT;",
+            "if (ip && isInNet(host, \"10.0.0.0\", \"255.0.0.0\")) return DIRECT;",
+            "if (suffix(host, \"ads.example.com\")) return PROXY;",
+            "if (suffix(host, \"example.com\")) return DIRECT;",
+            "if (host.indexOf(\"intranet\") !== -1) return DIRECT;",
+            "if (ip && isInNet(host, \"192.168.0.0\", \"255.255.0.0\")) return DIRECT;",
+            "return PROXY;\n}",
+        ];
+        let mut from = 0;
+        for check in checks {
+            let position = script[from..]
+                .find(check)
+                .unwrap_or_else(|| panic!("`{check}` missing or out of order in\n{script}"));
+            from += position + check.len();
+        }
+        assert!(!script.contains("\"cn\""));
+
+        let mut server = PacServer::default();
+        server.set_script(script.clone());
+        server.listen(0).expect("listen");
+        let url = server.url().expect("url");
+        assert!(url.ends_with("/proxy.pac?v=1"));
+        server.set_script(script.clone());
+        assert_eq!(server.url().as_deref(), Some(url.as_str()));
+
+        let address = url
+            .trim_start_matches("http://")
+            .split_once('/')
+            .map(|(address, _)| address.to_string())
+            .expect("address");
+        let mut stream = TcpStream::connect(&address).expect("connect");
+        stream
+            .write_all(b"GET /proxy.pac?v=1 HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .expect("request");
+        let mut response = String::new();
+        stream.read_to_string(&mut response).expect("response");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("application/x-ns-proxy-autoconfig"));
+        assert!(response.ends_with(&script));
+
+        server.set_script(String::new());
+        assert!(server.url().expect("url").ends_with("?v=2"));
+        server.stop();
+        assert!(server.url().is_none());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::runtime::app_config_dir;
+use crate::{CoreError, CoreResult, now_unix_seconds};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use tracing::warn;
+
+const PROFILE_CACHE_DIR: &str = "profiles";
+
+/// On-disk copy of a profile document, one JSON file per profile id.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct CachedProfile {
+    pub id: String,
+    pub fetched_at: u64,
+    pub sha256: String,
+    pub content: String,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ProfileCache {
+    dir: PathBuf,
+}
+
+impl Default for ProfileCache {
+    fn default() -> Self {
+        let mut dir = app_config_dir().unwrap_or_else(std::env::temp_dir);
+        dir.push(PROFILE_CACHE_DIR);
+        Self { dir }
+    }
+}
+
+impl ProfileCache {
+    #[cfg(test)]
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn store(&self, id: &str, content: &str) -> CoreResult<CachedProfile> {
+        let path = self.entry_path(id)?;
+        fs::create_dir_all(&self.dir).map_err(|error| {
+            CoreError::InvalidConfig(format!(
+                "failed to create profile cache `{}`: {error}",
+                self.dir.display()
+            ))
+        })?;
+
+        let entry = CachedProfile {
+            id: id.to_string(),
+            fetched_at: now_unix_seconds(),
+            sha256: content_digest(content),
+            content: content.to_string(),
+        };
+        let json = serde_json::to_string(&entry)
+            .map_err(|error| CoreError::InvalidConfig(error.to_string()))?;
+
+        // Write to a sibling file first so a crash never leaves a torn entry.
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, json).map_err(|error| {
+            CoreError::InvalidConfig(format!(
+                "failed to write `{}`: {error}",
+                temp_path.display()
+            ))
+        })?;
+        fs::rename(&temp_path, &path).map_err(|error| {
+            CoreError::InvalidConfig(format!("failed to write `{}`: {error}", path.display()))
+        })?;
+        Ok(entry)
+    }
+
+    pub fn load(&self, id: &str) -> Option<CachedProfile> {
+        let path = self.entry_path(id).ok()?;
+        let json = fs::read_to_string(&path).ok()?;
+        let entry = match serde_json::from_str::<CachedProfile>(&json) {
+            Ok(entry) => entry,
+            Err(error) => {
+                warn!(
+                    "ignoring unreadable profile cache `{}`: {error}",
+                    path.display()
+                );
+                return None;
+            }
+        };
+        if entry.sha256 != content_digest(&entry.content) {
+            warn!("ignoring corrupted profile cache `{}`", path.display());
+            return None;
+        }
+        Some(entry)
+    }
+
+    pub fn remove(&self, id: &str) {
+        let Ok(path) = self.entry_path(id) else {
+            return;
+        };
+        if let Err(error) = fs::remove_file(&path)
+            && error.kind() != std::io::ErrorKind::NotFound
+        {
+            warn!(
+                "failed to remove profile cache `{}`: {error}",
+                path.display()
+            );
+        }
+    }
+
+    fn entry_path(&self, id: &str) -> CoreResult<PathBuf> {
+        if !is_valid_profile_id(id) {
+            return Err(CoreError::InvalidConfig(format!(
+                "invalid profile id `{id}`"
+            )));
+        }
+        Ok(self.dir.join(format!("{id}.json")))
+    }
+}
+
+/// Profile ids double as file names, so only `[A-Za-z0-9_-]` is accepted.
+pub(crate) fn is_valid_profile_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_'))
+}
+
+fn content_digest(content: &str) -> String {
+    let digest = Sha256::digest(content.as_bytes());
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache(name: &str) -> ProfileCache {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!(
+            "linkpad-profile-cache-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        ProfileCache::new(dir)
+    }
+
+    #[test]
+    fn stores_and_loads_profile_content() {
+        let cache = temp_cache("roundtrip");
+        let stored = cache
+            .store("p-1-abc", "proxies: []\n")
+            .expect("store should succeed");
+        assert_eq!(stored.sha256.len(), 64);
+
+        let loaded = cache.load("p-1-abc").expect("entry should load");
+        assert_eq!(loaded.content, "proxies: []\n");
+        assert_eq!(loaded.sha256, stored.sha256);
+        assert_eq!(loaded.fetched_at, stored.fetched_at);
+
+        cache.remove("p-1-abc");
+        assert!(cache.load("p-1-abc").is_none());
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn rejects_corrupted_entries_and_unsafe_ids() {
+        let cache = temp_cache("corrupt");
+        cache.store("p-2", "mode: rule\n").expect("store");
+
+        let path = cache.entry_path("p-2").expect("valid id");
+        let tampered = fs::read_to_string(&path)
+            .expect("read entry")
+            .replace("mode: rule", "mode: direct");
+        fs::write(&path, tampered).expect("tamper entry");
+        assert!(cache.load("p-2").is_none());
+
+        assert!(cache.store("../escape", "x").is_err());
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+}
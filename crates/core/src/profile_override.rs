@@ -0,0 +1,346 @@
+//! User overrides merged on top of profile documents.
+//!
+//! An override is a YAML mapping applied to the profile document with these
+//! rules:
+//!
+//! - mappings are merged key by key, recursing into nested mappings;
+//! - any other value replaces the value in the profile;
+//! - `key!: value` replaces `key` as a whole instead of merging into it;
+//! - `key: null` deletes `key` from the profile;
+//! - `prepend-rules` / `append-rules` add rules before / after the profile
+//!   rules, and the same goes for `proxies` and `proxy-groups`. A prepended or
+//!   appended proxy or group replaces the profile entry with the same name.
+//!
+//! The global override is applied first, then the one of the profile.
+
+use crate::profile_cache::is_valid_profile_id;
+use crate::runtime::app_config_dir;
+use crate::{CoreError, CoreResult};
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+const OVERRIDE_DIR: &str = "overrides";
+const GLOBAL_OVERRIDE_FILE: &str = "global.yaml";
+const LIST_KEYS: [&str; 3] = ["rules", "proxies", "proxy-groups"];
+
+/// Override documents stored as `overrides/global.yaml` and
+/// `overrides/<profile id>.yaml` so they can also be edited by hand.
+#[derive(Clone, Debug)]
+pub(crate) struct OverrideStore {
+    dir: PathBuf,
+}
+
+impl Default for OverrideStore {
+    fn default() -> Self {
+        let mut dir = app_config_dir().unwrap_or_else(std::env::temp_dir);
+        dir.push(OVERRIDE_DIR);
+        Self { dir }
+    }
+}
+
+impl OverrideStore {
+    #[cfg(test)]
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn dir(&self) -> &PathBuf {
+        &self.dir
+    }
+
+    pub fn load_global(&self) -> String {
+        self.read(&self.dir.join(GLOBAL_OVERRIDE_FILE))
+    }
+
+    pub fn store_global(&self, content: &str) -> CoreResult<()> {
+        self.write(self.dir.join(GLOBAL_OVERRIDE_FILE), content)
+    }
+
+    pub fn load_profile(&self, id: &str) -> CoreResult<String> {
+        Ok(self.read(&self.profile_path(id)?))
+    }
+
+    pub fn store_profile(&self, id: &str, content: &str) -> CoreResult<()> {
+        self.write(self.profile_path(id)?, content)
+    }
+
+    pub fn remove_profile(&self, id: &str) {
+        let Ok(path) = self.profile_path(id) else {
+            return;
+        };
+        if let Err(error) = fs::remove_file(&path)
+            && error.kind() != std::io::ErrorKind::NotFound
+        {
+            warn!("failed to remove override `{}`: {error}", path.display());
+        }
+    }
+
+    /// Override documents for a profile in the order they are applied.
+    pub fn layers(&self, profile_id: &str) -> CoreResult<Vec<String>> {
+        Ok(vec![self.load_global(), self.load_profile(profile_id)?])
+    }
+
+    fn profile_path(&self, id: &str) -> CoreResult<PathBuf> {
+        if !is_valid_profile_id(id) {
+            return Err(CoreError::InvalidConfig(format!(
+                "invalid profile id `{id}`"
+            )));
+        }
+        Ok(self.dir.join(format!("{id}.yaml")))
+    }
+
+    fn read(&self, path: &Path) -> String {
+        fs::read_to_string(path).unwrap_or_default()
+    }
+
+    /// Validates and writes `content`; an empty document removes the file.
+    fn write(&self, path: PathBuf, content: &str) -> CoreResult<()> {
+        if content.trim().is_empty() {
+            if let Err(error) = fs::remove_file(&path)
+                && error.kind() != std::io::ErrorKind::NotFound
+            {
+                return Err(CoreError::InvalidConfig(format!(
+                    "failed to remove `{}`: {error}",
+                    path.display()
+                )));
+            }
+            return Ok(());
+        }
+
+        parse_override(content)?;
+        fs::create_dir_all(&self.dir).map_err(|error| {
+            CoreError::InvalidConfig(format!(
+                "failed to create override dir `{}`: {error}",
+                self.dir.display()
+            ))
+        })?;
+        fs::write(&path, content).map_err(|error| {
+            CoreError::InvalidConfig(format!("failed to write `{}`: {error}", path.display()))
+        })
+    }
+}
+
+fn parse_override(content: &str) -> CoreResult<Mapping> {
+    if content.trim().is_empty() {
+        return Ok(Mapping::new());
+    }
+    match serde_yaml::from_str::<Value>(content)
+        .map_err(|error| CoreError::Parse(error.to_string()))?
+    {
+        Value::Mapping(mapping) => Ok(mapping),
+        Value::Null => Ok(Mapping::new()),
+        _ => Err(CoreError::InvalidConfig(
+            "override root must be a YAML mapping".to_string(),
+        )),
+    }
+}
+
+/// Merges the override document `content` into `root`.
+pub(crate) fn apply_override(root: &mut Mapping, content: &str) -> CoreResult<()> {
+    let mut overlay = parse_override(content)?;
+    for list_key in LIST_KEYS {
+        let prepend = take_sequence(&mut overlay, &format!("prepend-{list_key}"))?;
+        let append = take_sequence(&mut overlay, &format!("append-{list_key}"))?;
+        if prepend.is_empty() && append.is_empty() {
+            continue;
+        }
+        let key = Value::String(list_key.to_string());
+        let base = match root.remove(&key) {
+            Some(Value::Sequence(items)) => items,
+            Some(Value::Null) | None => Vec::new(),
+            Some(_) => {
+                return Err(CoreError::InvalidConfig(format!(
+                    "`{list_key}` in profile is not a list"
+                )));
+            }
+        };
+        root.insert(key, Value::Sequence(splice_list(prepend, base, append)));
+    }
+    merge_mapping(root, overlay);
+    Ok(())
+}
+
+fn take_sequence(overlay: &mut Mapping, key: &str) -> CoreResult<Vec<Value>> {
+    match overlay.remove(key) {
+        Some(Value::Sequence(items)) => Ok(items),
+        Some(Value::Null) | None => Ok(Vec::new()),
+        Some(_) => Err(CoreError::InvalidConfig(format!(
+            "`{key}` in override must be a list"
+        ))),
+    }
+}
+
+/// Joins the lists, dropping base entries whose `name` is redefined by the
+/// override so mihomo does not see duplicate proxies or groups.
+fn splice_list(prepend: Vec<Value>, base: Vec<Value>, append: Vec<Value>) -> Vec<Value> {
+    let overridden = prepend
+        .iter()
+        .chain(append.iter())
+        .filter_map(entry_name)
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let base = base.into_iter().filter(|item| {
+        entry_name(item).is_none_or(|name| !overridden.iter().any(|other| other == name))
+    });
+    prepend.into_iter().chain(base).chain(append).collect()
+}
+
+fn entry_name(value: &Value) -> Option<&str> {
+    value.as_mapping()?.get("name")?.as_str()
+}
+
+fn merge_mapping(base: &mut Mapping, overlay: Mapping) {
+    for (key, value) in overlay {
+        if let Some(name) = key.as_str()
+            && let Some(name) = name.strip_suffix('!')
+        {
+            base.insert(Value::String(name.to_string()), value);
+            continue;
+        }
+        match value {
+            Value::Null => {
+                base.remove(&key);
+            }
+            Value::Mapping(overlay_child) => match base.get_mut(&key) {
+                Some(Value::Mapping(base_child)) => merge_mapping(base_child, overlay_child),
+                _ => {
+                    let mut child = Mapping::new();
+                    merge_mapping(&mut child, overlay_child);
+                    base.insert(key, Value::Mapping(child));
+                }
+            },
+            value => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(yaml: &str) -> Mapping {
+        serde_yaml::from_str(yaml).expect("test yaml")
+    }
+
+    #[test]
+    fn deep_merges_and_deletes_keys() {
+        let mut root = mapping(
+            r#"
+dns:
+  enable: true
+  nameserver: [223.5.5.5]
+  fallback: [8.8.8.8]
+tun:
+  enable: true
+sniffer:
+  enable: true
+  sniff: { TLS: { ports: [443] } }
+"#,
+        );
+        apply_override(
+            &mut root,
+            r#"
+dns:
+  nameserver: [1.1.1.1]
+  fallback: null
+tun: null
+sniffer!:
+  enable: false
+"#,
+        )
+        .expect("override should apply");
+
+        assert_eq!(
+            root,
+            mapping(
+                r#"
+dns:
+  enable: true
+  nameserver: [1.1.1.1]
+sniffer:
+  enable: false
+"#
+            )
+        );
+    }
+
+    #[test]
+    fn prepends_and_appends_lists() {
+        let mut root = mapping(
+            r#"
+proxies:
+  - { name: a, type: ss }
+  - { name: b, type: ss }
+rules:
+  - MATCH,Proxy
+"#,
+        );
+        apply_override(
+            &mut root,
+            r#"
+prepend-rules:
+  - DOMAIN-SUFFIX,corp.example,DIRECT
+append-proxies:
+  - { name: a, type: trojan }
+prepend-proxy-groups:
+  - { name: Corp, type: select, proxies: [DIRECT] }
+"#,
+        )
+        .expect("override should apply");
+
+        assert_eq!(
+            root,
+            mapping(
+                r#"
+proxies:
+  - { name: b, type: ss }
+  - { name: a, type: trojan }
+rules:
+  - DOMAIN-SUFFIX,corp.example,DIRECT
+  - MATCH,Proxy
+proxy-groups:
+  - { name: Corp, type: select, proxies: [DIRECT] }
+"#
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_overrides() {
+        let mut root = Mapping::new();
+        assert!(apply_override(&mut root, "- not a mapping").is_err());
+        assert!(apply_override(&mut root, "prepend-rules: MATCH,DIRECT").is_err());
+        assert!(apply_override(&mut root, "").is_ok());
+    }
+
+    #[test]
+    fn applies_global_then_profile_override() {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!("linkpad-overrides-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = OverrideStore::new(dir.clone());
+        store
+            .store_global("log-level: info\nipv6: false\n")
+            .expect("store global");
+        store
+            .store_profile("p-1", "log-level: debug\n")
+            .expect("store profile");
+        assert!(store.store_profile("p-1", "[").is_err());
+
+        let mut root = Mapping::new();
+        for layer in store.layers("p-1").expect("load layers") {
+            apply_override(&mut root, &layer).expect("apply override");
+        }
+        assert_eq!(root, mapping("log-level: debug\nipv6: false\n"));
+
+        store
+            .store_profile("p-1", "")
+            .expect("clear profile override");
+        assert_eq!(store.load_profile("p-1").expect("load"), "");
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
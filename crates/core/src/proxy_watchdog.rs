@@ -0,0 +1,213 @@
+//! Watches the OS proxy settings while the system proxy is enabled, and puts
+//! them back when another program, such as a VPN client, changed them.
+
+use crate::runtime::{SystemProxyConfig, SystemProxyStatus};
+use crate::{CoreEvent, CoreState};
+use std::sync::{Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+const WATCHDOG_TICK: Duration = Duration::from_secs(5);
+/// Shortest time between two re-applies. Settings that drift again sooner are
+/// being managed by another program, so the user is told instead of the two
+/// taking turns.
+const REAPPLY_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Default)]
+pub(crate) struct ProxyWatchdog {
+    last_reapply: Option<Instant>,
+    /// Drift was reported without re-applying; stays quiet until the settings
+    /// match again or the proxy is enabled anew.
+    reported: bool,
+}
+
+/// Checks the settings until the owning [`crate::Core`] is dropped.
+pub(crate) fn spawn(inner: Weak<Mutex<CoreState>>) {
+    let spawned = thread::Builder::new()
+        .name("linkpad-proxy-watchdog".to_string())
+        .spawn(move || {
+            info!("system proxy watchdog started");
+            loop {
+                thread::sleep(WATCHDOG_TICK);
+                let Some(inner) = inner.upgrade() else {
+                    break;
+                };
+                tick(&inner, Instant::now());
+            }
+            info!("system proxy watchdog stopped");
+        });
+    if let Err(error) = spawned {
+        warn!("failed to start system proxy watchdog: {error}");
+    }
+}
+
+/// Reads the OS settings without holding the core lock, which can take a
+/// while on platforms that ask a system tool, then checks them.
+fn tick(inner: &Mutex<CoreState>, now: Instant) {
+    let (manager, applied) = {
+        let state = inner.lock().expect("core state poisoned");
+        let Some(applied) = state.system_proxy_applied.clone() else {
+            return;
+        };
+        if !state.system_proxy_enabled {
+            return;
+        }
+        (state.system_proxy_manager.clone(), applied)
+    };
+    let status = manager
+        .lock()
+        .expect("system proxy manager poisoned")
+        .status();
+    let status = match status {
+        Ok(status) => status,
+        Err(error) => {
+            warn!("failed to read system proxy settings: {error}");
+            return;
+        }
+    };
+
+    let mut state = inner.lock().expect("core state poisoned");
+    // The proxy was turned off or pointed elsewhere while reading.
+    if !state.system_proxy_enabled || state.system_proxy_applied.as_ref() != Some(&applied) {
+        return;
+    }
+    check(&mut state, &applied, &status, now);
+}
+
+/// Compares the OS settings with what was applied and raises
+/// [`CoreEvent::SystemProxyDrifted`] when they no longer match.
+fn check(
+    state: &mut CoreState,
+    applied: &SystemProxyConfig,
+    status: &SystemProxyStatus,
+    now: Instant,
+) {
+    if status.matches(applied) {
+        state.proxy_watchdog.reported = false;
+        return;
+    }
+    if state.proxy_watchdog.reported {
+        return;
+    }
+
+    warn!("system proxy settings changed outside Linkpad: {status:?}");
+    let may_reapply = state.config.system_proxy.restore_on_drift
+        && state
+            .proxy_watchdog
+            .last_reapply
+            .is_none_or(|at| now.duration_since(at) >= REAPPLY_BACKOFF);
+    let reapplied = may_reapply
+        && match state
+            .system_proxy_manager
+            .lock()
+            .expect("system proxy manager poisoned")
+            .enable_with_config(applied)
+        {
+            Ok(()) => true,
+            Err(error) => {
+                warn!("failed to re-apply system proxy: {error}");
+                false
+            }
+        };
+    if reapplied {
+        info!("system proxy re-applied");
+        state.proxy_watchdog.last_reapply = Some(now);
+    } else {
+        state.proxy_watchdog.reported = true;
+    }
+    state
+        .events
+        .push(CoreEvent::SystemProxyDrifted { reapplied });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{SystemProxyBackend, SystemProxyManager, SystemProxyResult};
+    use std::sync::{Arc, OnceLock};
+
+    /// Reports whatever the test put in `status`; enabling writes it.
+    /// Reading fails the test while `core` is locked.
+    #[derive(Debug)]
+    struct FakeBackend {
+        status: Arc<Mutex<SystemProxyStatus>>,
+        core: Arc<OnceLock<Weak<Mutex<CoreState>>>>,
+    }
+
+    impl SystemProxyBackend for FakeBackend {
+        fn enable(&mut self, config: &SystemProxyConfig) -> SystemProxyResult<()> {
+            let endpoint = |port| Some(crate::ProxyEndpoint::new(config.host.clone(), port));
+            *self.status.lock().unwrap() = SystemProxyStatus {
+                http: endpoint(config.http_port),
+                https: endpoint(config.https_port),
+                socks: endpoint(config.socks_port),
+                pac_url: config.pac_url.clone(),
+            };
+            Ok(())
+        }
+
+        fn disable(&mut self) -> SystemProxyResult<()> {
+            *self.status.lock().unwrap() = SystemProxyStatus::default();
+            Ok(())
+        }
+
+        fn status(&self) -> SystemProxyResult<SystemProxyStatus> {
+            if let Some(core) = self.core.get().and_then(Weak::upgrade) {
+                assert!(core.try_lock().is_ok(), "status read under the core lock");
+            }
+            Ok(self.status.lock().unwrap().clone())
+        }
+
+        fn snapshot(&self) -> Option<String> {
+            None
+        }
+
+        fn restore(&mut self, _snapshot: &str) -> SystemProxyResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reapplies_drifted_settings_then_backs_off() {
+        let status = Arc::new(Mutex::new(SystemProxyStatus::default()));
+        let core = Arc::new(OnceLock::new());
+        let mut manager = SystemProxyManager::new_with_backend(Box::new(FakeBackend {
+            status: status.clone(),
+            core: core.clone(),
+        }));
+        let applied = SystemProxyConfig::new("127.0.0.1", 7890);
+        manager.enable_with_config(&applied).expect("enable");
+        let inner = Arc::new(Mutex::new(CoreState {
+            system_proxy_manager: Arc::new(Mutex::new(manager)),
+            system_proxy_enabled: true,
+            system_proxy_applied: Some(applied.clone()),
+            ..CoreState::default()
+        }));
+        core.set(Arc::downgrade(&inner)).unwrap();
+        let events = || inner.lock().unwrap().events.clone();
+
+        let start = Instant::now();
+        tick(&inner, start);
+        assert!(events().is_empty());
+
+        // Another program turns the proxy off; it is put back.
+        *status.lock().unwrap() = SystemProxyStatus::default();
+        tick(&inner, start);
+        assert_eq!(
+            inner.lock().unwrap().events.pop(),
+            Some(CoreEvent::SystemProxyDrifted { reapplied: true })
+        );
+        assert!(status.lock().unwrap().matches(&applied));
+
+        // Changed again within the backoff: reported once, left alone.
+        *status.lock().unwrap() = SystemProxyStatus::default();
+        tick(&inner, start + Duration::from_secs(10));
+        tick(&inner, start + Duration::from_secs(15));
+        assert_eq!(
+            events(),
+            vec![CoreEvent::SystemProxyDrifted { reapplied: false }]
+        );
+        assert!(!status.lock().unwrap().matches(&applied));
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{Core, CoreEvent, CoreState, Profile, ProfileSourceKind, now_unix_seconds};
+use std::collections::HashMap;
+use std::sync::{Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const SCHEDULER_TICK: Duration = Duration::from_secs(60);
+/// Delay before a profile whose refresh failed is tried again.
+const FAILED_REFRESH_RETRY_SECS: u64 = 10 * 60;
+
+/// Runs the refresh loop until the owning [`Core`] is dropped.
+pub(crate) fn spawn(inner: Weak<Mutex<CoreState>>) {
+    let spawned = thread::Builder::new()
+        .name("linkpad-profile-refresh".to_string())
+        .spawn(move || {
+            info!("profile refresh scheduler started");
+            let mut failed_at = HashMap::new();
+            loop {
+                thread::sleep(SCHEDULER_TICK);
+                let Some(inner) = inner.upgrade() else {
+                    break;
+                };
+                run_due_refreshes(&Core { inner }, &mut failed_at);
+            }
+            info!("profile refresh scheduler stopped");
+        });
+    if let Err(error) = spawned {
+        warn!("failed to start profile refresh scheduler: {error}");
+    }
+}
+
+fn run_due_refreshes(core: &Core, failed_at: &mut HashMap<String, u64>) {
+    let now = now_unix_seconds();
+    let due = core
+        .profiles()
+        .into_iter()
+        .filter(|profile| is_refresh_due(profile, now, failed_at.get(&profile.id).copied()))
+        .collect::<Vec<_>>();
+
+    for profile in due {
+        info!("scheduled refresh: id={}", profile.id);
+        let event = match core.refresh_profile_checked(&profile.id) {
+            Ok((refreshed, changed)) => {
+                failed_at.remove(&profile.id);
+                CoreEvent::ProfileRefreshed {
+                    id: refreshed.id,
+                    name: refreshed.name,
+                    changed,
+                }
+            }
+            Err(error) => {
+                warn!("scheduled refresh failed: id={}, error={error}", profile.id);
+                failed_at.insert(profile.id.clone(), now);
+                CoreEvent::ProfileRefreshFailed {
+                    id: profile.id,
+                    name: profile.name,
+                    error: error.to_string(),
+                }
+            }
+        };
+        core.push_event(event);
+    }
+}
+
+fn is_refresh_due(profile: &Profile, now: u64, last_failure: Option<u64>) -> bool {
+    // Pasted profiles have no source to refresh from.
+    if profile.source_kind == ProfileSourceKind::Inline {
+        return false;
+    }
+    let Some(next_refresh_at) = profile.refresh.next_refresh_at() else {
+        return false;
+    };
+    let retry_allowed =
+        last_failure.is_none_or(|failed| now >= failed.saturating_add(FAILED_REFRESH_RETRY_SECS));
+    now >= next_refresh_at && retry_allowed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProfileRefreshState;
+
+    fn profile(source_kind: ProfileSourceKind, refresh: ProfileRefreshState) -> Profile {
+        Profile {
+            id: "p-1".to_string(),
+            name: "test".to_string(),
+            source_url: "https://example.com/sub.yaml".to_string(),
+            source_kind,
+            updated_at: "2026-01-01 00:00:00".to_string(),
+            subscription: None,
+            refresh,
+            node_count: 0,
+            group_count: 0,
+            rule_count: 0,
+            active: false,
+            proxy_groups: Vec::new(),
+            proxy_nodes: Vec::new(),
+            rules: Vec::new(),
+            rule_diagnostics: Vec::new(),
+            raw_yaml: String::new(),
+        }
+    }
+
+    #[test]
+    fn refresh_is_due_after_interval() {
+        let refresh = ProfileRefreshState {
+            provider_interval_hours: Some(12),
+            last_checked_at: Some(1_000),
+            ..Default::default()
+        };
+        let remote = profile(ProfileSourceKind::Remote, refresh.clone());
+        assert!(!is_refresh_due(&remote, 1_000 + 12 * 3600 - 1, None));
+        assert!(is_refresh_due(&remote, 1_000 + 12 * 3600, None));
+
+        let inline = profile(ProfileSourceKind::Inline, refresh);
+        assert!(!is_refresh_due(&inline, u64::MAX, None));
+        assert!(!is_refresh_due(&inline, u64::MAX, Some(0)));
+    }
+
+    #[test]
+    fn override_replaces_provider_interval() {
+        let mut refresh = ProfileRefreshState {
+            provider_interval_hours: Some(24),
+            interval_override_hours: Some(1),
+            last_checked_at: Some(0),
+            ..Default::default()
+        };
+        assert!(is_refresh_due(
+            &profile(ProfileSourceKind::Remote, refresh.clone()),
+            3600,
+            None
+        ));
+
+        refresh.interval_override_hours = Some(0);
+        assert_eq!(refresh.next_refresh_at(), None);
+        assert!(!is_refresh_due(
+            &profile(ProfileSourceKind::Remote, refresh),
+            u64::MAX,
+            None
+        ));
+    }
+
+    #[test]
+    fn failed_refresh_waits_before_retrying() {
+        let refresh = ProfileRefreshState {
+            interval_override_hours: Some(1),
+            ..Default::default()
+        };
+        let local = profile(ProfileSourceKind::LocalFile, refresh);
+        let failed = 10_000;
+        assert!(!is_refresh_due(&local, failed + 60, Some(failed)));
+        assert!(is_refresh_due(
+            &local,
+            failed + FAILED_REFRESH_RETRY_SECS,
+            Some(failed)
+        ));
+    }
+}
@@ -0,0 +1,524 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::net::IpAddr;
+use tracing::warn;
+
+/// Rule types understood by mihomo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RuleKind {
+    Domain,
+    DomainSuffix,
+    DomainKeyword,
+    DomainWildcard,
+    DomainRegex,
+    Geosite,
+    IpCidr,
+    IpCidr6,
+    IpSuffix,
+    IpAsn,
+    GeoIp,
+    SrcGeoIp,
+    SrcIpAsn,
+    SrcIpCidr,
+    SrcIpSuffix,
+    DstPort,
+    SrcPort,
+    InPort,
+    InType,
+    InUser,
+    InName,
+    ProcessPath,
+    ProcessPathRegex,
+    ProcessName,
+    ProcessNameRegex,
+    Uid,
+    Network,
+    Dscp,
+    RuleSet,
+    And,
+    Or,
+    Not,
+    SubRule,
+    Match,
+}
+
+impl RuleKind {
+    pub const ALL: [RuleKind; 34] = [
+        Self::Domain,
+        Self::DomainSuffix,
+        Self::DomainKeyword,
+        Self::DomainWildcard,
+        Self::DomainRegex,
+        Self::Geosite,
+        Self::IpCidr,
+        Self::IpCidr6,
+        Self::IpSuffix,
+        Self::IpAsn,
+        Self::GeoIp,
+        Self::SrcGeoIp,
+        Self::SrcIpAsn,
+        Self::SrcIpCidr,
+        Self::SrcIpSuffix,
+        Self::DstPort,
+        Self::SrcPort,
+        Self::InPort,
+        Self::InType,
+        Self::InUser,
+        Self::InName,
+        Self::ProcessPath,
+        Self::ProcessPathRegex,
+        Self::ProcessName,
+        Self::ProcessNameRegex,
+        Self::Uid,
+        Self::Network,
+        Self::Dscp,
+        Self::RuleSet,
+        Self::And,
+        Self::Or,
+        Self::Not,
+        Self::SubRule,
+        Self::Match,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Domain => "DOMAIN",
+            Self::DomainSuffix => "DOMAIN-SUFFIX",
+            Self::DomainKeyword => "DOMAIN-KEYWORD",
+            Self::DomainWildcard => "DOMAIN-WILDCARD",
+            Self::DomainRegex => "DOMAIN-REGEX",
+            Self::Geosite => "GEOSITE",
+            Self::IpCidr => "IP-CIDR",
+            Self::IpCidr6 => "IP-CIDR6",
+            Self::IpSuffix => "IP-SUFFIX",
+            Self::IpAsn => "IP-ASN",
+            Self::GeoIp => "GEOIP",
+            Self::SrcGeoIp => "SRC-GEOIP",
+            Self::SrcIpAsn => "SRC-IP-ASN",
+            Self::SrcIpCidr => "SRC-IP-CIDR",
+            Self::SrcIpSuffix => "SRC-IP-SUFFIX",
+            Self::DstPort => "DST-PORT",
+            Self::SrcPort => "SRC-PORT",
+            Self::InPort => "IN-PORT",
+            Self::InType => "IN-TYPE",
+            Self::InUser => "IN-USER",
+            Self::InName => "IN-NAME",
+            Self::ProcessPath => "PROCESS-PATH",
+            Self::ProcessPathRegex => "PROCESS-PATH-REGEX",
+            Self::ProcessName => "PROCESS-NAME",
+            Self::ProcessNameRegex => "PROCESS-NAME-REGEX",
+            Self::Uid => "UID",
+            Self::Network => "NETWORK",
+            Self::Dscp => "DSCP",
+            Self::RuleSet => "RULE-SET",
+            Self::And => "AND",
+            Self::Or => "OR",
+            Self::Not => "NOT",
+            Self::SubRule => "SUB-RULE",
+            Self::Match => "MATCH",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_uppercase();
+        Self::ALL.into_iter().find(|kind| kind.as_str() == name)
+    }
+
+    pub fn is_logic(self) -> bool {
+        matches!(self, Self::And | Self::Or | Self::Not)
+    }
+}
+
+impl fmt::Display for RuleKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One entry of a profile's `rules` list.
+///
+/// Conditions inside `AND`/`OR`/`NOT` and `SUB-RULE` are parsed into
+/// `conditions`; they carry an empty `target`. Rules are persisted in their
+/// textual form so older `profiles.json` files keep loading.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub kind: RuleKind,
+    /// Matched value; the raw condition list for logic rules and empty for
+    /// `MATCH`.
+    pub payload: String,
+    /// Policy or proxy group the rule routes to.
+    pub target: String,
+    /// Trailing flags such as `no-resolve` or `src`.
+    pub options: Vec<String>,
+    pub conditions: Vec<Rule>,
+}
+
+/// A rule line that could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleDiagnostic {
+    /// Position of the line in the profile's `rules` list.
+    pub index: usize,
+    pub line: String,
+    pub message: String,
+}
+
+impl Rule {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        parse_rule(line.trim(), true)
+    }
+
+    /// Parses a rule without a target, as used by classical rule-sets.
+    pub(crate) fn parse_condition(line: &str) -> Result<Self, String> {
+        parse_rule(line.trim(), false)
+    }
+
+    /// Parses every line, collecting the malformed ones as diagnostics.
+    pub fn parse_all<'a>(
+        lines: impl IntoIterator<Item = &'a str>,
+    ) -> (Vec<Rule>, Vec<RuleDiagnostic>) {
+        let mut rules = Vec::new();
+        let mut diagnostics = Vec::new();
+        for (index, line) in lines.into_iter().enumerate() {
+            match Rule::parse(line) {
+                Ok(rule) => rules.push(rule),
+                Err(message) => diagnostics.push(RuleDiagnostic {
+                    index,
+                    line: line.trim().to_string(),
+                    message,
+                }),
+            }
+        }
+        (rules, diagnostics)
+    }
+
+    pub fn has_option(&self, option: &str) -> bool {
+        self.options
+            .iter()
+            .any(|item| item.eq_ignore_ascii_case(option))
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.kind.as_str())?;
+        if self.kind != RuleKind::Match {
+            write!(f, ",{}", self.payload)?;
+        }
+        if !self.target.is_empty() {
+            write!(f, ",{}", self.target)?;
+        }
+        for option in &self.options {
+            write!(f, ",{option}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Rule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let line = String::deserialize(deserializer)?;
+        Rule::parse(&line).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Deserializes a persisted rule list, dropping entries that no longer parse
+/// instead of failing the whole profile.
+pub(crate) fn deserialize_rules<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Rule>, D::Error> {
+    let lines = Vec::<String>::deserialize(deserializer)?;
+    Ok(lines
+        .iter()
+        .filter_map(|line| match Rule::parse(line) {
+            Ok(rule) => Some(rule),
+            Err(error) => {
+                warn!("dropping persisted rule `{line}`: {error}");
+                None
+            }
+        })
+        .collect())
+}
+
+/// Parses `KIND,payload,target,options...`, or `(KIND,payload,options...)`
+/// without a target when `with_target` is false.
+fn parse_rule(text: &str, with_target: bool) -> Result<Rule, String> {
+    if text.is_empty() {
+        return Err("empty rule".to_string());
+    }
+    let fields = split_top_level(text)?;
+    let kind_name = fields[0].trim();
+    let kind =
+        RuleKind::from_name(kind_name).ok_or_else(|| format!("unknown rule type `{kind_name}`"))?;
+
+    if kind == RuleKind::Match {
+        let target = fields.get(1).map(|value| value.trim()).unwrap_or_default();
+        if with_target && target.is_empty() {
+            return Err("MATCH needs a target".to_string());
+        }
+        return Ok(Rule {
+            kind,
+            payload: String::new(),
+            target: target.to_string(),
+            options: fields
+                .iter()
+                .skip(2)
+                .map(|value| value.trim().to_string())
+                .collect(),
+            conditions: Vec::new(),
+        });
+    }
+
+    let payload = fields
+        .get(1)
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| format!("{kind} needs a payload"))?;
+    let (target, options) = if with_target {
+        let target = fields
+            .get(2)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| format!("{kind} needs a target"))?;
+        (target.to_string(), &fields[3.min(fields.len())..])
+    } else {
+        (String::new(), &fields[2.min(fields.len())..])
+    };
+
+    let conditions = match kind {
+        RuleKind::And | RuleKind::Or | RuleKind::Not => {
+            let conditions = parse_conditions(payload)?;
+            if kind == RuleKind::Not && conditions.len() != 1 {
+                return Err("NOT takes exactly one condition".to_string());
+            }
+            if conditions.is_empty() {
+                return Err(format!("{kind} needs at least one condition"));
+            }
+            conditions
+        }
+        RuleKind::SubRule => {
+            let inner = strip_parens(payload)
+                .ok_or_else(|| "SUB-RULE condition must be wrapped in parentheses".to_string())?;
+            vec![parse_rule(inner, false)?]
+        }
+        _ => {
+            validate_payload(kind, payload)?;
+            Vec::new()
+        }
+    };
+
+    Ok(Rule {
+        kind,
+        payload: payload.to_string(),
+        target,
+        options: options
+            .iter()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .collect(),
+        conditions,
+    })
+}
+
+/// Parses `((A,x),(B,y))` into its conditions.
+fn parse_conditions(payload: &str) -> Result<Vec<Rule>, String> {
+    let inner = strip_parens(payload)
+        .ok_or_else(|| "logic conditions must be wrapped in parentheses".to_string())?;
+    split_top_level(inner)?
+        .into_iter()
+        .map(|item| {
+            let item = item.trim();
+            let condition = strip_parens(item)
+                .ok_or_else(|| format!("condition `{item}` must be wrapped in parentheses"))?;
+            parse_rule(condition.trim(), false)
+        })
+        .collect()
+}
+
+fn strip_parens(text: &str) -> Option<&str> {
+    text.trim().strip_prefix('(')?.strip_suffix(')')
+}
+
+/// Splits on commas that are not nested inside parentheses.
+fn split_top_level(text: &str) -> Result<Vec<&str>, String> {
+    let mut fields = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (index, ch) in text.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| "unbalanced parentheses".to_string())?;
+            }
+            ',' if depth == 0 => {
+                fields.push(&text[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err("unbalanced parentheses".to_string());
+    }
+    fields.push(&text[start..]);
+    Ok(fields)
+}
+
+fn validate_payload(kind: RuleKind, payload: &str) -> Result<(), String> {
+    match kind {
+        RuleKind::IpCidr | RuleKind::IpCidr6 | RuleKind::SrcIpCidr => {
+            parse_cidr(payload).map(|_| ())
+        }
+        RuleKind::DstPort | RuleKind::SrcPort | RuleKind::InPort => {
+            parse_port_ranges(payload).map(|_| ())
+        }
+        RuleKind::IpAsn | RuleKind::SrcIpAsn | RuleKind::Dscp => payload
+            .parse::<u32>()
+            .map(|_| ())
+            .map_err(|_| format!("invalid {kind} value `{payload}`")),
+        RuleKind::Network => match payload.to_ascii_lowercase().as_str() {
+            "tcp" | "udp" => Ok(()),
+            _ => Err(format!("invalid network `{payload}`")),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Parses `addr/prefix`; a bare address is treated as a host route.
+pub(crate) fn parse_cidr(value: &str) -> Result<(IpAddr, u8), String> {
+    let (addr, prefix) = match value.split_once('/') {
+        Some((addr, prefix)) => (addr, Some(prefix)),
+        None => (value, None),
+    };
+    let addr = addr
+        .trim()
+        .parse::<IpAddr>()
+        .map_err(|_| format!("invalid IP address in `{value}`"))?;
+    let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+    let prefix = match prefix {
+        Some(prefix) => prefix
+            .trim()
+            .parse::<u8>()
+            .ok()
+            .filter(|prefix| *prefix <= max_prefix)
+            .ok_or_else(|| format!("invalid prefix length in `{value}`"))?,
+        None => max_prefix,
+    };
+    Ok((addr, prefix))
+}
+
+/// Parses `80`, `1000-2000` or `80/443/8000-9000`.
+pub(crate) fn parse_port_ranges(value: &str) -> Result<Vec<(u16, u16)>, String> {
+    value
+        .split('/')
+        .map(|part| {
+            let part = part.trim();
+            let parse = |port: &str| {
+                port.trim()
+                    .parse::<u16>()
+                    .map_err(|_| format!("invalid port `{port}`"))
+            };
+            let (start, end) = match part.split_once('-') {
+                Some((start, end)) => (parse(start)?, parse(end)?),
+                None => {
+                    let port = parse(part)?;
+                    (port, port)
+                }
+            };
+            if start > end {
+                return Err(format!("invalid port range `{part}`"));
+            }
+            Ok((start, end))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_rules() {
+        let rule = Rule::parse("IP-CIDR,10.0.0.0/8,DIRECT,no-resolve").expect("ip-cidr rule");
+        assert_eq!(rule.kind, RuleKind::IpCidr);
+        assert_eq!(rule.payload, "10.0.0.0/8");
+        assert_eq!(rule.target, "DIRECT");
+        assert!(rule.has_option("no-resolve"));
+
+        let rule = Rule::parse("MATCH,Proxy").expect("match rule");
+        assert_eq!(rule.kind, RuleKind::Match);
+        assert_eq!(rule.target, "Proxy");
+        assert_eq!(rule.to_string(), "MATCH,Proxy");
+
+        for line in [
+            "DOMAIN-SUFFIX,example.com,Proxy",
+            "DOMAIN-KEYWORD,google,Proxy",
+            "GEOIP,CN,DIRECT",
+            "GEOSITE,category-ads-all,REJECT",
+            "IP-CIDR6,2620:0:2d0:200::7/32,Proxy",
+            "SRC-IP-CIDR,192.168.1.201/32,DIRECT",
+            "DST-PORT,80/443/8000-9000,Proxy",
+            "PROCESS-PATH,/usr/bin/curl,DIRECT",
+            "RULE-SET,apple,DIRECT",
+        ] {
+            let rule = Rule::parse(line).expect(line);
+            assert_eq!(rule.to_string(), line);
+        }
+    }
+
+    #[test]
+    fn parses_logic_rules() {
+        let rule = Rule::parse("AND,((DOMAIN,baidu.com),(NETWORK,UDP)),DIRECT").expect("and rule");
+        assert_eq!(rule.kind, RuleKind::And);
+        assert_eq!(rule.target, "DIRECT");
+        assert_eq!(rule.conditions.len(), 2);
+        assert_eq!(rule.conditions[0].kind, RuleKind::Domain);
+        assert_eq!(rule.conditions[1].payload, "UDP");
+
+        let rule = Rule::parse("OR,((NOT,((DST-PORT,443))),(GEOIP,CN,no-resolve)),REJECT")
+            .expect("nested logic rule");
+        assert_eq!(rule.conditions[0].kind, RuleKind::Not);
+        assert_eq!(rule.conditions[0].conditions[0].kind, RuleKind::DstPort);
+        assert!(rule.conditions[1].has_option("no-resolve"));
+
+        let rule = Rule::parse("SUB-RULE,(NETWORK,tcp),tcp-rules").expect("sub-rule");
+        assert_eq!(rule.target, "tcp-rules");
+        assert_eq!(rule.conditions[0].kind, RuleKind::Network);
+    }
+
+    #[test]
+    fn reports_malformed_rules() {
+        let (rules, diagnostics) = Rule::parse_all([
+            "DOMAIN,example.com,DIRECT",
+            "DOMAIN-SUFIX,example.com,DIRECT",
+            "IP-CIDR,10.0.0.0/33,DIRECT",
+            "DST-PORT,443",
+            "NOT,((DOMAIN,a.com),(DOMAIN,b.com)),REJECT",
+            "AND,((DOMAIN,a.com),DIRECT",
+            "MATCH",
+        ]);
+        assert_eq!(rules.len(), 1);
+        let indexes = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.index)
+            .collect::<Vec<_>>();
+        assert_eq!(indexes, vec![1, 2, 3, 4, 5, 6]);
+        assert!(diagnostics[0].message.contains("unknown rule type"));
+    }
+
+    #[test]
+    fn persists_rules_as_strings() {
+        let rule = Rule::parse("GEOIP,CN,DIRECT,no-resolve").expect("geoip rule");
+        let json = serde_json::to_string(&rule).expect("serialize rule");
+        assert_eq!(json, "\"GEOIP,CN,DIRECT,no-resolve\"");
+        let restored: Rule = serde_json::from_str(&json).expect("deserialize rule");
+        assert_eq!(restored, rule);
+    }
+}
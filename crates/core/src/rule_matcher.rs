@@ -0,0 +1,822 @@
+//! Local evaluation of profile rules, answering "which rule would handle this
+//! connection" without asking the kernel.
+//!
+//! Rules are tried in order like mihomo does. Rules that depend on data only
+//! the kernel has (source address, inbound, regexes, ...) cannot be decided
+//! here; they are skipped and reported alongside the match.
+
+use crate::geodata::GeoData;
+use crate::rule::{parse_cidr, parse_port_ranges};
+use crate::{ProxyGroup, Rule, RuleKind};
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::net::{IpAddr, ToSocketAddrs};
+use std::path::Path;
+
+/// Nesting limit for sub-rules referring to each other.
+const MAX_SUB_RULE_DEPTH: usize = 8;
+
+/// The connection to match.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MatchQuery {
+    /// Lowercase domain or IP address.
+    pub host: String,
+    pub port: Option<u16>,
+    /// Process name or full path.
+    pub process: Option<String>,
+}
+
+impl MatchQuery {
+    pub fn new(host_or_ip: &str, port: Option<u16>, process: Option<&str>) -> Option<Self> {
+        let host = host_or_ip.trim();
+        let host = host
+            .strip_prefix('[')
+            .and_then(|host| host.strip_suffix(']'))
+            .unwrap_or(host)
+            .trim_end_matches('.')
+            .to_ascii_lowercase();
+        if host.is_empty() {
+            return None;
+        }
+        Some(Self {
+            host,
+            port,
+            process: process
+                .map(str::trim)
+                .filter(|process| !process.is_empty())
+                .map(str::to_string),
+        })
+    }
+
+    fn ip(&self) -> Option<IpAddr> {
+        self.host.parse().ok()
+    }
+
+    /// Domain of the connection; `None` when connecting to a bare IP.
+    fn domain(&self) -> Option<&str> {
+        self.ip().is_none().then_some(self.host.as_str())
+    }
+}
+
+/// First rule that handles a connection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleMatch {
+    /// Position of the rule in [`crate::Core::active_rules`].
+    pub index: usize,
+    pub rule: Rule,
+    /// Rule of the sub-rule list that decided the match, for `SUB-RULE`.
+    pub sub_rule: Option<Rule>,
+    /// The rule target followed through proxy groups, ending at a proxy or
+    /// built-in policy such as `DIRECT`.
+    pub policy_chain: Vec<String>,
+    /// Earlier rules that could not be evaluated locally.
+    pub skipped: Vec<SkippedRule>,
+}
+
+/// A rule [`RuleMatch`] passed over because it could not be evaluated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkippedRule {
+    pub index: usize,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Verdict {
+    Matched,
+    NotMatched,
+    Unknown(String),
+}
+
+impl From<bool> for Verdict {
+    fn from(matched: bool) -> Self {
+        if matched {
+            Self::Matched
+        } else {
+            Self::NotMatched
+        }
+    }
+}
+
+#[derive(Debug)]
+enum RuleProvider {
+    Domain(Vec<String>),
+    IpCidr(Vec<(IpAddr, u8)>),
+    Classical(Vec<Rule>),
+}
+
+pub(crate) struct RuleMatcher<'a> {
+    query: &'a MatchQuery,
+    document: Mapping,
+    runtime_dir: &'a Path,
+    geodata: &'a mut GeoData,
+    resolve: fn(&str) -> Vec<IpAddr>,
+    resolved: Option<Vec<IpAddr>>,
+    providers: HashMap<String, Result<RuleProvider, String>>,
+}
+
+impl<'a> RuleMatcher<'a> {
+    /// `document` is the runtime config YAML, used for `rule-providers` and
+    /// `sub-rules`; `runtime_dir` holds provider files. `geodata` is kept by
+    /// the caller so the databases are parsed once, not per query.
+    pub fn new(
+        query: &'a MatchQuery,
+        document: &str,
+        runtime_dir: &'a Path,
+        geodata: &'a mut GeoData,
+    ) -> Self {
+        let document = serde_yaml::from_str::<Mapping>(document).unwrap_or_default();
+        Self {
+            query,
+            document,
+            runtime_dir,
+            geodata,
+            resolve: resolve_host,
+            resolved: None,
+            providers: HashMap::new(),
+        }
+    }
+
+    /// Finds the first rule handling the query and resolves its policy through
+    /// `groups`, preferring the group selections reported by the kernel.
+    pub fn find_match(
+        &mut self,
+        rules: &[Rule],
+        groups: &[ProxyGroup],
+        selections: &BTreeMap<String, String>,
+    ) -> Option<RuleMatch> {
+        let mut skipped = Vec::new();
+        for (index, rule) in rules.iter().enumerate() {
+            let (verdict, sub_rule) = if rule.kind == RuleKind::SubRule {
+                match self.match_sub_rule(rule, 0) {
+                    Ok(Some(decider)) => (Verdict::Matched, Some(decider)),
+                    Ok(None) => (Verdict::NotMatched, None),
+                    Err(reason) => (Verdict::Unknown(reason), None),
+                }
+            } else {
+                (self.evaluate(rule), None)
+            };
+            match verdict {
+                Verdict::Matched => {
+                    let target = sub_rule.as_ref().unwrap_or(rule).target.clone();
+                    return Some(RuleMatch {
+                        index,
+                        rule: rule.clone(),
+                        sub_rule,
+                        policy_chain: policy_chain(&target, groups, selections),
+                        skipped,
+                    });
+                }
+                Verdict::NotMatched => {}
+                Verdict::Unknown(reason) => skipped.push(SkippedRule { index, reason }),
+            }
+        }
+        None
+    }
+
+    /// Evaluates a `SUB-RULE`, returning the rule of the named sub-rule list
+    /// that decided the match. Like mihomo, a sub-rule list without a match
+    /// falls through to the next top-level rule.
+    fn match_sub_rule(&mut self, rule: &Rule, depth: usize) -> Result<Option<Rule>, String> {
+        if depth >= MAX_SUB_RULE_DEPTH {
+            return Err("sub-rules nest too deeply".to_string());
+        }
+        let Some(condition) = rule.conditions.first() else {
+            return Ok(None);
+        };
+        match self.evaluate(condition) {
+            Verdict::Matched => {}
+            Verdict::NotMatched => return Ok(None),
+            Verdict::Unknown(reason) => return Err(reason),
+        }
+
+        let lines = self
+            .document
+            .get("sub-rules")
+            .and_then(|value| value.get(rule.target.as_str()))
+            .and_then(Value::as_sequence)
+            .map(|items| sequence_strings(items))
+            .ok_or_else(|| format!("sub-rule `{}` is not defined", rule.target))?;
+        let mut undecided = None;
+        for line in lines {
+            let Ok(sub_rule) = Rule::parse(&line) else {
+                continue;
+            };
+            if sub_rule.kind == RuleKind::SubRule {
+                match self.match_sub_rule(&sub_rule, depth + 1) {
+                    Ok(Some(decider)) => return Ok(Some(decider)),
+                    Ok(None) => {}
+                    Err(reason) => undecided = undecided.or(Some(reason)),
+                }
+                continue;
+            }
+            match self.evaluate(&sub_rule) {
+                Verdict::Matched => return Ok(Some(sub_rule)),
+                Verdict::NotMatched => {}
+                Verdict::Unknown(reason) => undecided = undecided.or(Some(reason)),
+            }
+        }
+        undecided.map_or(Ok(None), Err)
+    }
+
+    fn evaluate(&mut self, rule: &Rule) -> Verdict {
+        let payload = rule.payload.as_str();
+        match rule.kind {
+            RuleKind::Domain => self.match_domain(|host| host == payload.to_ascii_lowercase()),
+            RuleKind::DomainSuffix => {
+                let suffix = payload.to_ascii_lowercase();
+                self.match_domain(|host| domain_has_suffix(host, &suffix))
+            }
+            RuleKind::DomainKeyword => {
+                let keyword = payload.to_ascii_lowercase();
+                self.match_domain(|host| host.contains(&keyword))
+            }
+            RuleKind::DomainWildcard => {
+                let pattern = payload.to_ascii_lowercase();
+                self.match_domain(|host| wildcard_match(&pattern, host))
+            }
+            RuleKind::Geosite => match self.query.domain() {
+                Some(host) => match self.geodata.site_contains(payload, host) {
+                    Some(found) => found.into(),
+                    None => Verdict::Unknown("GeoSite database not found".to_string()),
+                },
+                None => Verdict::NotMatched,
+            },
+            RuleKind::IpCidr | RuleKind::IpCidr6 => match parse_cidr(payload) {
+                Ok(network) => self.match_ips(rule, |ip| cidr_contains(network, ip)),
+                Err(error) => Verdict::Unknown(error),
+            },
+            RuleKind::IpSuffix => match parse_cidr(payload) {
+                Ok(network) => self.match_ips(rule, |ip| ip_has_suffix(network, ip)),
+                Err(error) => Verdict::Unknown(error),
+            },
+            RuleKind::GeoIp => self.match_geoip(rule),
+            RuleKind::DstPort => match (self.query.port, parse_port_ranges(payload)) {
+                (Some(port), Ok(ranges)) => ranges
+                    .iter()
+                    .any(|(start, end)| (*start..=*end).contains(&port))
+                    .into(),
+                (None, _) => Verdict::Unknown("no port given".to_string()),
+                (_, Err(error)) => Verdict::Unknown(error),
+            },
+            RuleKind::ProcessName => match &self.query.process {
+                Some(process) => {
+                    let name = Path::new(process)
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .unwrap_or(process);
+                    name.eq_ignore_ascii_case(payload).into()
+                }
+                None => Verdict::Unknown("no process given".to_string()),
+            },
+            RuleKind::ProcessPath => match &self.query.process {
+                Some(process) => process.eq_ignore_ascii_case(payload).into(),
+                None => Verdict::Unknown("no process given".to_string()),
+            },
+            // Lookups are assumed to be for TCP connections.
+            RuleKind::Network => payload.eq_ignore_ascii_case("tcp").into(),
+            RuleKind::RuleSet => self.match_rule_set(rule),
+            RuleKind::And => {
+                let mut verdict = Verdict::Matched;
+                for condition in &rule.conditions {
+                    match self.evaluate(condition) {
+                        Verdict::NotMatched => return Verdict::NotMatched,
+                        Verdict::Unknown(reason) => verdict = Verdict::Unknown(reason),
+                        Verdict::Matched => {}
+                    }
+                }
+                verdict
+            }
+            RuleKind::Or => {
+                let mut verdict = Verdict::NotMatched;
+                for condition in &rule.conditions {
+                    match self.evaluate(condition) {
+                        Verdict::Matched => return Verdict::Matched,
+                        Verdict::Unknown(reason) => verdict = Verdict::Unknown(reason),
+                        Verdict::NotMatched => {}
+                    }
+                }
+                verdict
+            }
+            RuleKind::Not => match rule.conditions.first().map(|inner| self.evaluate(inner)) {
+                Some(Verdict::Matched) => Verdict::NotMatched,
+                Some(Verdict::NotMatched) => Verdict::Matched,
+                Some(unknown) => unknown,
+                None => Verdict::NotMatched,
+            },
+            RuleKind::Match => Verdict::Matched,
+            RuleKind::DomainRegex | RuleKind::ProcessPathRegex | RuleKind::ProcessNameRegex => {
+                Verdict::Unknown(format!("{} rules are not evaluated locally", rule.kind))
+            }
+            RuleKind::SubRule => {
+                Verdict::Unknown("SUB-RULE cannot be nested in conditions".to_string())
+            }
+            RuleKind::IpAsn
+            | RuleKind::SrcGeoIp
+            | RuleKind::SrcIpAsn
+            | RuleKind::SrcIpCidr
+            | RuleKind::SrcIpSuffix
+            | RuleKind::SrcPort
+            | RuleKind::InPort
+            | RuleKind::InType
+            | RuleKind::InUser
+            | RuleKind::InName
+            | RuleKind::Uid
+            | RuleKind::Dscp => {
+                Verdict::Unknown(format!("{} depends on data only the kernel has", rule.kind))
+            }
+        }
+    }
+
+    fn match_domain(&self, matches: impl Fn(&str) -> bool) -> Verdict {
+        self.query.domain().is_some_and(matches).into()
+    }
+
+    fn match_ips(&mut self, rule: &Rule, matches: impl Fn(IpAddr) -> bool) -> Verdict {
+        self.destination_ips(rule.has_option("no-resolve"))
+            .into_iter()
+            .any(matches)
+            .into()
+    }
+
+    fn match_geoip(&mut self, rule: &Rule) -> Verdict {
+        let code = rule.payload.to_ascii_uppercase();
+        let ips = self.destination_ips(rule.has_option("no-resolve"));
+        if code == "LAN" || code == "PRIVATE" {
+            return ips.into_iter().any(is_private_ip).into();
+        }
+        let mut verdict = Verdict::NotMatched;
+        for ip in ips {
+            match self.geodata.country(ip) {
+                Some(Some(country)) if country == code => return Verdict::Matched,
+                Some(_) => {}
+                None => verdict = Verdict::Unknown("GeoIP database not found".to_string()),
+            }
+        }
+        verdict
+    }
+
+    /// Addresses the connection goes to, resolving domains through the system
+    /// resolver unless the rule asks for `no-resolve`.
+    fn destination_ips(&mut self, no_resolve: bool) -> Vec<IpAddr> {
+        if let Some(ip) = self.query.ip() {
+            return vec![ip];
+        }
+        if no_resolve {
+            return Vec::new();
+        }
+        let resolve = self.resolve;
+        self.resolved
+            .get_or_insert_with(|| resolve(&self.query.host))
+            .clone()
+    }
+
+    fn match_rule_set(&mut self, rule: &Rule) -> Verdict {
+        let name = rule.payload.clone();
+        if !self.providers.contains_key(&name) {
+            let provider = self.load_provider(&name);
+            self.providers.insert(name.clone(), provider);
+        }
+        let provider = match self.providers.remove(&name) {
+            Some(Ok(provider)) => provider,
+            Some(Err(reason)) => {
+                self.providers.insert(name, Err(reason.clone()));
+                return Verdict::Unknown(reason);
+            }
+            None => return Verdict::NotMatched,
+        };
+
+        let verdict = match &provider {
+            RuleProvider::Domain(entries) => self.match_domain(|host| {
+                entries
+                    .iter()
+                    .any(|entry| domain_set_entry_matches(entry, host))
+            }),
+            RuleProvider::IpCidr(networks) => self.match_ips(rule, |ip| {
+                networks.iter().any(|net| cidr_contains(*net, ip))
+            }),
+            RuleProvider::Classical(rules) => {
+                let mut verdict = Verdict::NotMatched;
+                for inner in rules {
+                    match self.evaluate(inner) {
+                        Verdict::Matched => {
+                            verdict = Verdict::Matched;
+                            break;
+                        }
+                        Verdict::Unknown(reason) => verdict = Verdict::Unknown(reason),
+                        Verdict::NotMatched => {}
+                    }
+                }
+                verdict
+            }
+        };
+        self.providers.insert(name, Ok(provider));
+        verdict
+    }
+
+    fn load_provider(&self, name: &str) -> Result<RuleProvider, String> {
+        let config = self
+            .document
+            .get("rule-providers")
+            .and_then(|providers| providers.get(name))
+            .and_then(Value::as_mapping)
+            .ok_or_else(|| format!("rule provider `{name}` is not defined"))?;
+        let field = |key: &str| {
+            config
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::to_ascii_lowercase)
+                .unwrap_or_default()
+        };
+        let format = field("format");
+
+        let entries = if field("type") == "inline" {
+            config
+                .get("payload")
+                .and_then(Value::as_sequence)
+                .map(|items| sequence_strings(items))
+                .unwrap_or_default()
+        } else {
+            if format == "mrs" {
+                return Err(format!("rule provider `{name}` uses the binary mrs format"));
+            }
+            let path = config
+                .get("path")
+                .and_then(Value::as_str)
+                .ok_or_else(|| format!("rule provider `{name}` has no local path"))?;
+            let content = fs::read_to_string(self.runtime_dir.join(path))
+                .map_err(|_| format!("rule provider `{name}` has not been downloaded"))?;
+            if format == "text" {
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string)
+                    .collect()
+            } else {
+                serde_yaml::from_str::<Mapping>(&content)
+                    .ok()
+                    .and_then(|doc| {
+                        doc.get("payload")?
+                            .as_sequence()
+                            .map(|i| sequence_strings(i))
+                    })
+                    .ok_or_else(|| format!("rule provider `{name}` has no payload"))?
+            }
+        };
+
+        match field("behavior").as_str() {
+            "domain" => Ok(RuleProvider::Domain(
+                entries
+                    .into_iter()
+                    .map(|entry| entry.to_ascii_lowercase())
+                    .collect(),
+            )),
+            "ipcidr" => Ok(RuleProvider::IpCidr(
+                entries
+                    .iter()
+                    .filter_map(|entry| parse_cidr(entry).ok())
+                    .collect(),
+            )),
+            "classical" | "" => Ok(RuleProvider::Classical(
+                entries
+                    .iter()
+                    .filter_map(|entry| Rule::parse_condition(entry).ok())
+                    .collect(),
+            )),
+            other => Err(format!(
+                "rule provider `{name}` has unknown behavior `{other}`"
+            )),
+        }
+    }
+}
+
+fn sequence_strings(items: &[Value]) -> Vec<String> {
+    items
+        .iter()
+        .filter_map(Value::as_str)
+        .map(|item| item.trim().to_string())
+        .collect()
+}
+
+fn resolve_host(host: &str) -> Vec<IpAddr> {
+    (host, 0)
+        .to_socket_addrs()
+        .map(|addrs| addrs.map(|addr| addr.ip()).collect())
+        .unwrap_or_default()
+}
+
+/// Follows `target` through proxy groups. A group routes to its selection
+/// reported by the kernel, or to its first member when the kernel is not
+/// running.
+fn policy_chain(
+    target: &str,
+    groups: &[ProxyGroup],
+    selections: &BTreeMap<String, String>,
+) -> Vec<String> {
+    let mut chain = vec![target.to_string()];
+    let mut current = target;
+    while let Some(group) = groups.iter().find(|group| group.name == current) {
+        let Some(next) = selections
+            .get(&group.name)
+            .or_else(|| group.proxies.first())
+        else {
+            break;
+        };
+        if chain.contains(next) {
+            break;
+        }
+        chain.push(next.clone());
+        current = next;
+    }
+    chain
+}
+
+fn domain_has_suffix(host: &str, suffix: &str) -> bool {
+    host == suffix
+        || host
+            .strip_suffix(suffix)
+            .is_some_and(|rest| rest.ends_with('.'))
+}
+
+/// Entry of a `domain` rule-set: `+.x` matches x and its subdomains, `.x`
+/// only subdomains, `*.x` one extra label, anything else the exact name or
+/// a wildcard pattern.
+fn domain_set_entry_matches(entry: &str, host: &str) -> bool {
+    if let Some(suffix) = entry.strip_prefix("+.") {
+        return domain_has_suffix(host, suffix);
+    }
+    if entry.starts_with('.') {
+        return host.ends_with(entry);
+    }
+    if let Some(suffix) = entry.strip_prefix("*.") {
+        return host
+            .strip_suffix(suffix)
+            .and_then(|rest| rest.strip_suffix('.'))
+            .is_some_and(|label| !label.is_empty() && !label.contains('.'));
+    }
+    if entry.contains('*') {
+        return wildcard_match(entry, host);
+    }
+    entry == host
+}
+
+/// Glob match where `*` matches any run of characters and `?` one character.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let text = text.as_bytes();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == b'?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|byte| *byte == b'*')
+}
+
+fn ip_bits(ip: IpAddr) -> (u128, u32) {
+    match ip {
+        IpAddr::V4(v4) => (u128::from(u32::from(v4)), 32),
+        IpAddr::V6(v6) => (u128::from(v6), 128),
+    }
+}
+
+fn cidr_contains((network, prefix): (IpAddr, u8), ip: IpAddr) -> bool {
+    let (network, width) = ip_bits(network);
+    let (ip, ip_width) = ip_bits(ip);
+    if width != ip_width {
+        return false;
+    }
+    let shift = width - u32::from(prefix);
+    network.checked_shr(shift).unwrap_or(0) == ip.checked_shr(shift).unwrap_or(0)
+}
+
+/// `IP-SUFFIX` compares the trailing `prefix` bits of the address.
+fn ip_has_suffix((suffix, prefix): (IpAddr, u8), ip: IpAddr) -> bool {
+    let (suffix, width) = ip_bits(suffix);
+    let (ip, ip_width) = ip_bits(ip);
+    if width != ip_width {
+        return false;
+    }
+    let mask = 1u128
+        .checked_shl(u32::from(prefix))
+        .map_or(u128::MAX, |bit| bit - 1);
+    suffix & mask == ip & mask
+}
+
+fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || cidr_contains((IpAddr::from([100, 64, 0, 0]), 10), ip)
+        }
+        IpAddr::V6(v6) => {
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || (v6.segments()[0] & 0xfe00) == 0xfc00
+                || (v6.segments()[0] & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(lines: &[&str]) -> Vec<Rule> {
+        lines
+            .iter()
+            .map(|line| Rule::parse(line).expect("test rule"))
+            .collect()
+    }
+
+    fn find_in(
+        dir: &Path,
+        query: MatchQuery,
+        document: &str,
+        lines: &[&str],
+        groups: &[ProxyGroup],
+    ) -> Option<RuleMatch> {
+        let mut geodata = GeoData::new(dir);
+        let mut matcher = RuleMatcher::new(&query, document, dir, &mut geodata);
+        matcher.resolve = |host| match host {
+            "resolved.example" => vec!["10.1.2.3".parse().unwrap()],
+            _ => Vec::new(),
+        };
+        matcher.find_match(&rules(lines), groups, &BTreeMap::new())
+    }
+
+    fn find(host: &str, document: &str, lines: &[&str]) -> Option<RuleMatch> {
+        let query = MatchQuery::new(host, None, None).expect("query");
+        let dir = std::env::temp_dir().join("linkpad-rule-matcher-missing");
+        find_in(&dir, query, document, lines, &[])
+    }
+
+    #[test]
+    fn matches_basic_rules_in_order() {
+        let lines = [
+            "DOMAIN,exact.example,A",
+            "DOMAIN-SUFFIX,example.com,B",
+            "DOMAIN-KEYWORD,tracker,C",
+            "IP-CIDR,10.0.0.0/8,D,no-resolve",
+            "IP-CIDR,10.1.0.0/16,E",
+            "DST-PORT,22/2000-3000,F",
+            "PROCESS-NAME,curl,G",
+            "MATCH,H",
+        ];
+        let dir = std::env::temp_dir().join("linkpad-rule-matcher-missing");
+        let index = |host: &str, port: Option<u16>, process: Option<&str>| {
+            let query = MatchQuery::new(host, port, process).expect("query");
+            find_in(&dir, query, "", &lines, &[]).map(|found| found.index)
+        };
+        assert_eq!(index("Exact.Example.", None, None), Some(0));
+        assert_eq!(index("www.example.com", None, None), Some(1));
+        assert_eq!(index("notexample.com", Some(80), Some("x")), Some(7));
+        assert_eq!(index("ads.tracker.net", None, None), Some(2));
+        assert_eq!(index("10.2.3.4", None, None), Some(3));
+        assert_eq!(index("resolved.example", Some(80), Some("x")), Some(4));
+        assert_eq!(index("host.lan", Some(2222), None), Some(5));
+        assert_eq!(index("host.lan", Some(80), Some("/usr/bin/curl")), Some(6));
+
+        let found = find("host.lan", "", &lines).expect("MATCH should catch it");
+        let skipped = found
+            .skipped
+            .iter()
+            .map(|skipped| skipped.index)
+            .collect::<Vec<_>>();
+        assert_eq!(skipped, vec![5, 6]);
+    }
+
+    #[test]
+    fn evaluates_logic_and_skips_unsupported_rules() {
+        let lines = [
+            "DOMAIN-REGEX,^ads\\.,REJECT",
+            "AND,((DOMAIN-SUFFIX,example.com),(NOT,((DOMAIN,www.example.com)))),A",
+            "OR,((SRC-IP-CIDR,192.168.0.0/16),(DOMAIN-WILDCARD,*.test)),B",
+            "GEOIP,LAN,DIRECT",
+            "MATCH,C",
+        ];
+        assert_eq!(
+            find("api.example.com", "", &lines).map(|m| m.index),
+            Some(1)
+        );
+        assert_eq!(
+            find("www.example.com", "", &lines).map(|m| m.index),
+            Some(4)
+        );
+        assert_eq!(find("192.168.1.5", "", &lines).map(|m| m.index), Some(3));
+
+        assert_eq!(find("a.test", "", &lines).map(|m| m.index), Some(2));
+
+        // The OR is undecided because of its SRC-IP-CIDR branch.
+        let found = find("other.net", "", &lines).expect("match");
+        assert_eq!(found.index, 4);
+        assert_eq!(
+            found
+                .skipped
+                .iter()
+                .map(|skipped| skipped.index)
+                .collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+    }
+
+    #[test]
+    fn uses_rule_providers_and_sub_rules() {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!("linkpad-rule-matcher-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("ruleset")).expect("create test dir");
+        fs::write(dir.join("ruleset/cidr.txt"), "# comment\n203.0.113.0/24\n")
+            .expect("write provider");
+        let document = r#"
+rule-providers:
+  ads:
+    type: inline
+    behavior: domain
+    payload: ["+.ads.example", "*.cdn.example"]
+  cidr:
+    type: file
+    behavior: ipcidr
+    format: text
+    path: ./ruleset/cidr.txt
+  missing:
+    type: http
+    behavior: classical
+    path: ./ruleset/missing.yaml
+sub-rules:
+  corp:
+    - DOMAIN,vpn.corp.example,Office
+    - MATCH,DIRECT
+"#;
+        let lines = [
+            "RULE-SET,missing,X",
+            "RULE-SET,ads,REJECT",
+            "RULE-SET,cidr,Proxy",
+            "SUB-RULE,(DOMAIN-SUFFIX,corp.example),corp",
+            "MATCH,Proxy",
+        ];
+        let groups = vec![
+            ProxyGroup {
+                name: "Office".to_string(),
+                kind: "select".to_string(),
+                size: 2,
+                proxies: vec!["Auto".to_string(), "DIRECT".to_string()],
+            },
+            ProxyGroup {
+                name: "Auto".to_string(),
+                kind: "url-test".to_string(),
+                size: 1,
+                proxies: vec!["node-1".to_string()],
+            },
+        ];
+        let find = |host: &str| {
+            let query = MatchQuery::new(host, None, None).expect("query");
+            find_in(&dir, query, document, &lines, &groups).expect("match")
+        };
+
+        let found = find("x.ads.example");
+        assert_eq!(found.index, 1);
+        assert_eq!(found.skipped.len(), 1);
+        assert_eq!(find("a.cdn.example").index, 1);
+        assert_eq!(find("a.b.cdn.example").index, 4);
+        assert_eq!(find("203.0.113.9").index, 2);
+
+        let found = find("vpn.corp.example");
+        assert_eq!(found.index, 3);
+        assert_eq!(
+            found.sub_rule.map(|rule| rule.to_string()),
+            Some("DOMAIN,vpn.corp.example,Office".to_string())
+        );
+        assert_eq!(found.policy_chain, vec!["Office", "Auto", "node-1"]);
+        assert_eq!(find("mail.corp.example").policy_chain, vec!["DIRECT"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn matches_ip_helpers() {
+        let ip = |value: &str| value.parse::<IpAddr>().unwrap();
+        assert!(cidr_contains((ip("10.0.0.0"), 8), ip("10.255.0.1")));
+        assert!(!cidr_contains((ip("10.0.0.0"), 8), ip("11.0.0.1")));
+        assert!(cidr_contains((ip("0.0.0.0"), 0), ip("1.2.3.4")));
+        assert!(!cidr_contains((ip("::"), 0), ip("1.2.3.4")));
+        assert!(ip_has_suffix((ip("0.0.0.1"), 8), ip("192.168.3.1")));
+        assert!(wildcard_match("*.goo?le.com", "www.google.com"));
+        assert!(!wildcard_match("*.google.com", "google.com"));
+    }
+}
@@ -0,0 +1,147 @@
+use super::{
+    ControllerDialect, GithubReleaseAsset, KernelBackend, extract_from_zip, normalize_version_tag,
+    release_arch_tag, release_os_tag,
+};
+use crate::{CoreError, CoreResult};
+use flate2::read::GzDecoder;
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+/// mihomo reads Linkpad's Clash-style runtime config as it is.
+#[derive(Debug)]
+pub(crate) struct MihomoBackend;
+
+impl KernelBackend for MihomoBackend {
+    fn binary_stem(&self) -> &'static str {
+        "mihomo"
+    }
+
+    fn binary_env_var(&self) -> &'static str {
+        "LINKPAD_MIHOMO_PATH"
+    }
+
+    fn config_file_name(&self) -> &'static str {
+        "runtime.yaml"
+    }
+
+    fn launch_args(&self, config_path: &Path, runtime_dir: &Path) -> Vec<OsString> {
+        vec![
+            "-f".into(),
+            config_path.into(),
+            "-d".into(),
+            runtime_dir.into(),
+        ]
+    }
+
+    fn test_args(&self, config_path: &Path, runtime_dir: &Path) -> Vec<OsString> {
+        let mut args = vec![OsString::from("-t")];
+        args.extend(self.launch_args(config_path, runtime_dir));
+        args
+    }
+
+    fn version_args(&self) -> &'static [&'static [&'static str]] {
+        &[&["-v"], &["--version"], &["version"]]
+    }
+
+    fn runtime_config(&self, clash_config: &str) -> CoreResult<String> {
+        Ok(clash_config.to_string())
+    }
+
+    fn release_repo(&self) -> &'static str {
+        "MetaCubeX/mihomo"
+    }
+
+    fn alpha_release_tag(&self) -> Option<&'static str> {
+        Some("Prerelease-Alpha")
+    }
+
+    fn select_release_asset<'a>(
+        &self,
+        assets: &'a [GithubReleaseAsset],
+    ) -> Option<&'a GithubReleaseAsset> {
+        let os = release_os_tag();
+        let arch = release_arch_tag();
+        let required_prefix = format!("mihomo-{os}-{arch}-");
+
+        let mut matches = assets
+            .iter()
+            .filter(|asset| {
+                let lower = asset.name.to_ascii_lowercase();
+                asset.name.starts_with(&required_prefix)
+                    && (lower.ends_with(".gz") || lower.ends_with(".zip"))
+            })
+            .collect::<Vec<_>>();
+
+        matches.sort_by_key(|asset| score_release_asset(&asset.name));
+        matches.into_iter().next()
+    }
+
+    fn release_asset_candidates(&self, tag: &str) -> Vec<String> {
+        let os = release_os_tag();
+        let arch = release_arch_tag();
+        let normalized_tag = normalize_version_tag(tag);
+        let mut names = BTreeSet::new();
+
+        names.insert(format!("mihomo-{os}-{arch}-{normalized_tag}.gz"));
+        names.insert(format!("mihomo-{os}-{arch}-go124-{normalized_tag}.gz"));
+        names.insert(format!("mihomo-{os}-{arch}-go122-{normalized_tag}.gz"));
+        names.insert(format!("mihomo-{os}-{arch}-go120-{normalized_tag}.gz"));
+        names.insert(format!("mihomo-{os}-{arch}-v1-{normalized_tag}.gz"));
+        names.insert(format!("mihomo-{os}-{arch}-v2-{normalized_tag}.gz"));
+        names.insert(format!("mihomo-{os}-{arch}-v1-go124-{normalized_tag}.gz"));
+        names.insert(format!("mihomo-{os}-{arch}-v1-go122-{normalized_tag}.gz"));
+        names.insert(format!("mihomo-{os}-{arch}-v1-go120-{normalized_tag}.gz"));
+        names.insert(format!("mihomo-{os}-{arch}-v2-go124-{normalized_tag}.gz"));
+        names.insert(format!("mihomo-{os}-{arch}-v2-go122-{normalized_tag}.gz"));
+        names.insert(format!("mihomo-{os}-{arch}-v2-go120-{normalized_tag}.gz"));
+        if arch == "amd64" {
+            names.insert(format!("mihomo-{os}-{arch}-compatible-{normalized_tag}.gz"));
+        }
+
+        names.into_iter().collect()
+    }
+
+    /// Release assets are a gzip-compressed binary, or on Windows a zip
+    /// archive holding `mihomo-<os>-<arch>….exe`.
+    fn extract_binary(&self, asset_name: &str, bytes: &[u8]) -> CoreResult<Vec<u8>> {
+        if !asset_name.to_ascii_lowercase().ends_with(".zip") {
+            return decompress_gzip(bytes);
+        }
+        extract_from_zip(bytes, |name| name.starts_with("mihomo"))?.ok_or_else(|| {
+            CoreError::InvalidConfig(format!("no mihomo binary found in `{asset_name}`"))
+        })
+    }
+
+    fn controller_dialect(&self) -> ControllerDialect {
+        ControllerDialect::Mihomo
+    }
+}
+
+fn score_release_asset(name: &str) -> (u8, u8, u8, usize) {
+    let lower = name.to_ascii_lowercase();
+    let has_alpha = lower.contains("alpha");
+    let has_compatible = lower.contains("compatible");
+    let has_go = lower.contains("-go");
+    (
+        has_alpha as u8,
+        has_compatible as u8,
+        has_go as u8,
+        name.len(),
+    )
+}
+
+fn decompress_gzip(bytes: &[u8]) -> CoreResult<Vec<u8>> {
+    let mut decoder = GzDecoder::new(Cursor::new(bytes));
+    let mut output = Vec::new();
+    decoder
+        .read_to_end(&mut output)
+        .map_err(|error| CoreError::InvalidConfig(error.to_string()))?;
+    if output.is_empty() {
+        return Err(CoreError::InvalidConfig(
+            "downloaded kernel archive is empty".to_string(),
+        ));
+    }
+    Ok(output)
+}
@@ -0,0 +1,214 @@
+mod mihomo;
+mod sing_box;
+
+use crate::{CoreError, CoreResult};
+use mihomo::MihomoBackend;
+use serde::{Deserialize, Serialize};
+use sing_box::SingBoxBackend;
+use std::ffi::OsString;
+use std::fmt;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+/// Proxy kernel Linkpad runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KernelKind {
+    #[default]
+    Mihomo,
+    /// sing-box 1.11 or newer, driven through its Clash API.
+    SingBox,
+}
+
+impl KernelKind {
+    pub const ALL: [KernelKind; 2] = [Self::Mihomo, Self::SingBox];
+
+    pub fn as_str(self) -> &'static str {
+        self.backend().binary_stem()
+    }
+
+    pub(crate) fn backend(self) -> &'static dyn KernelBackend {
+        match self {
+            Self::Mihomo => &MihomoBackend,
+            Self::SingBox => &SingBoxBackend,
+        }
+    }
+}
+
+/// Flavour of the Clash-compatible API a kernel's external controller speaks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ControllerDialect {
+    Mihomo,
+    /// sing-box's `clash_api`, which answers `PUT /configs` without loading
+    /// anything.
+    SingBoxClash,
+}
+
+impl ControllerDialect {
+    /// Whether the kernel can load a new config file without a restart.
+    pub fn supports_reload(self) -> bool {
+        matches!(self, Self::Mihomo)
+    }
+}
+
+/// Everything [`super::KernelRuntime`] needs to know about one kernel: where
+/// its binary lives, how it is started, what config it reads and where its
+/// releases come from.
+pub(crate) trait KernelBackend: Sync {
+    /// Name of the binary without the platform's executable suffix.
+    fn binary_stem(&self) -> &'static str;
+
+    fn binary_name(&self) -> String {
+        format!("{}{}", self.binary_stem(), std::env::consts::EXE_SUFFIX)
+    }
+
+    /// Variable that points at a binary to use instead of looking one up.
+    fn binary_env_var(&self) -> &'static str;
+
+    /// Name of the runtime config inside the runtime directory.
+    fn config_file_name(&self) -> &'static str;
+
+    fn launch_args(&self, config_path: &Path, runtime_dir: &Path) -> Vec<OsString>;
+
+    /// Arguments that only check the config and exit non-zero when the
+    /// kernel refuses it.
+    fn test_args(&self, config_path: &Path, runtime_dir: &Path) -> Vec<OsString>;
+
+    /// Argument lists that print the version, tried in order.
+    fn version_args(&self) -> &'static [&'static [&'static str]];
+
+    /// Renders the config the kernel reads from Linkpad's Clash-style runtime
+    /// config, which is the active profile with its overrides and Linkpad's
+    /// own settings applied.
+    fn runtime_config(&self, clash_config: &str) -> CoreResult<String>;
+
+    /// GitHub repository the kernel is released from, as `owner/name`.
+    fn release_repo(&self) -> &'static str;
+
+    /// Tag the kernel keeps moving to its newest alpha build, if it has one.
+    fn alpha_release_tag(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Best asset of a release for this platform.
+    fn select_release_asset<'a>(
+        &self,
+        assets: &'a [GithubReleaseAsset],
+    ) -> Option<&'a GithubReleaseAsset>;
+
+    /// Asset names to try when only the tag of the latest release is known.
+    fn release_asset_candidates(&self, tag: &str) -> Vec<String>;
+
+    /// Takes the binary out of a downloaded release asset.
+    fn extract_binary(&self, asset_name: &str, bytes: &[u8]) -> CoreResult<Vec<u8>>;
+
+    fn controller_dialect(&self) -> ControllerDialect;
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GithubRelease {
+    #[serde(default)]
+    pub tag_name: String,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub assets: Vec<GithubReleaseAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GithubReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+    #[serde(default)]
+    pub digest: Option<String>,
+}
+
+pub(crate) fn normalize_version_tag(tag: &str) -> String {
+    let trimmed = tag.trim();
+    if trimmed.is_empty() {
+        return String::new();
+    }
+    if trimmed.starts_with('v') {
+        trimmed.to_string()
+    } else {
+        format!("v{trimmed}")
+    }
+}
+
+fn archive_error(error: impl fmt::Display) -> CoreError {
+    CoreError::InvalidConfig(format!("failed to read kernel archive: {error}"))
+}
+
+/// Contents of the first file in a zip archive whose name passes
+/// `is_binary`.
+fn extract_from_zip(bytes: &[u8], is_binary: impl Fn(&str) -> bool) -> CoreResult<Option<Vec<u8>>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(archive_error)?;
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(archive_error)?;
+        let matches = file.is_file()
+            && Path::new(file.name())
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(&is_binary);
+        if matches {
+            let mut binary = Vec::new();
+            file.read_to_end(&mut binary).map_err(archive_error)?;
+            return Ok(Some(binary));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(target_os = "macos")]
+fn release_os_tag() -> &'static str {
+    "darwin"
+}
+
+#[cfg(target_os = "linux")]
+fn release_os_tag() -> &'static str {
+    "linux"
+}
+
+#[cfg(target_os = "windows")]
+fn release_os_tag() -> &'static str {
+    "windows"
+}
+
+#[cfg(all(
+    not(target_os = "macos"),
+    not(target_os = "linux"),
+    not(target_os = "windows")
+))]
+fn release_os_tag() -> &'static str {
+    std::env::consts::OS
+}
+
+#[cfg(target_arch = "aarch64")]
+fn release_arch_tag() -> &'static str {
+    "arm64"
+}
+
+#[cfg(target_arch = "x86_64")]
+fn release_arch_tag() -> &'static str {
+    "amd64"
+}
+
+#[cfg(target_arch = "x86")]
+fn release_arch_tag() -> &'static str {
+    "386"
+}
+
+#[cfg(target_arch = "arm")]
+fn release_arch_tag() -> &'static str {
+    "armv7"
+}
+
+#[cfg(all(
+    not(target_arch = "aarch64"),
+    not(target_arch = "x86_64"),
+    not(target_arch = "x86"),
+    not(target_arch = "arm")
+))]
+fn release_arch_tag() -> &'static str {
+    std::env::consts::ARCH
+}
@@ -0,0 +1,831 @@
+use super::{
+    ControllerDialect, GithubReleaseAsset, KernelBackend, archive_error, extract_from_zip,
+    normalize_version_tag, release_arch_tag, release_os_tag,
+};
+use crate::{CoreError, CoreResult, Rule, RuleKind};
+use flate2::read::GzDecoder;
+use serde_json::{Map, Value as Json, json};
+use serde_yaml::Value as Yaml;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsString;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use tracing::warn;
+
+const DIRECT_TAG: &str = "DIRECT";
+const GLOBAL_TAG: &str = "GLOBAL";
+const DEFAULT_MIXED_PORT: u64 = 7890;
+const DEFAULT_CONTROLLER: &str = "127.0.0.1:9097";
+const DEFAULT_TEST_URL: &str = "https://www.gstatic.com/generate_204";
+const DEFAULT_TEST_INTERVAL_SECS: u64 = 300;
+const GEOIP_RULE_SET_BASE: &str = "https://raw.githubusercontent.com/SagerNet/sing-geoip/rule-set";
+const GEOSITE_RULE_SET_BASE: &str =
+    "https://raw.githubusercontent.com/SagerNet/sing-geosite/rule-set";
+
+/// sing-box gets a JSON config translated from the Clash-style runtime config.
+#[derive(Debug)]
+pub(crate) struct SingBoxBackend;
+
+impl KernelBackend for SingBoxBackend {
+    fn binary_stem(&self) -> &'static str {
+        "sing-box"
+    }
+
+    fn binary_env_var(&self) -> &'static str {
+        "LINKPAD_SING_BOX_PATH"
+    }
+
+    fn config_file_name(&self) -> &'static str {
+        "runtime.json"
+    }
+
+    fn launch_args(&self, config_path: &Path, runtime_dir: &Path) -> Vec<OsString> {
+        command_args("run", config_path, runtime_dir)
+    }
+
+    fn test_args(&self, config_path: &Path, runtime_dir: &Path) -> Vec<OsString> {
+        command_args("check", config_path, runtime_dir)
+    }
+
+    fn version_args(&self) -> &'static [&'static [&'static str]] {
+        &[&["version"]]
+    }
+
+    fn runtime_config(&self, clash_config: &str) -> CoreResult<String> {
+        translate(clash_config)
+    }
+
+    fn release_repo(&self) -> &'static str {
+        "SagerNet/sing-box"
+    }
+
+    fn select_release_asset<'a>(
+        &self,
+        assets: &'a [GithubReleaseAsset],
+    ) -> Option<&'a GithubReleaseAsset> {
+        // Builds with extra suffixes (`-glibc`, `-legacy-windows-7`, ...)
+        // do not end in the plain platform suffix.
+        let suffix = format!(
+            "-{}-{}{}",
+            release_os_tag(),
+            release_arch_tag(),
+            archive_extension()
+        );
+        assets
+            .iter()
+            .filter(|asset| asset.name.starts_with("sing-box-") && asset.name.ends_with(&suffix))
+            .min_by_key(|asset| asset.name.len())
+    }
+
+    fn release_asset_candidates(&self, tag: &str) -> Vec<String> {
+        let version = normalize_version_tag(tag);
+        let version = version.trim_start_matches('v');
+        vec![format!(
+            "sing-box-{version}-{}-{}{}",
+            release_os_tag(),
+            release_arch_tag(),
+            archive_extension()
+        )]
+    }
+
+    fn extract_binary(&self, asset_name: &str, bytes: &[u8]) -> CoreResult<Vec<u8>> {
+        let binary_name = self.binary_name();
+        let binary = if asset_name.to_ascii_lowercase().ends_with(".zip") {
+            extract_from_zip(bytes, |name| name == binary_name)?
+        } else {
+            extract_from_tar_gz(bytes, &binary_name)?
+        };
+        binary.ok_or_else(|| {
+            CoreError::InvalidConfig(format!("`{binary_name}` not found in `{asset_name}`"))
+        })
+    }
+
+    fn controller_dialect(&self) -> ControllerDialect {
+        ControllerDialect::SingBoxClash
+    }
+}
+
+fn command_args(command: &str, config_path: &Path, runtime_dir: &Path) -> Vec<OsString> {
+    vec![
+        command.into(),
+        "-c".into(),
+        config_path.into(),
+        "-D".into(),
+        runtime_dir.into(),
+        "--disable-color".into(),
+    ]
+}
+
+#[cfg(target_os = "windows")]
+fn archive_extension() -> &'static str {
+    ".zip"
+}
+
+#[cfg(not(target_os = "windows"))]
+fn archive_extension() -> &'static str {
+    ".tar.gz"
+}
+
+fn extract_from_tar_gz(bytes: &[u8], binary_name: &str) -> CoreResult<Option<Vec<u8>>> {
+    let mut archive = tar::Archive::new(GzDecoder::new(Cursor::new(bytes)));
+    for entry in archive.entries().map_err(archive_error)? {
+        let mut entry = entry.map_err(archive_error)?;
+        let is_binary = entry.header().entry_type().is_file()
+            && entry
+                .path()
+                .map_err(archive_error)?
+                .file_name()
+                .is_some_and(|name| name == binary_name);
+        if is_binary {
+            let mut binary = Vec::new();
+            entry.read_to_end(&mut binary).map_err(archive_error)?;
+            return Ok(Some(binary));
+        }
+    }
+    Ok(None)
+}
+
+/// Translates a Clash-style runtime config into sing-box's JSON. Proxies and
+/// rules sing-box has no counterpart for are left out with a warning; groups
+/// lose the members that were left out.
+fn translate(clash_config: &str) -> CoreResult<String> {
+    let root: Yaml =
+        serde_yaml::from_str(clash_config).map_err(|error| CoreError::Parse(error.to_string()))?;
+    if !root.is_mapping() {
+        return Err(CoreError::InvalidConfig(
+            "runtime config root must be a YAML mapping".to_string(),
+        ));
+    }
+
+    let mut outbounds = Vec::new();
+    let mut order = Vec::new();
+    for proxy in sequence(&root, "proxies") {
+        let name = scalar(proxy, "name").unwrap_or_default();
+        match proxy_outbound(proxy) {
+            Ok(outbound) => {
+                order.push(name);
+                outbounds.push(Json::Object(outbound));
+            }
+            Err(reason) => warn!("sing-box: proxy `{name}` left out: {reason}"),
+        }
+    }
+    let groups = sequence(&root, "proxy-groups")
+        .filter_map(|group| Some((scalar(group, "name")?, group)))
+        .collect::<Vec<_>>();
+    order.extend(groups.iter().map(|(name, _)| name.clone()));
+    let mut known = order.iter().cloned().collect::<BTreeSet<_>>();
+    known.insert(DIRECT_TAG.to_string());
+    for (name, group) in &groups {
+        outbounds.push(group_outbound(name, group, &known));
+    }
+    outbounds.push(json!({ "type": "direct", "tag": DIRECT_TAG }));
+    if known.insert(GLOBAL_TAG.to_string()) {
+        let mut members = order.clone();
+        members.push(DIRECT_TAG.to_string());
+        outbounds.push(json!({ "type": "selector", "tag": GLOBAL_TAG, "outbounds": members }));
+    }
+
+    let (rules, rule_sets, final_tag) = route_rules(&root, &known);
+    let mut route = json!({
+        "rules": rules,
+        "final": final_tag,
+        "auto_detect_interface": true,
+    });
+    if !rule_sets.is_empty() {
+        route["rule_set"] = rule_sets
+            .into_iter()
+            .map(|(tag, url)| json!({ "type": "remote", "tag": tag, "format": "binary", "url": url }))
+            .collect();
+    }
+
+    let listen = if bool_field(&root, "allow-lan") {
+        "::"
+    } else {
+        "127.0.0.1"
+    };
+    let mode = match scalar(&root, "mode").as_deref() {
+        Some("global") => "Global",
+        Some("direct") => "Direct",
+        _ => "Rule",
+    };
+    let config = json!({
+        "log": log_options(&root),
+        "inbounds": inbounds(&root, listen),
+        "outbounds": outbounds,
+        "route": route,
+        "experimental": {
+            "clash_api": {
+                "external_controller": scalar(&root, "external-controller")
+                    .unwrap_or_else(|| DEFAULT_CONTROLLER.to_string()),
+                "secret": scalar(&root, "secret").unwrap_or_default(),
+                "default_mode": mode,
+            }
+        }
+    });
+    serde_json::to_string_pretty(&config)
+        .map_err(|error| CoreError::InvalidConfig(error.to_string()))
+}
+
+/// The mixed listener, plus the dedicated `port`, `socks-port` and
+/// `listeners` entries of a kind sing-box also has.
+fn inbounds(root: &Yaml, listen: &str) -> Vec<Json> {
+    let mut inbounds = vec![json!({
+        "type": "mixed",
+        "tag": "mixed-in",
+        "listen": listen,
+        "listen_port": u64_field(root, "mixed-port").unwrap_or(DEFAULT_MIXED_PORT),
+    })];
+    for (key, kind) in [("port", "http"), ("socks-port", "socks")] {
+        if let Some(port) = u64_field(root, key) {
+            inbounds.push(json!({
+                "type": kind,
+                "tag": format!("{kind}-in"),
+                "listen": listen,
+                "listen_port": port,
+            }));
+        }
+    }
+    for listener in sequence(root, "listeners") {
+        let (Some(name), Some(kind), Some(port)) = (
+            scalar(listener, "name"),
+            scalar(listener, "type"),
+            u64_field(listener, "port"),
+        ) else {
+            continue;
+        };
+        if !matches!(kind.as_str(), "http" | "socks" | "mixed") {
+            warn!("sing-box: listener `{name}` left out: type `{kind}` is not supported");
+            continue;
+        }
+        inbounds.push(json!({
+            "type": kind,
+            "tag": name,
+            "listen": scalar(listener, "listen").unwrap_or_else(|| listen.to_string()),
+            "listen_port": port,
+        }));
+    }
+    inbounds
+}
+
+fn log_options(root: &Yaml) -> Json {
+    match scalar(root, "log-level").as_deref() {
+        Some("silent") => json!({ "disabled": true }),
+        Some("warning") => json!({ "level": "warn", "timestamp": true }),
+        Some(level @ ("error" | "debug")) => json!({ "level": level, "timestamp": true }),
+        _ => json!({ "level": "info", "timestamp": true }),
+    }
+}
+
+fn proxy_outbound(proxy: &Yaml) -> Result<Map<String, Json>, String> {
+    let proxy_type = scalar(proxy, "type")
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let mut outbound = Map::new();
+    outbound.insert("tag".into(), required(proxy, "name")?.into());
+    outbound.insert("server".into(), required(proxy, "server")?.into());
+    outbound.insert(
+        "server_port".into(),
+        u64_field(proxy, "port").ok_or("`port` is missing")?.into(),
+    );
+    let sing_box_type = match proxy_type.as_str() {
+        "ss" => {
+            if proxy.get("plugin").is_some() {
+                return Err("shadowsocks plugins are not translated".to_string());
+            }
+            outbound.insert("method".into(), required(proxy, "cipher")?.into());
+            outbound.insert("password".into(), required(proxy, "password")?.into());
+            "shadowsocks"
+        }
+        "vmess" => {
+            outbound.insert("uuid".into(), required(proxy, "uuid")?.into());
+            outbound.insert(
+                "alter_id".into(),
+                u64_field(proxy, "alterId").unwrap_or(0).into(),
+            );
+            outbound.insert(
+                "security".into(),
+                scalar(proxy, "cipher")
+                    .unwrap_or_else(|| "auto".to_string())
+                    .into(),
+            );
+            insert_transport(&mut outbound, proxy)?;
+            if bool_field(proxy, "tls") {
+                outbound.insert("tls".into(), tls_options(proxy, "servername"));
+            }
+            "vmess"
+        }
+        "vless" => {
+            outbound.insert("uuid".into(), required(proxy, "uuid")?.into());
+            if let Some(flow) = scalar(proxy, "flow") {
+                outbound.insert("flow".into(), flow.into());
+            }
+            insert_transport(&mut outbound, proxy)?;
+            if bool_field(proxy, "tls") {
+                outbound.insert("tls".into(), tls_options(proxy, "servername"));
+            }
+            "vless"
+        }
+        "trojan" => {
+            outbound.insert("password".into(), required(proxy, "password")?.into());
+            insert_transport(&mut outbound, proxy)?;
+            outbound.insert("tls".into(), tls_options(proxy, "sni"));
+            "trojan"
+        }
+        "hysteria2" => {
+            outbound.insert("password".into(), required(proxy, "password")?.into());
+            for (from, to) in [("up", "up_mbps"), ("down", "down_mbps")] {
+                if let Some(mbps) = scalar(proxy, from).as_deref().and_then(leading_number) {
+                    outbound.insert(to.into(), mbps.into());
+                }
+            }
+            if let Some(obfs) = scalar(proxy, "obfs") {
+                outbound.insert(
+                    "obfs".into(),
+                    json!({ "type": obfs, "password": scalar(proxy, "obfs-password").unwrap_or_default() }),
+                );
+            }
+            outbound.insert("tls".into(), tls_options(proxy, "sni"));
+            "hysteria2"
+        }
+        "tuic" => {
+            outbound.insert("uuid".into(), required(proxy, "uuid")?.into());
+            outbound.insert("password".into(), required(proxy, "password")?.into());
+            if let Some(congestion) = scalar(proxy, "congestion-controller") {
+                outbound.insert("congestion_control".into(), congestion.into());
+            }
+            if let Some(mode) = scalar(proxy, "udp-relay-mode") {
+                outbound.insert("udp_relay_mode".into(), mode.into());
+            }
+            outbound.insert("tls".into(), tls_options(proxy, "sni"));
+            "tuic"
+        }
+        "http" | "socks5" => {
+            for key in ["username", "password"] {
+                if let Some(value) = scalar(proxy, key) {
+                    outbound.insert(key.into(), value.into());
+                }
+            }
+            if bool_field(proxy, "tls") {
+                outbound.insert("tls".into(), tls_options(proxy, "sni"));
+            }
+            if proxy_type == "http" {
+                "http"
+            } else {
+                "socks"
+            }
+        }
+        other => return Err(format!("type `{other}` is not supported")),
+    };
+    outbound.insert("type".into(), sing_box_type.into());
+    Ok(outbound)
+}
+
+fn insert_transport(outbound: &mut Map<String, Json>, proxy: &Yaml) -> Result<(), String> {
+    let transport = match scalar(proxy, "network").as_deref().unwrap_or("tcp") {
+        "tcp" => return Ok(()),
+        "ws" => {
+            let options = proxy.get("ws-opts").unwrap_or(&Yaml::Null);
+            let mut transport = json!({ "type": "ws" });
+            if let Some(path) = scalar(options, "path") {
+                transport["path"] = path.into();
+            }
+            if let Some(headers) = options.get("headers").and_then(Yaml::as_mapping) {
+                transport["headers"] = headers
+                    .iter()
+                    .filter_map(|(key, value)| {
+                        Some((key.as_str()?.to_string(), Json::from(value.as_str()?)))
+                    })
+                    .collect::<Map<_, _>>()
+                    .into();
+            }
+            if let Some(early_data) = u64_field(options, "max-early-data") {
+                transport["max_early_data"] = early_data.into();
+            }
+            if let Some(header) = scalar(options, "early-data-header-name") {
+                transport["early_data_header_name"] = header.into();
+            }
+            transport
+        }
+        "grpc" => {
+            let options = proxy.get("grpc-opts").unwrap_or(&Yaml::Null);
+            json!({
+                "type": "grpc",
+                "service_name": scalar(options, "grpc-service-name").unwrap_or_default(),
+            })
+        }
+        "h2" => {
+            let options = proxy.get("h2-opts").unwrap_or(&Yaml::Null);
+            let mut transport = json!({ "type": "http", "host": string_list(options, "host") });
+            if let Some(path) = scalar(options, "path") {
+                transport["path"] = path.into();
+            }
+            transport
+        }
+        other => return Err(format!("network `{other}` is not supported")),
+    };
+    outbound.insert("transport".into(), transport);
+    Ok(())
+}
+
+/// TLS options; the server name is read from `server_name_key`, which is
+/// `servername` for VMess and VLESS and `sni` for the other protocols.
+fn tls_options(proxy: &Yaml, server_name_key: &str) -> Json {
+    let mut tls = json!({ "enabled": true });
+    if let Some(server_name) = scalar(proxy, server_name_key) {
+        tls["server_name"] = server_name.into();
+    }
+    if bool_field(proxy, "skip-cert-verify") {
+        tls["insecure"] = true.into();
+    }
+    let alpn = string_list(proxy, "alpn");
+    if !alpn.is_empty() {
+        tls["alpn"] = alpn.into();
+    }
+    let fingerprint = scalar(proxy, "client-fingerprint");
+    if let Some(reality) = proxy.get("reality-opts") {
+        tls["reality"] = json!({
+            "enabled": true,
+            "public_key": scalar(reality, "public-key").unwrap_or_default(),
+            "short_id": scalar(reality, "short-id").unwrap_or_default(),
+        });
+    }
+    // REALITY only works with uTLS in sing-box.
+    if fingerprint.is_some() || tls.get("reality").is_some() {
+        tls["utls"] = json!({
+            "enabled": true,
+            "fingerprint": fingerprint.unwrap_or_else(|| "chrome".to_string()),
+        });
+    }
+    tls
+}
+
+/// `select` groups become selectors; `url-test`, `fallback` and
+/// `load-balance` all become `urltest`, sing-box's only automatic group.
+fn group_outbound(name: &str, group: &Yaml, known: &BTreeSet<String>) -> Json {
+    let mut members = string_list(group, "proxies")
+        .into_iter()
+        .filter(|member| {
+            let found = known.contains(member);
+            if !found {
+                warn!("sing-box: `{member}` left out of group `{name}`");
+            }
+            found
+        })
+        .collect::<Vec<_>>();
+    if members.is_empty() {
+        members.push(DIRECT_TAG.to_string());
+    }
+
+    match scalar(group, "type").as_deref() {
+        Some("url-test" | "fallback" | "load-balance") => {
+            let interval = u64_field(group, "interval").unwrap_or(DEFAULT_TEST_INTERVAL_SECS);
+            let mut outbound = json!({
+                "type": "urltest",
+                "tag": name,
+                "outbounds": members,
+                "url": scalar(group, "url").unwrap_or_else(|| DEFAULT_TEST_URL.to_string()),
+                "interval": format!("{interval}s"),
+            });
+            if let Some(tolerance) = u64_field(group, "tolerance") {
+                outbound["tolerance"] = tolerance.into();
+            }
+            outbound
+        }
+        _ => json!({ "type": "selector", "tag": name, "outbounds": members }),
+    }
+}
+
+/// Route rules, the remote rule sets they use and the final outbound.
+/// Mode switching through the Clash API needs the two `clash_mode` rules in
+/// front of the profile's own.
+fn route_rules(
+    root: &Yaml,
+    known: &BTreeSet<String>,
+) -> (Vec<Json>, BTreeMap<String, String>, String) {
+    let mut rules = vec![
+        json!({ "action": "sniff" }),
+        json!({ "clash_mode": "Direct", "outbound": DIRECT_TAG }),
+        json!({ "clash_mode": "Global", "outbound": GLOBAL_TAG }),
+    ];
+    let mut rule_sets = BTreeMap::new();
+    let mut final_tag = DIRECT_TAG.to_string();
+    for line in sequence(root, "rules").filter_map(Yaml::as_str) {
+        let rule = match Rule::parse(line) {
+            Ok(rule) => rule,
+            Err(error) => {
+                warn!("sing-box: rule `{line}` left out: {error}");
+                continue;
+            }
+        };
+        if rule.kind == RuleKind::Match {
+            if known.contains(&rule.target) {
+                final_tag = rule.target;
+            } else {
+                warn!("sing-box: final outbound `{}` not found", rule.target);
+            }
+            // Nothing after MATCH can match.
+            break;
+        }
+        let Some(mut matcher) = rule_matcher(&rule, &mut rule_sets) else {
+            warn!("sing-box: rule `{line}` left out: not supported");
+            continue;
+        };
+        if matches!(rule.target.as_str(), "REJECT" | "REJECT-DROP") {
+            matcher.insert("action".into(), "reject".into());
+        } else if known.contains(&rule.target) {
+            matcher.insert("outbound".into(), rule.target.clone().into());
+        } else {
+            warn!("sing-box: rule `{line}` left out: unknown target");
+            continue;
+        }
+        rules.push(Json::Object(matcher));
+    }
+    (rules, rule_sets, final_tag)
+}
+
+fn rule_matcher(
+    rule: &Rule,
+    rule_sets: &mut BTreeMap<String, String>,
+) -> Option<Map<String, Json>> {
+    let payload = rule.payload.trim();
+    let (key, value) = match rule.kind {
+        RuleKind::Domain => ("domain", json!([payload])),
+        RuleKind::DomainSuffix => ("domain_suffix", json!([payload])),
+        RuleKind::DomainKeyword => ("domain_keyword", json!([payload])),
+        RuleKind::DomainRegex => ("domain_regex", json!([payload])),
+        RuleKind::IpCidr | RuleKind::IpCidr6 => ("ip_cidr", json!([payload])),
+        RuleKind::SrcIpCidr => ("source_ip_cidr", json!([payload])),
+        RuleKind::DstPort => return port_matcher("port", "port_range", payload),
+        RuleKind::SrcPort => return port_matcher("source_port", "source_port_range", payload),
+        RuleKind::ProcessName => ("process_name", json!([payload])),
+        RuleKind::ProcessPath => ("process_path", json!([payload])),
+        RuleKind::ProcessPathRegex => ("process_path_regex", json!([payload])),
+        RuleKind::Network => ("network", json!([payload.to_ascii_lowercase()])),
+        RuleKind::Uid => ("user_id", json!([payload.parse::<u32>().ok()?])),
+        RuleKind::GeoIp if matches!(payload.to_ascii_lowercase().as_str(), "lan" | "private") => {
+            ("ip_is_private", json!(true))
+        }
+        RuleKind::GeoIp => (
+            "rule_set",
+            json!([remote_rule_set(
+                rule_sets,
+                "geoip",
+                GEOIP_RULE_SET_BASE,
+                payload
+            )]),
+        ),
+        RuleKind::Geosite => (
+            "rule_set",
+            json!([remote_rule_set(
+                rule_sets,
+                "geosite",
+                GEOSITE_RULE_SET_BASE,
+                payload
+            )]),
+        ),
+        _ => return None,
+    };
+    Some(Map::from_iter([(key.to_string(), value)]))
+}
+
+/// Ports such as `443`, `80/443` or `8000-8080`; sing-box keeps single ports
+/// and ranges in separate fields that match either way.
+fn port_matcher(ports_key: &str, ranges_key: &str, payload: &str) -> Option<Map<String, Json>> {
+    let mut ports = Vec::new();
+    let mut ranges = Vec::new();
+    for item in payload.split(['/', ',']).map(str::trim) {
+        match item.split_once('-') {
+            Some((start, end)) => {
+                let start = start.trim().parse::<u16>().ok()?;
+                let end = end.trim().parse::<u16>().ok()?;
+                ranges.push(format!("{start}:{end}"));
+            }
+            None => ports.push(item.parse::<u16>().ok()?),
+        }
+    }
+    let mut matcher = Map::new();
+    if !ports.is_empty() {
+        matcher.insert(ports_key.to_string(), json!(ports));
+    }
+    if !ranges.is_empty() {
+        matcher.insert(ranges_key.to_string(), json!(ranges));
+    }
+    Some(matcher)
+}
+
+fn remote_rule_set(
+    rule_sets: &mut BTreeMap<String, String>,
+    prefix: &str,
+    base: &str,
+    name: &str,
+) -> String {
+    let tag = format!("{prefix}-{}", name.to_ascii_lowercase());
+    rule_sets.insert(tag.clone(), format!("{base}/{tag}.srs"));
+    tag
+}
+
+fn sequence<'a>(value: &'a Yaml, key: &str) -> impl Iterator<Item = &'a Yaml> {
+    value
+        .get(key)
+        .and_then(Yaml::as_sequence)
+        .into_iter()
+        .flatten()
+}
+
+/// A scalar field as text, so numeric passwords and the like still come
+/// through.
+fn scalar(value: &Yaml, key: &str) -> Option<String> {
+    let text = match value.get(key)? {
+        Yaml::String(text) => text.trim().to_string(),
+        Yaml::Number(number) => number.to_string(),
+        Yaml::Bool(flag) => flag.to_string(),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+fn required(value: &Yaml, key: &str) -> Result<String, String> {
+    scalar(value, key).ok_or_else(|| format!("`{key}` is missing"))
+}
+
+fn u64_field(value: &Yaml, key: &str) -> Option<u64> {
+    scalar(value, key)?.parse().ok()
+}
+
+fn bool_field(value: &Yaml, key: &str) -> bool {
+    scalar(value, key).is_some_and(|flag| flag == "true")
+}
+
+/// A list field; a single string counts as a list of one.
+fn string_list(value: &Yaml, key: &str) -> Vec<String> {
+    match value.get(key) {
+        Some(Yaml::Sequence(items)) => items
+            .iter()
+            .filter_map(Yaml::as_str)
+            .map(str::to_string)
+            .collect(),
+        Some(Yaml::String(item)) => vec![item.clone()],
+        _ => Vec::new(),
+    }
+}
+
+/// `30`, `30 Mbps` and the like.
+fn leading_number(text: &str) -> Option<u64> {
+    let digits = text
+        .trim()
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect::<String>();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    const CLASH_CONFIG: &str = r#"
+mixed-port: 7891
+socks-port: 7892
+listeners:
+  - { name: linkpad-https, type: http, listen: 127.0.0.1, port: 7893 }
+  - { name: tunnel, type: tunnel, port: 7894, target: example.com:443 }
+allow-lan: false
+mode: rule
+external-controller: 127.0.0.1:9097
+secret: s3cret
+proxies:
+  - { name: "ss-1", type: ss, server: a.example.com, port: 443, cipher: aes-128-gcm, password: 123456 }
+  - name: vmess-ws
+    type: vmess
+    server: b.example.com
+    port: 443
+    uuid: 9d4c1a7e-0000-4000-8000-000000000000
+    alterId: 0
+    tls: true
+    servername: cdn.example.com
+    network: ws
+    ws-opts: { path: /ray, headers: { Host: cdn.example.com } }
+  - { name: "trojan-1", type: trojan, server: c.example.com, port: 443, password: pwd, sni: c.example.com, skip-cert-verify: true }
+  - { name: "snell-1", type: snell, server: d.example.com, port: 443, psk: key }
+proxy-groups:
+  - { name: Proxy, type: select, proxies: [Auto, ss-1, snell-1, DIRECT] }
+  - { name: Auto, type: url-test, proxies: [ss-1, vmess-ws, trojan-1], interval: 600 }
+rules:
+  - DOMAIN-SUFFIX,ads.example.com,REJECT
+  - DOMAIN-SUFFIX,example.org,Proxy
+  - IP-CIDR,10.0.0.0/8,DIRECT,no-resolve
+  - DST-PORT,80/8000-8080,Auto
+  - GEOIP,LAN,DIRECT
+  - GEOIP,CN,DIRECT
+  - RULE-SET,private,DIRECT
+  - MATCH,Proxy
+"#;
+
+    fn outbound<'a>(config: &'a Json, tag: &str) -> &'a Json {
+        config["outbounds"]
+            .as_array()
+            .expect("outbounds")
+            .iter()
+            .find(|outbound| outbound["tag"] == tag)
+            .unwrap_or_else(|| panic!("outbound {tag} missing"))
+    }
+
+    #[test]
+    fn translates_clash_profile_to_sing_box() {
+        let config: Json =
+            serde_json::from_str(&translate(CLASH_CONFIG).expect("translate")).expect("json");
+
+        let inbound = &config["inbounds"][0];
+        assert_eq!(inbound["type"], "mixed");
+        assert_eq!(inbound["listen"], "127.0.0.1");
+        assert_eq!(inbound["listen_port"], 7891);
+        let inbounds = config["inbounds"].as_array().expect("inbounds");
+        assert_eq!(inbounds.len(), 3);
+        assert_eq!(inbounds[1]["type"], "socks");
+        assert_eq!(inbounds[1]["listen_port"], 7892);
+        assert_eq!(inbounds[2]["tag"], "linkpad-https");
+        assert_eq!(inbounds[2]["listen_port"], 7893);
+        let clash_api = &config["experimental"]["clash_api"];
+        assert_eq!(clash_api["external_controller"], "127.0.0.1:9097");
+        assert_eq!(clash_api["secret"], "s3cret");
+        assert_eq!(clash_api["default_mode"], "Rule");
+
+        let ss = outbound(&config, "ss-1");
+        assert_eq!(ss["type"], "shadowsocks");
+        assert_eq!(ss["method"], "aes-128-gcm");
+        assert_eq!(ss["password"], "123456");
+        let vmess = outbound(&config, "vmess-ws");
+        assert_eq!(vmess["transport"]["type"], "ws");
+        assert_eq!(vmess["transport"]["headers"]["Host"], "cdn.example.com");
+        assert_eq!(vmess["tls"]["server_name"], "cdn.example.com");
+        let trojan = outbound(&config, "trojan-1");
+        assert_eq!(trojan["tls"]["insecure"], true);
+
+        let tags = config["outbounds"]
+            .as_array()
+            .expect("outbounds")
+            .iter()
+            .map(|outbound| outbound["tag"].as_str().expect("tag"))
+            .collect::<Vec<_>>();
+        assert!(!tags.contains(&"snell-1"));
+        assert_eq!(
+            outbound(&config, "Proxy")["outbounds"],
+            json!(["Auto", "ss-1", "DIRECT"])
+        );
+        let auto = outbound(&config, "Auto");
+        assert_eq!(auto["type"], "urltest");
+        assert_eq!(auto["interval"], "600s");
+        assert_eq!(outbound(&config, "GLOBAL")["type"], "selector");
+
+        let route = &config["route"];
+        assert_eq!(route["final"], "Proxy");
+        let rules = route["rules"].as_array().expect("rules");
+        // sniff, the two clash_mode rules, then six of the seven profile rules.
+        assert_eq!(rules.len(), 9);
+        assert_eq!(
+            rules[3],
+            json!({ "domain_suffix": ["ads.example.com"], "action": "reject" })
+        );
+        assert_eq!(
+            rules[6],
+            json!({ "port": [80], "port_range": ["8000:8080"], "outbound": "Auto" })
+        );
+        assert_eq!(
+            rules[7],
+            json!({ "ip_is_private": true, "outbound": "DIRECT" })
+        );
+        assert_eq!(rules[8]["rule_set"], json!(["geoip-cn"]));
+        assert_eq!(route["rule_set"][0]["tag"], "geoip-cn");
+    }
+
+    #[test]
+    fn extracts_binary_from_release_archive() {
+        let payload = b"#!/bin/sh\necho sing-box\n";
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, data) in [
+            ("sing-box-1.11.0-linux-amd64/LICENSE", &b"license"[..]),
+            ("sing-box-1.11.0-linux-amd64/sing-box", &payload[..]),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, data)
+                .expect("append entry");
+        }
+        let archive = builder
+            .into_inner()
+            .and_then(GzEncoder::finish)
+            .expect("finish archive");
+
+        let binary = extract_from_tar_gz(&archive, "sing-box").expect("read archive");
+        assert_eq!(binary.as_deref(), Some(&payload[..]));
+        assert_eq!(
+            extract_from_tar_gz(&archive, "mihomo").expect("read archive"),
+            None
+        );
+    }
+}
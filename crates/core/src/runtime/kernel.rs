@@ -1,30 +1,38 @@
-use crate::{CoreError, CoreResult};
-use flate2::read::GzDecoder;
+use super::ReadinessProbe;
+use super::backend::{
+    ControllerDialect, GithubRelease, GithubReleaseAsset, KernelBackend, KernelKind,
+    normalize_version_tag,
+};
+use super::validation::{ConfigDiagnostic, parse_test_output};
+use super::versions::{KernelChannel, KernelVersions, VersionStore, version_id};
+use crate::{ControllerClient, CoreError, CoreResult, LINKPAD_HTTP_USER_AGENT, LogLevel};
 use reqwest::blocking::Client;
 use reqwest::header::{ACCEPT, USER_AGENT};
 use robius_directories::ProjectDirs;
-use serde::Deserialize;
 use sha2::{Digest, Sha256};
 use std::collections::BTreeSet;
 use std::fs::{self, File, OpenOptions};
-use std::io::{Cursor, Read, Write};
+use std::io::{self, Write};
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::{info, warn};
 
-const DEFAULT_KERNEL_BINARY: &str = "mihomo";
-const RUNTIME_CONFIG_FILE: &str = "runtime.yaml";
 const RUNTIME_LOG_FILE: &str = "mihomo.log";
 const RUNTIME_PID_FILE: &str = "mihomo.pid";
+/// Delay between readiness checks while the kernel starts.
+const READINESS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Log lines attached to [`CoreError::KernelNotReady`].
+const NOT_READY_LOG_LINES: usize = 10;
+/// Names tried for a candidate config before giving up, in case earlier
+/// runs left theirs behind.
+const CANDIDATE_NAME_ATTEMPTS: u32 = 16;
 const APP_QUALIFIER: &str = "";
 const APP_ORGANIZATION: &str = "";
 const APP_NAME: &str = "linkpad";
-const MIHOMO_RELEASE_API: &str = "https://api.github.com/repos/MetaCubeX/mihomo/releases/latest";
-const MIHOMO_RELEASE_LATEST_PAGE: &str = "https://github.com/MetaCubeX/mihomo/releases/latest";
-const LINKPAD_HTTP_USER_AGENT: &str = "linkpad-core/0.1";
+const GITHUB_BASE_URL: &str = "https://github.com";
 
 #[derive(Clone, Debug)]
 pub struct KernelInfo {
@@ -39,64 +47,221 @@ pub struct KernelUpgradeInfo {
     pub version: String,
     pub binary_path: String,
     pub asset_name: String,
+    /// Version a rollback returns to.
+    pub previous: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-struct GithubRelease {
-    #[serde(default)]
-    tag_name: String,
-    #[serde(default)]
-    assets: Vec<GithubReleaseAsset>,
+/// Runs a kernel's config test (`mihomo -t`, `sing-box check`), made by
+/// [`KernelRuntime::config_tester`].
+#[derive(Debug)]
+pub struct ConfigTester {
+    kind: KernelKind,
+    kernel_binary: PathBuf,
+    runtime_dir: PathBuf,
 }
 
-#[derive(Debug, Deserialize)]
-struct GithubReleaseAsset {
-    name: String,
-    browser_download_url: String,
-    #[serde(default)]
-    digest: Option<String>,
+impl ConfigTester {
+    /// Has the kernel test the config it would render from `config_yaml`.
+    pub fn test(&self, config_yaml: &str) -> CoreResult<()> {
+        let kind = self.kind;
+        let backend = kind.backend();
+        let candidate_path = self.write_candidate_config(
+            backend.config_file_name(),
+            &backend.runtime_config(config_yaml)?,
+        )?;
+
+        let kernel_binary = &self.kernel_binary;
+        let mut command = Command::new(kernel_binary);
+        configure_windows_hidden_command(&mut command);
+        let output = command
+            .args(backend.test_args(&candidate_path, &self.runtime_dir))
+            .stdin(Stdio::null())
+            .output();
+        let _ = fs::remove_file(&candidate_path);
+        let output = output.map_err(|error| {
+            CoreError::InvalidConfig(format!(
+                "failed to run `{}`: {error}",
+                kernel_binary.display()
+            ))
+        })?;
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let text = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let mut diagnostics = parse_test_output(&text);
+        if diagnostics.is_empty() {
+            // Without a recognised location, the last line usually says why.
+            let message = text
+                .lines()
+                .map(str::trim)
+                .rfind(|line| !line.is_empty())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("config test exited with {}", output.status));
+            diagnostics.push(ConfigDiagnostic {
+                level: LogLevel::Error,
+                section: None,
+                index: None,
+                message,
+            });
+        }
+        warn!(
+            "{} rejected runtime config: status={}, diagnostics={}",
+            kind.as_str(),
+            output.status,
+            diagnostics.len()
+        );
+        Err(CoreError::ConfigRejected(diagnostics))
+    }
+
+    /// Writes a config for [`ConfigTester::test`] to a file of its
+    /// own in the runtime dir. The file must not exist yet, so a link placed
+    /// at that name is never followed.
+    fn write_candidate_config(&self, file_name: &str, contents: &str) -> CoreResult<PathBuf> {
+        for attempt in 0..CANDIDATE_NAME_ATTEMPTS {
+            let path = self.runtime_dir.join(format!(
+                "candidate-{}-{attempt}-{file_name}",
+                std::process::id()
+            ));
+            let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => file,
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(error) => return Err(CoreError::InvalidConfig(error.to_string())),
+            };
+            if let Err(error) = file.write_all(contents.as_bytes()) {
+                let _ = fs::remove_file(&path);
+                return Err(CoreError::InvalidConfig(error.to_string()));
+            }
+            return Ok(path);
+        }
+        Err(CoreError::InvalidConfig(format!(
+            "no free candidate config name in {}",
+            self.runtime_dir.display()
+        )))
+    }
+}
+
+/// Kernel process started by [`KernelRuntime::spawn`] that has not passed its
+/// readiness check yet. Waiting needs no access to the runtime, so it can
+/// happen without holding the core lock.
+#[derive(Debug)]
+pub struct PendingKernel {
+    child: Child,
+    kind: KernelKind,
+    config_yaml: String,
+    log_path: PathBuf,
+}
+
+impl PendingKernel {
+    /// Waits until `readiness` passes, killing the kernel again when that
+    /// does not happen in time.
+    pub fn wait_until_ready(&mut self, readiness: &ReadinessProbe) -> CoreResult<()> {
+        let started = Instant::now();
+        loop {
+            if let Some(status) = self
+                .child
+                .try_wait()
+                .map_err(|error| CoreError::InvalidConfig(error.to_string()))?
+            {
+                return Err(CoreError::InvalidConfig(format!(
+                    "{} exited early with status {status}; check {}",
+                    self.kind.as_str(),
+                    self.log_path.display()
+                )));
+            }
+            if readiness.is_ready() {
+                info!(
+                    "{} runtime ready after {}ms",
+                    self.kind.as_str(),
+                    started.elapsed().as_millis()
+                );
+                return Ok(());
+            }
+            if started.elapsed() >= readiness.timeout() {
+                self.kill();
+                warn!(
+                    "{} runtime not ready after {:?}",
+                    self.kind.as_str(),
+                    readiness.timeout()
+                );
+                return Err(CoreError::KernelNotReady {
+                    timeout: readiness.timeout(),
+                    log_tail: crate::log_tail::last_lines(&self.log_path, NOT_READY_LOG_LINES),
+                });
+            }
+            thread::sleep(READINESS_POLL_INTERVAL);
+        }
+    }
+
+    pub fn kill(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
 }
 
 #[derive(Debug)]
 pub struct KernelRuntime {
     child: Option<Child>,
+    /// How the kernel ended when it exited without [`KernelRuntime::stop`].
+    unexpected_exit: Option<String>,
+    /// Runtime config the kernel is currently running with.
+    applied_config: Option<String>,
     runtime_dir: PathBuf,
-    kernel_binary: String,
+    /// Kernel started last, which [`KernelRuntime::stop`] and
+    /// [`KernelRuntime::reload`] act on.
+    kind: KernelKind,
+    /// Binary used instead of looking one up for the kernel.
+    kernel_binary: Option<PathBuf>,
+    /// Where installed kernels live: the config `bin` directory, or the one
+    /// in the runtime directory when there is no config directory.
+    bin_dir: PathBuf,
 }
 
 impl Default for KernelRuntime {
     fn default() -> Self {
-        let mut runtime_dir = app_config_dir().unwrap_or_else(std::env::temp_dir);
+        let config_dir = app_config_dir();
+        let mut runtime_dir = config_dir.clone().unwrap_or_else(std::env::temp_dir);
         runtime_dir.push("runtime");
+        let bin_dir = config_dir.map_or_else(|| runtime_dir.join("bin"), |dir| dir.join("bin"));
         Self {
             child: None,
+            unexpected_exit: None,
+            applied_config: None,
             runtime_dir,
-            kernel_binary: DEFAULT_KERNEL_BINARY.to_string(),
+            kind: KernelKind::default(),
+            kernel_binary: None,
+            bin_dir,
         }
     }
 }
 
 impl KernelRuntime {
-    pub fn start(&mut self, config_yaml: &str) -> CoreResult<()> {
+    /// Launches `kind` with the Clash-style `config_yaml`. The kernel only
+    /// counts as running once the returned [`PendingKernel`] passed its
+    /// readiness check and was handed to [`KernelRuntime::adopt`].
+    pub fn spawn(&mut self, config_yaml: &str, kind: KernelKind) -> CoreResult<PendingKernel> {
         if self.is_running() {
             return Err(CoreError::AlreadyRunning);
         }
 
         self.ensure_runtime_dir()?;
         self.cleanup_stale_kernel_processes()?;
+        self.kind = kind;
+        let backend = kind.backend();
         let config_path = self.config_path();
-        fs::write(&config_path, config_yaml)
+        fs::write(&config_path, backend.runtime_config(config_yaml)?)
             .map_err(|error| CoreError::InvalidConfig(error.to_string()))?;
 
         let log_file = self.open_log_file()?;
-        let kernel_binary = self.resolve_kernel_binary()?;
+        let kernel_binary = self.resolve_kernel_binary(kind)?;
         let mut command = Command::new(&kernel_binary);
         configure_windows_hidden_command(&mut command);
         command
-            .arg("-f")
-            .arg(&config_path)
-            .arg("-d")
-            .arg(&self.runtime_dir)
+            .args(backend.launch_args(&config_path, &self.runtime_dir))
             .stdout(Stdio::from(
                 log_file
                     .try_clone()
@@ -104,37 +269,87 @@ impl KernelRuntime {
             ))
             .stderr(Stdio::from(log_file));
 
-        let mut child = command.spawn().map_err(|error| {
+        let child = command.spawn().map_err(|error| {
             CoreError::InvalidConfig(format!(
                 "failed to start `{}`: {error}",
                 kernel_binary.display()
             ))
         })?;
-        let child_pid = child.id();
+        Ok(PendingKernel {
+            child,
+            kind,
+            config_yaml: config_yaml.to_string(),
+            log_path: self.log_path(),
+        })
+    }
 
-        thread::sleep(Duration::from_millis(400));
-        if let Some(status) = child
-            .try_wait()
-            .map_err(|error| CoreError::InvalidConfig(error.to_string()))?
-        {
-            self.remove_pid_file();
+    /// Takes over a kernel from [`KernelRuntime::spawn`] that is ready.
+    pub fn adopt(&mut self, pending: PendingKernel) -> CoreResult<()> {
+        let child_pid = pending.child.id();
+        self.write_pid_file(child_pid)?;
+        self.child = Some(pending.child);
+        self.unexpected_exit = None;
+        self.applied_config = Some(pending.config_yaml);
+        info!("started {} runtime pid={child_pid}", pending.kind.as_str());
+        Ok(())
+    }
+
+    /// Lets `kind` test configs, so one it would refuse is caught before the
+    /// running kernel is replaced. The tester needs nothing from the runtime,
+    /// so it can run while nobody holds it.
+    pub fn config_tester(&self, kind: KernelKind) -> CoreResult<ConfigTester> {
+        self.ensure_runtime_dir()?;
+        Ok(ConfigTester {
+            kind,
+            kernel_binary: self.resolve_kernel_binary(kind)?,
+            runtime_dir: self.runtime_dir.clone(),
+        })
+    }
+
+    /// Loads `config_yaml` into the running kernel through `controller`,
+    /// keeping its open connections. Only kernels whose
+    /// [`KernelRuntime::controller_dialect`] supports it can reload.
+    pub fn reload(&mut self, config_yaml: &str, controller: &ControllerClient) -> CoreResult<()> {
+        if !self.is_running() {
+            return Err(CoreError::NotRunning);
+        }
+        let backend = self.kind.backend();
+        if !backend.controller_dialect().supports_reload() {
             return Err(CoreError::InvalidConfig(format!(
-                "mihomo exited early with status {status}; check {}",
-                self.log_path().display()
+                "{} cannot reload its config",
+                self.kind.as_str()
             )));
         }
-
-        self.write_pid_file(child_pid)?;
-        self.child = Some(child);
-        info!("started mihomo runtime pid={child_pid}");
+        let config_path = self.config_path();
+        fs::write(&config_path, backend.runtime_config(config_yaml)?)
+            .map_err(|error| CoreError::InvalidConfig(error.to_string()))?;
+        controller.reload_configs(&config_path.display().to_string(), true)?;
+        self.applied_config = Some(config_yaml.to_string());
+        info!("reloaded {} runtime config", self.kind.as_str());
         Ok(())
     }
 
+    /// Kernel started last.
+    pub fn kind(&self) -> KernelKind {
+        self.kind
+    }
+
+    pub(crate) fn controller_dialect(&self) -> ControllerDialect {
+        self.kind.backend().controller_dialect()
+    }
+
+    /// Runtime config the running kernel was started or last reloaded with.
+    pub fn applied_config(&self) -> Option<&str> {
+        self.applied_config.as_deref()
+    }
+
     pub fn stop(&mut self) -> CoreResult<()> {
         if let Some(mut child) = self.child.take() {
             let _ = child.kill();
             let _ = child.wait();
         }
+        self.unexpected_exit = None;
+        self.applied_config = None;
         let _ = self.cleanup_stale_kernel_processes();
         self.remove_pid_file();
         Ok(())
@@ -145,27 +360,36 @@ impl KernelRuntime {
             return false;
         };
 
-        match child.try_wait() {
-            Ok(Some(_)) => {
-                self.child = None;
-                self.remove_pid_file();
-                false
-            }
-            Ok(None) => true,
-            Err(_) => {
-                self.child = None;
-                self.remove_pid_file();
-                false
-            }
-        }
+        let exit = match child.try_wait() {
+            Ok(Some(status)) => status.to_string(),
+            Ok(None) => return true,
+            Err(error) => error.to_string(),
+        };
+        warn!("{} runtime exited unexpectedly: {exit}", self.kind.as_str());
+        self.child = None;
+        self.unexpected_exit = Some(exit);
+        self.remove_pid_file();
+        false
+    }
+
+    /// Takes the exit status of a kernel that died on its own since the last
+    /// call, as noticed by [`KernelRuntime::is_running`].
+    pub fn take_unexpected_exit(&mut self) -> Option<String> {
+        self.unexpected_exit.take()
+    }
+
+    /// Working directory of the kernel, where mihomo also looks up geodata
+    /// files and relative rule-provider paths.
+    pub fn runtime_dir(&self) -> &Path {
+        &self.runtime_dir
     }
 
-    pub fn kernel_info(&self) -> KernelInfo {
-        let suggested_path = self.default_install_path().display().to_string();
-        match self.resolve_kernel_binary() {
+    pub fn kernel_info(&self, kind: KernelKind) -> KernelInfo {
+        let suggested_path = self.default_install_path(kind).display().to_string();
+        match self.resolve_kernel_binary(kind) {
             Ok(path) => KernelInfo {
                 binary_path: Some(path.display().to_string()),
-                version: detect_kernel_version(&path).or_else(|| {
+                version: detect_kernel_version(&path, kind.backend()).or_else(|| {
                     path.file_name()
                         .and_then(|name| name.to_str())
                         .and_then(extract_version_from_filename)
@@ -182,75 +406,173 @@ impl KernelRuntime {
         }
     }
 
-    pub fn install_latest_kernel(&self) -> CoreResult<KernelUpgradeInfo> {
+    /// Installs the release `channel` points at next to the versions already
+    /// installed and makes it current. Downloads go through `mirrors` in
+    /// order before github.com. A binary that does not run is removed again
+    /// and the current version stays in place.
+    pub fn install_kernel(
+        &self,
+        kind: KernelKind,
+        channel: &KernelChannel,
+        mirrors: &[String],
+    ) -> CoreResult<KernelUpgradeInfo> {
         self.ensure_runtime_dir()?;
-        let release = fetch_latest_release()?;
-        let (asset_name, bytes) = if let Some(asset) = select_release_asset(&release.assets) {
-            let bytes = download_release_asset(asset)?;
-            verify_download_digest(asset, &bytes)?;
-            (asset.name.clone(), bytes)
-        } else {
-            let candidates = release_asset_name_candidates(&release.tag_name);
-            download_release_asset_by_candidates(&release.tag_name, &candidates)?
-        };
-        let binary = decompress_gzip(&bytes)?;
+        let release = fetch_release(kind.backend(), channel, mirrors)?;
+        self.install_release(kind, &release, channel, mirrors)
+    }
 
-        let install_path = self.install_target_path();
-        if let Some(parent) = install_path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|error| CoreError::InvalidConfig(error.to_string()))?;
+    fn install_release(
+        &self,
+        kind: KernelKind,
+        release: &GithubRelease,
+        channel: &KernelChannel,
+        mirrors: &[String],
+    ) -> CoreResult<KernelUpgradeInfo> {
+        let backend = kind.backend();
+        let selected = backend.select_release_asset(&release.assets);
+        let version = version_id(
+            &release.tag_name,
+            selected.map_or("", |asset| asset.name.as_str()),
+        );
+        if version.is_empty() {
+            return Err(CoreError::Network(format!(
+                "release `{}` of {} has no asset for this platform",
+                release.tag_name,
+                backend.binary_stem()
+            )));
         }
+        let store = self.version_store(kind);
+        let mut asset_name = selected.map(|asset| asset.name.clone()).unwrap_or_default();
+        if store.contains(&version) {
+            info!("{} {version} is already installed", backend.binary_stem());
+        } else {
+            let bytes = if let Some(asset) = selected {
+                let bytes = download_release_asset(asset, mirrors)?;
+                verify_download_digest(asset, &bytes)?;
+                bytes
+            } else {
+                // Only the latest release may be looked up under
+                // `releases/latest`; other tags must match exactly.
+                let latest = *channel == KernelChannel::Stable;
+                let candidates = backend.release_asset_candidates(&release.tag_name);
+                let (name, bytes) = download_release_asset_by_candidates(
+                    backend,
+                    &release.tag_name,
+                    &candidates,
+                    latest,
+                    mirrors,
+                )?;
+                asset_name = name;
+                bytes
+            };
+            store.install(&version, &backend.extract_binary(&asset_name, &bytes)?)?;
+        }
+        self.activate_version(kind, &store, version, asset_name)
+    }
 
-        if install_path.exists() && install_path.is_dir() {
+    /// Installs a release asset downloaded by hand, such as
+    /// `mihomo-linux-amd64-v1.19.2.gz`. A `<file>.sha256` next to it is
+    /// checked when present.
+    pub fn install_kernel_from_file(
+        &self,
+        kind: KernelKind,
+        path: &Path,
+    ) -> CoreResult<KernelUpgradeInfo> {
+        let backend = kind.backend();
+        let asset_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let lower = asset_name.to_ascii_lowercase();
+        if !(lower.ends_with(".gz") || lower.ends_with(".zip")) {
             return Err(CoreError::InvalidConfig(format!(
-                "kernel install target is still a directory: `{}`",
-                install_path.display()
+                "`{}` is not a `.gz` or `.zip` {} release asset",
+                path.display(),
+                backend.binary_stem()
             )));
         }
+        let bytes = fs::read(path).map_err(|error| {
+            CoreError::InvalidConfig(format!("failed to read `{}`: {error}", path.display()))
+        })?;
+        let mut checksum_path = path.as_os_str().to_owned();
+        checksum_path.push(".sha256");
+        let digest = fs::read_to_string(&checksum_path)
+            .ok()
+            .and_then(|content| content.split_whitespace().next().map(str::to_string));
+        verify_download_digest(
+            &GithubReleaseAsset {
+                name: asset_name.clone(),
+                browser_download_url: path.display().to_string(),
+                digest,
+            },
+            &bytes,
+        )?;
+
+        let tag = extract_version_from_filename(&asset_name).unwrap_or_default();
+        let version = version_id(&tag, &asset_name);
+        let store = self.version_store(kind);
+        store.install(&version, &backend.extract_binary(&asset_name, &bytes)?)?;
+        self.activate_version(kind, &store, version, asset_name)
+    }
 
-        let temp_path = install_path.with_extension("tmp");
-        {
-            let mut temp_file = File::create(&temp_path)
-                .map_err(|error| CoreError::InvalidConfig(error.to_string()))?;
-            temp_file
-                .write_all(&binary)
-                .map_err(|error| CoreError::InvalidConfig(error.to_string()))?;
-        }
-        set_executable_permissions(&temp_path)?;
-
-        if install_path.exists() {
-            fs::remove_file(&install_path).map_err(|error| {
-                CoreError::InvalidConfig(format!(
-                    "failed to replace existing kernel binary `{}`: {error}",
-                    install_path.display()
-                ))
-            })?;
+    /// Makes an installed version current once its binary runs.
+    fn activate_version(
+        &self,
+        kind: KernelKind,
+        store: &VersionStore,
+        version: String,
+        asset_name: String,
+    ) -> CoreResult<KernelUpgradeInfo> {
+        let backend = kind.backend();
+        let binary_path = store.binary_path(&version);
+        if detect_kernel_version(&binary_path, backend).is_none() {
+            store.remove(&version);
+            return Err(CoreError::InvalidConfig(format!(
+                "{} {version} does not run on this system; keeping the current version",
+                backend.binary_stem()
+            )));
         }
-
-        fs::rename(&temp_path, &install_path).map_err(|error| {
-            CoreError::InvalidConfig(format!(
-                "failed to install kernel binary at `{}`: {error}",
-                install_path.display()
-            ))
-        })?;
+        store.activate(&version)?;
 
         Ok(KernelUpgradeInfo {
-            version: release.tag_name,
-            binary_path: install_path.display().to_string(),
+            version,
+            binary_path: binary_path.display().to_string(),
             asset_name,
+            previous: store.versions().previous,
         })
     }
 
+    /// Switches `kind` back to the version used before the last install or
+    /// rollback and returns the version now current, `None` meaning the
+    /// binary found outside the installed versions.
+    pub fn rollback_kernel(&self, kind: KernelKind) -> CoreResult<Option<String>> {
+        self.version_store(kind).rollback()
+    }
+
+    pub fn kernel_versions(&self, kind: KernelKind) -> KernelVersions {
+        self.version_store(kind).versions()
+    }
+
+    fn version_store(&self, kind: KernelKind) -> VersionStore {
+        let backend = kind.backend();
+        VersionStore::new(
+            self.bin_dir.join("versions").join(backend.binary_stem()),
+            backend.binary_name(),
+        )
+    }
+
     fn ensure_runtime_dir(&self) -> CoreResult<()> {
         fs::create_dir_all(&self.runtime_dir)
             .map_err(|error| CoreError::InvalidConfig(error.to_string()))
     }
 
     fn config_path(&self) -> PathBuf {
-        self.runtime_dir.join(RUNTIME_CONFIG_FILE)
+        self.runtime_dir
+            .join(self.kind.backend().config_file_name())
     }
 
-    fn log_path(&self) -> PathBuf {
+    pub fn log_path(&self) -> PathBuf {
         self.runtime_dir.join(RUNTIME_LOG_FILE)
     }
 
@@ -279,17 +601,31 @@ impl KernelRuntime {
         let mut pids = BTreeSet::new();
         if let Ok(raw_pid) = fs::read_to_string(self.pid_path()) {
             if let Ok(pid) = raw_pid.trim().parse::<u32>() {
-                if is_runtime_mihomo_pid(pid, &self.runtime_dir, &self.config_path()) {
+                if process_command(pid).is_some_and(|command| self.is_managed_kernel(&command)) {
                     pids.insert(pid);
                 }
             }
         }
-        for pid in list_runtime_mihomo_processes(&self.runtime_dir, &self.config_path()) {
-            pids.insert(pid);
+        for (pid, command) in list_processes() {
+            if self.is_managed_kernel(&command) {
+                pids.insert(pid);
+            }
         }
         pids.into_iter().collect()
     }
 
+    /// Whether `command` runs any of the kernels on this runtime directory,
+    /// so one left over from before a kernel switch is found too.
+    fn is_managed_kernel(&self, command: &str) -> bool {
+        KernelKind::ALL.iter().any(|kind| {
+            let backend = kind.backend();
+            let config_path = self.runtime_dir.join(backend.config_file_name());
+            command.contains(backend.binary_stem())
+                && command.contains(&self.runtime_dir.display().to_string())
+                && command.contains(&config_path.display().to_string())
+        })
+    }
+
     fn cleanup_stale_kernel_processes(&self) -> CoreResult<()> {
         let stale_pids = self.find_managed_kernel_pids();
         if stale_pids.is_empty() {
@@ -298,38 +634,47 @@ impl KernelRuntime {
         }
 
         for pid in stale_pids {
-            warn!("terminating stale mihomo process pid={pid}");
+            warn!("terminating stale kernel process pid={pid}");
             terminate_process(pid)?;
         }
         self.remove_pid_file();
         Ok(())
     }
 
-    fn resolve_kernel_binary(&self) -> CoreResult<PathBuf> {
+    fn resolve_kernel_binary(&self, kind: KernelKind) -> CoreResult<PathBuf> {
+        let backend = kind.backend();
         let mut checked_paths: Vec<PathBuf> = Vec::new();
         let mut non_executable_paths: Vec<PathBuf> = Vec::new();
 
-        if let Some(from_env) = std::env::var_os("LINKPAD_MIHOMO_PATH") {
+        if let Some(from_env) = std::env::var_os(backend.binary_env_var()) {
             let path = PathBuf::from(from_env);
-            if let Some(resolved) = resolve_kernel_candidate_path(&path, &mut non_executable_paths)
+            if let Some(resolved) =
+                resolve_kernel_candidate_path(&path, backend, &mut non_executable_paths)
             {
                 return Ok(resolved);
             }
             checked_paths.push(path);
         }
 
-        let mut candidate_paths = self.known_kernel_candidates();
-        candidate_paths.insert(0, PathBuf::from(&self.kernel_binary));
+        let mut candidate_paths = Vec::new();
+        if let Some(kernel_binary) = &self.kernel_binary {
+            candidate_paths.push(kernel_binary.clone());
+        } else {
+            candidate_paths.extend(self.version_store(kind).current_binary());
+            candidate_paths.push(PathBuf::from(backend.binary_stem()));
+        }
+        candidate_paths.extend(self.known_kernel_candidates(kind));
 
         for path in candidate_paths {
-            if let Some(resolved) = resolve_kernel_candidate_path(&path, &mut non_executable_paths)
+            if let Some(resolved) =
+                resolve_kernel_candidate_path(&path, backend, &mut non_executable_paths)
             {
                 return Ok(resolved);
             }
             checked_paths.push(path);
         }
 
-        if let Some(found_in_path) = find_in_path(&self.kernel_binary) {
+        if let Some(found_in_path) = find_in_path(&backend.binary_name()) {
             if is_executable_file(&found_in_path) {
                 return Ok(found_in_path);
             }
@@ -337,9 +682,11 @@ impl KernelRuntime {
             checked_paths.push(found_in_path);
         }
 
-        let hint_path = self.default_install_path();
+        let hint_path = self.default_install_path(kind);
         let mut message = format!(
-            "mihomo binary not found. Set `LINKPAD_MIHOMO_PATH`, or place it at `{}`. checked: {}",
+            "{} binary not found. Set `{}`, or place it at `{}`. checked: {}",
+            backend.binary_stem(),
+            backend.binary_env_var(),
             hint_path.display(),
             checked_paths
                 .iter()
@@ -362,9 +709,10 @@ impl KernelRuntime {
         Err(CoreError::InvalidConfig(format!("{message}")))
     }
 
-    fn known_kernel_candidates(&self) -> Vec<PathBuf> {
+    fn known_kernel_candidates(&self, kind: KernelKind) -> Vec<PathBuf> {
         let mut candidates = Vec::new();
-        let binary_name = platform_kernel_binary_name();
+        let binary_name = kind.backend().binary_name();
+        let binary_name = binary_name.as_str();
 
         let runtime_bin_dir = self.runtime_dir.join("bin");
         candidates.push(runtime_bin_dir.join(binary_name));
@@ -439,21 +787,8 @@ impl KernelRuntime {
         candidates
     }
 
-    fn default_install_path(&self) -> PathBuf {
-        if let Some(config_dir) = app_config_dir() {
-            return config_dir.join("bin").join(platform_kernel_binary_name());
-        }
-        self.runtime_dir
-            .join("bin")
-            .join(platform_kernel_binary_name())
-    }
-
-    fn install_target_path(&self) -> PathBuf {
-        let preferred = self.default_install_path();
-        if preferred.is_dir() {
-            return preferred.join(platform_kernel_binary_name());
-        }
-        preferred
+    fn default_install_path(&self, kind: KernelKind) -> PathBuf {
+        self.bin_dir.join(kind.backend().binary_name())
     }
 }
 
@@ -463,36 +798,129 @@ impl Drop for KernelRuntime {
     }
 }
 
-fn app_config_dir() -> Option<PathBuf> {
+pub(crate) fn app_config_dir() -> Option<PathBuf> {
     let project_dirs = ProjectDirs::from(APP_QUALIFIER, APP_ORGANIZATION, APP_NAME)?;
     Some(project_dirs.config_dir().to_path_buf())
 }
 
-fn fetch_latest_release() -> CoreResult<GithubRelease> {
-    match fetch_latest_release_from_api() {
-        Ok(release) => Ok(release),
-        Err(api_error) => {
-            let fallback_tag = fetch_latest_release_tag_from_web().map_err(|web_error| {
-                CoreError::Network(format!(
-                    "{api_error}; fallback(web latest) failed: {web_error}"
-                ))
-            })?;
-            Ok(GithubRelease {
-                tag_name: fallback_tag,
-                assets: Vec::new(),
+/// Latest release from the GitHub API, or only its tag read from the release
+/// page on github.com or a mirror when the API cannot be reached.
+fn fetch_latest_release(
+    backend: &dyn KernelBackend,
+    mirrors: &[String],
+) -> CoreResult<GithubRelease> {
+    let api_error = match fetch_latest_release_from_api(backend) {
+        Ok(release) => return Ok(release),
+        Err(error) => error,
+    };
+    let mut web_errors = Vec::new();
+    for base in mirrors
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::once(GITHUB_BASE_URL))
+    {
+        match fetch_latest_release_tag_from_web(backend, base) {
+            Ok(tag) => {
+                return Ok(GithubRelease {
+                    tag_name: tag,
+                    prerelease: false,
+                    assets: Vec::new(),
+                });
+            }
+            Err(error) => web_errors.push(error.to_string()),
+        }
+    }
+    Err(CoreError::Network(format!(
+        "{api_error}; fallback(web latest) failed: {}",
+        web_errors.join("; ")
+    )))
+}
+
+/// Release `channel` points at. mihomo publishes alpha builds under a fixed
+/// tag; for other kernels the newest pre-release is used.
+fn fetch_release(
+    backend: &dyn KernelBackend,
+    channel: &KernelChannel,
+    mirrors: &[String],
+) -> CoreResult<GithubRelease> {
+    match channel {
+        KernelChannel::Stable => fetch_latest_release(backend, mirrors),
+        KernelChannel::Alpha => match backend.alpha_release_tag() {
+            Some(tag) => fetch_release_by_tag(backend, tag),
+            None => {
+                let body = fetch_github_api(backend, "releases?per_page=20")?;
+                let releases: Vec<GithubRelease> = serde_json::from_str(&body)
+                    .map_err(|error| CoreError::Parse(error.to_string()))?;
+                releases
+                    .into_iter()
+                    .find(|release| release.prerelease)
+                    .ok_or_else(|| {
+                        CoreError::Network(format!(
+                            "no {} pre-release found",
+                            backend.binary_stem()
+                        ))
+                    })
+            }
+        },
+        KernelChannel::Pinned(tag) => {
+            let tag = tag.trim();
+            if tag.is_empty() {
+                return Err(CoreError::InvalidConfig(
+                    "pinned kernel version is empty".to_string(),
+                ));
+            }
+            let tag = if tag.starts_with(|ch: char| ch.is_ascii_digit()) {
+                format!("v{tag}")
+            } else {
+                tag.to_string()
+            };
+            // The tag is known, so assets can still be guessed from it when
+            // only a mirror is reachable.
+            fetch_release_by_tag(backend, &tag).or_else(|error| {
+                if mirrors.is_empty() {
+                    return Err(error);
+                }
+                warn!("release lookup failed, trying mirrors with guessed assets: {error}");
+                Ok(GithubRelease {
+                    tag_name: tag,
+                    prerelease: false,
+                    assets: Vec::new(),
+                })
             })
         }
     }
 }
 
-fn fetch_latest_release_from_api() -> CoreResult<GithubRelease> {
+fn fetch_release_by_tag(backend: &dyn KernelBackend, tag: &str) -> CoreResult<GithubRelease> {
+    let body = fetch_github_api(backend, &format!("releases/tags/{tag}"))?;
+    serde_json::from_str(&body).map_err(|error| CoreError::Parse(error.to_string()))
+}
+
+fn fetch_latest_release_from_api(backend: &dyn KernelBackend) -> CoreResult<GithubRelease> {
+    let body = fetch_github_api(backend, "releases/latest")?;
+    let mut release: GithubRelease =
+        serde_json::from_str(&body).map_err(|error| CoreError::Parse(error.to_string()))?;
+    release.tag_name = normalize_version_tag(&release.tag_name);
+    if release.tag_name.is_empty() {
+        return Err(CoreError::Parse(
+            "latest release has an empty tag".to_string(),
+        ));
+    }
+    Ok(release)
+}
+
+/// Body of a GitHub API request under the kernel's repository.
+fn fetch_github_api(backend: &dyn KernelBackend, path: &str) -> CoreResult<String> {
     let client = Client::builder()
         .timeout(Duration::from_secs(20))
         .build()
         .map_err(|error| CoreError::Network(error.to_string()))?;
 
     let mut request = client
-        .get(MIHOMO_RELEASE_API)
+        .get(format!(
+            "https://api.github.com/repos/{}/{path}",
+            backend.release_repo()
+        ))
         .header(USER_AGENT, LINKPAD_HTTP_USER_AGENT)
         .header(ACCEPT, "application/vnd.github+json");
     if let Ok(token) = std::env::var("LINKPAD_GITHUB_TOKEN") {
@@ -511,29 +939,28 @@ fn fetch_latest_release_from_api() -> CoreResult<GithubRelease> {
         .map_err(|error| CoreError::Network(error.to_string()))?;
     if !status.is_success() {
         return Err(CoreError::Network(format!(
-            "failed to fetch latest mihomo release: {status} {body}"
+            "failed to fetch {} {path}: {status} {body}",
+            backend.binary_stem()
         )));
     }
-
-    let mut release: GithubRelease =
-        serde_json::from_str(&body).map_err(|error| CoreError::Parse(error.to_string()))?;
-    release.tag_name = normalize_version_tag(&release.tag_name);
-    if release.tag_name.is_empty() {
-        return Err(CoreError::Parse(
-            "latest release has an empty tag".to_string(),
-        ));
-    }
-    Ok(release)
+    Ok(body)
 }
 
-fn fetch_latest_release_tag_from_web() -> CoreResult<String> {
+fn fetch_latest_release_tag_from_web(
+    backend: &dyn KernelBackend,
+    base: &str,
+) -> CoreResult<String> {
     let client = Client::builder()
         .timeout(Duration::from_secs(20))
         .build()
         .map_err(|error| CoreError::Network(error.to_string()))?;
 
     let response = client
-        .get(MIHOMO_RELEASE_LATEST_PAGE)
+        .get(format!(
+            "{}/{}/releases/latest",
+            base.trim_end_matches('/'),
+            backend.release_repo()
+        ))
         .header(USER_AGENT, LINKPAD_HTTP_USER_AGENT)
         .send()
         .map_err(|error| CoreError::Network(error.to_string()))?;
@@ -564,51 +991,12 @@ fn fetch_latest_release_tag_from_web() -> CoreResult<String> {
     Ok(tag)
 }
 
-fn select_release_asset(assets: &[GithubReleaseAsset]) -> Option<&GithubReleaseAsset> {
-    let os = release_os_tag();
-    let arch = release_arch_tag();
-    let required_prefix = format!("mihomo-{os}-{arch}-");
-
-    let mut matches = assets
-        .iter()
-        .filter(|asset| {
-            asset.name.starts_with(&required_prefix)
-                && asset.name.to_ascii_lowercase().ends_with(".gz")
-        })
-        .collect::<Vec<_>>();
-
-    matches.sort_by_key(|asset| score_release_asset(&asset.name));
-    matches.into_iter().next()
-}
-
-fn release_asset_name_candidates(tag: &str) -> Vec<String> {
-    let os = release_os_tag();
-    let arch = release_arch_tag();
-    let normalized_tag = normalize_version_tag(tag);
-    let mut names = BTreeSet::new();
-
-    names.insert(format!("mihomo-{os}-{arch}-{normalized_tag}.gz"));
-    names.insert(format!("mihomo-{os}-{arch}-go124-{normalized_tag}.gz"));
-    names.insert(format!("mihomo-{os}-{arch}-go122-{normalized_tag}.gz"));
-    names.insert(format!("mihomo-{os}-{arch}-go120-{normalized_tag}.gz"));
-    names.insert(format!("mihomo-{os}-{arch}-v1-{normalized_tag}.gz"));
-    names.insert(format!("mihomo-{os}-{arch}-v2-{normalized_tag}.gz"));
-    names.insert(format!("mihomo-{os}-{arch}-v1-go124-{normalized_tag}.gz"));
-    names.insert(format!("mihomo-{os}-{arch}-v1-go122-{normalized_tag}.gz"));
-    names.insert(format!("mihomo-{os}-{arch}-v1-go120-{normalized_tag}.gz"));
-    names.insert(format!("mihomo-{os}-{arch}-v2-go124-{normalized_tag}.gz"));
-    names.insert(format!("mihomo-{os}-{arch}-v2-go122-{normalized_tag}.gz"));
-    names.insert(format!("mihomo-{os}-{arch}-v2-go120-{normalized_tag}.gz"));
-    if arch == "amd64" {
-        names.insert(format!("mihomo-{os}-{arch}-compatible-{normalized_tag}.gz"));
-    }
-
-    names.into_iter().collect()
-}
-
 fn download_release_asset_by_candidates(
+    backend: &dyn KernelBackend,
     tag: &str,
     candidates: &[String],
+    latest: bool,
+    mirrors: &[String],
 ) -> CoreResult<(String, Vec<u8>)> {
     let client = Client::builder()
         .timeout(Duration::from_secs(60))
@@ -616,16 +1004,21 @@ fn download_release_asset_by_candidates(
         .map_err(|error| CoreError::Network(error.to_string()))?;
 
     let mut attempts = Vec::new();
+    let repo = backend.release_repo();
     for asset_name in candidates {
-        let urls = [
-            format!("https://github.com/MetaCubeX/mihomo/releases/download/{tag}/{asset_name}"),
-            format!("https://github.com/MetaCubeX/mihomo/releases/latest/download/{asset_name}"),
-        ];
-        for url in &urls {
-            match download_asset_from_url(&client, url) {
+        let mut urls = vec![format!(
+            "{GITHUB_BASE_URL}/{repo}/releases/download/{tag}/{asset_name}"
+        )];
+        if latest {
+            urls.push(format!(
+                "{GITHUB_BASE_URL}/{repo}/releases/latest/download/{asset_name}"
+            ));
+        }
+        for url in urls.iter().flat_map(|url| mirrored_urls(url, mirrors)) {
+            match download_asset_from_url(&client, &url) {
                 Ok(Some(bytes)) => return Ok((asset_name.clone(), bytes)),
-                Ok(None) => attempts.push(format!("{asset_name}@404")),
-                Err(error) => attempts.push(format!("{asset_name}@{error}")),
+                Ok(None) => attempts.push(format!("{url}@404")),
+                Err(error) => attempts.push(format!("{url}@{error}")),
             }
         }
     }
@@ -636,32 +1029,42 @@ fn download_release_asset_by_candidates(
     )))
 }
 
-fn score_release_asset(name: &str) -> (u8, u8, u8, usize) {
-    let lower = name.to_ascii_lowercase();
-    let has_alpha = lower.contains("alpha");
-    let has_compatible = lower.contains("compatible");
-    let has_go = lower.contains("-go");
-    (
-        has_alpha as u8,
-        has_compatible as u8,
-        has_go as u8,
-        name.len(),
-    )
-}
-
-fn download_release_asset(asset: &GithubReleaseAsset) -> CoreResult<Vec<u8>> {
+fn download_release_asset(asset: &GithubReleaseAsset, mirrors: &[String]) -> CoreResult<Vec<u8>> {
     let client = Client::builder()
         .timeout(Duration::from_secs(60))
         .build()
         .map_err(|error| CoreError::Network(error.to_string()))?;
 
-    match download_asset_from_url(&client, &asset.browser_download_url)? {
-        Some(bytes) => Ok(bytes),
-        None => Err(CoreError::Network(format!(
-            "failed to download kernel asset `{}`: 404 Not Found",
-            asset.name
-        ))),
+    let mut attempts = Vec::new();
+    for url in mirrored_urls(&asset.browser_download_url, mirrors) {
+        match download_asset_from_url(&client, &url) {
+            Ok(Some(bytes)) => return Ok(bytes),
+            Ok(None) => attempts.push(format!("{url}@404")),
+            Err(error) => attempts.push(format!("{url}@{error}")),
+        }
+    }
+    Err(CoreError::Network(format!(
+        "failed to download kernel asset `{}`. attempts: {}",
+        asset.name,
+        attempts.join(", ")
+    )))
+}
+
+/// `url` on each mirror in order, then `url` itself. A mirror base replaces
+/// `https://github.com`, so both release mirrors and prefix proxies such as
+/// `https://proxy.example/https://github.com` work.
+fn mirrored_urls(url: &str, mirrors: &[String]) -> Vec<String> {
+    let mut urls = Vec::new();
+    if let Some(path) = url.strip_prefix(GITHUB_BASE_URL) {
+        for mirror in mirrors {
+            let mirror = mirror.trim().trim_end_matches('/');
+            if !mirror.is_empty() {
+                urls.push(format!("{mirror}{path}"));
+            }
+        }
     }
+    urls.push(url.to_string());
+    urls
 }
 
 fn download_asset_from_url(client: &Client, url: &str) -> CoreResult<Option<Vec<u8>>> {
@@ -716,28 +1119,13 @@ fn verify_download_digest(asset: &GithubReleaseAsset, bytes: &[u8]) -> CoreResul
     Ok(())
 }
 
-fn decompress_gzip(bytes: &[u8]) -> CoreResult<Vec<u8>> {
-    let mut decoder = GzDecoder::new(Cursor::new(bytes));
-    let mut output = Vec::new();
-    decoder
-        .read_to_end(&mut output)
-        .map_err(|error| CoreError::InvalidConfig(error.to_string()))?;
-    if output.is_empty() {
-        return Err(CoreError::InvalidConfig(
-            "downloaded kernel archive is empty".to_string(),
-        ));
-    }
-    Ok(output)
-}
-
+/// Running processes with their command lines.
 #[cfg(unix)]
-fn list_runtime_mihomo_processes(runtime_dir: &Path, config_path: &Path) -> Vec<u32> {
+fn list_processes() -> Vec<(u32, String)> {
     let output = match Command::new("ps").args(["-axo", "pid=,command="]).output() {
         Ok(output) if output.status.success() => output,
         _ => return Vec::new(),
     };
-    let runtime_marker = runtime_dir.display().to_string();
-    let config_marker = config_path.display().to_string();
 
     String::from_utf8_lossy(&output.stdout)
         .lines()
@@ -746,42 +1134,31 @@ fn list_runtime_mihomo_processes(runtime_dir: &Path, config_path: &Path) -> Vec<
             let mut parts = trimmed.splitn(2, char::is_whitespace);
             let pid = parts.next()?.trim().parse::<u32>().ok()?;
             let command = parts.next()?.trim();
-            if !command.contains("mihomo") {
-                return None;
-            }
-            if !command.contains(&runtime_marker) || !command.contains(&config_marker) {
-                return None;
-            }
-            Some(pid)
+            Some((pid, command.to_string()))
         })
         .collect()
 }
 
 #[cfg(unix)]
-fn is_runtime_mihomo_pid(pid: u32, runtime_dir: &Path, config_path: &Path) -> bool {
+fn process_command(pid: u32) -> Option<String> {
     let pid_arg = pid.to_string();
     let output = match Command::new("ps")
         .args(["-p", &pid_arg, "-o", "command="])
         .output()
     {
         Ok(output) if output.status.success() => output,
-        _ => return false,
+        _ => return None,
     };
-    let command = String::from_utf8_lossy(&output.stdout);
-    let runtime_marker = runtime_dir.display().to_string();
-    let config_marker = config_path.display().to_string();
-    command.contains("mihomo")
-        && command.contains(&runtime_marker)
-        && command.contains(&config_marker)
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
 }
 
 #[cfg(not(unix))]
-fn is_runtime_mihomo_pid(_pid: u32, _runtime_dir: &Path, _config_path: &Path) -> bool {
-    false
+fn process_command(_pid: u32) -> Option<String> {
+    None
 }
 
 #[cfg(not(unix))]
-fn list_runtime_mihomo_processes(_runtime_dir: &Path, _config_path: &Path) -> Vec<u32> {
+fn list_processes() -> Vec<(u32, String)> {
     Vec::new()
 }
 
@@ -805,7 +1182,7 @@ fn terminate_process(pid: u32) -> CoreResult<()> {
     }
 
     Err(CoreError::InvalidConfig(format!(
-        "failed to terminate stale mihomo process pid={pid}"
+        "failed to terminate stale kernel process pid={pid}"
     )))
 }
 
@@ -825,7 +1202,7 @@ fn process_exists(pid: u32) -> bool {
 }
 
 #[cfg(unix)]
-fn set_executable_permissions(path: &Path) -> CoreResult<()> {
+pub(super) fn set_executable_permissions(path: &Path) -> CoreResult<()> {
     use std::os::unix::fs::PermissionsExt;
     let mut perms = fs::metadata(path)
         .map_err(|error| CoreError::InvalidConfig(error.to_string()))?
@@ -835,76 +1212,10 @@ fn set_executable_permissions(path: &Path) -> CoreResult<()> {
 }
 
 #[cfg(not(unix))]
-fn set_executable_permissions(_path: &Path) -> CoreResult<()> {
+pub(super) fn set_executable_permissions(_path: &Path) -> CoreResult<()> {
     Ok(())
 }
 
-#[cfg(target_os = "macos")]
-fn release_os_tag() -> &'static str {
-    "darwin"
-}
-
-#[cfg(target_os = "linux")]
-fn release_os_tag() -> &'static str {
-    "linux"
-}
-
-#[cfg(target_os = "windows")]
-fn release_os_tag() -> &'static str {
-    "windows"
-}
-
-#[cfg(all(
-    not(target_os = "macos"),
-    not(target_os = "linux"),
-    not(target_os = "windows")
-))]
-fn release_os_tag() -> &'static str {
-    std::env::consts::OS
-}
-
-#[cfg(target_arch = "aarch64")]
-fn release_arch_tag() -> &'static str {
-    "arm64"
-}
-
-#[cfg(target_arch = "x86_64")]
-fn release_arch_tag() -> &'static str {
-    "amd64"
-}
-
-#[cfg(target_arch = "x86")]
-fn release_arch_tag() -> &'static str {
-    "386"
-}
-
-#[cfg(target_arch = "arm")]
-fn release_arch_tag() -> &'static str {
-    "armv7"
-}
-
-#[cfg(all(
-    not(target_arch = "aarch64"),
-    not(target_arch = "x86_64"),
-    not(target_arch = "x86"),
-    not(target_arch = "arm")
-))]
-fn release_arch_tag() -> &'static str {
-    std::env::consts::ARCH
-}
-
-fn normalize_version_tag(tag: &str) -> String {
-    let trimmed = tag.trim();
-    if trimmed.is_empty() {
-        return String::new();
-    }
-    if trimmed.starts_with('v') {
-        trimmed.to_string()
-    } else {
-        format!("v{trimmed}")
-    }
-}
-
 fn find_in_path(binary_name: &str) -> Option<PathBuf> {
     let path_var = std::env::var_os("PATH")?;
     for dir in std::env::split_paths(&path_var) {
@@ -916,9 +1227,8 @@ fn find_in_path(binary_name: &str) -> Option<PathBuf> {
     None
 }
 
-fn detect_kernel_version(path: &Path) -> Option<String> {
-    let commands: &[&[&str]] = &[&["-v"], &["--version"], &["version"]];
-    for args in commands {
+fn detect_kernel_version(path: &Path, backend: &dyn KernelBackend) -> Option<String> {
+    for args in backend.version_args() {
         let mut command = Command::new(path);
         configure_windows_hidden_command(&mut command);
         let output = match command.args(*args).output() {
@@ -995,6 +1305,7 @@ fn parse_version_token(token: &str) -> Option<String> {
 
 fn resolve_kernel_candidate_path(
     path: &Path,
+    backend: &dyn KernelBackend,
     non_executable_paths: &mut Vec<PathBuf>,
 ) -> Option<PathBuf> {
     if path.is_file() {
@@ -1009,11 +1320,12 @@ fn resolve_kernel_candidate_path(
         return None;
     }
 
-    find_kernel_binary_in_dir(path, non_executable_paths)
+    find_kernel_binary_in_dir(path, backend.binary_stem(), non_executable_paths)
 }
 
 fn find_kernel_binary_in_dir(
     dir: &Path,
+    stem: &str,
     non_executable_paths: &mut Vec<PathBuf>,
 ) -> Option<PathBuf> {
     let entries = fs::read_dir(dir).ok()?;
@@ -1026,13 +1338,13 @@ fn find_kernel_binary_in_dir(
             continue;
         }
         let file_name = entry.file_name().to_string_lossy().to_ascii_lowercase();
-        if file_name == "mihomo"
-            || file_name.starts_with("mihomo-")
-            || file_name.starts_with("mihomo_")
-            || file_name.starts_with("mihomo.")
+        if file_name == stem
+            || file_name.starts_with(&format!("{stem}-"))
+            || file_name.starts_with(&format!("{stem}_"))
+            || file_name.starts_with(&format!("{stem}."))
         {
             candidates.push(path);
-        } else if file_name.contains("mihomo") {
+        } else if file_name.contains(stem) {
             fallback.push(path);
         }
     }
@@ -1071,12 +1383,299 @@ fn configure_windows_hidden_command(command: &mut Command) {
 #[cfg(not(target_os = "windows"))]
 fn configure_windows_hidden_command(_command: &mut Command) {}
 
-#[cfg(target_os = "windows")]
-fn platform_kernel_binary_name() -> &'static str {
-    "mihomo.exe"
-}
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::StubServer;
+    use std::net::{Ipv4Addr, TcpListener};
+    use std::os::unix::fs::PermissionsExt;
 
-#[cfg(not(target_os = "windows"))]
-fn platform_kernel_binary_name() -> &'static str {
-    "mihomo"
+    /// Stands in for mihomo's test mode: configs mentioning `bad-type` fail.
+    const FAKE_KERNEL: &str = r#"#!/bin/sh
+if grep -q bad-type "$3"; then
+  echo 'time="2026-01-01T00:00:00Z" level=error msg="proxy 0: unsupport proxy type: bad-type"'
+  echo "configuration file $3 test failed"
+  exit 1
+fi
+echo "configuration file $3 test is successful"
+"#;
+
+    /// Stands in for sing-box: `check` refuses configs mentioning
+    /// `bad.example`, `run` records its arguments and idles.
+    const FAKE_SING_BOX: &str = r#"#!/bin/sh
+case "$1" in
+  check)
+    if grep -q bad.example "$3"; then
+      echo "FATAL[0000] decode config at $3: outbounds[0].server: bad.example is refused"
+      exit 1
+    fi
+    ;;
+  run)
+    echo "$@" > "$5/args"
+    exec sleep 30
+    ;;
+esac
+"#;
+
+    /// Stands in for a downloaded mihomo binary.
+    const FAKE_RELEASE_BINARY: &str = "#!/bin/sh\necho 'Mihomo Meta v1.2.3 linux amd64'\n";
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, bytes).expect("gzip");
+        encoder.finish().expect("finish gzip")
+    }
+
+    fn sha256(bytes: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(bytes))
+    }
+
+    fn fake_runtime(name: &str, script: &str) -> (PathBuf, KernelRuntime) {
+        let dir = std::env::temp_dir().join(format!("linkpad-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create test dir");
+        let binary = dir.join(format!("fake-{name}"));
+        fs::write(&binary, script).expect("write fake kernel");
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).expect("chmod");
+        let runtime = KernelRuntime {
+            child: None,
+            unexpected_exit: None,
+            applied_config: None,
+            runtime_dir: dir.join("runtime"),
+            kind: KernelKind::default(),
+            kernel_binary: Some(binary),
+            bin_dir: dir.join("bin"),
+        };
+        (dir, runtime)
+    }
+
+    #[test]
+    fn validates_config_with_kernel_test_mode() {
+        let (dir, runtime) = fake_runtime("validate", FAKE_KERNEL);
+        let tester = runtime
+            .config_tester(KernelKind::Mihomo)
+            .expect("config tester");
+
+        tester.test("proxies: []\n").expect("valid config");
+        let Err(CoreError::ConfigRejected(diagnostics)) =
+            tester.test("proxies:\n  - {name: a, type: bad-type}\n")
+        else {
+            panic!("config should be rejected");
+        };
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].section.as_deref(), Some("proxies"));
+        assert_eq!(diagnostics[0].index, Some(0));
+
+        // A link planted at a candidate name is neither followed nor removed.
+        let target = dir.join("target");
+        fs::write(&target, "untouched").expect("write target");
+        let planted = runtime.runtime_dir.join(format!(
+            "candidate-{}-0-{}",
+            std::process::id(),
+            KernelKind::Mihomo.backend().config_file_name()
+        ));
+        std::os::unix::fs::symlink(&target, &planted).expect("plant link");
+        tester.test("proxies: []\n").expect("valid config");
+        assert_eq!(fs::read_to_string(&target).expect("target"), "untouched");
+        let left = fs::read_dir(&runtime.runtime_dir)
+            .expect("runtime dir")
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("candidate-")
+            })
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>();
+        assert_eq!(left, vec![planted]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn runs_sing_box_with_translated_config() {
+        let (dir, mut runtime) = fake_runtime("sing-box", FAKE_SING_BOX);
+        let server = StubServer::start();
+        server.route("GET", "/version", 200, r#"{"version":"sing-box 1.11.0"}"#);
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).expect("bind mixed port");
+        let mixed_port = listener.local_addr().expect("local addr").port();
+        let controller = ControllerClient::new(&server.url(), None).expect("client");
+        let readiness = ReadinessProbe::new(controller.clone(), mixed_port, Duration::from_secs(5));
+        let config = format!(
+            "mixed-port: {mixed_port}\nexternal-controller: {}\nproxies:\n  - {{name: a, type: ss, server: SERVER, port: 443, cipher: aes-128-gcm, password: pwd}}\nrules:\n  - MATCH,a\n",
+            server.url().trim_start_matches("http://")
+        );
+
+        let tester = runtime
+            .config_tester(KernelKind::SingBox)
+            .expect("config tester");
+        let Err(CoreError::ConfigRejected(diagnostics)) =
+            tester.test(&config.replace("SERVER", "bad.example"))
+        else {
+            panic!("config should be rejected");
+        };
+        assert!(diagnostics[0].message.contains("outbounds[0].server"));
+
+        let config = config.replace("SERVER", "good.example");
+        tester.test(&config).expect("valid config");
+        let mut pending = runtime
+            .spawn(&config, KernelKind::SingBox)
+            .expect("start fake sing-box");
+        assert!(!runtime.is_running());
+        pending.wait_until_ready(&readiness).expect("ready");
+        runtime.adopt(pending).expect("adopt");
+        assert!(runtime.is_running());
+        assert_eq!(runtime.kind(), KernelKind::SingBox);
+
+        let config_path = runtime.runtime_dir().join("runtime.json");
+        // The stub controller answers before the script may have written
+        // its arguments.
+        let args_path = runtime.runtime_dir().join("args");
+        for _ in 0..50 {
+            if fs::read_to_string(&args_path).is_ok_and(|args| args.ends_with('\n')) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let args = fs::read_to_string(args_path).expect("args");
+        assert_eq!(
+            args.trim(),
+            format!(
+                "run -c {} -D {} --disable-color",
+                config_path.display(),
+                runtime.runtime_dir().display()
+            )
+        );
+        let written: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).expect("config"))
+                .expect("json config");
+        assert_eq!(written["inbounds"][0]["listen_port"], mixed_port);
+        assert_eq!(written["route"]["final"], "a");
+
+        assert!(runtime.reload(&config, &controller).is_err());
+        runtime.stop().expect("stop");
+        assert!(!runtime.is_running());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn installs_releases_through_mirrors() {
+        let (dir, runtime) = fake_runtime("mirror-install", FAKE_KERNEL);
+        let backend = KernelKind::Mihomo.backend();
+        let mirror = StubServer::start();
+        let archive = gzip(FAKE_RELEASE_BINARY.as_bytes());
+        let asset_name = backend.release_asset_candidates("v1.2.3")[0].clone();
+        let asset_path = format!("/MetaCubeX/mihomo/releases/download/v1.2.3/{asset_name}");
+        mirror.route_bytes("GET", &asset_path, 200, &archive);
+        let release = GithubRelease {
+            tag_name: "v1.2.3".to_string(),
+            prerelease: false,
+            assets: vec![GithubReleaseAsset {
+                name: asset_name.clone(),
+                browser_download_url: format!("{GITHUB_BASE_URL}{asset_path}"),
+                digest: Some(format!("sha256:{}", sha256(&archive))),
+            }],
+        };
+        // A dead mirror first: the next one in line serves the asset.
+        let mirrors = vec!["http://127.0.0.1:9/".to_string(), mirror.url()];
+
+        let upgrade = runtime
+            .install_release(
+                KernelKind::Mihomo,
+                &release,
+                &KernelChannel::Stable,
+                &mirrors,
+            )
+            .expect("install through mirror");
+        assert_eq!(upgrade.version, "v1.2.3");
+        assert_eq!(upgrade.asset_name, asset_name);
+        let versions = runtime.kernel_versions(KernelKind::Mihomo);
+        assert_eq!(versions.current.as_deref(), Some("v1.2.3"));
+
+        // Without assets, a pinned tag is guessed from candidate names and
+        // never looked up under `releases/latest`.
+        let pinned = GithubRelease {
+            tag_name: "v1.2.4".to_string(),
+            prerelease: false,
+            assets: Vec::new(),
+        };
+        let asset_name = backend.release_asset_candidates("v1.2.4")[0].clone();
+        mirror.route_bytes(
+            "GET",
+            &format!("/MetaCubeX/mihomo/releases/download/v1.2.4/{asset_name}"),
+            200,
+            &archive,
+        );
+        let upgrade = runtime
+            .install_release(
+                KernelKind::Mihomo,
+                &pinned,
+                &KernelChannel::Pinned("v1.2.4".to_string()),
+                &[mirror.url()],
+            )
+            .expect("install guessed asset");
+        assert_eq!(upgrade.version, "v1.2.4");
+        assert_eq!(upgrade.previous.as_deref(), Some("v1.2.3"));
+        assert!(
+            mirror
+                .requests()
+                .iter()
+                .all(|request| !request.path.contains("/latest/"))
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn installs_kernel_from_local_archives() {
+        let (dir, runtime) = fake_runtime("file-install", FAKE_KERNEL);
+        let archive = gzip(FAKE_RELEASE_BINARY.as_bytes());
+        let gz_path = dir.join("mihomo-linux-amd64-v1.2.3.gz");
+        fs::write(&gz_path, &archive).expect("write gz");
+        fs::write(
+            dir.join("mihomo-linux-amd64-v1.2.3.gz.sha256"),
+            format!("{}  mihomo-linux-amd64-v1.2.3.gz\n", sha256(&archive)),
+        )
+        .expect("write checksum");
+
+        let upgrade = runtime
+            .install_kernel_from_file(KernelKind::Mihomo, &gz_path)
+            .expect("install gz");
+        assert_eq!(upgrade.version, "v1.2.3");
+        assert!(Path::new(&upgrade.binary_path).starts_with(dir.join("bin")));
+
+        let zip_path = dir.join("mihomo-windows-amd64-v1.3.0.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&zip_path).expect("create zip"));
+        writer
+            .start_file(
+                "mihomo-windows-amd64.exe",
+                zip::write::SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Stored),
+            )
+            .expect("start zip entry");
+        std::io::Write::write_all(&mut writer, FAKE_RELEASE_BINARY.as_bytes()).expect("zip");
+        writer.finish().expect("finish zip");
+        let upgrade = runtime
+            .install_kernel_from_file(KernelKind::Mihomo, &zip_path)
+            .expect("install zip");
+        assert_eq!(upgrade.version, "v1.3.0");
+        assert_eq!(upgrade.previous.as_deref(), Some("v1.2.3"));
+
+        let tampered = dir.join("mihomo-linux-amd64-v1.4.0.gz");
+        fs::write(&tampered, &archive).expect("write tampered gz");
+        fs::write(dir.join("mihomo-linux-amd64-v1.4.0.gz.sha256"), "0000\n")
+            .expect("write checksum");
+        assert!(
+            runtime
+                .install_kernel_from_file(KernelKind::Mihomo, &tampered)
+                .is_err()
+        );
+        assert!(
+            runtime
+                .install_kernel_from_file(KernelKind::Mihomo, &dir.join("mihomo.tar"))
+                .is_err()
+        );
+        let versions = runtime.kernel_versions(KernelKind::Mihomo);
+        assert_eq!(versions.current.as_deref(), Some("v1.3.0"));
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
@@ -1,5 +1,20 @@
+mod backend;
 mod kernel;
+mod readiness;
+mod validation;
+mod versions;
 
-pub use kernel::{KernelInfo, KernelRuntime, KernelUpgradeInfo};
-pub use linkpad_proxy::{SystemProxyError, SystemProxyManager};
+pub use backend::KernelKind;
+pub(crate) use kernel::app_config_dir;
+pub use kernel::{KernelInfo, KernelRuntime, KernelUpgradeInfo, PendingKernel};
+pub use linkpad_proxy::{
+    DEFAULT_BYPASS, ProxyEndpoint, ShellSyntax, SystemProxyConfig, SystemProxyError,
+    SystemProxyManager, SystemProxyStatus,
+};
+#[cfg(test)]
+pub use linkpad_proxy::{SystemProxyBackend, SystemProxyResult};
+pub(crate) use linkpad_proxy::{install_shell_hooks, shell_hooks_installed, uninstall_shell_hooks};
 pub use linkpad_startup::{StartupError, StartupManager, StartupStatus};
+pub(crate) use readiness::ReadinessProbe;
+pub use validation::ConfigDiagnostic;
+pub use versions::{KernelChannel, KernelVersions};
@@ -0,0 +1,65 @@
+use crate::ControllerClient;
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::time::Duration;
+
+/// Time allowed for one connection attempt to the mixed port.
+const PORT_CONNECT_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// What [`super::KernelRuntime::start`] waits for before it reports the
+/// kernel as started: the external controller answering `/version` and the
+/// mixed port accepting connections.
+#[derive(Clone, Debug)]
+pub struct ReadinessProbe {
+    controller: ControllerClient,
+    mixed_port: u16,
+    timeout: Duration,
+}
+
+impl ReadinessProbe {
+    pub fn new(controller: ControllerClient, mixed_port: u16, timeout: Duration) -> Self {
+        Self {
+            controller,
+            mixed_port,
+            timeout,
+        }
+    }
+
+    /// How long the kernel may take to become ready.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn is_ready(&self) -> bool {
+        let address = SocketAddr::from((Ipv4Addr::LOCALHOST, self.mixed_port));
+        self.controller.version().is_ok()
+            && TcpStream::connect_timeout(&address, PORT_CONNECT_TIMEOUT).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::StubServer;
+    use std::net::TcpListener;
+
+    #[test]
+    fn needs_controller_and_mixed_port() {
+        let server = StubServer::start();
+        let controller = ControllerClient::new(&server.url(), None).expect("client");
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).expect("bind mixed port");
+        let mixed_port = listener.local_addr().expect("local addr").port();
+        let probe = ReadinessProbe::new(controller, mixed_port, Duration::from_secs(1));
+
+        assert!(!probe.is_ready(), "controller has no /version yet");
+        server.route(
+            "GET",
+            "/version",
+            200,
+            r#"{"meta":true,"version":"v1.19.2"}"#,
+        );
+        assert!(probe.is_ready());
+
+        drop(listener);
+        assert!(!probe.is_ready(), "mixed port is closed");
+    }
+}
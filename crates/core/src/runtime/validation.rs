@@ -0,0 +1,104 @@
+use crate::LogLevel;
+use crate::log_tail::parse_line;
+use std::fmt;
+
+/// A problem the kernel reported while testing a runtime config.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigDiagnostic {
+    pub level: LogLevel,
+    /// Config section the message points at, such as `rules` or `proxies`.
+    pub section: Option<String>,
+    /// Zero-based position within `section`.
+    pub index: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.section, self.index) {
+            (Some(section), Some(index)) => write!(f, "{section}[{index}]: {}", self.message),
+            (Some(section), None) => write!(f, "{section}: {}", self.message),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Locations in mihomo's parse errors, as printed by its config loader,
+/// with the section they belong to.
+const LOCATION_PATTERNS: [(&str, &str); 4] = [
+    ("rules[", "rules"),
+    ("proxy group[", "proxy-groups"),
+    ("proxy ", "proxies"),
+    ("sub-rule ", "sub-rules"),
+];
+
+/// Collects the warnings and errors from the output of `mihomo -t`.
+pub(crate) fn parse_test_output(output: &str) -> Vec<ConfigDiagnostic> {
+    output
+        .lines()
+        .filter(|line| !is_test_summary(line))
+        .filter_map(parse_line)
+        .filter(|(_, level, _)| *level >= LogLevel::Warning)
+        .map(|(_, level, message)| {
+            let (section, index) = locate(&message);
+            ConfigDiagnostic {
+                level,
+                section,
+                index,
+                message,
+            }
+        })
+        .collect()
+}
+
+/// `configuration file <path> test failed` and its success counterpart.
+fn is_test_summary(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with("configuration file ")
+        && (line.ends_with("test failed") || line.ends_with("test is successful"))
+}
+
+fn locate(message: &str) -> (Option<String>, Option<usize>) {
+    for (pattern, section) in LOCATION_PATTERNS {
+        let Some(start) = message.find(pattern) else {
+            continue;
+        };
+        let digits = message[start + pattern.len()..]
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>();
+        if let Ok(index) = digits.parse() {
+            return (Some(section.to_string()), Some(index));
+        }
+    }
+    (None, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_kernel_test_errors() {
+        let output = concat!(
+            "time=\"2026-01-01T00:00:00Z\" level=info msg=\"Start initial configuration in progress\"\n",
+            "time=\"2026-01-01T00:00:00Z\" level=error msg=\"rules[12] [DOMAIN,a.test,Missing] error: proxy [Missing] not found\"\n",
+            "time=\"2026-01-01T00:00:00Z\" level=error msg=\"proxy 3: unsupport proxy type: foo\"\n",
+            "unexpected EOF\n",
+            "configuration file /tmp/candidate.yaml test failed\n",
+        );
+        let diagnostics = parse_test_output(output);
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].section.as_deref(), Some("rules"));
+        assert_eq!(diagnostics[0].index, Some(12));
+        assert_eq!(diagnostics[1].section.as_deref(), Some("proxies"));
+        assert_eq!(diagnostics[1].index, Some(3));
+        assert_eq!(
+            diagnostics[1].to_string(),
+            "proxies[3]: proxy 3: unsupport proxy type: foo"
+        );
+        assert_eq!(diagnostics[2].level, LogLevel::Error);
+        assert_eq!(diagnostics[2].section, None);
+        assert_eq!(diagnostics[2].message, "unexpected EOF");
+    }
+}
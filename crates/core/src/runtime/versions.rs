@@ -0,0 +1,307 @@
+use crate::{CoreError, CoreResult};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+const STATE_FILE: &str = "installed.json";
+/// Installed versions kept besides the current and previous one.
+const SPARE_VERSIONS: usize = 1;
+
+/// Which releases an upgrade installs.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KernelChannel {
+    /// The latest stable release.
+    #[default]
+    Stable,
+    /// The latest pre-release build.
+    Alpha,
+    /// Exactly this release tag, such as `v1.19.2`.
+    Pinned(String),
+}
+
+/// Kernel versions installed side by side by Linkpad.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KernelVersions {
+    /// Version in use; `None` while a binary found elsewhere is used.
+    pub current: Option<String>,
+    /// Version a rollback returns to.
+    pub previous: Option<String>,
+    /// Every installed version, oldest first.
+    pub installed: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoreState {
+    current: Option<String>,
+    previous: Option<String>,
+}
+
+/// Versions of one kernel under `<bin>/versions/<kernel>/<version>/`, with
+/// `installed.json` recording the current and previous one.
+#[derive(Debug)]
+pub(crate) struct VersionStore {
+    root: PathBuf,
+    binary_name: String,
+}
+
+impl VersionStore {
+    pub fn new(root: PathBuf, binary_name: String) -> Self {
+        Self { root, binary_name }
+    }
+
+    pub fn binary_path(&self, version: &str) -> PathBuf {
+        self.root.join(version).join(&self.binary_name)
+    }
+
+    pub fn contains(&self, version: &str) -> bool {
+        self.binary_path(version).is_file()
+    }
+
+    /// Binary of the current version, if it is still on disk.
+    pub fn current_binary(&self) -> Option<PathBuf> {
+        let current = self.load().current?;
+        check_version_id(&current).ok()?;
+        let path = self.binary_path(&current);
+        path.is_file().then_some(path)
+    }
+
+    pub fn versions(&self) -> KernelVersions {
+        let state = self.load();
+        KernelVersions {
+            current: state.current,
+            previous: state.previous,
+            installed: self.installed(),
+        }
+    }
+
+    /// Writes `binary` as `version` without making it current.
+    pub fn install(&self, version: &str, binary: &[u8]) -> CoreResult<PathBuf> {
+        check_version_id(version)?;
+        let path = self.binary_path(version);
+        let dir = path.parent().unwrap_or(&self.root);
+        fs::create_dir_all(dir).map_err(|error| CoreError::InvalidConfig(error.to_string()))?;
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, binary)
+            .map_err(|error| CoreError::InvalidConfig(error.to_string()))?;
+        super::kernel::set_executable_permissions(&temp_path)?;
+        fs::rename(&temp_path, &path).map_err(|error| {
+            CoreError::InvalidConfig(format!(
+                "failed to install kernel binary at `{}`: {error}",
+                path.display()
+            ))
+        })?;
+        Ok(path)
+    }
+
+    /// Deletes an installed version that never became current.
+    pub fn remove(&self, version: &str) {
+        if let Err(error) = check_version_id(version) {
+            warn!("not removing kernel version: {error}");
+            return;
+        }
+        if let Err(error) = fs::remove_dir_all(self.root.join(version)) {
+            warn!("failed to remove kernel version {version}: {error}");
+        }
+    }
+
+    /// Makes `version` current, keeping the one it replaces as previous.
+    pub fn activate(&self, version: &str) -> CoreResult<()> {
+        check_version_id(version)?;
+        let mut state = self.load();
+        if state.current.as_deref() != Some(version) {
+            state.previous = state.current.take();
+            state.current = Some(version.to_string());
+        }
+        self.save(&state)?;
+        info!("kernel version activated: {version}");
+        self.prune(&state);
+        Ok(())
+    }
+
+    /// Swaps the current and previous version. Without a previous version,
+    /// the binary found outside the store is used again. Returns the version
+    /// now current.
+    pub fn rollback(&self) -> CoreResult<Option<String>> {
+        let mut state = self.load();
+        if state.current.is_none() {
+            return Err(CoreError::InvalidConfig(
+                "no installed kernel version to roll back from".to_string(),
+            ));
+        }
+        std::mem::swap(&mut state.current, &mut state.previous);
+        self.save(&state)?;
+        info!(
+            "kernel version rolled back to {}",
+            state.current.as_deref().unwrap_or("the unmanaged binary")
+        );
+        Ok(state.current)
+    }
+
+    fn installed(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(&self.root) else {
+            return Vec::new();
+        };
+        let mut versions = entries
+            .flatten()
+            .filter(|entry| entry.path().join(&self.binary_name).is_file())
+            .filter_map(|entry| {
+                let modified = entry.metadata().and_then(|meta| meta.modified()).ok()?;
+                Some((modified, entry.file_name().to_string_lossy().into_owned()))
+            })
+            .collect::<Vec<_>>();
+        versions.sort();
+        versions.into_iter().map(|(_, version)| version).collect()
+    }
+
+    /// Removes the oldest versions that are neither current nor previous.
+    fn prune(&self, state: &StoreState) {
+        let spares = self
+            .installed()
+            .into_iter()
+            .filter(|version| {
+                state.current.as_deref() != Some(version)
+                    && state.previous.as_deref() != Some(version)
+            })
+            .collect::<Vec<_>>();
+        for version in &spares[..spares.len().saturating_sub(SPARE_VERSIONS)] {
+            match fs::remove_dir_all(self.root.join(version)) {
+                Ok(()) => info!("removed old kernel version {version}"),
+                Err(error) => warn!("failed to remove kernel version {version}: {error}"),
+            }
+        }
+    }
+
+    fn load(&self) -> StoreState {
+        fs::read_to_string(self.root.join(STATE_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, state: &StoreState) -> CoreResult<()> {
+        fs::create_dir_all(&self.root)
+            .map_err(|error| CoreError::InvalidConfig(error.to_string()))?;
+        let content = serde_json::to_string_pretty(state)
+            .map_err(|error| CoreError::InvalidConfig(error.to_string()))?;
+        fs::write(self.root.join(STATE_FILE), content)
+            .map_err(|error| CoreError::InvalidConfig(error.to_string()))
+    }
+}
+
+/// Directory name for a release: its tag, unless the tag is a moving one
+/// such as mihomo's `Prerelease-Alpha`; then the asset name, which carries
+/// the build.
+pub(crate) fn version_id(tag: &str, asset_name: &str) -> String {
+    let tag = tag.trim();
+    let is_version = tag
+        .strip_prefix('v')
+        .is_some_and(|rest| rest.starts_with(|ch: char| ch.is_ascii_digit()));
+    if is_version {
+        return tag.to_string();
+    }
+    let mut id = asset_name.trim();
+    for suffix in [".tar.gz", ".gz", ".zip"] {
+        id = id.strip_suffix(suffix).unwrap_or(id);
+    }
+    id.to_string()
+}
+
+/// Refuses version ids that would not name a single directory inside the
+/// store, such as ones made from a tag like `v1/../../bin`.
+fn check_version_id(version: &str) -> CoreResult<()> {
+    let invalid = version.is_empty()
+        || version == "."
+        || version.contains("..")
+        || version.contains(['/', '\\']);
+    if invalid {
+        return Err(CoreError::InvalidConfig(format!(
+            "invalid kernel version `{version}`"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn store(name: &str) -> (PathBuf, VersionStore) {
+        let dir = std::env::temp_dir().join(format!("linkpad-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = VersionStore::new(dir.join("mihomo"), "mihomo".to_string());
+        (dir, store)
+    }
+
+    fn install(store: &VersionStore, version: &str) -> PathBuf {
+        let path = store.install(version, version.as_bytes()).expect("install");
+        // Keeps modification times apart so versions sort by install order.
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        path
+    }
+
+    #[test]
+    fn activates_rolls_back_and_prunes() {
+        let (dir, store) = store("kernel-versions");
+        assert_eq!(store.versions(), KernelVersions::default());
+        assert!(store.rollback().is_err());
+
+        let v1 = install(&store, "v1.0.0");
+        store.activate("v1.0.0").expect("activate v1");
+        assert_eq!(store.current_binary(), Some(v1.clone()));
+        install(&store, "v1.1.0");
+        store.activate("v1.1.0").expect("activate v1.1");
+        let versions = store.versions();
+        assert_eq!(versions.current.as_deref(), Some("v1.1.0"));
+        assert_eq!(versions.previous.as_deref(), Some("v1.0.0"));
+
+        assert_eq!(
+            store.rollback().expect("rollback").as_deref(),
+            Some("v1.0.0")
+        );
+        assert_eq!(store.current_binary(), Some(v1));
+        assert_eq!(store.versions().previous.as_deref(), Some("v1.1.0"));
+
+        for version in ["v1.2.0", "v1.3.0", "v1.4.0"] {
+            install(&store, version);
+            store.activate(version).expect("activate");
+        }
+        // Current, previous and one spare survive.
+        assert_eq!(store.versions().installed, ["v1.2.0", "v1.3.0", "v1.4.0"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn names_moving_tags_after_the_asset() {
+        assert_eq!(
+            version_id("v1.19.2", "mihomo-linux-amd64-v1.19.2.gz"),
+            "v1.19.2"
+        );
+        assert_eq!(
+            version_id("Prerelease-Alpha", "mihomo-linux-amd64-alpha-e2d9d7c.gz"),
+            "mihomo-linux-amd64-alpha-e2d9d7c"
+        );
+        assert_eq!(
+            version_id("", "sing-box-1.12.0-linux-amd64.tar.gz"),
+            "sing-box-1.12.0-linux-amd64"
+        );
+        assert!(Path::new(&version_id("v1.0.0", "")).is_relative());
+    }
+
+    #[test]
+    fn refuses_version_ids_leaving_the_store() {
+        let (dir, store) = store("kernel-version-ids");
+        for version in ["", ".", "..", "v1/../../bin", "v1\\..\\bin", "v1/evil"] {
+            assert!(store.install(version, b"binary").is_err(), "{version:?}");
+            assert!(store.activate(version).is_err(), "{version:?}");
+        }
+        let outside = dir.join("outside");
+        fs::create_dir_all(&outside).expect("create outside dir");
+        store.remove("../outside");
+        assert!(outside.is_dir());
+        assert!(store.versions().installed.is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,1011 @@
+use base64::Engine as _;
+use base64::engine::general_purpose;
+use percent_encoding::percent_decode_str;
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{CoreError, CoreResult};
+
+pub(crate) const SUBSCRIPTION_GROUP_NAME: &str = "All Proxies";
+
+/// A share link decoded into a Clash `proxies:` entry.
+#[derive(Clone, Debug)]
+pub(crate) struct ShareLinkProxy {
+    pub name: String,
+    pub kind: String,
+    pub fields: Mapping,
+}
+
+pub(crate) fn decode_share_link(line: &str) -> CoreResult<ShareLinkProxy> {
+    let line = line.trim();
+    let (scheme, _) = line
+        .split_once("://")
+        .ok_or_else(|| CoreError::InvalidProfile("share link has no scheme".to_string()))?;
+
+    match scheme.to_ascii_lowercase().as_str() {
+        "ss" => decode_shadowsocks(line),
+        "vmess" => decode_vmess(line),
+        "vless" => decode_vless(line),
+        "trojan" => decode_trojan(line),
+        "hysteria2" | "hy2" => decode_hysteria2(line),
+        "tuic" => decode_tuic(line),
+        other => Err(CoreError::InvalidProfile(format!(
+            "unsupported share link scheme `{other}`"
+        ))),
+    }
+}
+
+/// Builds a complete Clash document for decoded share links: every proxy, an
+/// `All Proxies` selector, one selector per protocol and a catch-all rule.
+pub(crate) fn build_subscription_document(proxies: &[ShareLinkProxy]) -> CoreResult<String> {
+    let mut seen = BTreeSet::new();
+    let mut entries = Vec::with_capacity(proxies.len());
+    let mut all_names = Vec::with_capacity(proxies.len());
+    let mut by_kind: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for proxy in proxies {
+        let name = unique_proxy_name(&proxy.name, &mut seen);
+        let mut entry = Mapping::new();
+        entry.insert(Value::from("name"), Value::from(name.clone()));
+        for (key, value) in &proxy.fields {
+            if key.as_str() != Some("name") {
+                entry.insert(key.clone(), value.clone());
+            }
+        }
+        entries.push(Value::Mapping(entry));
+        all_names.push(name.clone());
+        by_kind.entry(proxy.kind.clone()).or_default().push(name);
+    }
+
+    let mut groups = vec![selector_group(SUBSCRIPTION_GROUP_NAME, &all_names)];
+    if by_kind.len() > 1 {
+        for (kind, names) in &by_kind {
+            groups.push(selector_group(
+                &format!("{} Nodes", kind.to_uppercase()),
+                names,
+            ));
+        }
+    }
+
+    let mut root = Mapping::new();
+    root.insert(Value::from("proxies"), Value::Sequence(entries));
+    root.insert(Value::from("proxy-groups"), Value::Sequence(groups));
+    root.insert(
+        Value::from("rules"),
+        Value::Sequence(vec![Value::from(format!(
+            "MATCH,{SUBSCRIPTION_GROUP_NAME}"
+        ))]),
+    );
+
+    serde_yaml::to_string(&Value::Mapping(root))
+        .map_err(|error| CoreError::InvalidProfile(error.to_string()))
+}
+
+fn selector_group(name: &str, proxies: &[String]) -> Value {
+    let mut group = Mapping::new();
+    group.insert(Value::from("name"), Value::from(name));
+    group.insert(Value::from("type"), Value::from("select"));
+    group.insert(
+        Value::from("proxies"),
+        Value::Sequence(proxies.iter().cloned().map(Value::from).collect()),
+    );
+    Value::Mapping(group)
+}
+
+fn unique_proxy_name(name: &str, seen: &mut BTreeSet<String>) -> String {
+    let mut candidate = name.to_string();
+    let mut suffix = 2;
+    while !seen.insert(candidate.clone()) {
+        candidate = format!("{name} {suffix}");
+        suffix += 1;
+    }
+    candidate
+}
+
+fn decode_shadowsocks(line: &str) -> CoreResult<ShareLinkProxy> {
+    let body = &line["ss://".len()..];
+    let (body, fragment) = split_fragment(body);
+
+    // Legacy form: ss://base64(method:password@host:port)#name
+    let expanded;
+    let body = if body.contains('@') {
+        body
+    } else {
+        let (encoded, rest) = match body.find(['/', '?']) {
+            Some(index) => body.split_at(index),
+            None => (body, ""),
+        };
+        let decoded = decode_base64_text(encoded).ok_or_else(|| {
+            CoreError::InvalidProfile("ss link has an invalid base64 body".to_string())
+        })?;
+        expanded = format!("{decoded}{rest}");
+        expanded.as_str()
+    };
+
+    let url = parse_link_url(&format!("ss://{body}"))?;
+    let (cipher, password) = match url.password() {
+        Some(password) => (percent_decode(url.username()), percent_decode(password)),
+        None => {
+            let userinfo =
+                decode_base64_text(&percent_decode(url.username())).ok_or_else(|| {
+                    CoreError::InvalidProfile("ss link has invalid user info".to_string())
+                })?;
+            let (cipher, password) = userinfo.split_once(':').ok_or_else(|| {
+                CoreError::InvalidProfile("ss user info must be `method:password`".to_string())
+            })?;
+            (cipher.to_string(), password.to_string())
+        }
+    };
+    if cipher.trim().is_empty() {
+        return Err(CoreError::InvalidProfile(
+            "ss link has no cipher".to_string(),
+        ));
+    }
+
+    let (server, port) = server_and_port(&url)?;
+    let query = query_map(&url);
+    let mut proxy = ProxyFields::new("ss", &server, port);
+    proxy.set_str("cipher", &cipher);
+    proxy.set_str("password", &password);
+    proxy.set("udp", Value::Bool(true));
+
+    if let Some(plugin) = query.get("plugin").filter(|value| !value.is_empty()) {
+        apply_ss_plugin(&mut proxy, plugin)?;
+    }
+
+    Ok(proxy.finish(fragment, "SS"))
+}
+
+fn apply_ss_plugin(proxy: &mut ProxyFields, plugin: &str) -> CoreResult<()> {
+    let mut parts = plugin.split(';');
+    let plugin_name = parts.next().unwrap_or_default().trim();
+    let options = parts
+        .map(|part| match part.split_once('=') {
+            Some((key, value)) => (key.trim().to_string(), value.trim().to_string()),
+            None => (part.trim().to_string(), String::new()),
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut plugin_opts = Mapping::new();
+    match plugin_name {
+        "obfs-local" | "simple-obfs" | "obfs" => {
+            proxy.set_str("plugin", "obfs");
+            if let Some(mode) = options.get("obfs") {
+                plugin_opts.insert(Value::from("mode"), Value::from(mode.as_str()));
+            }
+            if let Some(host) = options.get("obfs-host") {
+                plugin_opts.insert(Value::from("host"), Value::from(host.as_str()));
+            }
+        }
+        "v2ray-plugin" => {
+            proxy.set_str("plugin", "v2ray-plugin");
+            let mode = options
+                .get("mode")
+                .map(String::as_str)
+                .unwrap_or("websocket");
+            plugin_opts.insert(Value::from("mode"), Value::from(mode));
+            if options.contains_key("tls") {
+                plugin_opts.insert(Value::from("tls"), Value::Bool(true));
+            }
+            if let Some(host) = options.get("host") {
+                plugin_opts.insert(Value::from("host"), Value::from(host.as_str()));
+            }
+            if let Some(path) = options.get("path") {
+                plugin_opts.insert(Value::from("path"), Value::from(path.as_str()));
+            }
+            if options.contains_key("mux") {
+                plugin_opts.insert(Value::from("mux"), Value::Bool(true));
+            }
+        }
+        other => {
+            return Err(CoreError::InvalidProfile(format!(
+                "unsupported ss plugin `{other}`"
+            )));
+        }
+    }
+    if !plugin_opts.is_empty() {
+        proxy.set("plugin-opts", Value::Mapping(plugin_opts));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct VmessShare {
+    #[serde(default)]
+    ps: String,
+    #[serde(default)]
+    add: String,
+    #[serde(default)]
+    port: LooseValue,
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    aid: LooseValue,
+    #[serde(default)]
+    scy: String,
+    #[serde(default)]
+    net: String,
+    #[serde(default, rename = "type")]
+    header_type: String,
+    #[serde(default)]
+    host: String,
+    #[serde(default)]
+    path: String,
+    #[serde(default)]
+    tls: String,
+    #[serde(default)]
+    sni: String,
+    #[serde(default)]
+    alpn: String,
+    #[serde(default)]
+    fp: String,
+    #[serde(default, rename = "allowInsecure")]
+    allow_insecure: LooseValue,
+}
+
+/// vmess JSON payloads encode numbers either as numbers or strings.
+#[derive(Debug, Default, Deserialize)]
+#[serde(untagged)]
+enum LooseValue {
+    Number(u64),
+    Bool(bool),
+    Text(String),
+    #[default]
+    Missing,
+}
+
+impl LooseValue {
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            Self::Text(value) => value.trim().parse().ok(),
+            Self::Bool(_) | Self::Missing => None,
+        }
+    }
+
+    fn is_truthy(&self) -> bool {
+        match self {
+            Self::Number(value) => *value != 0,
+            Self::Bool(value) => *value,
+            Self::Text(value) => is_truthy(value),
+            Self::Missing => false,
+        }
+    }
+}
+
+fn decode_vmess(line: &str) -> CoreResult<ShareLinkProxy> {
+    let body = &line["vmess://".len()..];
+    let (encoded, fragment) = split_fragment(body);
+    let Some(json) = decode_base64_text(encoded) else {
+        // Some providers emit vmess in the same URL form as vless.
+        return decode_url_style_vmess(line);
+    };
+
+    let share: VmessShare = serde_json::from_str(&json)
+        .map_err(|error| CoreError::InvalidProfile(format!("invalid vmess payload: {error}")))?;
+    let port = share
+        .port
+        .as_u64()
+        .and_then(|port| u16::try_from(port).ok())
+        .filter(|port| *port != 0)
+        .ok_or_else(|| CoreError::InvalidProfile("vmess link has no valid port".to_string()))?;
+    if share.add.trim().is_empty() || share.id.trim().is_empty() {
+        return Err(CoreError::InvalidProfile(
+            "vmess link is missing server or uuid".to_string(),
+        ));
+    }
+
+    let mut proxy = ProxyFields::new("vmess", share.add.trim(), port);
+    proxy.set_str("uuid", share.id.trim());
+    proxy.set("alterId", Value::from(share.aid.as_u64().unwrap_or(0)));
+    proxy.set_str(
+        "cipher",
+        if share.scy.trim().is_empty() {
+            "auto"
+        } else {
+            share.scy.trim()
+        },
+    );
+    proxy.set("udp", Value::Bool(true));
+
+    let tls = share.tls.eq_ignore_ascii_case("tls");
+    if tls {
+        proxy.set("tls", Value::Bool(true));
+        proxy.set_non_empty("servername", &share.sni);
+        proxy.set_alpn(&share.alpn);
+        proxy.set_non_empty("client-fingerprint", &share.fp);
+        if share.allow_insecure.is_truthy() {
+            proxy.set("skip-cert-verify", Value::Bool(true));
+        }
+    }
+
+    let network = if share.net.trim().is_empty() {
+        "tcp"
+    } else {
+        share.net.trim()
+    };
+    apply_transport(
+        &mut proxy,
+        &TransportParams {
+            network,
+            header_type: &share.header_type,
+            host: &share.host,
+            path: &share.path,
+            service_name: &share.path,
+        },
+    )?;
+
+    let fallback_name = fragment
+        .map(percent_decode)
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or(share.ps);
+    Ok(proxy.finish_named(fallback_name, "VMESS"))
+}
+
+fn decode_url_style_vmess(line: &str) -> CoreResult<ShareLinkProxy> {
+    let (body, fragment) = split_fragment(line);
+    let url = parse_link_url(body)?;
+    let (server, port) = server_and_port(&url)?;
+    let query = query_map(&url);
+    let uuid = required_username(&url, "vmess")?;
+
+    let mut proxy = ProxyFields::new("vmess", &server, port);
+    proxy.set_str("uuid", &uuid);
+    proxy.set(
+        "alterId",
+        Value::from(
+            query
+                .get("alterId")
+                .or_else(|| query.get("aid"))
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or(0),
+        ),
+    );
+    proxy.set_str(
+        "cipher",
+        query
+            .get("encryption")
+            .or_else(|| query.get("scy"))
+            .map(String::as_str)
+            .filter(|value| !value.is_empty() && *value != "none")
+            .unwrap_or("auto"),
+    );
+    proxy.set("udp", Value::Bool(true));
+    apply_url_tls(&mut proxy, &query, "servername")?;
+    apply_url_transport(&mut proxy, &query)?;
+    Ok(proxy.finish(fragment, "VMESS"))
+}
+
+fn decode_vless(line: &str) -> CoreResult<ShareLinkProxy> {
+    let (body, fragment) = split_fragment(line);
+    let url = parse_link_url(body)?;
+    let (server, port) = server_and_port(&url)?;
+    let query = query_map(&url);
+    let uuid = required_username(&url, "vless")?;
+
+    let mut proxy = ProxyFields::new("vless", &server, port);
+    proxy.set_str("uuid", &uuid);
+    proxy.set("udp", Value::Bool(true));
+    if let Some(flow) = query.get("flow").filter(|value| !value.is_empty()) {
+        proxy.set_str("flow", flow);
+    }
+    apply_url_tls(&mut proxy, &query, "servername")?;
+    apply_url_transport(&mut proxy, &query)?;
+    Ok(proxy.finish(fragment, "VLESS"))
+}
+
+fn decode_trojan(line: &str) -> CoreResult<ShareLinkProxy> {
+    let (body, fragment) = split_fragment(line);
+    let url = parse_link_url(body)?;
+    let (server, port) = server_and_port(&url)?;
+    let query = query_map(&url);
+    let password = required_username(&url, "trojan")?;
+
+    let mut proxy = ProxyFields::new("trojan", &server, port);
+    proxy.set_str("password", &password);
+    proxy.set("udp", Value::Bool(true));
+    if let Some(sni) = query
+        .get("sni")
+        .or_else(|| query.get("peer"))
+        .filter(|value| !value.is_empty())
+    {
+        proxy.set_str("sni", sni);
+    }
+    if query_flag(&query, &["allowInsecure", "insecure", "skip-cert-verify"]) {
+        proxy.set("skip-cert-verify", Value::Bool(true));
+    }
+    if let Some(alpn) = query.get("alpn") {
+        proxy.set_alpn(alpn);
+    }
+    if let Some(fingerprint) = query.get("fp").filter(|value| !value.is_empty()) {
+        proxy.set_str("client-fingerprint", fingerprint);
+    }
+    apply_url_transport(&mut proxy, &query)?;
+    Ok(proxy.finish(fragment, "TROJAN"))
+}
+
+fn decode_hysteria2(line: &str) -> CoreResult<ShareLinkProxy> {
+    let (body, fragment) = split_fragment(line);
+    let url = parse_link_url(body)?;
+    let (server, port) = server_and_port_with_default(&url, Some(443))?;
+    let query = query_map(&url);
+
+    // The whole user info is the auth string, including a `user:pass` pair.
+    let mut password = percent_decode(url.username());
+    if let Some(secret) = url.password() {
+        password = format!("{password}:{}", percent_decode(secret));
+    }
+
+    let mut proxy = ProxyFields::new("hysteria2", &server, port);
+    if !password.is_empty() {
+        proxy.set_str("password", &password);
+    }
+    if let Some(ports) = query.get("mport").filter(|value| !value.is_empty()) {
+        proxy.set_str("ports", ports);
+    }
+    if let Some(sni) = query.get("sni").filter(|value| !value.is_empty()) {
+        proxy.set_str("sni", sni);
+    }
+    if query_flag(&query, &["insecure", "allowInsecure"]) {
+        proxy.set("skip-cert-verify", Value::Bool(true));
+    }
+    if let Some(obfs) = query.get("obfs").filter(|value| !value.is_empty()) {
+        proxy.set_str("obfs", obfs);
+        if let Some(obfs_password) = query.get("obfs-password") {
+            proxy.set_str("obfs-password", obfs_password);
+        }
+    }
+    if let Some(pin) = query.get("pinSHA256").filter(|value| !value.is_empty()) {
+        proxy.set_str("fingerprint", pin);
+    }
+    if let Some(alpn) = query.get("alpn") {
+        proxy.set_alpn(alpn);
+    }
+    proxy.set("udp", Value::Bool(true));
+    Ok(proxy.finish(fragment, "HYSTERIA2"))
+}
+
+fn decode_tuic(line: &str) -> CoreResult<ShareLinkProxy> {
+    let (body, fragment) = split_fragment(line);
+    let url = parse_link_url(body)?;
+    let (server, port) = server_and_port(&url)?;
+    let query = query_map(&url);
+    let uuid = required_username(&url, "tuic")?;
+    let password = url.password().map(percent_decode).unwrap_or_default();
+
+    let mut proxy = ProxyFields::new("tuic", &server, port);
+    proxy.set_str("uuid", &uuid);
+    proxy.set_str("password", &password);
+    if let Some(congestion) = query
+        .get("congestion_control")
+        .or_else(|| query.get("congestion-controller"))
+        .filter(|value| !value.is_empty())
+    {
+        proxy.set_str("congestion-controller", congestion);
+    }
+    if let Some(mode) = query
+        .get("udp_relay_mode")
+        .or_else(|| query.get("udp-relay-mode"))
+        .filter(|value| !value.is_empty())
+    {
+        proxy.set_str("udp-relay-mode", mode);
+    }
+    if let Some(alpn) = query.get("alpn") {
+        proxy.set_alpn(alpn);
+    }
+    if let Some(sni) = query.get("sni").filter(|value| !value.is_empty()) {
+        proxy.set_str("sni", sni);
+    }
+    if query_flag(&query, &["allow_insecure", "allowInsecure", "insecure"]) {
+        proxy.set("skip-cert-verify", Value::Bool(true));
+    }
+    if query_flag(&query, &["disable_sni", "disable-sni"]) {
+        proxy.set("disable-sni", Value::Bool(true));
+    }
+    proxy.set("udp", Value::Bool(true));
+    Ok(proxy.finish(fragment, "TUIC"))
+}
+
+/// Applies `security=tls|reality` options shared by vless and URL-style vmess.
+fn apply_url_tls(
+    proxy: &mut ProxyFields,
+    query: &HashMap<String, String>,
+    sni_key: &str,
+) -> CoreResult<()> {
+    let security = query
+        .get("security")
+        .map(|value| value.to_ascii_lowercase())
+        .unwrap_or_default();
+    match security.as_str() {
+        "" | "none" => return Ok(()),
+        "tls" | "xtls" | "reality" => {}
+        other => {
+            return Err(CoreError::InvalidProfile(format!(
+                "unsupported security `{other}`"
+            )));
+        }
+    }
+
+    proxy.set("tls", Value::Bool(true));
+    if let Some(sni) = query
+        .get("sni")
+        .or_else(|| query.get("peer"))
+        .filter(|value| !value.is_empty())
+    {
+        proxy.set_str(sni_key, sni);
+    }
+    if let Some(fingerprint) = query.get("fp").filter(|value| !value.is_empty()) {
+        proxy.set_str("client-fingerprint", fingerprint);
+    }
+    if let Some(alpn) = query.get("alpn") {
+        proxy.set_alpn(alpn);
+    }
+    if query_flag(query, &["allowInsecure", "insecure", "skip-cert-verify"]) {
+        proxy.set("skip-cert-verify", Value::Bool(true));
+    }
+
+    if security == "reality" {
+        let public_key = query
+            .get("pbk")
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| {
+                CoreError::InvalidProfile("reality link is missing `pbk`".to_string())
+            })?;
+        let mut reality = Mapping::new();
+        reality.insert(Value::from("public-key"), Value::from(public_key.as_str()));
+        if let Some(short_id) = query.get("sid").filter(|value| !value.is_empty()) {
+            reality.insert(Value::from("short-id"), Value::from(short_id.as_str()));
+        }
+        proxy.set("reality-opts", Value::Mapping(reality));
+    }
+    Ok(())
+}
+
+fn apply_url_transport(proxy: &mut ProxyFields, query: &HashMap<String, String>) -> CoreResult<()> {
+    let empty = String::new();
+    let network = query
+        .get("type")
+        .map(String::as_str)
+        .filter(|value| !value.is_empty())
+        .unwrap_or("tcp");
+    apply_transport(
+        proxy,
+        &TransportParams {
+            network,
+            header_type: query.get("headerType").unwrap_or(&empty),
+            host: query.get("host").unwrap_or(&empty),
+            path: query.get("path").unwrap_or(&empty),
+            service_name: query
+                .get("serviceName")
+                .or_else(|| query.get("path"))
+                .unwrap_or(&empty),
+        },
+    )
+}
+
+struct TransportParams<'a> {
+    network: &'a str,
+    header_type: &'a str,
+    host: &'a str,
+    path: &'a str,
+    service_name: &'a str,
+}
+
+fn apply_transport(proxy: &mut ProxyFields, params: &TransportParams<'_>) -> CoreResult<()> {
+    let host = params.host.trim();
+    let path = params.path.trim();
+    match params.network.to_ascii_lowercase().as_str() {
+        "tcp" | "raw" => {
+            if params.header_type.eq_ignore_ascii_case("http") {
+                proxy.set_str("network", "http");
+                let mut opts = Mapping::new();
+                opts.insert(
+                    Value::from("path"),
+                    Value::Sequence(vec![Value::from(if path.is_empty() { "/" } else { path })]),
+                );
+                if !host.is_empty() {
+                    let mut headers = Mapping::new();
+                    headers.insert(
+                        Value::from("Host"),
+                        Value::Sequence(split_list(host).into_iter().map(Value::from).collect()),
+                    );
+                    opts.insert(Value::from("headers"), Value::Mapping(headers));
+                }
+                proxy.set("http-opts", Value::Mapping(opts));
+            }
+        }
+        "ws" | "websocket" => {
+            proxy.set_str("network", "ws");
+            let mut opts = Mapping::new();
+            if !path.is_empty() {
+                opts.insert(Value::from("path"), Value::from(path));
+            }
+            if !host.is_empty() {
+                let mut headers = Mapping::new();
+                headers.insert(Value::from("Host"), Value::from(host));
+                opts.insert(Value::from("headers"), Value::Mapping(headers));
+            }
+            if !opts.is_empty() {
+                proxy.set("ws-opts", Value::Mapping(opts));
+            }
+        }
+        "grpc" => {
+            proxy.set_str("network", "grpc");
+            let service_name = params.service_name.trim();
+            if !service_name.is_empty() {
+                let mut opts = Mapping::new();
+                opts.insert(Value::from("grpc-service-name"), Value::from(service_name));
+                proxy.set("grpc-opts", Value::Mapping(opts));
+            }
+        }
+        "h2" | "http" => {
+            proxy.set_str("network", "h2");
+            let mut opts = Mapping::new();
+            if !host.is_empty() {
+                opts.insert(
+                    Value::from("host"),
+                    Value::Sequence(split_list(host).into_iter().map(Value::from).collect()),
+                );
+            }
+            if !path.is_empty() {
+                opts.insert(Value::from("path"), Value::from(path));
+            }
+            if !opts.is_empty() {
+                proxy.set("h2-opts", Value::Mapping(opts));
+            }
+        }
+        "httpupgrade" => {
+            proxy.set_str("network", "ws");
+            let mut opts = Mapping::new();
+            opts.insert(Value::from("v2ray-http-upgrade"), Value::Bool(true));
+            if !path.is_empty() {
+                opts.insert(Value::from("path"), Value::from(path));
+            }
+            if !host.is_empty() {
+                let mut headers = Mapping::new();
+                headers.insert(Value::from("Host"), Value::from(host));
+                opts.insert(Value::from("headers"), Value::Mapping(headers));
+            }
+            proxy.set("ws-opts", Value::Mapping(opts));
+        }
+        other => {
+            return Err(CoreError::InvalidProfile(format!(
+                "unsupported transport `{other}`"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Ordered Clash proxy fields, starting with `name`, `type`, `server` and `port`.
+struct ProxyFields {
+    kind: String,
+    fields: Mapping,
+}
+
+impl ProxyFields {
+    fn new(kind: &str, server: &str, port: u16) -> Self {
+        let mut fields = Mapping::new();
+        fields.insert(Value::from("name"), Value::from(""));
+        fields.insert(Value::from("type"), Value::from(kind));
+        fields.insert(Value::from("server"), Value::from(server));
+        fields.insert(Value::from("port"), Value::from(port));
+        Self {
+            kind: kind.to_string(),
+            fields,
+        }
+    }
+
+    fn set(&mut self, key: &str, value: Value) {
+        self.fields.insert(Value::from(key), value);
+    }
+
+    fn set_str(&mut self, key: &str, value: &str) {
+        self.set(key, Value::from(value));
+    }
+
+    fn set_non_empty(&mut self, key: &str, value: &str) {
+        let trimmed = value.trim();
+        if !trimmed.is_empty() {
+            self.set_str(key, trimmed);
+        }
+    }
+
+    fn set_alpn(&mut self, value: &str) {
+        let alpn = split_list(value);
+        if !alpn.is_empty() {
+            self.set(
+                "alpn",
+                Value::Sequence(alpn.into_iter().map(Value::from).collect()),
+            );
+        }
+    }
+
+    fn finish(self, fragment: Option<&str>, fallback_prefix: &str) -> ShareLinkProxy {
+        let name = fragment.map(percent_decode).unwrap_or_default();
+        self.finish_named(name, fallback_prefix)
+    }
+
+    fn finish_named(mut self, name: String, fallback_prefix: &str) -> ShareLinkProxy {
+        let trimmed = name.trim();
+        let name = if trimmed.is_empty() {
+            let server = self
+                .fields
+                .get("server")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let port = self
+                .fields
+                .get("port")
+                .and_then(Value::as_u64)
+                .unwrap_or_default();
+            format!("{fallback_prefix} {server}:{port}")
+        } else {
+            trimmed.to_string()
+        };
+        self.fields
+            .insert(Value::from("name"), Value::from(name.clone()));
+        ShareLinkProxy {
+            name,
+            kind: self.kind,
+            fields: self.fields,
+        }
+    }
+}
+
+fn split_fragment(value: &str) -> (&str, Option<&str>) {
+    match value.split_once('#') {
+        Some((body, fragment)) => (body, Some(fragment)),
+        None => (value, None),
+    }
+}
+
+fn parse_link_url(value: &str) -> CoreResult<url::Url> {
+    url::Url::parse(value)
+        .map_err(|error| CoreError::InvalidProfile(format!("invalid share link: {error}")))
+}
+
+fn server_and_port(url: &url::Url) -> CoreResult<(String, u16)> {
+    server_and_port_with_default(url, None)
+}
+
+fn server_and_port_with_default(
+    url: &url::Url,
+    default_port: Option<u16>,
+) -> CoreResult<(String, u16)> {
+    let server = match url.host() {
+        Some(url::Host::Domain(domain)) => percent_decode(domain),
+        Some(url::Host::Ipv4(address)) => address.to_string(),
+        Some(url::Host::Ipv6(address)) => address.to_string(),
+        None => {
+            return Err(CoreError::InvalidProfile(
+                "share link has no server".to_string(),
+            ));
+        }
+    };
+    if server.is_empty() {
+        return Err(CoreError::InvalidProfile(
+            "share link has no server".to_string(),
+        ));
+    }
+    let port = url
+        .port()
+        .or(default_port)
+        .ok_or_else(|| CoreError::InvalidProfile("share link has no port".to_string()))?;
+    Ok((server, port))
+}
+
+fn required_username(url: &url::Url, scheme: &str) -> CoreResult<String> {
+    let value = percent_decode(url.username());
+    if value.trim().is_empty() {
+        return Err(CoreError::InvalidProfile(format!(
+            "{scheme} link is missing its credential"
+        )));
+    }
+    Ok(value)
+}
+
+fn query_map(url: &url::Url) -> HashMap<String, String> {
+    url.query_pairs()
+        .map(|(key, value)| (key.into_owned(), value.trim().to_string()))
+        .collect()
+}
+
+fn query_flag(query: &HashMap<String, String>, keys: &[&str]) -> bool {
+    keys.iter()
+        .filter_map(|key| query.get(*key))
+        .any(|value| is_truthy(value))
+}
+
+fn is_truthy(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes"
+    )
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+    percent_decode_str(value).decode_utf8_lossy().into_owned()
+}
+
+fn decode_base64_text(value: &str) -> Option<String> {
+    let compact = value.trim();
+    if compact.is_empty() {
+        return None;
+    }
+    for engine in [
+        &general_purpose::STANDARD,
+        &general_purpose::STANDARD_NO_PAD,
+        &general_purpose::URL_SAFE,
+        &general_purpose::URL_SAFE_NO_PAD,
+    ] {
+        if let Ok(bytes) = engine.decode(compact)
+            && let Ok(text) = String::from_utf8(bytes)
+        {
+            return Some(text);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes a link, renders it into a generated document and reads the
+    /// proxy back through the regular Clash profile parser.
+    fn round_trip(link: &str) -> Mapping {
+        let proxy = decode_share_link(link).expect("share link should decode");
+        let document = build_subscription_document(&[proxy]).expect("document should render");
+
+        let parsed = crate::parse_profile_yaml("https://example.com/sub", &document)
+            .expect("generated document should parse as clash yaml");
+        assert_eq!(parsed.node_count, 1);
+        assert!(parsed.generated_yaml.is_none());
+        assert_eq!(
+            parsed.rules[0].to_string(),
+            format!("MATCH,{SUBSCRIPTION_GROUP_NAME}")
+        );
+
+        let root: Value = serde_yaml::from_str(&document).expect("document should be yaml");
+        root["proxies"][0]
+            .as_mapping()
+            .cloned()
+            .expect("proxy entry should be a mapping")
+    }
+
+    fn text<'a>(proxy: &'a Mapping, key: &str) -> &'a str {
+        proxy
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_else(|| panic!("missing `{key}`"))
+    }
+
+    #[test]
+    fn round_trips_shadowsocks_links() {
+        let proxy = round_trip(
+            "ss://YWVzLTI1Ni1nY206c2VjcmV0@1.2.3.4:8388/?plugin=obfs-local%3Bobfs%3Dhttp%3Bobfs-host%3Dcdn.example.com#SS%20Node",
+        );
+        assert_eq!(text(&proxy, "name"), "SS Node");
+        assert_eq!(text(&proxy, "type"), "ss");
+        assert_eq!(text(&proxy, "server"), "1.2.3.4");
+        assert_eq!(proxy["port"].as_u64(), Some(8388));
+        assert_eq!(text(&proxy, "cipher"), "aes-256-gcm");
+        assert_eq!(text(&proxy, "password"), "secret");
+        assert_eq!(text(&proxy, "plugin"), "obfs");
+        assert_eq!(
+            proxy["plugin-opts"]["host"].as_str(),
+            Some("cdn.example.com")
+        );
+
+        let legacy =
+            general_purpose::STANDARD.encode("chacha20-ietf-poly1305:pa:ss@example.com:443");
+        let proxy = round_trip(&format!("ss://{legacy}#Legacy"));
+        assert_eq!(text(&proxy, "cipher"), "chacha20-ietf-poly1305");
+        assert_eq!(text(&proxy, "password"), "pa:ss");
+        assert_eq!(text(&proxy, "server"), "example.com");
+    }
+
+    #[test]
+    fn round_trips_vmess_links() {
+        let payload = r#"{"v":"2","ps":"VMess WS","add":"vm.example.com","port":"443","id":"b831381d-6324-4d53-ad4f-8cda48b30811","aid":"0","scy":"auto","net":"ws","type":"none","host":"cdn.example.com","path":"/ray","tls":"tls","sni":"vm.example.com","alpn":"h2,http/1.1"}"#;
+        let proxy = round_trip(&format!(
+            "vmess://{}",
+            general_purpose::STANDARD.encode(payload)
+        ));
+        assert_eq!(text(&proxy, "name"), "VMess WS");
+        assert_eq!(text(&proxy, "type"), "vmess");
+        assert_eq!(proxy["port"].as_u64(), Some(443));
+        assert_eq!(text(&proxy, "uuid"), "b831381d-6324-4d53-ad4f-8cda48b30811");
+        assert_eq!(proxy["alterId"].as_u64(), Some(0));
+        assert_eq!(proxy["tls"].as_bool(), Some(true));
+        assert_eq!(text(&proxy, "servername"), "vm.example.com");
+        assert_eq!(text(&proxy, "network"), "ws");
+        assert_eq!(proxy["ws-opts"]["path"].as_str(), Some("/ray"));
+        assert_eq!(
+            proxy["ws-opts"]["headers"]["Host"].as_str(),
+            Some("cdn.example.com")
+        );
+        assert_eq!(proxy["alpn"].as_sequence().map(Vec::len), Some(2));
+    }
+
+    #[test]
+    fn round_trips_vless_reality_links() {
+        let proxy = round_trip(
+            "vless://0a1b2c3d-0000-4000-8000-123456789abc@[2001:db8::1]:443?encryption=none&security=reality&sni=www.example.com&fp=chrome&pbk=PUBKEY&sid=6ba85179&type=grpc&serviceName=svc&flow=xtls-rprx-vision#VLESS",
+        );
+        assert_eq!(text(&proxy, "type"), "vless");
+        assert_eq!(text(&proxy, "server"), "2001:db8::1");
+        assert_eq!(text(&proxy, "flow"), "xtls-rprx-vision");
+        assert_eq!(text(&proxy, "servername"), "www.example.com");
+        assert_eq!(text(&proxy, "client-fingerprint"), "chrome");
+        assert_eq!(proxy["reality-opts"]["public-key"].as_str(), Some("PUBKEY"));
+        assert_eq!(proxy["reality-opts"]["short-id"].as_str(), Some("6ba85179"));
+        assert_eq!(text(&proxy, "network"), "grpc");
+        assert_eq!(
+            proxy["grpc-opts"]["grpc-service-name"].as_str(),
+            Some("svc")
+        );
+    }
+
+    #[test]
+    fn round_trips_trojan_links() {
+        let proxy = round_trip(
+            "trojan://p%40ss@tr.example.com:443?sni=sni.example.com&allowInsecure=1&type=ws&path=%2Fws#Trojan",
+        );
+        assert_eq!(text(&proxy, "type"), "trojan");
+        assert_eq!(text(&proxy, "password"), "p@ss");
+        assert_eq!(text(&proxy, "sni"), "sni.example.com");
+        assert_eq!(proxy["skip-cert-verify"].as_bool(), Some(true));
+        assert_eq!(text(&proxy, "network"), "ws");
+        assert_eq!(proxy["ws-opts"]["path"].as_str(), Some("/ws"));
+    }
+
+    #[test]
+    fn round_trips_hysteria2_links() {
+        let proxy = round_trip(
+            "hy2://letmein@hy.example.com:8443/?sni=hy.example.com&insecure=1&obfs=salamander&obfs-password=cry&mport=20000-30000#HY2",
+        );
+        assert_eq!(text(&proxy, "type"), "hysteria2");
+        assert_eq!(text(&proxy, "password"), "letmein");
+        assert_eq!(proxy["port"].as_u64(), Some(8443));
+        assert_eq!(text(&proxy, "ports"), "20000-30000");
+        assert_eq!(text(&proxy, "obfs"), "salamander");
+        assert_eq!(text(&proxy, "obfs-password"), "cry");
+        assert_eq!(proxy["skip-cert-verify"].as_bool(), Some(true));
+    }
+
+    #[test]
+    fn round_trips_tuic_links() {
+        let proxy = round_trip(
+            "tuic://5b2f0a0e-1111-4222-8333-444455556666:pw@tuic.example.com:443?congestion_control=bbr&udp_relay_mode=native&alpn=h3&sni=tuic.example.com#TUIC",
+        );
+        assert_eq!(text(&proxy, "type"), "tuic");
+        assert_eq!(text(&proxy, "uuid"), "5b2f0a0e-1111-4222-8333-444455556666");
+        assert_eq!(text(&proxy, "password"), "pw");
+        assert_eq!(text(&proxy, "congestion-controller"), "bbr");
+        assert_eq!(text(&proxy, "udp-relay-mode"), "native");
+        assert_eq!(proxy["alpn"][0].as_str(), Some("h3"));
+        assert_eq!(proxy["udp"].as_bool(), Some(true));
+    }
+
+    #[test]
+    fn deduplicates_names_in_generated_document() {
+        let first = decode_share_link("trojan://a@one.example.com:443#Same").expect("decode");
+        let second = decode_share_link("trojan://b@two.example.com:443#Same").expect("decode");
+        let document = build_subscription_document(&[first, second]).expect("render");
+        let root: Value = serde_yaml::from_str(&document).expect("yaml");
+        assert_eq!(root["proxies"][0]["name"].as_str(), Some("Same"));
+        assert_eq!(root["proxies"][1]["name"].as_str(), Some("Same 2"));
+    }
+}
@@ -0,0 +1,324 @@
+use crate::{ControllerClient, Core, CoreError, CoreEvent, CoreState, log_tail};
+use std::collections::BTreeMap;
+use std::sync::{Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+const SUPERVISOR_TICK: Duration = Duration::from_secs(1);
+/// Delay before the first restart; doubled for each further attempt.
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Restarts tried before giving up on the kernel.
+const MAX_RESTART_ATTEMPTS: u32 = 5;
+/// Uptime after which the kernel counts as recovered and the attempts reset.
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+/// How often group selections are read back from a healthy kernel, to also
+/// catch changes made from other dashboards.
+const SELECTION_SYNC_INTERVAL: Duration = Duration::from_secs(30);
+/// Log lines attached to [`CoreEvent::KernelExited`].
+const CRASH_LOG_LINES: usize = 20;
+
+#[derive(Debug, Default)]
+pub(crate) struct Supervisor {
+    /// When the next restart is due while the kernel is down.
+    next_restart_at: Option<Instant>,
+    /// Restarts since the kernel was last up for [`STABLE_UPTIME`].
+    attempts: u32,
+    up_since: Option<Instant>,
+    /// Proxy chosen in each selector group, re-applied after a restart.
+    selections: BTreeMap<String, String>,
+    selections_synced_at: Option<Instant>,
+}
+
+impl Supervisor {
+    /// Forgets a pending restart, for when the kernel is started or stopped
+    /// on purpose.
+    pub fn cancel(&mut self) {
+        self.next_restart_at = None;
+        self.attempts = 0;
+    }
+
+    pub fn record_selection(&mut self, group: &str, proxy: &str) {
+        self.selections.insert(group.to_string(), proxy.to_string());
+    }
+
+    pub fn record_selections(&mut self, selections: BTreeMap<String, String>) {
+        self.selections.extend(selections);
+    }
+
+    pub fn selections(&self) -> &BTreeMap<String, String> {
+        &self.selections
+    }
+
+    fn kernel_exited(&mut self, now: Instant) {
+        self.up_since = None;
+        self.next_restart_at = Some(now + backoff(self.attempts));
+    }
+
+    fn restart_due(&self, now: Instant) -> bool {
+        self.next_restart_at.is_some_and(|due| now >= due)
+    }
+
+    fn kernel_up(&mut self, now: Instant) {
+        let up_since = *self.up_since.get_or_insert(now);
+        if now.duration_since(up_since) >= STABLE_UPTIME {
+            self.attempts = 0;
+        }
+    }
+
+    fn selection_sync_due(&self, now: Instant) -> bool {
+        self.selections_synced_at
+            .is_none_or(|synced| now.duration_since(synced) >= SELECTION_SYNC_INTERVAL)
+    }
+}
+
+fn backoff(attempts: u32) -> Duration {
+    BASE_BACKOFF
+        .saturating_mul(2_u32.saturating_pow(attempts))
+        .min(MAX_BACKOFF)
+}
+
+/// Watches the kernel until the owning [`Core`] is dropped, restarting it
+/// after unexpected exits.
+pub(crate) fn spawn(inner: Weak<Mutex<CoreState>>) {
+    let spawned = thread::Builder::new()
+        .name("linkpad-supervisor".to_string())
+        .spawn(move || {
+            info!("kernel supervisor started");
+            loop {
+                thread::sleep(SUPERVISOR_TICK);
+                let Some(inner) = inner.upgrade() else {
+                    break;
+                };
+                supervise(&Core { inner }, Instant::now());
+            }
+            info!("kernel supervisor stopped");
+        });
+    if let Err(error) = spawned {
+        warn!("failed to start kernel supervisor: {error}");
+    }
+}
+
+fn supervise(core: &Core, now: Instant) {
+    let mut state = core.inner.lock().expect("core state poisoned");
+    if state.running && state.kernel_runtime.is_running() {
+        state.supervisor.kernel_up(now);
+        if !state.supervisor.selection_sync_due(now) {
+            return;
+        }
+        state.supervisor.selections_synced_at = Some(now);
+        let controller = state.controller.clone();
+        drop(state);
+        sync_selections(core, controller);
+        return;
+    }
+
+    if let Some(status) = state.kernel_runtime.take_unexpected_exit() {
+        state.running = false;
+        state.controller = None;
+        state.supervisor.kernel_exited(now);
+        let log_tail = log_tail::last_lines(&state.kernel_runtime.log_path(), CRASH_LOG_LINES);
+        warn!("kernel exited unexpectedly: {status}");
+        state
+            .events
+            .push(CoreEvent::KernelExited { status, log_tail });
+        return;
+    }
+
+    // A launch started elsewhere is still waiting for the kernel; it decides
+    // whether a restart is needed once it returns.
+    if state.launching || !state.supervisor.restart_due(now) {
+        return;
+    }
+    state.supervisor.attempts += 1;
+    let attempt = state.supervisor.attempts;
+    info!("restarting kernel: attempt={attempt}/{MAX_RESTART_ATTEMPTS}");
+    // The lock is released while the kernel comes up, so the time is taken
+    // again afterwards.
+    let (mut state, launched) = core.launch(state);
+    let now = Instant::now();
+    match launched {
+        Ok(()) => {
+            state.supervisor.next_restart_at = None;
+            state.supervisor.up_since = Some(now);
+            let controller = state.controller.clone();
+            let selections = state.supervisor.selections.clone();
+            state
+                .events
+                .push(CoreEvent::KernelRestarted { attempts: attempt });
+            drop(state);
+            if let Some(controller) = controller {
+                restore_selections(&controller, &selections);
+            }
+        }
+        Err(CoreError::NotRunning) => {
+            info!("kernel restart cancelled: the core was stopped");
+        }
+        Err(error) if attempt < MAX_RESTART_ATTEMPTS => {
+            warn!("kernel restart failed: attempt={attempt}, error={error}");
+            state.supervisor.kernel_exited(now);
+        }
+        Err(error) => {
+            warn!("kernel restart failed, giving up: {error}");
+            give_up(&mut state, error.to_string());
+        }
+    }
+}
+
+/// Stops retrying and takes the system proxy down, so traffic does not keep
+/// going to a port nothing listens on.
+fn give_up(state: &mut CoreState, error: String) {
+    state.supervisor.cancel();
+    let system_proxy_disabled = if state.system_proxy_enabled {
+        let disabled = state
+            .system_proxy_manager
+            .lock()
+            .expect("system proxy manager poisoned")
+            .disable();
+        match disabled {
+            Ok(()) => {
+                state.system_proxy_enabled = false;
+                state.system_proxy_applied = None;
+                state.pac.stop();
+                Core::sync_shell_proxy(state);
+                info!("system proxy disabled after kernel recovery failed");
+                true
+            }
+            Err(error) => {
+                warn!("failed to disable system proxy: {error}");
+                false
+            }
+        }
+    } else {
+        false
+    };
+    state.events.push(CoreEvent::KernelRecoveryFailed {
+        error,
+        system_proxy_disabled,
+    });
+}
+
+fn sync_selections(core: &Core, controller: Option<ControllerClient>) {
+    let Some(controller) = controller else {
+        return;
+    };
+    match controller.group_selections() {
+        Ok(selections) => {
+            let mut state = core.inner.lock().expect("core state poisoned");
+            state.supervisor.record_selections(selections);
+        }
+        Err(error) => warn!("failed to read group selections: {error}"),
+    }
+}
+
+/// Selects the recorded proxies again on a restarted kernel, skipping groups
+/// the profile no longer has.
+pub(crate) fn restore_selections(
+    controller: &ControllerClient,
+    selections: &BTreeMap<String, String>,
+) {
+    if selections.is_empty() {
+        return;
+    }
+    let current = match controller.group_selections() {
+        Ok(current) => current,
+        Err(error) => {
+            warn!("group selections not restored: {error}");
+            return;
+        }
+    };
+    for (group, proxy) in selections {
+        let Some(now) = current.get(group) else {
+            continue;
+        };
+        if now == proxy {
+            continue;
+        }
+        match controller.select_proxy(group, proxy) {
+            Ok(()) => info!("group selection restored: group={group}, proxy={proxy}"),
+            Err(error) => warn!("failed to restore selection of {group}: {error}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::StubServer;
+
+    #[test]
+    fn backs_off_exponentially_and_resets_when_stable() {
+        assert_eq!(backoff(0), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(8));
+        assert_eq!(backoff(10), MAX_BACKOFF);
+        assert_eq!(backoff(u32::MAX), MAX_BACKOFF);
+
+        let start = Instant::now();
+        let mut supervisor = Supervisor {
+            attempts: 2,
+            ..Default::default()
+        };
+        supervisor.kernel_exited(start);
+        assert!(!supervisor.restart_due(start + Duration::from_secs(3)));
+        assert!(supervisor.restart_due(start + Duration::from_secs(4)));
+
+        supervisor.kernel_up(start);
+        supervisor.kernel_up(start + STABLE_UPTIME / 2);
+        assert_eq!(supervisor.attempts, 2);
+        supervisor.kernel_up(start + STABLE_UPTIME);
+        assert_eq!(supervisor.attempts, 0);
+
+        supervisor.cancel();
+        assert!(!supervisor.restart_due(start + MAX_BACKOFF));
+    }
+
+    #[test]
+    fn leaves_restarts_to_a_launch_in_progress() {
+        let core = Core {
+            inner: std::sync::Arc::new(Mutex::new(CoreState {
+                launching: true,
+                ..CoreState::default()
+            })),
+        };
+        let start = Instant::now();
+        core.inner.lock().unwrap().supervisor.kernel_exited(start);
+
+        supervise(&core, start + MAX_BACKOFF);
+        let state = core.inner.lock().unwrap();
+        assert_eq!(state.supervisor.attempts, 0);
+        assert!(state.supervisor.restart_due(start + MAX_BACKOFF));
+    }
+
+    #[test]
+    fn restores_changed_selections_of_existing_groups() {
+        let server = StubServer::start();
+        server.route(
+            "GET",
+            "/proxies",
+            200,
+            r#"{"proxies":{
+                "Proxy":{"name":"Proxy","type":"Selector","now":"node-a","all":["node-a","node-b"]},
+                "Auto":{"name":"Auto","type":"Selector","now":"node-a","all":["node-a"]}
+            }}"#,
+        );
+        server.route("PUT", "/proxies/Proxy", 204, "");
+        let controller = ControllerClient::new(&server.url(), None).expect("client");
+
+        let selections = BTreeMap::from([
+            ("Proxy".to_string(), "node-b".to_string()),
+            ("Auto".to_string(), "node-a".to_string()),
+            ("Removed".to_string(), "node-c".to_string()),
+        ]);
+        restore_selections(&controller, &selections);
+
+        let puts = server
+            .requests()
+            .into_iter()
+            .filter(|request| request.method == "PUT")
+            .collect::<Vec<_>>();
+        assert_eq!(puts.len(), 1);
+        assert_eq!(puts[0].path, "/proxies/Proxy");
+        assert!(puts[0].body.contains("node-b"));
+    }
+}
@@ -0,0 +1,193 @@
+//! Helpers shared by unit tests.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A request received by [`StubServer`].
+#[derive(Clone, Debug)]
+pub(crate) struct StubRequest {
+    pub method: String,
+    /// Path including the query string.
+    pub path: String,
+    /// Header names are lowercase.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl StubRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct StubRoute {
+    method: String,
+    path: String,
+    status: u16,
+    body: Vec<u8>,
+}
+
+/// Minimal HTTP/1.1 server on localhost answering canned responses, used to
+/// test clients without the real services.
+///
+/// Routes match on method and path without the query string; unmatched
+/// requests get `404`. Every request is recorded for later assertions.
+pub(crate) struct StubServer {
+    addr: SocketAddr,
+    routes: Arc<Mutex<Vec<StubRoute>>>,
+    requests: Arc<Mutex<Vec<StubRequest>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl StubServer {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind stub server");
+        let addr = listener.local_addr().expect("stub server address");
+        let routes = Arc::new(Mutex::new(Vec::<StubRoute>::new()));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let (thread_routes, thread_requests, thread_stopped) =
+            (routes.clone(), requests.clone(), stopped.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                handle_connection(stream, &thread_routes, &thread_requests);
+            }
+        });
+
+        Self {
+            addr,
+            routes,
+            requests,
+            stopped,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn route(&self, method: &str, path: &str, status: u16, body: &str) -> &Self {
+        self.route_bytes(method, path, status, body.as_bytes())
+    }
+
+    /// Like [`StubServer::route`], for binary bodies such as archives.
+    pub fn route_bytes(&self, method: &str, path: &str, status: u16, body: &[u8]) -> &Self {
+        let mut routes = self.routes.lock().expect("stub routes poisoned");
+        routes.retain(|route| !(route.method == method && route.path == path));
+        routes.push(StubRoute {
+            method: method.to_string(),
+            path: path.to_string(),
+            status,
+            body: body.to_vec(),
+        });
+        self
+    }
+
+    pub fn requests(&self) -> Vec<StubRequest> {
+        self.requests
+            .lock()
+            .expect("stub requests poisoned")
+            .clone()
+    }
+
+    pub fn last_request(&self) -> StubRequest {
+        self.requests()
+            .pop()
+            .expect("stub server received no request")
+    }
+}
+
+impl Drop for StubServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wake up the accept loop so the thread can exit.
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    routes: &Mutex<Vec<StubRoute>>,
+    requests: &Mutex<Vec<StubRequest>>,
+) {
+    let Some(request) = read_request(&stream) else {
+        return;
+    };
+    let path = request
+        .path
+        .split_once('?')
+        .map_or(request.path.as_str(), |(path, _)| path)
+        .to_string();
+    let route = routes
+        .lock()
+        .expect("stub routes poisoned")
+        .iter()
+        .find(|route| route.method == request.method && route.path == path)
+        .cloned();
+    requests
+        .lock()
+        .expect("stub requests poisoned")
+        .push(request);
+
+    let (status, body) = route.map_or((404, b"not found".to_vec()), |route| {
+        (route.status, route.body)
+    });
+    let head = format!(
+        "HTTP/1.1 {status} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    let mut stream = stream;
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(&body);
+    let _ = stream.flush();
+}
+
+fn read_request(stream: &TcpStream) -> Option<StubRequest> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+    Some(StubRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
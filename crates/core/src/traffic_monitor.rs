@@ -0,0 +1,250 @@
+use crate::{
+    ControllerClient, ControllerMemory, ControllerResult, ControllerStream, ControllerTraffic,
+    CoreState, now_unix_seconds,
+};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Samples kept for the throughput chart; mihomo sends one per second.
+const HISTORY_LEN: usize = 120;
+/// Delay before reconnecting after a stream ended or failed to open.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Upload and download rates of one second, in bytes per second.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrafficSample {
+    /// Unix seconds.
+    pub timestamp: u64,
+    pub up: u64,
+    pub down: u64,
+    /// Memory in use by the kernel when the sample was taken.
+    pub memory: Option<u64>,
+}
+
+/// Recent traffic of the kernel, see [`crate::Core::traffic_stats`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrafficStats {
+    /// Whether the traffic stream is currently connected.
+    pub connected: bool,
+    /// Oldest first, at most two minutes.
+    pub samples: Vec<TrafficSample>,
+    pub memory: Option<u64>,
+}
+
+impl TrafficStats {
+    /// The newest sample while the stream is connected.
+    pub fn current(&self) -> Option<&TrafficSample> {
+        self.samples.last().filter(|_| self.connected)
+    }
+
+    /// Highest upload or download rate in the history.
+    pub fn peak_rate(&self) -> u64 {
+        self.samples
+            .iter()
+            .map(|sample| sample.up.max(sample.down))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct TrafficHistory {
+    samples: VecDeque<TrafficSample>,
+    memory: Option<u64>,
+    connected: bool,
+}
+
+impl TrafficHistory {
+    fn record_traffic(&mut self, timestamp: u64, traffic: ControllerTraffic) {
+        self.connected = true;
+        if self.samples.len() == HISTORY_LEN {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(TrafficSample {
+            timestamp,
+            up: traffic.up,
+            down: traffic.down,
+            memory: self.memory,
+        });
+    }
+
+    fn record_memory(&mut self, memory: ControllerMemory) {
+        // The first sample mihomo sends is always zero.
+        if memory.inuse > 0 {
+            self.memory = Some(memory.inuse);
+        }
+    }
+
+    pub fn snapshot(&self) -> TrafficStats {
+        TrafficStats {
+            connected: self.connected,
+            samples: self.samples.iter().copied().collect(),
+            memory: self.memory,
+        }
+    }
+}
+
+/// Follows the traffic and memory streams until the owning
+/// [`crate::Core`] is dropped, reconnecting whenever the kernel restarts.
+pub(crate) fn spawn(inner: Weak<Mutex<CoreState>>) {
+    spawn_follower(
+        "linkpad-traffic",
+        inner.clone(),
+        ControllerClient::traffic,
+        |history, traffic| history.record_traffic(now_unix_seconds(), traffic),
+        |history| history.connected = false,
+    );
+    spawn_follower(
+        "linkpad-memory",
+        inner,
+        ControllerClient::memory,
+        TrafficHistory::record_memory,
+        |history| history.memory = None,
+    );
+}
+
+type OpenStream<T> = fn(&ControllerClient) -> ControllerResult<ControllerStream<T>>;
+
+fn spawn_follower<T: serde::de::DeserializeOwned + 'static>(
+    name: &str,
+    inner: Weak<Mutex<CoreState>>,
+    open: OpenStream<T>,
+    record: fn(&mut TrafficHistory, T),
+    disconnect: fn(&mut TrafficHistory),
+) {
+    let thread_name = name.to_string();
+    let spawned = thread::Builder::new()
+        .name(thread_name.clone())
+        .spawn(move || {
+            info!("{thread_name} started");
+            loop {
+                let controller = {
+                    let Some(inner) = inner.upgrade() else {
+                        break;
+                    };
+                    let state = inner.lock().expect("core state poisoned");
+                    state.controller.clone()
+                };
+                if let Some(controller) = controller
+                    && !follow_stream(&inner, &controller, open, record)
+                {
+                    break;
+                }
+                if let Some(inner) = inner.upgrade() {
+                    disconnect(&mut inner.lock().expect("core state poisoned").traffic);
+                }
+                thread::sleep(RECONNECT_DELAY);
+            }
+            info!("{thread_name} stopped");
+        });
+    if let Err(error) = spawned {
+        warn!("failed to start {name}: {error}");
+    }
+}
+
+/// Records items until the stream ends. Returns `false` once the core is
+/// gone and the follower should stop.
+fn follow_stream<T: serde::de::DeserializeOwned>(
+    inner: &Weak<Mutex<CoreState>>,
+    controller: &ControllerClient,
+    open: OpenStream<T>,
+    record: fn(&mut TrafficHistory, T),
+) -> bool {
+    let stream = match open(controller) {
+        Ok(stream) => stream,
+        Err(error) => {
+            warn!("controller stream unavailable: {error}");
+            return true;
+        }
+    };
+    for item in stream {
+        let Some(inner) = inner.upgrade() else {
+            return false;
+        };
+        match item {
+            Ok(item) => record_item(&inner, record, item),
+            Err(error) => {
+                warn!("controller stream interrupted: {error}");
+                break;
+            }
+        }
+    }
+    true
+}
+
+fn record_item<T>(inner: &Arc<Mutex<CoreState>>, record: fn(&mut TrafficHistory, T), item: T) {
+    let mut state = inner.lock().expect("core state poisoned");
+    record(&mut state.traffic, item);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::StubServer;
+
+    #[test]
+    fn history_keeps_recent_samples_with_memory() {
+        let mut history = TrafficHistory::default();
+        history.record_memory(ControllerMemory {
+            inuse: 0,
+            oslimit: 0,
+        });
+        history.record_traffic(1, ControllerTraffic { up: 1, down: 2 });
+        history.record_memory(ControllerMemory {
+            inuse: 4096,
+            oslimit: 0,
+        });
+        for second in 2..=HISTORY_LEN as u64 + 1 {
+            history.record_traffic(
+                second,
+                ControllerTraffic {
+                    up: second,
+                    down: 0,
+                },
+            );
+        }
+
+        let stats = history.snapshot();
+        assert_eq!(stats.samples.len(), HISTORY_LEN);
+        assert_eq!(stats.samples[0].timestamp, 2);
+        assert_eq!(stats.samples[0].memory, Some(4096));
+        assert_eq!(stats.current().map(|sample| sample.up), Some(121));
+        assert_eq!(stats.peak_rate(), 121);
+
+        history.connected = false;
+        assert_eq!(history.snapshot().current(), None);
+    }
+
+    #[test]
+    fn follows_stream_until_it_ends() {
+        let server = StubServer::start();
+        server.route(
+            "GET",
+            "/traffic",
+            200,
+            "{\"up\":5,\"down\":6}\n{\"up\":7,\"down\":8}\n",
+        );
+        let controller = ControllerClient::new(&server.url(), None).expect("client");
+        let inner = Arc::new(Mutex::new(CoreState::default()));
+
+        let keep_going = follow_stream(
+            &Arc::downgrade(&inner),
+            &controller,
+            ControllerClient::traffic,
+            |history, traffic| history.record_traffic(1, traffic),
+        );
+        assert!(keep_going);
+        let stats = inner.lock().unwrap().traffic.snapshot();
+        assert_eq!(
+            stats
+                .samples
+                .iter()
+                .map(|sample| (sample.up, sample.down))
+                .collect::<Vec<_>>(),
+            vec![(5, 6), (7, 8)]
+        );
+    }
+}
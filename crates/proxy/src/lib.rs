@@ -1,11 +1,22 @@
 use std::fmt;
+use std::fs;
+use std::path::PathBuf;
 
+#[cfg(target_os = "linux")]
+mod linux;
 #[cfg(target_os = "macos")]
 mod macos;
-#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod shell_env;
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
 mod unsupported;
 #[cfg(target_os = "windows")]
 mod windows;
+#[cfg(any(target_os = "windows", test))]
+mod wininet;
+
+pub use shell_env::{
+    ShellSyntax, install_shell_hooks, shell_hooks_installed, uninstall_shell_hooks,
+};
 
 #[derive(Debug, Clone)]
 pub struct SystemProxyError {
@@ -30,13 +41,70 @@ impl std::error::Error for SystemProxyError {}
 
 pub type SystemProxyResult<T> = Result<T, SystemProxyError>;
 
+/// Hosts a new install sends around the proxy: loopback, link-local and
+/// private networks.
+pub const DEFAULT_BYPASS: [&str; 8] = [
+    "localhost",
+    "*.local",
+    "127.0.0.0/8",
+    "::1",
+    "10.0.0.0/8",
+    "172.16.0.0/12",
+    "192.168.0.0/16",
+    "169.254.0.0/16",
+];
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SystemProxyConfig {
     pub host: String,
-    pub port: u16,
+    pub http_port: u16,
+    pub https_port: u16,
+    pub socks_port: u16,
+    /// Host names, `*.` domain wildcards and CIDRs that skip the proxy.
+    pub bypass: Vec<String>,
+    /// Automatic configuration (PAC) URL; when set, the OS loads the proxies
+    /// from the script there instead of the endpoints above.
+    pub pac_url: Option<String>,
 }
 
 impl SystemProxyConfig {
+    /// Points every protocol at `port` and bypasses nothing.
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        Self {
+            host: host.into(),
+            http_port: port,
+            https_port: port,
+            socks_port: port,
+            bypass: Vec::new(),
+            pac_url: None,
+        }
+    }
+
+    pub fn with_bypass(mut self, bypass: Vec<String>) -> Self {
+        self.bypass = bypass;
+        self
+    }
+
+    pub fn with_pac_url(mut self, pac_url: Option<String>) -> Self {
+        self.pac_url = pac_url;
+        self
+    }
+
+    /// The port shared by every protocol, if they all use one.
+    pub fn single_port(&self) -> Option<u16> {
+        (self.http_port == self.https_port && self.https_port == self.socks_port)
+            .then_some(self.http_port)
+    }
+}
+
+/// Host and port the OS sends one protocol to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProxyEndpoint {
+    pub host: String,
+    pub port: u16,
+}
+
+impl ProxyEndpoint {
     pub fn new(host: impl Into<String>, port: u16) -> Self {
         Self {
             host: host.into(),
@@ -45,14 +113,49 @@ impl SystemProxyConfig {
     }
 }
 
+/// The proxy settings the OS currently has, see [`SystemProxyBackend::status`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SystemProxyStatus {
+    /// `None` where the protocol goes direct.
+    pub http: Option<ProxyEndpoint>,
+    pub https: Option<ProxyEndpoint>,
+    pub socks: Option<ProxyEndpoint>,
+    pub pac_url: Option<String>,
+}
+
+impl SystemProxyStatus {
+    /// Whether the OS still points where `config` does. Bypass lists are not
+    /// compared, since every platform rewrites them in its own way.
+    pub fn matches(&self, config: &SystemProxyConfig) -> bool {
+        if let Some(url) = &config.pac_url {
+            return self.pac_url.as_ref() == Some(url);
+        }
+        let expected = |port| Some(ProxyEndpoint::new(config.host.clone(), port));
+        self.pac_url.is_none()
+            && self.http == expected(config.http_port)
+            && self.https == expected(config.https_port)
+            && self.socks == expected(config.socks_port)
+    }
+}
+
 pub trait SystemProxyBackend: Send + fmt::Debug {
     fn enable(&mut self, config: &SystemProxyConfig) -> SystemProxyResult<()>;
     fn disable(&mut self) -> SystemProxyResult<()>;
+    /// Reads the settings back from the OS.
+    fn status(&self) -> SystemProxyResult<SystemProxyStatus>;
+    /// The settings found before the first `enable`, serialized for
+    /// [`SystemProxyBackend::restore`]; `None` while nothing is changed.
+    fn snapshot(&self) -> Option<String>;
+    /// Puts back settings from a `snapshot` taken by an earlier process.
+    fn restore(&mut self, snapshot: &str) -> SystemProxyResult<()>;
 }
 
 #[derive(Debug)]
 pub struct SystemProxyManager {
     backend: Box<dyn SystemProxyBackend>,
+    /// Where the backend's snapshot is kept while the proxy is enabled, so a
+    /// process that dies without disabling it can be cleaned up after.
+    snapshot_path: Option<PathBuf>,
 }
 
 impl Default for SystemProxyManager {
@@ -65,11 +168,26 @@ impl SystemProxyManager {
     pub fn new() -> Self {
         Self {
             backend: create_default_backend(),
+            snapshot_path: None,
         }
     }
 
     pub fn new_with_backend(backend: Box<dyn SystemProxyBackend>) -> Self {
-        Self { backend }
+        Self {
+            backend,
+            snapshot_path: None,
+        }
+    }
+
+    /// A manager for terminals instead of the OS: it keeps env files for
+    /// every [`ShellSyntax`] in `dir`, which rc files source once
+    /// [`install_shell_hooks`] ran.
+    pub fn new_shell_env(dir: PathBuf) -> Self {
+        Self::new_with_backend(shell_env::create_backend(dir))
+    }
+
+    pub fn set_snapshot_path(&mut self, path: Option<PathBuf>) {
+        self.snapshot_path = path;
     }
 
     pub fn enable(&mut self, host: &str, port: u16) -> SystemProxyResult<()> {
@@ -78,14 +196,57 @@ impl SystemProxyManager {
     }
 
     pub fn enable_with_config(&mut self, config: &SystemProxyConfig) -> SystemProxyResult<()> {
-        self.backend.enable(config)
+        self.backend.enable(config)?;
+        if let (Some(path), Some(snapshot)) = (&self.snapshot_path, self.backend.snapshot()) {
+            // The proxy works without the file; only crash recovery is lost.
+            let _ = write_snapshot(path, &snapshot);
+        }
+        Ok(())
     }
 
     pub fn disable(&mut self) -> SystemProxyResult<()> {
-        self.backend.disable()
+        self.backend.disable()?;
+        if let Some(path) = &self.snapshot_path {
+            let _ = fs::remove_file(path);
+        }
+        Ok(())
+    }
+
+    pub fn status(&self) -> SystemProxyResult<SystemProxyStatus> {
+        self.backend.status()
+    }
+
+    /// Restores the settings saved by a process that enabled the proxy and
+    /// ended without disabling it. Returns whether a snapshot was found.
+    pub fn recover(&mut self) -> SystemProxyResult<bool> {
+        let Some(path) = &self.snapshot_path else {
+            return Ok(false);
+        };
+        let snapshot = match fs::read_to_string(path) {
+            Ok(snapshot) => snapshot,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(error) => {
+                return Err(SystemProxyError::new(format!(
+                    "failed to read {}: {error}",
+                    path.display()
+                )));
+            }
+        };
+        self.backend.restore(&snapshot)?;
+        let _ = fs::remove_file(path);
+        Ok(true)
     }
 }
 
+fn write_snapshot(path: &std::path::Path, snapshot: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let partial = path.with_extension("partial");
+    fs::write(&partial, snapshot)?;
+    fs::rename(&partial, path)
+}
+
 #[cfg(target_os = "macos")]
 fn create_default_backend() -> Box<dyn SystemProxyBackend> {
     macos::create_backend()
@@ -96,7 +257,12 @@ fn create_default_backend() -> Box<dyn SystemProxyBackend> {
     windows::create_backend()
 }
 
-#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+#[cfg(target_os = "linux")]
+fn create_default_backend() -> Box<dyn SystemProxyBackend> {
+    linux::create_backend()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
 fn create_default_backend() -> Box<dyn SystemProxyBackend> {
     unsupported::create_backend()
 }
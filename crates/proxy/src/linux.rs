@@ -0,0 +1,705 @@
+use crate::{
+    ProxyEndpoint, SystemProxyBackend, SystemProxyConfig, SystemProxyError, SystemProxyResult,
+    SystemProxyStatus,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const GNOME_SCHEMA: &str = "org.gnome.system.proxy";
+const KDE_FILE: &str = "kioslaverc";
+const KDE_GROUP: &str = "Proxy Settings";
+/// `ProxyType` values in `kioslaverc`.
+const KDE_PROXY_NONE: &str = "0";
+const KDE_PROXY_MANUAL: &str = "1";
+const KDE_PROXY_PAC: &str = "2";
+
+pub(crate) fn create_backend() -> Box<dyn SystemProxyBackend> {
+    let desktop = detect_desktop(
+        std::env::var("XDG_CURRENT_DESKTOP").ok().as_deref(),
+        std::env::var("KDE_SESSION_VERSION").ok().as_deref(),
+    );
+    Box::new(LinuxSystemProxyBackend::new(
+        desktop,
+        Box::new(SystemCommandRunner),
+    ))
+}
+
+/// Runs the desktop's settings tools; replaced by a fake in tests.
+trait CommandRunner: Send + fmt::Debug {
+    fn run(&self, program: &str, args: &[&str]) -> SystemProxyResult<String>;
+}
+
+#[derive(Debug)]
+struct SystemCommandRunner;
+
+impl CommandRunner for SystemCommandRunner {
+    fn run(&self, program: &str, args: &[&str]) -> SystemProxyResult<String> {
+        use std::process::Command;
+
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|error| SystemProxyError::new(format!("{program}: {error}")))?;
+
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let reason = if !stderr.trim().is_empty() {
+            stderr.trim().to_string()
+        } else {
+            stdout.trim().to_string()
+        };
+        Err(SystemProxyError::new(format!(
+            "{program} {} failed: {reason}",
+            args.join(" ")
+        )))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Desktop {
+    /// GNOME and desktops built on its settings, such as Cinnamon or Budgie.
+    Gnome,
+    /// KDE Plasma, with the major version picking `kwriteconfig5` or `6`.
+    Kde { version: u8 },
+}
+
+/// Reads the desktop from `XDG_CURRENT_DESKTOP`, a colon-separated list such
+/// as `ubuntu:GNOME`.
+fn detect_desktop(current_desktop: Option<&str>, kde_version: Option<&str>) -> Option<Desktop> {
+    let names = current_desktop?
+        .split(':')
+        .map(|name| name.trim().to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    if names.iter().any(|name| name == "kde") {
+        let version = match kde_version.map(str::trim) {
+            Some("6") => 6,
+            _ => 5,
+        };
+        return Some(Desktop::Kde { version });
+    }
+    let gnome_family = [
+        "gnome",
+        "gnome-classic",
+        "gnome-flashback",
+        "unity",
+        "cinnamon",
+        "x-cinnamon",
+        "budgie",
+        "budgie-desktop",
+        "pantheon",
+        "pop",
+        "ubuntu",
+    ];
+    names
+        .iter()
+        .any(|name| gnome_family.contains(&name.as_str()))
+        .then_some(Desktop::Gnome)
+}
+
+#[derive(Debug)]
+struct LinuxSystemProxyBackend {
+    desktop: Option<Desktop>,
+    runner: Box<dyn CommandRunner>,
+    snapshot: Option<SystemProxySnapshot>,
+}
+
+impl LinuxSystemProxyBackend {
+    fn new(desktop: Option<Desktop>, runner: Box<dyn CommandRunner>) -> Self {
+        Self {
+            desktop,
+            runner,
+            snapshot: None,
+        }
+    }
+
+    fn desktop(&self) -> SystemProxyResult<Desktop> {
+        self.desktop.ok_or_else(|| {
+            SystemProxyError::new(format!(
+                "system proxy manager supports GNOME and KDE desktops only (XDG_CURRENT_DESKTOP=`{}`)",
+                std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default()
+            ))
+        })
+    }
+}
+
+impl SystemProxyBackend for LinuxSystemProxyBackend {
+    fn enable(&mut self, config: &SystemProxyConfig) -> SystemProxyResult<()> {
+        let desktop = self.desktop()?;
+        if self.snapshot.is_none() {
+            self.snapshot = Some(capture_snapshot(self.runner.as_ref(), desktop)?);
+        }
+
+        if let Err(error) = apply_proxy(self.runner.as_ref(), desktop, config) {
+            if let Some(snapshot) = self.snapshot.as_ref() {
+                let _ = restore_snapshot(self.runner.as_ref(), desktop, snapshot);
+            }
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    fn disable(&mut self) -> SystemProxyResult<()> {
+        let desktop = self.desktop()?;
+        disable_proxy(self.runner.as_ref(), desktop)?;
+        self.snapshot = None;
+        Ok(())
+    }
+
+    fn status(&self) -> SystemProxyResult<SystemProxyStatus> {
+        read_status(self.runner.as_ref(), self.desktop()?)
+    }
+
+    fn snapshot(&self) -> Option<String> {
+        serde_json::to_string(self.snapshot.as_ref()?).ok()
+    }
+
+    fn restore(&mut self, snapshot: &str) -> SystemProxyResult<()> {
+        let desktop = self.desktop()?;
+        let snapshot = serde_json::from_str::<SystemProxySnapshot>(snapshot)
+            .map_err(|error| SystemProxyError::new(format!("invalid snapshot: {error}")))?;
+        restore_snapshot(self.runner.as_ref(), desktop, &snapshot)
+    }
+}
+
+/// Previous values, as `(key, value)` pairs in the form the desktop's tools
+/// print and accept back.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SystemProxySnapshot {
+    values: Vec<(String, String)>,
+}
+
+/// GNOME keys as `(schema, key)`, the mode last so it is restored after the
+/// addresses it points at.
+const GNOME_KEYS: [(&str, &str); 9] = [
+    ("org.gnome.system.proxy.http", "host"),
+    ("org.gnome.system.proxy.http", "port"),
+    ("org.gnome.system.proxy.https", "host"),
+    ("org.gnome.system.proxy.https", "port"),
+    ("org.gnome.system.proxy.socks", "host"),
+    ("org.gnome.system.proxy.socks", "port"),
+    (GNOME_SCHEMA, "ignore-hosts"),
+    (GNOME_SCHEMA, "autoconfig-url"),
+    (GNOME_SCHEMA, "mode"),
+];
+
+const KDE_KEYS: [&str; 6] = [
+    "httpProxy",
+    "httpsProxy",
+    "socksProxy",
+    "NoProxyFor",
+    "Proxy Config Script",
+    "ProxyType",
+];
+
+fn capture_snapshot(
+    runner: &dyn CommandRunner,
+    desktop: Desktop,
+) -> SystemProxyResult<SystemProxySnapshot> {
+    let mut values = Vec::new();
+    match desktop {
+        Desktop::Gnome => {
+            for (schema, key) in GNOME_KEYS {
+                let value = runner.run("gsettings", &["get", schema, key])?;
+                values.push((format!("{schema} {key}"), value.trim().to_string()));
+            }
+        }
+        Desktop::Kde { version } => {
+            for key in KDE_KEYS {
+                // A key missing from the file reads as empty.
+                let value = runner
+                    .run(
+                        &kde_tool("kreadconfig", version),
+                        &["--file", KDE_FILE, "--group", KDE_GROUP, "--key", key],
+                    )
+                    .unwrap_or_default();
+                values.push((key.to_string(), value.trim().to_string()));
+            }
+        }
+    }
+    Ok(SystemProxySnapshot { values })
+}
+
+fn restore_snapshot(
+    runner: &dyn CommandRunner,
+    desktop: Desktop,
+    snapshot: &SystemProxySnapshot,
+) -> SystemProxyResult<()> {
+    match desktop {
+        Desktop::Gnome => {
+            for (schema_key, value) in &snapshot.values {
+                let Some((schema, key)) = schema_key.split_once(' ') else {
+                    continue;
+                };
+                gsettings_set(runner, schema, key, value)?;
+            }
+        }
+        Desktop::Kde { version } => {
+            for (key, value) in &snapshot.values {
+                kde_write(runner, version, key, value)?;
+            }
+            notify_kde(runner);
+        }
+    }
+    Ok(())
+}
+
+fn apply_proxy(
+    runner: &dyn CommandRunner,
+    desktop: Desktop,
+    config: &SystemProxyConfig,
+) -> SystemProxyResult<()> {
+    match desktop {
+        Desktop::Gnome => {
+            let host = gvariant_string(&config.host);
+            let ignore_hosts = format!(
+                "[{}]",
+                config
+                    .bypass
+                    .iter()
+                    .map(|entry| gvariant_string(entry))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            let autoconfig_url = gvariant_string(config.pac_url.as_deref().unwrap_or_default());
+            let mode = if config.pac_url.is_some() {
+                "'auto'"
+            } else {
+                "'manual'"
+            };
+            for (schema, key) in GNOME_KEYS {
+                let value = match (schema, key) {
+                    (_, "host") => host.clone(),
+                    ("org.gnome.system.proxy.http", "port") => config.http_port.to_string(),
+                    ("org.gnome.system.proxy.https", "port") => config.https_port.to_string(),
+                    (_, "port") => config.socks_port.to_string(),
+                    (_, "ignore-hosts") => ignore_hosts.clone(),
+                    (_, "autoconfig-url") => autoconfig_url.clone(),
+                    _ => mode.to_string(),
+                };
+                gsettings_set(runner, schema, key, &value)?;
+            }
+        }
+        Desktop::Kde { version } => {
+            // kioslaverc writes a proxy as `scheme://host port`.
+            let host = &config.host;
+            let http = format!("http://{host} {}", config.http_port);
+            let https = format!("http://{host} {}", config.https_port);
+            let socks = format!("socks://{host} {}", config.socks_port);
+            kde_write(runner, version, "httpProxy", &http)?;
+            kde_write(runner, version, "httpsProxy", &https)?;
+            kde_write(runner, version, "socksProxy", &socks)?;
+            kde_write(runner, version, "NoProxyFor", &config.bypass.join(","))?;
+            let script = config.pac_url.as_deref().unwrap_or_default();
+            kde_write(runner, version, "Proxy Config Script", script)?;
+            let proxy_type = if config.pac_url.is_some() {
+                KDE_PROXY_PAC
+            } else {
+                KDE_PROXY_MANUAL
+            };
+            kde_write(runner, version, "ProxyType", proxy_type)?;
+            notify_kde(runner);
+        }
+    }
+    Ok(())
+}
+
+fn read_status(
+    runner: &dyn CommandRunner,
+    desktop: Desktop,
+) -> SystemProxyResult<SystemProxyStatus> {
+    let mut status = SystemProxyStatus::default();
+    match desktop {
+        Desktop::Gnome => {
+            let get = |schema: &str, key: &str| -> SystemProxyResult<String> {
+                let value = runner.run("gsettings", &["get", schema, key])?;
+                Ok(gvariant_unquote(value.trim()))
+            };
+            match get(GNOME_SCHEMA, "mode")?.as_str() {
+                "manual" => {
+                    let endpoint = |protocol: &str| -> SystemProxyResult<Option<ProxyEndpoint>> {
+                        let schema = format!("{GNOME_SCHEMA}.{protocol}");
+                        let host = get(&schema, "host")?;
+                        let port = get(&schema, "port")?.parse::<u16>().unwrap_or(0);
+                        Ok((!host.is_empty() && port > 0).then(|| ProxyEndpoint::new(host, port)))
+                    };
+                    status.http = endpoint("http")?;
+                    status.https = endpoint("https")?;
+                    status.socks = endpoint("socks")?;
+                }
+                "auto" => {
+                    status.pac_url = Some(get(GNOME_SCHEMA, "autoconfig-url")?);
+                }
+                _ => {}
+            }
+        }
+        Desktop::Kde { version } => {
+            let read = |key: &str| {
+                runner
+                    .run(
+                        &kde_tool("kreadconfig", version),
+                        &["--file", KDE_FILE, "--group", KDE_GROUP, "--key", key],
+                    )
+                    .map(|value| value.trim().to_string())
+            };
+            match read("ProxyType")?.as_str() {
+                KDE_PROXY_MANUAL => {
+                    status.http = kde_endpoint(&read("httpProxy")?);
+                    status.https = kde_endpoint(&read("httpsProxy")?);
+                    status.socks = kde_endpoint(&read("socksProxy")?);
+                }
+                KDE_PROXY_PAC => status.pac_url = Some(read("Proxy Config Script")?),
+                _ => {}
+            }
+        }
+    }
+    Ok(status)
+}
+
+/// Parses a `kioslaverc` proxy, `scheme://host port`.
+fn kde_endpoint(value: &str) -> Option<ProxyEndpoint> {
+    let value = value.split_once("://").map_or(value, |(_, rest)| rest);
+    let (host, port) = value.trim().rsplit_once(' ')?;
+    Some(ProxyEndpoint::new(host.trim(), port.parse().ok()?))
+}
+
+/// A GVariant string as `gsettings get` prints it, without the quotes;
+/// numbers come back unchanged.
+fn gvariant_unquote(value: &str) -> String {
+    value
+        .strip_prefix('\'')
+        .and_then(|value| value.strip_suffix('\''))
+        .map(|value| value.replace("\\'", "'").replace("\\\\", "\\"))
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// `value` quoted as a GVariant string for `gsettings set`.
+fn gvariant_string(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn disable_proxy(runner: &dyn CommandRunner, desktop: Desktop) -> SystemProxyResult<()> {
+    match desktop {
+        Desktop::Gnome => gsettings_set(runner, GNOME_SCHEMA, "mode", "'none'"),
+        Desktop::Kde { version } => {
+            kde_write(runner, version, "ProxyType", KDE_PROXY_NONE)?;
+            notify_kde(runner);
+            Ok(())
+        }
+    }
+}
+
+fn gsettings_set(
+    runner: &dyn CommandRunner,
+    schema: &str,
+    key: &str,
+    value: &str,
+) -> SystemProxyResult<()> {
+    let _ = runner.run("gsettings", &["set", schema, key, value])?;
+    Ok(())
+}
+
+fn kde_write(
+    runner: &dyn CommandRunner,
+    version: u8,
+    key: &str,
+    value: &str,
+) -> SystemProxyResult<()> {
+    let _ = runner.run(
+        &kde_tool("kwriteconfig", version),
+        &[
+            "--file", KDE_FILE, "--group", KDE_GROUP, "--key", key, value,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Asks running KDE applications to reread `kioslaverc`. Applications started
+/// later read it anyway, so a failure is ignored.
+fn notify_kde(runner: &dyn CommandRunner) {
+    let _ = runner.run(
+        "dbus-send",
+        &[
+            "--type=signal",
+            "/KIO/Scheduler",
+            "org.kde.KIO.Scheduler.reparseSlaveConfiguration",
+            "string:",
+        ],
+    );
+}
+
+fn kde_tool(name: &str, version: u8) -> String {
+    format!("{name}{version}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Default)]
+    struct FakeRunner {
+        outputs: HashMap<String, String>,
+        /// Command lines whose run fails.
+        failing: Vec<String>,
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl CommandRunner for FakeRunner {
+        fn run(&self, program: &str, args: &[&str]) -> SystemProxyResult<String> {
+            let line = format!("{program} {}", args.join(" "));
+            self.calls.lock().unwrap().push(line.clone());
+            if self.failing.contains(&line) {
+                return Err(SystemProxyError::new(format!("{line} failed")));
+            }
+            Ok(self.outputs.get(&line).cloned().unwrap_or_default())
+        }
+    }
+
+    fn backend(desktop: Desktop, runner: FakeRunner) -> LinuxSystemProxyBackend {
+        LinuxSystemProxyBackend::new(Some(desktop), Box::new(runner))
+    }
+
+    #[test]
+    fn detects_desktops() {
+        assert_eq!(
+            detect_desktop(Some("ubuntu:GNOME"), None),
+            Some(Desktop::Gnome)
+        );
+        assert_eq!(
+            detect_desktop(Some("X-Cinnamon"), None),
+            Some(Desktop::Gnome)
+        );
+        assert_eq!(
+            detect_desktop(Some("KDE"), Some("6")),
+            Some(Desktop::Kde { version: 6 })
+        );
+        assert_eq!(
+            detect_desktop(Some("KDE"), None),
+            Some(Desktop::Kde { version: 5 })
+        );
+        assert_eq!(detect_desktop(Some("sway"), None), None);
+        assert_eq!(detect_desktop(None, None), None);
+
+        let mut unsupported = LinuxSystemProxyBackend::new(None, Box::new(FakeRunner::default()));
+        let config = SystemProxyConfig::new("127.0.0.1", 7890);
+        assert!(unsupported.enable(&config).is_err());
+        assert!(unsupported.disable().is_err());
+    }
+
+    #[test]
+    fn sets_gnome_proxy_and_restores_on_failure() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut outputs = HashMap::new();
+        outputs.insert(
+            "gsettings get org.gnome.system.proxy mode".to_string(),
+            "'auto'\n".to_string(),
+        );
+        outputs.insert(
+            "gsettings get org.gnome.system.proxy.http host".to_string(),
+            "'proxy.corp'\n".to_string(),
+        );
+        let runner = FakeRunner {
+            outputs,
+            failing: vec!["gsettings set org.gnome.system.proxy mode 'manual'".to_string()],
+            calls: calls.clone(),
+        };
+        let mut backend = backend(Desktop::Gnome, runner);
+        let config = SystemProxyConfig::new("127.0.0.1", 7890)
+            .with_bypass(vec!["localhost".to_string(), "10.0.0.0/8".to_string()]);
+        assert!(backend.enable(&config).is_err());
+
+        let calls = calls.lock().unwrap().clone();
+        assert!(
+            calls.contains(
+                &"gsettings set org.gnome.system.proxy.http host '127.0.0.1'".to_string()
+            )
+        );
+        assert!(
+            calls.contains(
+                &"gsettings set org.gnome.system.proxy ignore-hosts ['localhost', '10.0.0.0/8']"
+                    .to_string()
+            )
+        );
+        let restored = calls
+            .iter()
+            .position(|call| call == "gsettings set org.gnome.system.proxy.http host 'proxy.corp'")
+            .expect("http host restored");
+        let mode = calls
+            .iter()
+            .position(|call| call == "gsettings set org.gnome.system.proxy mode 'auto'")
+            .expect("mode restored");
+        assert!(restored < mode);
+    }
+
+    #[test]
+    fn sets_and_clears_kde_proxy() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let runner = FakeRunner {
+            calls: calls.clone(),
+            ..FakeRunner::default()
+        };
+        let mut backend = backend(Desktop::Kde { version: 6 }, runner);
+        let config = SystemProxyConfig {
+            socks_port: 7891,
+            ..SystemProxyConfig::new("127.0.0.1", 7890)
+        }
+        .with_bypass(vec!["localhost".to_string(), ".corp.example".to_string()]);
+        backend.enable(&config).expect("enable");
+        backend.disable().expect("disable");
+
+        let calls = calls.lock().unwrap().clone();
+        let write = "kwriteconfig6 --file kioslaverc --group Proxy Settings --key";
+        assert!(calls.contains(&format!("{write} httpProxy http://127.0.0.1 7890")));
+        assert!(calls.contains(&format!("{write} socksProxy socks://127.0.0.1 7891")));
+        assert!(calls.contains(&format!("{write} NoProxyFor localhost,.corp.example")));
+        assert!(calls.contains(&format!("{write} ProxyType 1")));
+        assert_eq!(
+            calls.last().map(String::as_str),
+            Some(
+                "dbus-send --type=signal /KIO/Scheduler org.kde.KIO.Scheduler.reparseSlaveConfiguration string:"
+            )
+        );
+        assert!(calls.contains(&format!("{write} ProxyType 0")));
+        assert!(backend.snapshot.is_none());
+    }
+
+    #[test]
+    fn reads_status_back() {
+        let mut outputs = HashMap::new();
+        for (key, value) in [
+            ("org.gnome.system.proxy mode", "'manual'"),
+            ("org.gnome.system.proxy.http host", "'127.0.0.1'"),
+            ("org.gnome.system.proxy.http port", "7890"),
+            ("org.gnome.system.proxy.https host", "'127.0.0.1'"),
+            ("org.gnome.system.proxy.https port", "7890"),
+            ("org.gnome.system.proxy.socks host", "'127.0.0.1'"),
+            ("org.gnome.system.proxy.socks port", "7891"),
+        ] {
+            outputs.insert(format!("gsettings get {key}"), format!("{value}\n"));
+        }
+        let runner = FakeRunner {
+            outputs,
+            ..FakeRunner::default()
+        };
+        let status = backend(Desktop::Gnome, runner).status().expect("status");
+        let config = SystemProxyConfig {
+            socks_port: 7891,
+            ..SystemProxyConfig::new("127.0.0.1", 7890)
+        };
+        assert!(status.matches(&config));
+        assert!(!status.matches(&SystemProxyConfig::new("127.0.0.1", 7890)));
+
+        let mut outputs = HashMap::new();
+        let read = "kreadconfig6 --file kioslaverc --group Proxy Settings --key";
+        outputs.insert(format!("{read} ProxyType"), "2\n".to_string());
+        outputs.insert(
+            format!("{read} Proxy Config Script"),
+            "http://127.0.0.1:7899/proxy.pac?v=3\n".to_string(),
+        );
+        let runner = FakeRunner {
+            outputs,
+            ..FakeRunner::default()
+        };
+        let status = backend(Desktop::Kde { version: 6 }, runner)
+            .status()
+            .expect("status");
+        assert_eq!(
+            status.pac_url.as_deref(),
+            Some("http://127.0.0.1:7899/proxy.pac?v=3")
+        );
+        assert_eq!(status.http, None);
+    }
+
+    #[test]
+    fn recovers_snapshot_left_by_earlier_process() {
+        let path = std::env::temp_dir().join(format!(
+            "linkpad-proxy-snapshot-{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut outputs = HashMap::new();
+        outputs.insert(
+            "gsettings get org.gnome.system.proxy mode".to_string(),
+            "'auto'\n".to_string(),
+        );
+        outputs.insert(
+            "gsettings get org.gnome.system.proxy.http host".to_string(),
+            "'proxy.corp'\n".to_string(),
+        );
+        let runner = FakeRunner {
+            outputs,
+            ..FakeRunner::default()
+        };
+        let mut manager =
+            crate::SystemProxyManager::new_with_backend(Box::new(backend(Desktop::Gnome, runner)));
+        manager.set_snapshot_path(Some(path.clone()));
+        manager.enable("127.0.0.1", 7890).expect("enable");
+        assert!(path.exists());
+        // The process ends here without disabling the proxy.
+        drop(manager);
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let runner = FakeRunner {
+            calls: calls.clone(),
+            ..FakeRunner::default()
+        };
+        let mut manager =
+            crate::SystemProxyManager::new_with_backend(Box::new(backend(Desktop::Gnome, runner)));
+        manager.set_snapshot_path(Some(path.clone()));
+        assert!(manager.recover().expect("recover"));
+        assert!(!path.exists());
+        let calls = calls.lock().unwrap().clone();
+        assert!(
+            calls.contains(
+                &"gsettings set org.gnome.system.proxy.http host 'proxy.corp'".to_string()
+            )
+        );
+        assert_eq!(
+            calls.last().map(String::as_str),
+            Some("gsettings set org.gnome.system.proxy mode 'auto'")
+        );
+        assert!(!manager.recover().expect("nothing left to recover"));
+    }
+
+    #[test]
+    fn points_desktops_at_pac_url() {
+        let url = "http://127.0.0.1:7899/proxy.pac?v=1";
+        let config = SystemProxyConfig::new("127.0.0.1", 7890).with_pac_url(Some(url.to_string()));
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let runner = FakeRunner {
+            calls: calls.clone(),
+            ..FakeRunner::default()
+        };
+        backend(Desktop::Gnome, runner)
+            .enable(&config)
+            .expect("enable gnome");
+        let calls = calls.lock().unwrap().clone();
+        assert!(calls.contains(&format!(
+            "gsettings set org.gnome.system.proxy autoconfig-url '{url}'"
+        )));
+        assert_eq!(
+            calls.last().map(String::as_str),
+            Some("gsettings set org.gnome.system.proxy mode 'auto'")
+        );
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let runner = FakeRunner {
+            calls: calls.clone(),
+            ..FakeRunner::default()
+        };
+        backend(Desktop::Kde { version: 5 }, runner)
+            .enable(&config)
+            .expect("enable kde");
+        let calls = calls.lock().unwrap().clone();
+        let write = "kwriteconfig5 --file kioslaverc --group Proxy Settings --key";
+        assert!(calls.contains(&format!("{write} Proxy Config Script {url}")));
+        assert!(calls.contains(&format!("{write} ProxyType 2")));
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{SystemProxyBackend, SystemProxyConfig, SystemProxyError, SystemProxyResult};
+use crate::{
+    ProxyEndpoint, SystemProxyBackend, SystemProxyConfig, SystemProxyError, SystemProxyResult,
+    SystemProxyStatus,
+};
+use serde::{Deserialize, Serialize};
 
 pub(crate) fn create_backend() -> Box<dyn SystemProxyBackend> {
     Box::new(MacosSystemProxyBackend::default())
@@ -15,7 +19,7 @@ impl SystemProxyBackend for MacosSystemProxyBackend {
             self.snapshot = Some(capture_snapshot()?);
         }
 
-        if let Err(error) = apply_proxy_for_all_services(&config.host, config.port) {
+        if let Err(error) = apply_proxy_for_all_services(config) {
             if let Some(snapshot) = self.snapshot.as_ref() {
                 let _ = restore_snapshot(snapshot);
             }
@@ -29,22 +33,61 @@ impl SystemProxyBackend for MacosSystemProxyBackend {
         self.snapshot = None;
         Ok(())
     }
+
+    /// networksetup keeps settings per service; the first active service in
+    /// the service order stands for the system.
+    fn status(&self) -> SystemProxyResult<SystemProxyStatus> {
+        let Some(service) = list_active_services()?.into_iter().next() else {
+            return Ok(SystemProxyStatus::default());
+        };
+        let endpoint = |protocol| -> SystemProxyResult<Option<ProxyEndpoint>> {
+            let state = get_proxy_state(&service, protocol)?;
+            Ok(state
+                .enabled
+                .then(|| ProxyEndpoint::new(state.server, state.port)))
+        };
+        let auto_proxy = get_auto_proxy_state(&service)?;
+        Ok(SystemProxyStatus {
+            http: endpoint(ProxyProtocol::Web)?,
+            https: endpoint(ProxyProtocol::SecureWeb)?,
+            socks: endpoint(ProxyProtocol::Socks)?,
+            pac_url: auto_proxy.enabled.then_some(auto_proxy.url),
+        })
+    }
+
+    fn snapshot(&self) -> Option<String> {
+        serde_json::to_string(self.snapshot.as_ref()?).ok()
+    }
+
+    fn restore(&mut self, snapshot: &str) -> SystemProxyResult<()> {
+        let snapshot = serde_json::from_str::<SystemProxySnapshot>(snapshot)
+            .map_err(|error| SystemProxyError::new(format!("invalid snapshot: {error}")))?;
+        restore_snapshot(&snapshot)
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct SystemProxySnapshot {
     services: Vec<ServiceProxyState>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct ServiceProxyState {
     name: String,
     web: ProxyState,
     secure_web: ProxyState,
     socks: ProxyState,
+    bypass: Vec<String>,
+    auto_proxy: AutoProxyState,
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct AutoProxyState {
+    enabled: bool,
+    url: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 struct ProxyState {
     enabled: bool,
     server: String,
@@ -82,6 +125,14 @@ impl ProxyProtocol {
             Self::Socks => "-setsocksfirewallproxystate",
         }
     }
+
+    fn port(self, config: &SystemProxyConfig) -> u16 {
+        match self {
+            Self::Web => config.http_port,
+            Self::SecureWeb => config.https_port,
+            Self::Socks => config.socks_port,
+        }
+    }
 }
 
 fn capture_snapshot() -> SystemProxyResult<SystemProxySnapshot> {
@@ -92,6 +143,8 @@ fn capture_snapshot() -> SystemProxyResult<SystemProxySnapshot> {
             web: get_proxy_state(&service, ProxyProtocol::Web)?,
             secure_web: get_proxy_state(&service, ProxyProtocol::SecureWeb)?,
             socks: get_proxy_state(&service, ProxyProtocol::Socks)?,
+            bypass: get_bypass_domains(&service)?,
+            auto_proxy: get_auto_proxy_state(&service)?,
         });
     }
     Ok(SystemProxySnapshot { services })
@@ -102,6 +155,13 @@ fn restore_snapshot(snapshot: &SystemProxySnapshot) -> SystemProxyResult<()> {
         restore_protocol_state(&service.name, ProxyProtocol::Web, &service.web)?;
         restore_protocol_state(&service.name, ProxyProtocol::SecureWeb, &service.secure_web)?;
         restore_protocol_state(&service.name, ProxyProtocol::Socks, &service.socks)?;
+        set_bypass_domains(&service.name, &service.bypass)?;
+        if service.auto_proxy.enabled && !service.auto_proxy.url.is_empty() {
+            set_auto_proxy_url(&service.name, &service.auto_proxy.url)?;
+            set_auto_proxy_state(&service.name, true)?;
+        } else {
+            set_auto_proxy_state(&service.name, false)?;
+        }
     }
     Ok(())
 }
@@ -120,15 +180,26 @@ fn restore_protocol_state(
     Ok(())
 }
 
-fn apply_proxy_for_all_services(host: &str, port: u16) -> SystemProxyResult<()> {
+fn apply_proxy_for_all_services(config: &SystemProxyConfig) -> SystemProxyResult<()> {
     for service in list_active_services()? {
+        // A PAC URL replaces the manual proxies, which would otherwise win.
         for protocol in [
             ProxyProtocol::Web,
             ProxyProtocol::SecureWeb,
             ProxyProtocol::Socks,
         ] {
-            set_proxy(&service, protocol, host, port)?;
-            set_proxy_state(&service, protocol, true)?;
+            if config.pac_url.is_none() {
+                set_proxy(&service, protocol, &config.host, protocol.port(config))?;
+            }
+            set_proxy_state(&service, protocol, config.pac_url.is_none())?;
+        }
+        set_bypass_domains(&service, &config.bypass)?;
+        match &config.pac_url {
+            Some(url) => {
+                set_auto_proxy_url(&service, url)?;
+                set_auto_proxy_state(&service, true)?;
+            }
+            None => set_auto_proxy_state(&service, false)?,
         }
     }
     Ok(())
@@ -143,6 +214,7 @@ fn disable_proxy_for_all_services() -> SystemProxyResult<()> {
         ] {
             set_proxy_state(&service, protocol, false)?;
         }
+        set_auto_proxy_state(&service, false)?;
     }
     Ok(())
 }
@@ -181,6 +253,64 @@ fn get_proxy_state(service: &str, protocol: ProxyProtocol) -> SystemProxyResult<
     Ok(state)
 }
 
+/// Bypass entries of `service`; networksetup prints a sentence instead when
+/// there are none.
+fn get_bypass_domains(service: &str) -> SystemProxyResult<Vec<String>> {
+    let output = run_networksetup(["-getproxybypassdomains", service])?;
+    if output.contains("There aren't any bypass domains") {
+        return Ok(Vec::new());
+    }
+    Ok(output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+fn set_bypass_domains(service: &str, bypass: &[String]) -> SystemProxyResult<()> {
+    let mut args = vec!["-setproxybypassdomains", service];
+    if bypass.is_empty() {
+        // networksetup clears the list when given the literal `Empty`.
+        args.push("Empty");
+    } else {
+        args.extend(bypass.iter().map(String::as_str));
+    }
+    let _ = run_networksetup(args)?;
+    Ok(())
+}
+
+/// PAC state of `service`; networksetup prints `(null)` for a URL never set.
+fn get_auto_proxy_state(service: &str) -> SystemProxyResult<AutoProxyState> {
+    let output = run_networksetup(["-getautoproxyurl", service])?;
+    let mut state = AutoProxyState::default();
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(value) = line.strip_prefix("Enabled:") {
+            state.enabled = value.trim().eq_ignore_ascii_case("Yes");
+            continue;
+        }
+        if let Some(value) = line.strip_prefix("URL:") {
+            let value = value.trim();
+            if value != "(null)" {
+                state.url = value.to_string();
+            }
+        }
+    }
+    Ok(state)
+}
+
+fn set_auto_proxy_url(service: &str, url: &str) -> SystemProxyResult<()> {
+    let _ = run_networksetup(["-setautoproxyurl", service, url])?;
+    Ok(())
+}
+
+fn set_auto_proxy_state(service: &str, enabled: bool) -> SystemProxyResult<()> {
+    let state = if enabled { "on" } else { "off" };
+    let _ = run_networksetup(["-setautoproxystate", service, state])?;
+    Ok(())
+}
+
 fn set_proxy(
     service: &str,
     protocol: ProxyProtocol,
@@ -0,0 +1,453 @@
+//! Proxy variables for terminal tools. curl, git, cargo and npm ignore the
+//! desktop proxy settings but read `http_proxy` and friends, so this backend
+//! keeps env files that shell rc files source, see [`install_shell_hooks`].
+
+use crate::{
+    ProxyEndpoint, SystemProxyBackend, SystemProxyConfig, SystemProxyError, SystemProxyResult,
+    SystemProxyStatus,
+};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const HOOK_BEGIN: &str = "# >>> linkpad proxy >>>";
+const HOOK_END: &str = "# <<< linkpad proxy <<<";
+const HEADER: &str = "# Generated by Linkpad; rewritten whenever the system proxy changes.";
+
+/// Shell language an env file or command is written in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ShellSyntax {
+    /// bash, zsh and other POSIX shells.
+    Posix,
+    Fish,
+    PowerShell,
+}
+
+impl ShellSyntax {
+    pub const ALL: [ShellSyntax; 3] = [Self::Posix, Self::Fish, Self::PowerShell];
+
+    /// The syntax of the shell a terminal on this machine most likely runs:
+    /// PowerShell on Windows, otherwise fish when it is the login shell.
+    pub fn current() -> Self {
+        if cfg!(target_os = "windows") {
+            return Self::PowerShell;
+        }
+        match std::env::var("SHELL") {
+            Ok(shell) if shell.ends_with("/fish") => Self::Fish,
+            _ => Self::Posix,
+        }
+    }
+
+    /// Name of the env file for this syntax.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Posix => "proxy.sh",
+            Self::Fish => "proxy.fish",
+            Self::PowerShell => "proxy.ps1",
+        }
+    }
+
+    /// Lines that point a shell at `config`'s endpoints. A PAC URL is
+    /// ignored, since terminal tools cannot evaluate scripts.
+    pub fn export_commands(self, config: &SystemProxyConfig) -> String {
+        proxy_variables(config)
+            .iter()
+            .flat_map(|(name, value)| {
+                [name.to_string(), name.to_uppercase()].map(|name| self.assign(&name, value))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn assign(self, name: &str, value: &str) -> String {
+        let value = self.quote(value);
+        match self {
+            Self::Posix => format!("export {name}={value}"),
+            Self::Fish => format!("set -gx {name} {value}"),
+            Self::PowerShell => format!("$env:{name} = {value}"),
+        }
+    }
+
+    /// Line that sources `file` in an rc file, when it exists.
+    fn hook_line(self, file: &Path) -> String {
+        let file = self.quote(&file.to_string_lossy());
+        match self {
+            Self::Posix => format!("[ -f {file} ] && . {file}"),
+            Self::Fish => format!("test -f {file}; and source {file}"),
+            Self::PowerShell => format!("if (Test-Path {file}) {{ . {file} }}"),
+        }
+    }
+
+    /// `value` as a literal string. All three syntaxes take single quotes
+    /// literally, but differ in how a quote inside is written: POSIX shells
+    /// close the string around an escaped one, fish escapes it and the
+    /// backslash with a backslash, and PowerShell doubles it, treating the
+    /// typographic single quotes the same way.
+    fn quote(self, value: &str) -> String {
+        let mut quoted = String::with_capacity(value.len() + 2);
+        quoted.push('\'');
+        for ch in value.chars() {
+            match (self, ch) {
+                (Self::Posix, '\'') => quoted.push_str("'\\''"),
+                (Self::Fish, '\'' | '\\') => {
+                    quoted.push('\\');
+                    quoted.push(ch);
+                }
+                (Self::PowerShell, '\'' | '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}') => {
+                    quoted.push(ch);
+                    quoted.push(ch);
+                }
+                _ => quoted.push(ch),
+            }
+        }
+        quoted.push('\'');
+        quoted
+    }
+
+    /// Startup files of this syntax's shells under `home`, with the name of
+    /// the shell that reads each.
+    fn rc_files(self, home: &Path) -> Vec<(&'static str, PathBuf)> {
+        match self {
+            Self::Posix => vec![("bash", home.join(".bashrc")), ("zsh", home.join(".zshrc"))],
+            Self::Fish => vec![("fish", home.join(".config/fish/config.fish"))],
+            Self::PowerShell if cfg!(target_os = "windows") => {
+                let documents = dirs::document_dir().unwrap_or_else(|| home.join("Documents"));
+                vec![
+                    (
+                        "pwsh",
+                        documents.join("PowerShell/Microsoft.PowerShell_profile.ps1"),
+                    ),
+                    (
+                        "powershell",
+                        documents.join("WindowsPowerShell/Microsoft.PowerShell_profile.ps1"),
+                    ),
+                ]
+            }
+            Self::PowerShell => vec![(
+                "pwsh",
+                home.join(".config/powershell/Microsoft.PowerShell_profile.ps1"),
+            )],
+        }
+    }
+}
+
+/// Lower-case variable names and values for `config`. curl matches
+/// `no_proxy` entries as domain suffixes, so `*.` wildcards lose the `*`.
+fn proxy_variables(config: &SystemProxyConfig) -> Vec<(&'static str, String)> {
+    let host = &config.host;
+    let mut variables = vec![
+        ("http_proxy", format!("http://{host}:{}", config.http_port)),
+        (
+            "https_proxy",
+            format!("http://{host}:{}", config.https_port),
+        ),
+        (
+            "all_proxy",
+            format!("socks5h://{host}:{}", config.socks_port),
+        ),
+    ];
+    if !config.bypass.is_empty() {
+        let no_proxy = config
+            .bypass
+            .iter()
+            .map(|entry| entry.strip_prefix('*').unwrap_or(entry))
+            .collect::<Vec<_>>()
+            .join(",");
+        variables.push(("no_proxy", no_proxy));
+    }
+    variables
+}
+
+pub(crate) fn create_backend(dir: PathBuf) -> Box<dyn SystemProxyBackend> {
+    Box::new(ShellEnvBackend {
+        dir,
+        enabled: false,
+    })
+}
+
+/// Writes an env file per [`ShellSyntax`] into its directory while enabled
+/// and removes them on disable, so new shells start without the proxy.
+#[derive(Debug)]
+struct ShellEnvBackend {
+    dir: PathBuf,
+    enabled: bool,
+}
+
+impl ShellEnvBackend {
+    fn remove_files(&self) -> SystemProxyResult<()> {
+        for syntax in ShellSyntax::ALL {
+            let path = self.dir.join(syntax.file_name());
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(io_error("remove", &path, error)),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SystemProxyBackend for ShellEnvBackend {
+    fn enable(&mut self, config: &SystemProxyConfig) -> SystemProxyResult<()> {
+        fs::create_dir_all(&self.dir).map_err(|error| io_error("create", &self.dir, error))?;
+        for syntax in ShellSyntax::ALL {
+            let path = self.dir.join(syntax.file_name());
+            let contents = format!("{HEADER}\n{}\n", syntax.export_commands(config));
+            fs::write(&path, contents).map_err(|error| io_error("write", &path, error))?;
+        }
+        self.enabled = true;
+        Ok(())
+    }
+
+    fn disable(&mut self) -> SystemProxyResult<()> {
+        self.remove_files()?;
+        self.enabled = false;
+        Ok(())
+    }
+
+    /// Reads the endpoints back from the POSIX file.
+    fn status(&self) -> SystemProxyResult<SystemProxyStatus> {
+        let path = self.dir.join(ShellSyntax::Posix.file_name());
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(SystemProxyStatus::default());
+            }
+            Err(error) => return Err(io_error("read", &path, error)),
+        };
+        let variable = |name: &str| {
+            let prefix = format!("export {name}=");
+            contents.lines().find_map(|line| {
+                let value = line.strip_prefix(&prefix)?.trim_matches('\'');
+                let (_, address) = value.split_once("://")?;
+                let (host, port) = address.rsplit_once(':')?;
+                Some(ProxyEndpoint::new(host, port.parse().ok()?))
+            })
+        };
+        Ok(SystemProxyStatus {
+            http: variable("http_proxy"),
+            https: variable("https_proxy"),
+            socks: variable("all_proxy"),
+            pac_url: None,
+        })
+    }
+
+    /// There is nothing to put back beyond removing the files, so the
+    /// snapshot is empty; it only marks that they were written.
+    fn snapshot(&self) -> Option<String> {
+        self.enabled.then(String::new)
+    }
+
+    fn restore(&mut self, _snapshot: &str) -> SystemProxyResult<()> {
+        self.disable()
+    }
+}
+
+/// Adds a block to the rc files of bash, zsh, fish and PowerShell that
+/// sources the env files in `dir`. A missing rc file is only created for the
+/// login shell, or when the shell's own config directory exists. Running it
+/// again rewrites the block in place. Returns the files changed.
+pub fn install_shell_hooks(dir: &Path) -> SystemProxyResult<Vec<PathBuf>> {
+    let home = home_dir()?;
+    let login_shell = std::env::var("SHELL").unwrap_or_default();
+    let mut changed = Vec::new();
+    for syntax in ShellSyntax::ALL {
+        let block = format!(
+            "{HOOK_BEGIN}\n{}\n{HOOK_END}\n",
+            syntax.hook_line(&dir.join(syntax.file_name()))
+        );
+        for (shell, rc_file) in syntax.rc_files(&home) {
+            let existing = read_rc_file(&rc_file)?;
+            let wanted = existing.is_some()
+                || login_shell.ends_with(&format!("/{shell}"))
+                || (syntax != ShellSyntax::Posix && rc_file.parent().is_some_and(Path::is_dir));
+            if !wanted {
+                continue;
+            }
+            let existing = existing.unwrap_or_default();
+            let mut contents = remove_hook(&existing);
+            if !contents.is_empty() && !contents.ends_with('\n') {
+                contents.push('\n');
+            }
+            contents.push_str(&block);
+            if contents != existing {
+                write_rc_file(&rc_file, &contents)?;
+                changed.push(rc_file);
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// Removes the blocks [`install_shell_hooks`] added. Returns the files
+/// changed.
+pub fn uninstall_shell_hooks() -> SystemProxyResult<Vec<PathBuf>> {
+    let home = home_dir()?;
+    let mut changed = Vec::new();
+    for syntax in ShellSyntax::ALL {
+        for (_, rc_file) in syntax.rc_files(&home) {
+            let Some(existing) = read_rc_file(&rc_file)? else {
+                continue;
+            };
+            let contents = remove_hook(&existing);
+            if contents != existing {
+                write_rc_file(&rc_file, &contents)?;
+                changed.push(rc_file);
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// Whether any rc file has the block from [`install_shell_hooks`].
+pub fn shell_hooks_installed() -> bool {
+    let Ok(home) = home_dir() else {
+        return false;
+    };
+    ShellSyntax::ALL
+        .iter()
+        .flat_map(|syntax| syntax.rc_files(&home))
+        .any(|(_, rc_file)| {
+            fs::read_to_string(rc_file).is_ok_and(|contents| contents.contains(HOOK_BEGIN))
+        })
+}
+
+/// `contents` without the marked block, including its line break.
+fn remove_hook(contents: &str) -> String {
+    let mut kept = String::with_capacity(contents.len());
+    let mut inside = false;
+    for line in contents.split_inclusive('\n') {
+        match line.trim_end() {
+            HOOK_BEGIN => inside = true,
+            HOOK_END if inside => inside = false,
+            _ if !inside => kept.push_str(line),
+            _ => {}
+        }
+    }
+    kept
+}
+
+fn home_dir() -> SystemProxyResult<PathBuf> {
+    dirs::home_dir().ok_or_else(|| SystemProxyError::new("failed to locate the home directory"))
+}
+
+fn read_rc_file(path: &Path) -> SystemProxyResult<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(io_error("read", path, error)),
+    }
+}
+
+fn write_rc_file(path: &Path, contents: &str) -> SystemProxyResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|error| io_error("create", parent, error))?;
+    }
+    fs::write(path, contents).map_err(|error| io_error("write", path, error))
+}
+
+fn io_error(action: &str, path: &Path, error: io::Error) -> SystemProxyError {
+    SystemProxyError::new(format!("failed to {action} {}: {error}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SystemProxyManager;
+
+    #[test]
+    fn writes_exports_for_each_shell() {
+        let config = SystemProxyConfig {
+            socks_port: 7891,
+            ..SystemProxyConfig::new("127.0.0.1", 7890)
+        }
+        .with_bypass(vec!["localhost".to_string(), "*.local".to_string()]);
+
+        let posix = ShellSyntax::Posix.export_commands(&config);
+        assert!(posix.contains("export http_proxy='http://127.0.0.1:7890'"));
+        assert!(posix.contains("export HTTPS_PROXY='http://127.0.0.1:7890'"));
+        assert!(posix.contains("export all_proxy='socks5h://127.0.0.1:7891'"));
+        assert!(posix.contains("export no_proxy='localhost,.local'"));
+        assert!(
+            ShellSyntax::Fish
+                .export_commands(&config)
+                .contains("set -gx http_proxy 'http://127.0.0.1:7890'")
+        );
+        assert!(
+            ShellSyntax::PowerShell
+                .export_commands(&config)
+                .contains("$env:http_proxy = 'http://127.0.0.1:7890'")
+        );
+    }
+
+    #[test]
+    fn escapes_quotes_for_each_shell() {
+        let value = r"it's C:\dir";
+        assert_eq!(ShellSyntax::Posix.quote(value), r"'it'\''s C:\dir'");
+        assert_eq!(ShellSyntax::Fish.quote(value), r"'it\'s C:\\dir'");
+        assert_eq!(ShellSyntax::PowerShell.quote(value), r"'it''s C:\dir'");
+        assert_eq!(
+            ShellSyntax::PowerShell.quote("it\u{2019}s"),
+            "'it\u{2019}\u{2019}s'"
+        );
+    }
+
+    /// A quote in a bypass entry or in the env dir must reach the shell as
+    /// part of the value instead of ending the string.
+    #[cfg(unix)]
+    #[test]
+    fn posix_shell_reads_values_with_quotes() {
+        let dir = std::env::temp_dir().join(format!("linkpad-shell-'env'-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut manager = SystemProxyManager::new_with_backend(create_backend(dir.clone()));
+        let config = SystemProxyConfig::new("127.0.0.1", 7890)
+            .with_bypass(vec!["o'brien.example".to_string(), "*.local".to_string()]);
+        manager.enable_with_config(&config).expect("enable");
+
+        let hook = ShellSyntax::Posix.hook_line(&dir.join(ShellSyntax::Posix.file_name()));
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(format!("{hook}; printf %s \"$no_proxy\""))
+            .output()
+            .expect("run sh");
+        assert!(output.status.success(), "{output:?}");
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "o'brien.example,.local"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn env_files_follow_the_proxy() {
+        let dir = std::env::temp_dir().join(format!("linkpad-shell-env-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut manager = SystemProxyManager::new_with_backend(create_backend(dir.clone()));
+        let config = SystemProxyConfig::new("127.0.0.1", 7890);
+
+        manager.enable_with_config(&config).expect("enable");
+        for syntax in ShellSyntax::ALL {
+            assert!(dir.join(syntax.file_name()).is_file());
+        }
+        assert!(manager.status().expect("status").matches(&config));
+
+        manager.disable().expect("disable");
+        assert!(!dir.join(ShellSyntax::Posix.file_name()).exists());
+        assert_eq!(
+            manager.status().expect("status"),
+            SystemProxyStatus::default()
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn replaces_hook_block_in_place() {
+        let contents =
+            format!("alias ll='ls -l'\n{HOOK_BEGIN}\nold\n{HOOK_END}\nexport EDITOR=vi\n");
+        assert_eq!(
+            remove_hook(&contents),
+            "alias ll='ls -l'\nexport EDITOR=vi\n"
+        );
+        assert_eq!(remove_hook("no hook\n"), "no hook\n");
+    }
+}
@@ -1,4 +1,6 @@
-use crate::{SystemProxyBackend, SystemProxyConfig, SystemProxyError, SystemProxyResult};
+use crate::{
+    SystemProxyBackend, SystemProxyConfig, SystemProxyError, SystemProxyResult, SystemProxyStatus,
+};
 
 pub(crate) fn create_backend() -> Box<dyn SystemProxyBackend> {
     Box::new(UnsupportedSystemProxyBackend)
@@ -9,16 +11,29 @@ struct UnsupportedSystemProxyBackend;
 
 impl SystemProxyBackend for UnsupportedSystemProxyBackend {
     fn enable(&mut self, _config: &SystemProxyConfig) -> SystemProxyResult<()> {
-        Err(SystemProxyError::new(format!(
-            "system proxy manager is not implemented for platform `{}`",
-            std::env::consts::OS
-        )))
+        Err(unsupported())
     }
 
     fn disable(&mut self) -> SystemProxyResult<()> {
-        Err(SystemProxyError::new(format!(
-            "system proxy manager is not implemented for platform `{}`",
-            std::env::consts::OS
-        )))
+        Err(unsupported())
     }
+
+    fn status(&self) -> SystemProxyResult<SystemProxyStatus> {
+        Err(unsupported())
+    }
+
+    fn snapshot(&self) -> Option<String> {
+        None
+    }
+
+    fn restore(&mut self, _snapshot: &str) -> SystemProxyResult<()> {
+        Err(unsupported())
+    }
+}
+
+fn unsupported() -> SystemProxyError {
+    SystemProxyError::new(format!(
+        "system proxy manager is not implemented for platform `{}`",
+        std::env::consts::OS
+    ))
 }
@@ -1,18 +1,78 @@
-use crate::{SystemProxyBackend, SystemProxyConfig, SystemProxyError, SystemProxyResult};
+use crate::wininet::{parse_proxy_server, proxy_override, proxy_server};
+use crate::{
+    SystemProxyBackend, SystemProxyConfig, SystemProxyError, SystemProxyResult, SystemProxyStatus,
+};
+use serde::{Deserialize, Serialize};
 
 const WINDOWS_PROXY_REG_PATH: &str =
     r"HKCU\Software\Microsoft\Windows\CurrentVersion\Internet Settings";
 
 pub(crate) fn create_backend() -> Box<dyn SystemProxyBackend> {
-    Box::new(WindowsSystemProxyBackend)
+    Box::new(WindowsSystemProxyBackend::default())
 }
 
+/// Registry values the backend writes, with their types.
+const REG_VALUES: [(&str, &str); 4] = [
+    ("ProxyEnable", "REG_DWORD"),
+    ("ProxyServer", "REG_SZ"),
+    ("ProxyOverride", "REG_SZ"),
+    ("AutoConfigURL", "REG_SZ"),
+];
+
 #[derive(Debug, Default)]
-struct WindowsSystemProxyBackend;
+struct WindowsSystemProxyBackend {
+    snapshot: Option<SystemProxySnapshot>,
+    /// Whether `AutoConfigURL` holds a URL written here, so only that one is
+    /// replaced again.
+    auto_config_url_set: bool,
+}
+
+/// Values of [`REG_VALUES`] before the first enable, `None` where a value
+/// did not exist; DWORDs in decimal as `reg add` takes them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SystemProxySnapshot {
+    values: Vec<(String, Option<String>)>,
+}
+
+impl WindowsSystemProxyBackend {
+    /// Puts back the PAC URL found before enabling, or removes ours.
+    fn clear_auto_config_url(&mut self) -> SystemProxyResult<()> {
+        if self.auto_config_url_set {
+            let previous = self.snapshot.as_ref().and_then(|snapshot| {
+                snapshot
+                    .values
+                    .iter()
+                    .find(|(name, _)| name == "AutoConfigURL")
+                    .and_then(|(_, value)| value.clone())
+            });
+            match previous {
+                Some(url) => set_value("AutoConfigURL", "REG_SZ", &url)?,
+                None => delete_value("AutoConfigURL")?,
+            }
+            self.auto_config_url_set = false;
+        }
+        Ok(())
+    }
+}
 
 impl SystemProxyBackend for WindowsSystemProxyBackend {
     fn enable(&mut self, config: &SystemProxyConfig) -> SystemProxyResult<()> {
-        let proxy_server = format!("{}:{}", config.host, config.port);
+        if self.snapshot.is_none() {
+            self.snapshot = Some(capture_snapshot());
+        }
+        let proxy_server = proxy_server(config);
+        let proxy_override = proxy_override(&config.bypass);
+        run_reg(&[
+            "add",
+            WINDOWS_PROXY_REG_PATH,
+            "/v",
+            "ProxyOverride",
+            "/t",
+            "REG_SZ",
+            "/d",
+            proxy_override.as_str(),
+            "/f",
+        ])?;
         run_reg(&[
             "add",
             WINDOWS_PROXY_REG_PATH,
@@ -24,6 +84,26 @@ impl SystemProxyBackend for WindowsSystemProxyBackend {
             proxy_server.as_str(),
             "/f",
         ])?;
+        // With a PAC URL the manual proxy stays off, or WinINet would use it
+        // for whatever the script sends direct.
+        match &config.pac_url {
+            Some(url) => {
+                run_reg(&[
+                    "add",
+                    WINDOWS_PROXY_REG_PATH,
+                    "/v",
+                    "AutoConfigURL",
+                    "/t",
+                    "REG_SZ",
+                    "/d",
+                    url.as_str(),
+                    "/f",
+                ])?;
+                self.auto_config_url_set = true;
+            }
+            None => self.clear_auto_config_url()?,
+        }
+        let proxy_enable = if config.pac_url.is_some() { "0" } else { "1" };
         run_reg(&[
             "add",
             WINDOWS_PROXY_REG_PATH,
@@ -32,7 +112,7 @@ impl SystemProxyBackend for WindowsSystemProxyBackend {
             "/t",
             "REG_DWORD",
             "/d",
-            "1",
+            proxy_enable,
             "/f",
         ])?;
         notify_windows_proxy_changed();
@@ -40,6 +120,7 @@ impl SystemProxyBackend for WindowsSystemProxyBackend {
     }
 
     fn disable(&mut self) -> SystemProxyResult<()> {
+        self.clear_auto_config_url()?;
         run_reg(&[
             "add",
             WINDOWS_PROXY_REG_PATH,
@@ -52,8 +133,95 @@ impl SystemProxyBackend for WindowsSystemProxyBackend {
             "/f",
         ])?;
         notify_windows_proxy_changed();
+        self.snapshot = None;
         Ok(())
     }
+
+    fn status(&self) -> SystemProxyResult<SystemProxyStatus> {
+        let mut status = SystemProxyStatus {
+            pac_url: query_value("AutoConfigURL").filter(|url| !url.is_empty()),
+            ..SystemProxyStatus::default()
+        };
+        if query_value("ProxyEnable").as_deref() == Some("1")
+            && let Some(server) = query_value("ProxyServer")
+        {
+            status = SystemProxyStatus {
+                pac_url: status.pac_url,
+                ..parse_proxy_server(&server)
+            };
+        }
+        Ok(status)
+    }
+
+    fn snapshot(&self) -> Option<String> {
+        serde_json::to_string(self.snapshot.as_ref()?).ok()
+    }
+
+    fn restore(&mut self, snapshot: &str) -> SystemProxyResult<()> {
+        let snapshot = serde_json::from_str::<SystemProxySnapshot>(snapshot)
+            .map_err(|error| SystemProxyError::new(format!("invalid snapshot: {error}")))?;
+        for (name, value) in &snapshot.values {
+            let Some((_, kind)) = REG_VALUES.iter().find(|(known, _)| known == name) else {
+                continue;
+            };
+            match value {
+                Some(value) => set_value(name, kind, value)?,
+                // Nothing to remove when it is already gone.
+                None => {
+                    let _ = delete_value(name);
+                }
+            }
+        }
+        notify_windows_proxy_changed();
+        Ok(())
+    }
+}
+
+fn capture_snapshot() -> SystemProxySnapshot {
+    SystemProxySnapshot {
+        values: REG_VALUES
+            .iter()
+            .map(|(name, _)| (name.to_string(), query_value(name)))
+            .collect(),
+    }
+}
+
+/// Data of a value under [`WINDOWS_PROXY_REG_PATH`]; `None` when it does not
+/// exist. `reg query` prints DWORDs in hex, which are turned into decimal.
+fn query_value(name: &str) -> Option<String> {
+    let output = run_reg(&["query", WINDOWS_PROXY_REG_PATH, "/v", name]).ok()?;
+    output.lines().find_map(|line| {
+        let rest = line.trim_start().strip_prefix(name)?;
+        let (kind, data) = rest.trim_start().split_once(char::is_whitespace)?;
+        let data = data.trim();
+        if kind == "REG_DWORD" {
+            let hex = data.trim_start_matches("0x");
+            return u32::from_str_radix(hex, 16)
+                .ok()
+                .map(|value| value.to_string());
+        }
+        Some(data.to_string())
+    })
+}
+
+fn set_value(name: &str, kind: &str, data: &str) -> SystemProxyResult<()> {
+    run_reg(&[
+        "add",
+        WINDOWS_PROXY_REG_PATH,
+        "/v",
+        name,
+        "/t",
+        kind,
+        "/d",
+        data,
+        "/f",
+    ])?;
+    Ok(())
+}
+
+fn delete_value(name: &str) -> SystemProxyResult<()> {
+    run_reg(&["delete", WINDOWS_PROXY_REG_PATH, "/v", name, "/f"])?;
+    Ok(())
 }
 
 fn notify_windows_proxy_changed() {
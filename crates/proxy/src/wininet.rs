@@ -0,0 +1,143 @@
+//! The `ProxyServer` and `ProxyOverride` registry formats WinINet reads.
+//! Kept apart from the Windows backend so they are tested on every platform.
+
+use crate::{ProxyEndpoint, SystemProxyConfig, SystemProxyStatus};
+
+/// Endpoints of a `ProxyServer` value, either `host:port` for every protocol
+/// or the per-protocol `http=…;https=…;socks=…` form.
+pub(crate) fn parse_proxy_server(value: &str) -> SystemProxyStatus {
+    let endpoint = |value: &str| {
+        let (host, port) = value.trim().rsplit_once(':')?;
+        Some(ProxyEndpoint::new(host, port.parse().ok()?))
+    };
+    if !value.contains('=') {
+        let shared = endpoint(value);
+        return SystemProxyStatus {
+            http: shared.clone(),
+            https: shared.clone(),
+            socks: shared,
+            pac_url: None,
+        };
+    }
+    let mut status = SystemProxyStatus::default();
+    for entry in value.split(';') {
+        let Some((scheme, address)) = entry.split_once('=') else {
+            continue;
+        };
+        match scheme.trim().to_ascii_lowercase().as_str() {
+            "http" => status.http = endpoint(address),
+            "https" => status.https = endpoint(address),
+            "socks" => status.socks = endpoint(address),
+            _ => {}
+        }
+    }
+    status
+}
+
+/// `host:port` when every protocol shares a port, otherwise the
+/// per-protocol `http=…;https=…;socks=…` form.
+pub(crate) fn proxy_server(config: &SystemProxyConfig) -> String {
+    let host = &config.host;
+    match config.single_port() {
+        Some(port) => format!("{host}:{port}"),
+        None => format!(
+            "http={host}:{};https={host}:{};socks={host}:{}",
+            config.http_port, config.https_port, config.socks_port
+        ),
+    }
+}
+
+/// WinINet matches bypass entries as wildcards and knows no CIDRs, so IPv4
+/// networks are written as the `a.b.*` patterns covering them.
+pub(crate) fn proxy_override(bypass: &[String]) -> String {
+    bypass
+        .iter()
+        .flat_map(|entry| ipv4_wildcards(entry).unwrap_or_else(|| vec![entry.clone()]))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+fn ipv4_wildcards(entry: &str) -> Option<Vec<String>> {
+    let (address, prefix) = entry.split_once('/')?;
+    let address = address.parse::<std::net::Ipv4Addr>().ok()?;
+    let prefix = prefix.parse::<u32>().ok().filter(|prefix| *prefix <= 32)?;
+    if prefix == 0 {
+        return Some(vec!["*".to_string()]);
+    }
+    // Whole octets fixed by the prefix, plus the partly fixed one expanded.
+    let octets = address.octets();
+    let fixed = (prefix / 8) as usize;
+    let rest = prefix % 8;
+    let head = octets[..fixed]
+        .iter()
+        .map(u8::to_string)
+        .collect::<Vec<_>>();
+    let mut patterns = Vec::new();
+    if rest == 0 || fixed == 4 {
+        patterns.push(head);
+    } else {
+        let mask = 0xffu8 << (8 - rest);
+        let first = octets[fixed] & mask;
+        for value in first..=(first | !mask) {
+            let mut parts = head.clone();
+            parts.push(value.to_string());
+            patterns.push(parts);
+        }
+    }
+    Some(
+        patterns
+            .into_iter()
+            .map(|mut parts| {
+                if parts.len() < 4 {
+                    parts.push("*".to_string());
+                }
+                parts.join(".")
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bypass(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(|entry| entry.to_string()).collect()
+    }
+
+    #[test]
+    fn expands_ipv4_networks_into_wildcards() {
+        assert_eq!(
+            proxy_override(&bypass(&["localhost", "10.0.0.0/8", "192.168.0.0/16"])),
+            "localhost;10.*;192.168.*"
+        );
+        let private = ipv4_wildcards("172.16.0.0/12").expect("a /12 expands");
+        assert_eq!(private.len(), 16);
+        assert_eq!(private.first().map(String::as_str), Some("172.16.*"));
+        assert_eq!(private.last().map(String::as_str), Some("172.31.*"));
+        assert_eq!(ipv4_wildcards("0.0.0.0/0"), Some(vec!["*".to_string()]));
+        assert_eq!(ipv4_wildcards("::1"), None);
+        assert_eq!(ipv4_wildcards("10.0.0.0/33"), None);
+    }
+
+    #[test]
+    fn round_trips_proxy_server_values() {
+        let shared = SystemProxyConfig::new("127.0.0.1", 7890);
+        assert_eq!(proxy_server(&shared), "127.0.0.1:7890");
+        assert!(parse_proxy_server(&proxy_server(&shared)).matches(&shared));
+
+        let split = SystemProxyConfig {
+            https_port: 7891,
+            socks_port: 7892,
+            ..SystemProxyConfig::new("127.0.0.1", 7890)
+        };
+        let value = proxy_server(&split);
+        assert_eq!(
+            value,
+            "http=127.0.0.1:7890;https=127.0.0.1:7891;socks=127.0.0.1:7892"
+        );
+        let status = parse_proxy_server(&value);
+        assert_eq!(status.socks, Some(ProxyEndpoint::new("127.0.0.1", 7892)));
+        assert!(status.matches(&split));
+    }
+}
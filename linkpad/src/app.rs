@@ -1,21 +1,30 @@
 use crate::i18n;
 use crate::state::{
-    AppState, Language, Page, ProfileSummary, ProxyGroupSummary, ProxyNodeSummary, RuleFilter,
-    ThemePreference,
+    AppState, Language, Page, ProfileSummary, ProxyGroupSummary, ProxyNodeSummary, ThemePreference,
 };
 use crate::store::profile_store;
 use crate::store::settings_store;
-use linkpad_core::{Core as LinkpadCore, KernelUpgradeInfo, ProxyMode};
+use linkpad_core::{
+    Core as LinkpadCore, CoreEvent, CoreResult, GeoDataKind, GeoDataSettings, GeoDataStatus,
+    KernelChannel, KernelKind, KernelUpgradeInfo, ProfileSourceKind, ProxyMode, RuleMatch,
+    ShellSyntax, SubscriptionUserinfo, SystemProxyMode, SystemProxySettings,
+};
 use makepad_components::button::MpButtonWidgetRefExt;
 use makepad_components::makepad_widgets::makepad_platform::CxOsOp;
 use makepad_components::makepad_widgets::*;
 use makepad_components::switch::MpSwitchWidgetRefExt;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Once;
 use std::sync::mpsc::{Receiver, TryRecvError};
 use std::thread;
 use tracing::{error, info, warn};
 
+#[path = "views/connections.rs"]
+mod connections;
+#[path = "views/logs.rs"]
+mod logs;
+#[path = "views/overview.rs"]
+mod overview;
 #[path = "views/profiles.rs"]
 mod profiles;
 #[path = "views/proxy_groups.rs"]
@@ -142,6 +151,8 @@ pub struct App {
     #[rust]
     import_poll_timer: Timer,
     #[rust]
+    subscription_warnings_sent: HashSet<String>,
+    #[rust]
     latency_rx: Option<Receiver<LatencyTaskEvent>>,
     #[rust]
     latency_poll_timer: Timer,
@@ -168,6 +179,8 @@ pub struct App {
     #[rust]
     core_task_kind: Option<CoreTaskKind>,
     #[rust]
+    core_event_timer: Timer,
+    #[rust]
     tray: Option<makepad_components::shell::TrayHandle>,
     #[rust]
     app_menu_installed: bool,
@@ -225,6 +238,11 @@ struct ProxyItemRowIds {
     select_btn: [LiveId; 7],
 }
 
+const PROFILE_USAGE_BAR_WIDTH: f64 = 240.0;
+const RULE_DIAGNOSTICS_SHOWN: usize = 5;
+const SUBSCRIPTION_QUOTA_WARN_RATIO: f64 = 0.9;
+const SUBSCRIPTION_EXPIRY_WARN_SECS: i64 = 3 * 24 * 60 * 60;
+
 type ImportTaskResult = Result<(), String>;
 type CoreTaskResult = Result<CoreTaskOutput, String>;
 
@@ -256,13 +274,36 @@ enum LatencyTaskEvent {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum CoreTaskKind {
     Upgrading,
+    Installing,
+    RollingBack,
     Restarting,
+    UpdatingGeoData,
+    ImportingGeoData,
+    MatchingRule,
+    SavingPort,
+    SwitchingKernel,
+    EnablingSystemProxy,
+    DisablingSystemProxy,
+    ActivatingProfile,
+    RefreshingProfile,
 }
 
 #[derive(Debug)]
 enum CoreTaskOutput {
     Upgraded(KernelUpgradeInfo),
+    /// Version now current, `None` for a kernel Linkpad did not install.
+    RolledBack(Option<String>),
     Restarted,
+    /// Status of each geodata file the task replaced or checked.
+    GeoDataUpdated(Vec<GeoDataStatus>),
+    /// Rule handling the tested host, `None` when no rule matches.
+    RuleMatched(Option<RuleMatch>),
+    PortSaved(u16),
+    KernelSwitched(KernelKind),
+    /// Whether the system proxy is now enabled.
+    SystemProxyToggled(bool),
+    ProfileActivated,
+    ProfileRefreshed,
 }
 
 #[derive(Clone)]
@@ -602,13 +643,19 @@ impl App {
 
     fn apply_state(&mut self, cx: &mut Cx) {
         let strings = i18n::strings(self.state.language);
+        let is_overview = self.state.active_page == Page::Overview;
         let is_profiles = self.state.active_page == Page::Profiles;
         let is_proxy_groups = self.state.active_page == Page::ProxyGroups;
         let is_rules = self.state.active_page == Page::Rules;
+        let is_connections = self.state.active_page == Page::Connections;
+        let is_logs = self.state.active_page == Page::Logs;
         let is_settings = self.state.active_page == Page::Settings;
         let title = i18n::page_title(strings, self.state.active_page);
 
         self.ui.label(ids!(header.title_label)).set_text(cx, title);
+        self.ui
+            .view(ids!(dashboard.overview_section))
+            .set_visible(cx, is_overview);
         self.ui
             .view(ids!(dashboard.profiles_section))
             .set_visible(cx, is_profiles);
@@ -618,16 +665,28 @@ impl App {
         self.ui
             .view(ids!(dashboard.rules_section))
             .set_visible(cx, is_rules);
+        self.ui
+            .view(ids!(dashboard.connections_section))
+            .set_visible(cx, is_connections);
+        self.ui
+            .view(ids!(dashboard.logs_section))
+            .set_visible(cx, is_logs);
         self.ui
             .view(ids!(dashboard.settings_section))
             .set_visible(cx, is_settings);
 
+        self.apply_traffic_state(cx, strings);
         self.apply_profiles_state(cx, strings);
         self.apply_proxy_groups_state(cx, strings);
         self.apply_rules_state(cx, strings);
+        self.apply_connections_state(cx, strings);
+        self.apply_logs_state(cx, strings);
         self.apply_settings_state(cx, strings);
         self.apply_notification_state(cx, strings);
 
+        self.ui
+            .mp_button(ids!(sidebar.menu_overview))
+            .set_text(strings.menu_overview);
         self.ui
             .mp_button(ids!(sidebar.menu_profiles))
             .set_text(strings.menu_profiles);
@@ -637,6 +696,12 @@ impl App {
         self.ui
             .mp_button(ids!(sidebar.menu_rules))
             .set_text(strings.menu_rules);
+        self.ui
+            .mp_button(ids!(sidebar.menu_connections))
+            .set_text(strings.menu_connections);
+        self.ui
+            .mp_button(ids!(sidebar.menu_logs))
+            .set_text(strings.menu_logs);
         self.ui
             .mp_button(ids!(sidebar.menu_settings))
             .set_text(strings.menu_settings);
@@ -671,6 +736,57 @@ impl App {
         self.ui
             .label(ids!(dashboard.system_proxy_label))
             .set_text(cx, strings.system_proxy_label);
+        self.ui
+            .label(ids!(dashboard.proxy_bypass_label))
+            .set_text(cx, strings.proxy_bypass_label);
+        self.ui
+            .label(ids!(dashboard.proxy_ports_label))
+            .set_text(cx, strings.proxy_ports_label);
+        self.ui
+            .label(ids!(dashboard.proxy_settings_hint))
+            .set_text(cx, strings.proxy_settings_hint);
+        self.ui
+            .label(ids!(dashboard.proxy_mode_label))
+            .set_text(cx, strings.proxy_mode_label);
+        self.ui
+            .label(ids!(dashboard.proxy_pac_direct_label))
+            .set_text(cx, strings.proxy_pac_direct_label);
+        self.ui
+            .label(ids!(dashboard.proxy_pac_hint))
+            .set_text(cx, strings.proxy_pac_hint);
+        self.ui
+            .label(ids!(dashboard.proxy_restore_on_drift_label))
+            .set_text(cx, strings.proxy_restore_on_drift_label);
+        self.ui
+            .label(ids!(dashboard.proxy_shell_env_label))
+            .set_text(cx, strings.proxy_shell_env_label);
+        self.ui
+            .mp_button(ids!(dashboard.proxy_copy_command_btn))
+            .set_text(strings.proxy_copy_command_button);
+        self.ui
+            .text_input(ids!(dashboard.proxy_pac_port_input))
+            .set_text(cx, &self.state.proxy_pac_port_input);
+        self.ui
+            .text_input(ids!(dashboard.proxy_pac_direct_input))
+            .set_text(cx, &self.state.proxy_pac_direct_input);
+        self.ui
+            .mp_button(ids!(dashboard.proxy_settings_save_btn))
+            .set_text(strings.clash_port_save_button);
+        self.ui
+            .text_input(ids!(dashboard.proxy_bypass_input))
+            .set_text(cx, &self.state.proxy_bypass_input);
+        for (index, port_input) in [
+            ids!(dashboard.proxy_http_port_input),
+            ids!(dashboard.proxy_https_port_input),
+            ids!(dashboard.proxy_socks_port_input),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            self.ui
+                .text_input(port_input)
+                .set_text(cx, &self.state.proxy_port_inputs[index]);
+        }
         self.ui
             .label(ids!(dashboard.auto_launch_label))
             .set_text(cx, strings.auto_launch_label);
@@ -680,12 +796,45 @@ impl App {
         self.ui
             .label(ids!(dashboard.clash_port_label))
             .set_text(cx, strings.clash_port_label);
+        self.ui
+            .label(ids!(dashboard.clash_kernel_label))
+            .set_text(cx, strings.clash_kernel_label);
+        self.ui
+            .label(ids!(dashboard.clash_channel_label))
+            .set_text(cx, strings.clash_channel_label);
+        self.ui
+            .label(ids!(dashboard.clash_mirrors_label))
+            .set_text(cx, strings.clash_mirrors_label);
+        self.ui
+            .label(ids!(dashboard.clash_core_file_label))
+            .set_text(cx, strings.clash_core_file_label);
+        self.ui
+            .label(ids!(dashboard.clash_core_previous_label))
+            .set_text(cx, strings.clash_core_previous_label);
         self.ui
             .label(ids!(dashboard.clash_core_version_label))
             .set_text(cx, strings.clash_core_version_label);
         self.ui
             .label(ids!(dashboard.clash_core_path_label))
             .set_text(cx, strings.clash_core_path_label);
+        self.ui
+            .label(ids!(dashboard.clash_override_label))
+            .set_text(cx, strings.clash_override_label);
+        self.ui
+            .label(ids!(dashboard.clash_override_hint))
+            .set_text(cx, strings.clash_override_hint);
+        self.ui
+            .label(ids!(dashboard.geodata_setting_title))
+            .set_text(cx, strings.geodata_setting_title);
+        self.ui
+            .label(ids!(dashboard.geodata_hint))
+            .set_text(cx, strings.geodata_hint);
+        self.ui
+            .label(ids!(dashboard.geodata_interval_label))
+            .set_text(cx, strings.geodata_interval_label);
+        self.ui
+            .label(ids!(dashboard.geodata_import_label))
+            .set_text(cx, strings.geodata_import_label);
         self.ui
             .mp_button(ids!(dashboard.clash_port_save_btn))
             .set_text(strings.clash_port_save_button);
@@ -696,6 +845,23 @@ impl App {
             } else {
                 strings.clash_core_upgrade_button
             });
+        self.ui
+            .mp_button(ids!(dashboard.clash_core_install_file_btn))
+            .set_text(if self.core_task_kind == Some(CoreTaskKind::Installing) {
+                strings.clash_core_installing_button
+            } else {
+                strings.clash_core_install_file_button
+            });
+        self.ui
+            .mp_button(ids!(dashboard.clash_mirrors_save_btn))
+            .set_text(strings.clash_port_save_button);
+        self.ui
+            .mp_button(ids!(dashboard.clash_core_rollback_btn))
+            .set_text(if self.core_task_kind == Some(CoreTaskKind::RollingBack) {
+                strings.clash_core_rolling_back_button
+            } else {
+                strings.clash_core_rollback_button
+            });
         self.ui
             .mp_button(ids!(dashboard.clash_core_restart_btn))
             .set_text(if self.core_task_kind == Some(CoreTaskKind::Restarting) {
@@ -703,20 +869,132 @@ impl App {
             } else {
                 strings.clash_core_restart_button
             });
+        self.ui
+            .mp_button(ids!(dashboard.geodata_update_btn))
+            .set_text(
+                if self.core_task_kind == Some(CoreTaskKind::UpdatingGeoData) {
+                    strings.geodata_updating_button
+                } else {
+                    strings.geodata_update_button
+                },
+            );
+        self.ui
+            .mp_button(ids!(dashboard.geodata_import_btn))
+            .set_text(
+                if self.core_task_kind == Some(CoreTaskKind::ImportingGeoData) {
+                    strings.geodata_importing_button
+                } else {
+                    strings.geodata_import_button
+                },
+            );
+        self.ui
+            .mp_button(ids!(dashboard.geodata_save_btn))
+            .set_text(strings.clash_port_save_button);
         self.ui
             .text_input(ids!(dashboard.clash_port_input))
             .set_text(cx, &self.state.clash_port_input);
         self.ui
             .label(ids!(dashboard.clash_core_version_value))
             .set_text(cx, &self.state.clash_core_version);
+        self.ui
+            .label(ids!(dashboard.clash_core_previous_value))
+            .set_text(cx, &self.state.clash_core_previous_version);
+        self.ui
+            .text_input(ids!(dashboard.clash_channel_pinned_input))
+            .set_text(cx, &self.state.kernel_pinned_input);
+        self.ui
+            .text_input(ids!(dashboard.clash_mirrors_input))
+            .set_text(cx, &self.state.kernel_mirrors_input);
+        self.ui
+            .text_input(ids!(dashboard.clash_core_file_input))
+            .set_text(cx, &self.state.kernel_file_input);
         self.ui
             .label(ids!(dashboard.clash_core_path_value))
             .set_text(cx, &self.state.clash_core_path);
+        self.ui
+            .label(ids!(dashboard.clash_override_value))
+            .set_text(cx, &self.state.clash_override_paths);
+        for (index, (value, url_input)) in [
+            (
+                ids!(dashboard.geodata_geoip_value),
+                ids!(dashboard.geodata_geoip_url_input),
+            ),
+            (
+                ids!(dashboard.geodata_geosite_value),
+                ids!(dashboard.geodata_geosite_url_input),
+            ),
+            (
+                ids!(dashboard.geodata_asn_value),
+                ids!(dashboard.geodata_asn_url_input),
+            ),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let kind = GeoDataKind::ALL[index];
+            let status = self
+                .state
+                .geodata_status
+                .iter()
+                .find(|status| status.kind == kind);
+            self.ui
+                .label(value)
+                .set_text(cx, &Self::format_geodata_status(status, strings));
+            self.ui
+                .text_input(url_input)
+                .set_text(cx, &self.state.geodata_url_inputs[index]);
+        }
+        self.ui
+            .text_input(ids!(dashboard.geodata_interval_input))
+            .set_text(cx, &self.state.geodata_interval_input);
+        self.ui
+            .text_input(ids!(dashboard.geodata_file_input))
+            .set_text(cx, &self.state.geodata_file_input);
+        self.ui
+            .drop_down(ids!(dashboard.geodata_import_dropdown))
+            .set_selected_item(cx, self.state.geodata_import_index);
 
         let language_dropdown = self.ui.drop_down(ids!(dashboard.language_dropdown));
         language_dropdown.set_labels(cx, i18n::language_options(self.state.language));
         language_dropdown.set_selected_item(cx, self.state.language.as_index());
 
+        let kernel_dropdown = self.ui.drop_down(ids!(dashboard.clash_kernel_dropdown));
+        kernel_dropdown.set_labels(
+            cx,
+            KernelKind::ALL
+                .iter()
+                .map(|kind| kind.as_str().to_string())
+                .collect(),
+        );
+        kernel_dropdown.set_selected_item(
+            cx,
+            KernelKind::ALL
+                .iter()
+                .position(|kind| *kind == self.state.kernel_kind)
+                .unwrap_or(0),
+        );
+
+        let proxy_mode_dropdown = self.ui.drop_down(ids!(dashboard.proxy_mode_dropdown));
+        proxy_mode_dropdown.set_labels(cx, i18n::system_proxy_mode_options(self.state.language));
+        proxy_mode_dropdown.set_selected_item(
+            cx,
+            match self.state.proxy_mode_input {
+                SystemProxyMode::Global => 0,
+                SystemProxyMode::Pac => 1,
+            },
+        );
+
+        let channel_dropdown = self.ui.drop_down(ids!(dashboard.clash_channel_dropdown));
+        channel_dropdown.set_labels(cx, i18n::kernel_channel_options(self.state.language));
+        channel_dropdown.set_selected_item(
+            cx,
+            match self.state.kernel_channel {
+                KernelChannel::Stable => 0,
+                KernelChannel::Alpha => 1,
+                KernelChannel::Pinned(_) => 2,
+            },
+        );
+
         let theme_dropdown = self.ui.drop_down(ids!(dashboard.theme_dropdown));
         theme_dropdown.set_labels(cx, i18n::theme_options(self.state.language));
         theme_dropdown.set_selected_item(cx, self.state.theme.as_index());
@@ -724,6 +1002,12 @@ impl App {
         self.ui
             .mp_switch(ids!(dashboard.system_proxy_switch))
             .set_on(cx, self.state.system_proxy_enabled);
+        self.ui
+            .mp_switch(ids!(dashboard.proxy_restore_on_drift_switch))
+            .set_on(cx, self.state.system_proxy_settings.restore_on_drift);
+        self.ui
+            .mp_switch(ids!(dashboard.proxy_shell_env_switch))
+            .set_on(cx, self.state.system_proxy_settings.shell_env);
         self.ui
             .mp_switch(ids!(dashboard.close_to_tray_switch))
             .set_on(cx, self.state.close_to_tray_enabled);
@@ -813,6 +1097,48 @@ impl App {
         self.ui
             .text_input(ids!(dashboard.profile_url_input))
             .set_text(cx, &self.state.profile_url_input);
+        self.ui
+            .label(ids!(dashboard.profile_local_label))
+            .set_text(cx, strings.profiles_import_local_label);
+        self.ui
+            .text_input(ids!(dashboard.profile_file_input))
+            .apply_over(
+                cx,
+                live! {
+                    empty_text: (strings.profiles_import_file_placeholder)
+                },
+            );
+        self.ui
+            .text_input(ids!(dashboard.profile_file_input))
+            .set_text(cx, &self.state.profile_file_input);
+        self.ui
+            .mp_button(ids!(dashboard.profile_file_import_btn))
+            .set_text(strings.profiles_import_file_button);
+        self.ui
+            .text_input(ids!(dashboard.profile_text_name_input))
+            .apply_over(
+                cx,
+                live! {
+                    empty_text: (strings.profiles_import_text_name_placeholder)
+                },
+            );
+        self.ui
+            .text_input(ids!(dashboard.profile_text_name_input))
+            .set_text(cx, &self.state.profile_text_name_input);
+        self.ui
+            .text_input(ids!(dashboard.profile_text_input))
+            .apply_over(
+                cx,
+                live! {
+                    empty_text: (strings.profiles_import_text_placeholder)
+                },
+            );
+        self.ui
+            .text_input(ids!(dashboard.profile_text_input))
+            .set_text(cx, &self.state.profile_text_input);
+        self.ui
+            .mp_button(ids!(dashboard.profile_text_import_btn))
+            .set_text(strings.profiles_import_text_button);
 
         let status_color = if self.state.import_status.is_error {
             palette.status_error
@@ -856,7 +1182,11 @@ impl App {
             ids!(dashboard.profile_row_1),
             ids!(dashboard.profile_row_1_name),
             ids!(dashboard.profile_row_1_meta),
+            ids!(dashboard.profile_row_1_usage),
+            ids!(dashboard.profile_row_1_usage_bar),
+            ids!(dashboard.profile_row_1_usage_fill),
             ids!(dashboard.profile_row_1_status),
+            ids!(dashboard.profile_row_1_interval_input),
             ids!(dashboard.profile_row_1_activate_btn),
             ids!(dashboard.profile_row_1_refresh_btn),
             ids!(dashboard.profile_row_1_delete_btn),
@@ -870,7 +1200,11 @@ impl App {
             ids!(dashboard.profile_row_2),
             ids!(dashboard.profile_row_2_name),
             ids!(dashboard.profile_row_2_meta),
+            ids!(dashboard.profile_row_2_usage),
+            ids!(dashboard.profile_row_2_usage_bar),
+            ids!(dashboard.profile_row_2_usage_fill),
             ids!(dashboard.profile_row_2_status),
+            ids!(dashboard.profile_row_2_interval_input),
             ids!(dashboard.profile_row_2_activate_btn),
             ids!(dashboard.profile_row_2_refresh_btn),
             ids!(dashboard.profile_row_2_delete_btn),
@@ -884,7 +1218,11 @@ impl App {
             ids!(dashboard.profile_row_3),
             ids!(dashboard.profile_row_3_name),
             ids!(dashboard.profile_row_3_meta),
+            ids!(dashboard.profile_row_3_usage),
+            ids!(dashboard.profile_row_3_usage_bar),
+            ids!(dashboard.profile_row_3_usage_fill),
             ids!(dashboard.profile_row_3_status),
+            ids!(dashboard.profile_row_3_interval_input),
             ids!(dashboard.profile_row_3_activate_btn),
             ids!(dashboard.profile_row_3_refresh_btn),
             ids!(dashboard.profile_row_3_delete_btn),
@@ -946,42 +1284,140 @@ impl App {
             .text_input(ids!(dashboard.rules_search_input))
             .set_text(cx, &self.state.rules_query);
         self.ui
-            .mp_button(ids!(dashboard.rules_filter_all_btn))
-            .set_text(strings.rules_filter_all);
+            .label(ids!(dashboard.rules_kind_label))
+            .set_text(cx, strings.rules_filter_type_label);
+        self.ui
+            .label(ids!(dashboard.rules_target_label))
+            .set_text(cx, strings.rules_filter_target_label);
+
+        let kind_options = self.rule_kind_options();
+        let mut kind_labels = vec![strings.rules_filter_all.to_string()];
+        kind_labels.extend(kind_options.iter().map(|kind| kind.as_str().to_string()));
+        let kind_index = self
+            .state
+            .rules_kind_filter
+            .and_then(|filter| kind_options.iter().position(|kind| *kind == filter))
+            .map_or(0, |index| index + 1);
+        let kind_dropdown = self.ui.drop_down(ids!(dashboard.rules_kind_dropdown));
+        kind_dropdown.set_labels(cx, kind_labels);
+        kind_dropdown.set_selected_item(cx, kind_index);
+
+        let target_options = self.rule_target_options();
+        let mut target_labels = vec![strings.rules_filter_all.to_string()];
+        target_labels.extend(target_options.iter().cloned());
+        let target_index = self
+            .state
+            .rules_target_filter
+            .as_ref()
+            .and_then(|filter| target_options.iter().position(|target| target == filter))
+            .map_or(0, |index| index + 1);
+        let target_dropdown = self.ui.drop_down(ids!(dashboard.rules_target_dropdown));
+        target_dropdown.set_labels(cx, target_labels);
+        target_dropdown.set_selected_item(cx, target_index);
+
+        self.ui
+            .text_input(ids!(dashboard.rules_match_input))
+            .apply_over(
+                cx,
+                live! {
+                    empty_text: (strings.rules_match_placeholder)
+                },
+            );
+        self.ui
+            .text_input(ids!(dashboard.rules_match_input))
+            .set_text(cx, &self.state.rules_match_input);
         self.ui
-            .mp_button(ids!(dashboard.rules_filter_domain_btn))
-            .set_text(strings.rules_filter_domain);
+            .text_input(ids!(dashboard.rules_match_process_input))
+            .apply_over(
+                cx,
+                live! {
+                    empty_text: (strings.rules_match_process_placeholder)
+                },
+            );
         self.ui
-            .mp_button(ids!(dashboard.rules_filter_ip_cidr_btn))
-            .set_text(strings.rules_filter_ip_cidr);
+            .text_input(ids!(dashboard.rules_match_process_input))
+            .set_text(cx, &self.state.rules_match_process);
+        self.ui.mp_button(ids!(dashboard.rules_match_btn)).set_text(
+            if self.core_task_kind == Some(CoreTaskKind::MatchingRule) {
+                strings.rules_matching_btn
+            } else {
+                strings.rules_match_btn
+            },
+        );
+        let matched_index = self
+            .state
+            .rules_match
+            .as_ref()
+            .and_then(|found| found.as_ref())
+            .map(|found| found.index);
+        let match_text = match self.state.rules_match.as_ref() {
+            None => String::new(),
+            Some(None) => strings.rules_match_none.to_string(),
+            Some(Some(found)) => {
+                let mut text = format!(
+                    "{}: #{} {}",
+                    strings.rules_match_prefix,
+                    found.index + 1,
+                    Self::truncate_text(&found.rule.to_string(), 80)
+                );
+                if let Some(sub_rule) = found.sub_rule.as_ref() {
+                    text.push_str(&format!(" / {sub_rule}"));
+                }
+                text.push_str(&format!(" → {}", found.policy_chain.join(" → ")));
+                if !found.skipped.is_empty() {
+                    let skipped = found
+                        .skipped
+                        .iter()
+                        .take(RULE_DIAGNOSTICS_SHOWN)
+                        .map(|skipped| format!("#{} ({})", skipped.index + 1, skipped.reason))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    text.push_str(&format!(
+                        "\n{} ({}): {skipped}",
+                        strings.rules_match_skipped_prefix,
+                        found.skipped.len()
+                    ));
+                }
+                text
+            }
+        };
         self.ui
-            .mp_button(ids!(dashboard.rules_filter_process_btn))
-            .set_text(strings.rules_filter_process_name);
+            .label(ids!(dashboard.rules_match_result))
+            .set_text(cx, &match_text);
 
         let palette = self.theme_palette();
-        self.apply_menu_button_style(
-            cx,
-            ids!(dashboard.rules_filter_all_btn),
-            self.state.rules_filter == RuleFilter::All,
-            palette,
-        );
-        self.apply_menu_button_style(
-            cx,
-            ids!(dashboard.rules_filter_domain_btn),
-            self.state.rules_filter == RuleFilter::Domain,
-            palette,
-        );
-        self.apply_menu_button_style(
-            cx,
-            ids!(dashboard.rules_filter_ip_cidr_btn),
-            self.state.rules_filter == RuleFilter::IpCidr,
-            palette,
-        );
-        self.apply_menu_button_style(
+        let diagnostics_text = if self.state.rule_diagnostics.is_empty() {
+            String::new()
+        } else {
+            let mut lines = vec![format!(
+                "{}: {}",
+                strings.rules_diagnostics_prefix,
+                self.state.rule_diagnostics.len()
+            )];
+            lines.extend(
+                self.state
+                    .rule_diagnostics
+                    .iter()
+                    .take(RULE_DIAGNOSTICS_SHOWN)
+                    .map(|diagnostic| {
+                        format!(
+                            "#{} {} ({})",
+                            diagnostic.index + 1,
+                            Self::truncate_text(&diagnostic.line, 80),
+                            diagnostic.message
+                        )
+                    }),
+            );
+            lines.join("\n")
+        };
+        self.ui
+            .label(ids!(dashboard.rules_diagnostics))
+            .set_text(cx, &diagnostics_text);
+        self.ui.label(ids!(dashboard.rules_diagnostics)).apply_over(
             cx,
-            ids!(dashboard.rules_filter_process_btn),
-            self.state.rules_filter == RuleFilter::ProcessName,
-            palette,
+            live! {
+                draw_text: { color: (palette.status_error) }
+            },
         );
 
         let filtered_rules = self.filtered_rules();
@@ -1009,8 +1445,14 @@ impl App {
         let rules_text = filtered_rules
             .iter()
             .take(show_count)
-            .enumerate()
-            .map(|(index, rule)| format!("{}. {}", index + 1, rule))
+            .map(|(index, rule)| {
+                let marker = if Some(*index) == matched_index {
+                    "▶ "
+                } else {
+                    ""
+                };
+                format!("{marker}{}. {rule}", index + 1)
+            })
             .collect::<Vec<_>>()
             .join("\n");
         self.ui
@@ -1028,7 +1470,11 @@ impl App {
         row_id: &[LiveId; 2],
         name_id: &[LiveId; 2],
         meta_id: &[LiveId; 2],
+        usage_id: &[LiveId; 2],
+        usage_bar_id: &[LiveId; 2],
+        usage_fill_id: &[LiveId; 2],
         status_id: &[LiveId; 2],
+        interval_input_id: &[LiveId; 2],
         activate_btn_id: &[LiveId; 2],
         refresh_btn_id: &[LiveId; 2],
         delete_btn_id: &[LiveId; 2],
@@ -1051,15 +1497,61 @@ impl App {
             },
         );
         ui.label(name_id).set_text(cx, &profile.name);
+        let source = match profile.source_kind {
+            ProfileSourceKind::Inline => strings.profiles_source_inline,
+            ProfileSourceKind::Remote | ProfileSourceKind::LocalFile => profile.source.as_str(),
+        };
+        let auto_update = profile
+            .refresh_interval_hours
+            .map(|hours| format!("{hours}h"))
+            .unwrap_or_else(|| strings.profiles_auto_update_off.to_string());
         ui.label(meta_id).set_text(
             cx,
             &format!(
-                "{}\n{}: {}",
-                Self::truncate_text(&profile.source, 54),
+                "{}\n{}: {} | {}: {}",
+                Self::truncate_text(source, 54),
                 strings.profiles_current_updated,
-                profile.updated_at
+                profile.updated_at,
+                strings.profiles_auto_update_prefix,
+                auto_update
             ),
         );
+        match profile.subscription {
+            Some(info) => {
+                ui.label(usage_id).set_visible(cx, true);
+                ui.label(usage_id)
+                    .set_text(cx, &Self::subscription_usage_text(strings, &info));
+                match info.usage_ratio() {
+                    Some(ratio) => {
+                        let fill_width = PROFILE_USAGE_BAR_WIDTH * ratio;
+                        let fill_color = if ratio >= SUBSCRIPTION_QUOTA_WARN_RATIO {
+                            palette.status_error
+                        } else {
+                            palette.status_success
+                        };
+                        ui.view(usage_bar_id).set_visible(cx, true);
+                        ui.view(usage_bar_id).apply_over(
+                            cx,
+                            live! {
+                                draw_bg: { color: (palette.panel_bg) }
+                            },
+                        );
+                        ui.view(usage_fill_id).apply_over(
+                            cx,
+                            live! {
+                                width: (fill_width),
+                                draw_bg: { color: (fill_color) }
+                            },
+                        );
+                    }
+                    None => ui.view(usage_bar_id).set_visible(cx, false),
+                }
+            }
+            None => {
+                ui.label(usage_id).set_visible(cx, false);
+                ui.view(usage_bar_id).set_visible(cx, false);
+            }
+        }
         ui.label(status_id).set_text(
             cx,
             if profile.active {
@@ -1068,6 +1560,21 @@ impl App {
                 strings.profiles_status_inactive
             },
         );
+        ui.text_input(interval_input_id)
+            .set_visible(cx, profile.source_kind != ProfileSourceKind::Inline);
+        ui.text_input(interval_input_id).apply_over(
+            cx,
+            live! {
+                empty_text: (strings.profiles_interval_placeholder)
+            },
+        );
+        ui.text_input(interval_input_id).set_text(
+            cx,
+            &profile
+                .interval_override_hours
+                .map(|hours| hours.to_string())
+                .unwrap_or_default(),
+        );
         ui.mp_button(activate_btn_id).set_text(if profile.active {
             strings.profiles_status_active
         } else {
@@ -1247,6 +1754,52 @@ impl App {
         }
     }
 
+    fn subscription_usage_text(strings: &i18n::Strings, info: &SubscriptionUserinfo) -> String {
+        let total = if info.total == 0 {
+            strings.profiles_usage_unlimited.to_string()
+        } else {
+            Self::format_bytes(info.total)
+        };
+        let mut text = format!(
+            "{} {} / {}",
+            strings.profiles_usage_used,
+            Self::format_bytes(info.used()),
+            total
+        );
+        if let Some(date) = info.expire_date() {
+            let now = Self::now_unix_seconds();
+            let label = if info.seconds_until_expiry(now).is_some_and(|secs| secs <= 0) {
+                strings.profiles_usage_expired
+            } else {
+                strings.profiles_usage_expires
+            };
+            text.push_str(&format!(" | {label}: {date}"));
+        }
+        text
+    }
+
+    fn format_bytes(bytes: u64) -> String {
+        const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+        let mut value = bytes as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            format!("{bytes} {}", UNITS[0])
+        } else {
+            format!("{value:.1} {}", UNITS[unit])
+        }
+    }
+
+    fn now_unix_seconds() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|value| value.as_secs())
+            .unwrap_or(0)
+    }
+
     fn truncate_text(input: &str, max_chars: usize) -> String {
         let mut chars = input.chars();
         let truncated: String = chars.by_ref().take(max_chars).collect();
@@ -1266,7 +1819,11 @@ impl App {
                 id: profile.id,
                 name: profile.name,
                 source: profile.source_url,
+                source_kind: profile.source_kind,
                 updated_at: profile.updated_at,
+                subscription: profile.subscription,
+                refresh_interval_hours: profile.refresh.interval_hours(),
+                interval_override_hours: profile.refresh.interval_override_hours,
                 node_count: profile.node_count,
                 group_count: profile.group_count,
                 rule_count: profile.rule_count,
@@ -1303,7 +1860,34 @@ impl App {
             })
             .collect();
 
-        self.state.rules = self.core.active_rules();
+        let rules = self.core.active_rules();
+        if rules != self.state.rules {
+            self.state.rules_match = None;
+        }
+        self.state.rules = rules;
+        self.state.rule_diagnostics = self.core.active_rule_diagnostics();
+        if let Some(kind) = self.state.rules_kind_filter
+            && !self.state.rules.iter().any(|rule| rule.kind == kind)
+        {
+            self.state.rules_kind_filter = None;
+        }
+        if let Some(target) = self.state.rules_target_filter.as_ref()
+            && !self.state.rules.iter().any(|rule| &rule.target == target)
+        {
+            self.state.rules_target_filter = None;
+        }
+        let override_dir = self.core.override_dir();
+        let mut override_paths = override_dir.join("global.yaml").display().to_string();
+        if let Some(profile) = self.state.active_profile() {
+            override_paths.push('\n');
+            override_paths.push_str(
+                &override_dir
+                    .join(format!("{}.yaml", profile.id))
+                    .display()
+                    .to_string(),
+            );
+        }
+        self.state.clash_override_paths = override_paths;
         self.reset_rules_pagination();
 
         self.state
@@ -1375,6 +1959,38 @@ impl App {
         }
         self.state.clash_mixed_port = config.mixed_port;
         self.state.clash_port_input = config.mixed_port.to_string();
+        self.state.kernel_kind = config.kernel;
+        if let KernelChannel::Pinned(tag) = &config.kernel_channel {
+            self.state.kernel_pinned_input = tag.clone();
+        }
+        self.state.kernel_channel = config.kernel_channel;
+        self.state.kernel_mirrors_input = config.kernel_mirrors.join(", ");
+        self.state.kernel_mirrors = config.kernel_mirrors;
+        self.state.geodata_url_inputs =
+            GeoDataKind::ALL.map(|kind| config.geodata.url(kind).to_string());
+        self.state.geodata_interval_input = config
+            .geodata
+            .update_interval_hours
+            .map(|hours| hours.to_string())
+            .unwrap_or_default();
+        self.state.geodata_settings = config.geodata;
+        self.state.proxy_bypass_input = config.system_proxy.bypass.join(", ");
+        self.state.proxy_port_inputs = [
+            config.system_proxy.http_port,
+            config.system_proxy.https_port,
+            config.system_proxy.socks_port,
+        ]
+        .map(|port| port.map(|port| port.to_string()).unwrap_or_default());
+        self.state.proxy_mode_input = config.system_proxy.mode;
+        self.state.proxy_pac_port_input = config.system_proxy.pac_port.to_string();
+        self.state.proxy_pac_direct_input = config.system_proxy.pac_direct.join(", ");
+        self.state.system_proxy_settings = config.system_proxy;
+        self.state.geodata_status = self.core.geodata_status();
+        self.state.clash_core_previous_version = self
+            .core
+            .kernel_versions()
+            .previous
+            .unwrap_or_else(|| "-".to_string());
 
         let strings = i18n::strings(self.state.language);
         let kernel_info = self.core.kernel_info();
@@ -1483,48 +2099,45 @@ impl App {
     }
 
     fn start_core_upgrade(&mut self, cx: &mut Cx) {
-        if self.core_task_rx.is_some() {
-            warn!("skip core upgrade: task already running");
-            return;
-        }
         info!("core upgrade requested");
-
-        let core = self.core.clone();
-        let (tx, rx) = std::sync::mpsc::channel::<CoreTaskResult>();
-        thread::spawn(move || {
-            let result = core
-                .upgrade_kernel_binary()
-                .and_then(|info| {
-                    core.verify_kernel_binary()?;
-                    Ok(CoreTaskOutput::Upgraded(info))
-                })
-                .map_err(|error| error.to_string());
-            let _ = tx.send(result);
+        self.start_core_task(cx, CoreTaskKind::Upgrading, |core| {
+            core.upgrade_kernel_binary().map(CoreTaskOutput::Upgraded)
         });
+    }
 
-        if !self.core_task_timer.is_empty() {
-            cx.stop_timer(self.core_task_timer);
-        }
-        self.core_task_rx = Some(rx);
-        self.core_task_kind = Some(CoreTaskKind::Upgrading);
-        self.core_task_timer = cx.start_interval(0.1);
-        self.refresh_ui(cx);
+    fn start_core_rollback(&mut self, cx: &mut Cx) {
+        info!("core rollback requested");
+        self.start_core_task(cx, CoreTaskKind::RollingBack, |core| {
+            core.rollback_kernel_binary()
+                .map(CoreTaskOutput::RolledBack)
+        });
     }
 
     fn start_core_restart(&mut self, cx: &mut Cx) {
+        info!("core restart requested");
+        self.start_core_task(cx, CoreTaskKind::Restarting, |core| {
+            core.restart_kernel_runtime()
+                .map(|_| CoreTaskOutput::Restarted)
+        });
+    }
+
+    /// Runs `task` on a worker thread; [`App::poll_core_task`] picks up the
+    /// result. Only one core task runs at a time.
+    fn start_core_task(
+        &mut self,
+        cx: &mut Cx,
+        kind: CoreTaskKind,
+        task: impl FnOnce(&LinkpadCore) -> CoreResult<CoreTaskOutput> + Send + 'static,
+    ) {
         if self.core_task_rx.is_some() {
-            warn!("skip core restart: task already running");
+            warn!("skip core task {kind:?}: task already running");
             return;
         }
-        info!("core restart requested");
 
         let core = self.core.clone();
         let (tx, rx) = std::sync::mpsc::channel::<CoreTaskResult>();
         thread::spawn(move || {
-            let result = core
-                .restart_kernel_runtime()
-                .map(|_| CoreTaskOutput::Restarted)
-                .map_err(|error| error.to_string());
+            let result = task(&core).map_err(|error| error.to_string());
             let _ = tx.send(result);
         });
 
@@ -1532,11 +2145,54 @@ impl App {
             cx.stop_timer(self.core_task_timer);
         }
         self.core_task_rx = Some(rx);
-        self.core_task_kind = Some(CoreTaskKind::Restarting);
+        self.core_task_kind = Some(kind);
         self.core_task_timer = cx.start_interval(0.1);
         self.refresh_ui(cx);
     }
 
+    fn poll_core_events(&mut self, cx: &mut Cx) {
+        let events = self.core.drain_events();
+        if events.is_empty() {
+            return;
+        }
+        for event in events {
+            match event {
+                CoreEvent::ProfileRefreshed { name, changed, .. } => {
+                    self.handle_scheduled_profile_refresh(cx, &name, changed);
+                }
+                CoreEvent::ProfileRefreshFailed { name, error, .. } => {
+                    self.handle_scheduled_profile_refresh_failure(cx, &name, &error);
+                }
+                CoreEvent::KernelExited { status, log_tail } => {
+                    self.handle_kernel_exited(cx, &status, &log_tail);
+                }
+                CoreEvent::KernelRestarted { attempts } => {
+                    self.handle_kernel_restarted(cx, attempts);
+                }
+                CoreEvent::KernelRecoveryFailed {
+                    error,
+                    system_proxy_disabled,
+                } => {
+                    self.handle_kernel_recovery_failed(cx, &error, system_proxy_disabled);
+                }
+                CoreEvent::GeoDataUpdated { kind, version } => {
+                    info!(
+                        "geodata updated by scheduler: {} version={version:?}",
+                        kind.file_name()
+                    );
+                    self.state.geodata_status = self.core.geodata_status();
+                }
+                CoreEvent::GeoDataUpdateFailed { kind, error } => {
+                    self.handle_geodata_update_failed(cx, kind, &error);
+                }
+                CoreEvent::SystemProxyDrifted { reapplied } => {
+                    self.handle_system_proxy_drifted(cx, reapplied);
+                }
+            }
+        }
+        self.refresh_ui(cx);
+    }
+
     fn poll_core_task(&mut self, cx: &mut Cx) {
         let Some(core_task_rx) = self.core_task_rx.as_ref() else {
             return;
@@ -1567,22 +2223,101 @@ impl App {
                     ),
                 );
             }
-            Ok(CoreTaskOutput::Restarted) => {
+            Ok(CoreTaskOutput::RolledBack(version)) => {
                 self.sync_from_core();
-                info!("core restart succeeded");
+                info!("core rollback succeeded: version={version:?}");
                 self.push_notification(
                     cx,
                     NotificationLevel::Success,
-                    strings.clash_core_restart_success.to_string(),
+                    format!(
+                        "{}: {}",
+                        strings.clash_core_rollback_success_prefix,
+                        version
+                            .as_deref()
+                            .unwrap_or(strings.clash_core_rollback_unmanaged)
+                    ),
                 );
             }
-            Err(error) => {
-                error!("core task failed: {error}");
+            Ok(CoreTaskOutput::GeoDataUpdated(statuses)) => {
+                self.state.geodata_status = self.core.geodata_status();
+                let files = statuses
+                    .iter()
+                    .map(|status| status.kind.file_name())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                info!("geodata task succeeded: {files}");
+                self.push_notification(
+                    cx,
+                    NotificationLevel::Success,
+                    format!("{}: {files}", strings.geodata_update_success_prefix),
+                );
+            }
+            Ok(CoreTaskOutput::RuleMatched(found)) => {
+                self.apply_rule_match(found);
+            }
+            Ok(CoreTaskOutput::PortSaved(port)) => {
+                self.apply_saved_port(cx, port);
+            }
+            Ok(CoreTaskOutput::KernelSwitched(kind)) => {
+                self.apply_kernel_switch(cx, kind);
+            }
+            Ok(CoreTaskOutput::SystemProxyToggled(on)) => {
+                self.apply_system_proxy_toggle(cx, on);
+            }
+            Ok(CoreTaskOutput::ProfileActivated) => {
+                self.apply_profile_activated();
+            }
+            Ok(CoreTaskOutput::ProfileRefreshed) => {
+                self.apply_profile_refreshed(cx);
+            }
+            Ok(CoreTaskOutput::Restarted) => {
+                self.sync_from_core();
+                info!("core restart succeeded");
+                self.push_notification(
+                    cx,
+                    NotificationLevel::Success,
+                    strings.clash_core_restart_success.to_string(),
+                );
+            }
+            Err(error) => {
+                error!("core task failed: {error}");
                 let prefix = match task_kind {
                     Some(CoreTaskKind::Upgrading) => strings.clash_core_upgrade_failed_prefix,
+                    Some(CoreTaskKind::RollingBack) => strings.clash_core_rollback_failed_prefix,
+                    Some(CoreTaskKind::Installing) => strings.clash_core_install_failed_prefix,
                     Some(CoreTaskKind::Restarting) => strings.clash_core_restart_failed_prefix,
+                    Some(CoreTaskKind::UpdatingGeoData) => strings.geodata_update_failed_prefix,
+                    Some(CoreTaskKind::ImportingGeoData) => strings.geodata_import_failed_prefix,
+                    Some(CoreTaskKind::MatchingRule) => strings.rules_match_failed_prefix,
+                    Some(CoreTaskKind::SavingPort) => strings.clash_port_update_failed_prefix,
+                    Some(CoreTaskKind::SwitchingKernel) => {
+                        strings.clash_kernel_update_failed_prefix
+                    }
+                    Some(CoreTaskKind::EnablingSystemProxy) => {
+                        strings.system_proxy_enable_failed_prefix
+                    }
+                    Some(CoreTaskKind::DisablingSystemProxy) => {
+                        strings.system_proxy_disable_failed_prefix
+                    }
+                    Some(CoreTaskKind::ActivatingProfile) => {
+                        strings.profiles_activate_failed_prefix
+                    }
+                    Some(CoreTaskKind::RefreshingProfile) => strings.profiles_refresh_failed_prefix,
                     None => strings.clash_core_upgrade_failed_prefix,
                 };
+                if matches!(
+                    task_kind,
+                    Some(CoreTaskKind::ActivatingProfile | CoreTaskKind::RefreshingProfile)
+                ) {
+                    self.set_import_status_error(error.clone());
+                }
+                if matches!(
+                    task_kind,
+                    Some(CoreTaskKind::EnablingSystemProxy | CoreTaskKind::DisablingSystemProxy)
+                ) {
+                    self.state.system_proxy_enabled = self.core.is_system_proxy_enabled();
+                    self.persist_settings();
+                }
                 self.push_notification(cx, NotificationLevel::Error, format!("{prefix}: {error}"));
             }
         }
@@ -1636,6 +2371,27 @@ impl App {
         );
     }
 
+    fn apply_input_theme(&mut self, cx: &mut Cx, id: &[LiveId; 2], palette: ThemePalette) {
+        self.ui.widget(id).apply_over(
+            cx,
+            live! {
+                draw_bg: {
+                    bg_color: (palette.panel_bg),
+                    bg_color_hover: (palette.panel_bg),
+                    bg_color_focus: (palette.panel_bg),
+                    border_color: (palette.border_color),
+                    border_color_hover: (palette.border_color),
+                    border_color_focus: (palette.menu_active_bg)
+                }
+                draw_text: {
+                    color: (palette.text_primary),
+                    color_empty: (palette.text_muted)
+                }
+                draw_cursor: { color: (palette.menu_active_bg) }
+            },
+        );
+    }
+
     fn apply_theme_palette(&mut self, cx: &mut Cx) {
         let palette = self.theme_palette();
 
@@ -1726,6 +2482,15 @@ impl App {
                 draw_bg: { color: (palette.panel_alt_bg) }
             },
         );
+        self.ui.widget(ids!(dashboard.overview_card)).apply_over(
+            cx,
+            live! {
+                draw_bg: { color: (palette.panel_alt_bg) }
+            },
+        );
+        self.apply_overview_palette(cx, palette);
+        self.apply_connections_palette(cx, palette);
+        self.apply_logs_palette(cx, palette);
 
         self.ui.view(ids!(dashboard.profile_row_1)).apply_over(
             cx,
@@ -1754,66 +2519,39 @@ impl App {
             );
         }
 
-        self.ui
-            .widget(ids!(dashboard.profile_url_input))
-            .apply_over(
-                cx,
-                live! {
-                    draw_bg: {
-                        bg_color: (palette.panel_bg),
-                        bg_color_hover: (palette.panel_bg),
-                        bg_color_focus: (palette.panel_bg),
-                        border_color: (palette.border_color),
-                        border_color_hover: (palette.border_color),
-                        border_color_focus: (palette.menu_active_bg)
-                    }
-                    draw_text: {
-                        color: (palette.text_primary),
-                        color_empty: (palette.text_muted)
-                    }
-                    draw_cursor: { color: (palette.menu_active_bg) }
-                },
-            );
+        self.apply_input_theme(cx, ids!(dashboard.profile_url_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.profile_file_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.profile_text_name_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.profile_text_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.profile_row_1_interval_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.profile_row_2_interval_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.profile_row_3_interval_input), palette);
         self.apply_dropdown_theme(cx, ids!(dashboard.language_dropdown), palette);
         self.apply_dropdown_theme(cx, ids!(dashboard.theme_dropdown), palette);
-        self.ui.widget(ids!(dashboard.clash_port_input)).apply_over(
-            cx,
-            live! {
-                draw_bg: {
-                    bg_color: (palette.panel_bg),
-                    bg_color_hover: (palette.panel_bg),
-                    bg_color_focus: (palette.panel_bg),
-                    border_color: (palette.border_color),
-                    border_color_hover: (palette.border_color),
-                    border_color_focus: (palette.menu_active_bg)
-                }
-                draw_text: {
-                    color: (palette.text_primary),
-                    color_empty: (palette.text_muted)
-                }
-                draw_cursor: { color: (palette.menu_active_bg) }
-            },
-        );
-        self.ui
-            .widget(ids!(dashboard.rules_search_input))
-            .apply_over(
-                cx,
-                live! {
-                    draw_bg: {
-                        bg_color: (palette.panel_bg),
-                        bg_color_hover: (palette.panel_bg),
-                        bg_color_focus: (palette.panel_bg),
-                        border_color: (palette.border_color),
-                        border_color_hover: (palette.border_color),
-                        border_color_focus: (palette.menu_active_bg)
-                    }
-                    draw_text: {
-                        color: (palette.text_primary),
-                        color_empty: (palette.text_muted)
-                    }
-                    draw_cursor: { color: (palette.menu_active_bg) }
-                },
-            );
+        self.apply_dropdown_theme(cx, ids!(dashboard.clash_kernel_dropdown), palette);
+        self.apply_dropdown_theme(cx, ids!(dashboard.clash_channel_dropdown), palette);
+        self.apply_dropdown_theme(cx, ids!(dashboard.proxy_mode_dropdown), palette);
+        self.apply_dropdown_theme(cx, ids!(dashboard.geodata_import_dropdown), palette);
+        self.apply_dropdown_theme(cx, ids!(dashboard.rules_kind_dropdown), palette);
+        self.apply_dropdown_theme(cx, ids!(dashboard.rules_target_dropdown), palette);
+        self.apply_input_theme(cx, ids!(dashboard.clash_port_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.proxy_bypass_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.proxy_http_port_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.proxy_https_port_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.proxy_socks_port_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.proxy_pac_port_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.proxy_pac_direct_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.clash_channel_pinned_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.clash_mirrors_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.clash_core_file_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.geodata_geoip_url_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.geodata_geosite_url_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.geodata_asn_url_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.geodata_interval_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.geodata_file_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.rules_search_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.rules_match_input), palette);
+        self.apply_input_theme(cx, ids!(dashboard.rules_match_process_input), palette);
 
         self.ui.label(ids!(sidebar.brand)).apply_over(
             cx,
@@ -1827,6 +2565,12 @@ impl App {
                 draw_text: { color: (palette.text_primary) }
             },
         );
+        self.ui.label(ids!(header.traffic_label)).apply_over(
+            cx,
+            live! {
+                draw_text: { color: (palette.text_muted) }
+            },
+        );
 
         self.ui
             .label(ids!(dashboard.basic_setting_title))
@@ -1852,6 +2596,14 @@ impl App {
                     draw_text: { color: (palette.text_primary) }
                 },
             );
+        self.ui
+            .label(ids!(dashboard.geodata_setting_title))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
         self.ui.label(ids!(dashboard.language_label)).apply_over(
             cx,
             live! {
@@ -1864,6 +2616,20 @@ impl App {
                 draw_text: { color: (palette.text_primary) }
             },
         );
+        self.ui.label(ids!(dashboard.rules_kind_label)).apply_over(
+            cx,
+            live! {
+                draw_text: { color: (palette.text_primary) }
+            },
+        );
+        self.ui
+            .label(ids!(dashboard.rules_target_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
         self.ui
             .label(ids!(dashboard.close_to_tray_label))
             .apply_over(
@@ -1880,6 +2646,64 @@ impl App {
                     draw_text: { color: (palette.text_primary) }
                 },
             );
+        self.ui
+            .label(ids!(dashboard.proxy_bypass_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+        self.ui.label(ids!(dashboard.proxy_ports_label)).apply_over(
+            cx,
+            live! {
+                draw_text: { color: (palette.text_primary) }
+            },
+        );
+        self.ui
+            .label(ids!(dashboard.proxy_settings_hint))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_muted) }
+                },
+            );
+        self.ui.label(ids!(dashboard.proxy_mode_label)).apply_over(
+            cx,
+            live! {
+                draw_text: { color: (palette.text_primary) }
+            },
+        );
+        self.ui
+            .label(ids!(dashboard.proxy_pac_direct_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+        self.ui.label(ids!(dashboard.proxy_pac_hint)).apply_over(
+            cx,
+            live! {
+                draw_text: { color: (palette.text_muted) }
+            },
+        );
+        self.ui
+            .label(ids!(dashboard.proxy_restore_on_drift_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+        self.ui
+            .label(ids!(dashboard.proxy_shell_env_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
         self.ui.label(ids!(dashboard.auto_launch_label)).apply_over(
             cx,
             live! {
@@ -1900,6 +2724,38 @@ impl App {
                 draw_text: { color: (palette.text_primary) }
             },
         );
+        self.ui
+            .label(ids!(dashboard.clash_kernel_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+        self.ui
+            .label(ids!(dashboard.clash_channel_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+        self.ui
+            .label(ids!(dashboard.clash_mirrors_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+        self.ui
+            .label(ids!(dashboard.clash_core_file_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
         self.ui
             .label(ids!(dashboard.clash_core_version_label))
             .apply_over(
@@ -1916,6 +2772,22 @@ impl App {
                     draw_text: { color: (palette.text_muted) }
                 },
             );
+        self.ui
+            .label(ids!(dashboard.clash_core_previous_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+        self.ui
+            .label(ids!(dashboard.clash_core_previous_value))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_muted) }
+                },
+            );
         self.ui
             .label(ids!(dashboard.clash_core_path_label))
             .apply_over(
@@ -1932,6 +2804,96 @@ impl App {
                     draw_text: { color: (palette.text_muted) }
                 },
             );
+        self.ui
+            .label(ids!(dashboard.clash_override_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+        self.ui
+            .label(ids!(dashboard.clash_override_hint))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_muted) }
+                },
+            );
+        self.ui
+            .label(ids!(dashboard.clash_override_value))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_muted) }
+                },
+            );
+        self.ui.label(ids!(dashboard.geodata_hint)).apply_over(
+            cx,
+            live! {
+                draw_text: { color: (palette.text_muted) }
+            },
+        );
+        self.ui
+            .label(ids!(dashboard.geodata_geoip_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+        self.ui
+            .label(ids!(dashboard.geodata_geoip_value))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_muted) }
+                },
+            );
+        self.ui
+            .label(ids!(dashboard.geodata_geosite_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+        self.ui
+            .label(ids!(dashboard.geodata_geosite_value))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_muted) }
+                },
+            );
+        self.ui.label(ids!(dashboard.geodata_asn_label)).apply_over(
+            cx,
+            live! {
+                draw_text: { color: (palette.text_primary) }
+            },
+        );
+        self.ui.label(ids!(dashboard.geodata_asn_value)).apply_over(
+            cx,
+            live! {
+                draw_text: { color: (palette.text_muted) }
+            },
+        );
+        self.ui
+            .label(ids!(dashboard.geodata_interval_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+        self.ui
+            .label(ids!(dashboard.geodata_import_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
 
         self.ui.label(ids!(dashboard.profile_url_label)).apply_over(
             cx,
@@ -1939,6 +2901,14 @@ impl App {
                 draw_text: { color: (palette.text_primary) }
             },
         );
+        self.ui
+            .label(ids!(dashboard.profile_local_label))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
         self.ui
             .label(ids!(dashboard.current_profile_title))
             .apply_over(
@@ -2180,6 +3150,14 @@ impl App {
                 draw_text: { color: (palette.text_muted) }
             },
         );
+        self.ui
+            .label(ids!(dashboard.rules_match_result))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
     }
 
     fn apply_menu_button_style(
@@ -2276,6 +3254,12 @@ impl App {
 
     fn update_menu_buttons(&mut self, cx: &mut Cx) {
         let palette = self.theme_palette();
+        self.apply_menu_button_style(
+            cx,
+            ids!(sidebar.menu_overview),
+            self.state.active_page == Page::Overview,
+            palette,
+        );
         self.apply_menu_button_style(
             cx,
             ids!(sidebar.menu_profiles),
@@ -2294,6 +3278,18 @@ impl App {
             self.state.active_page == Page::Rules,
             palette,
         );
+        self.apply_menu_button_style(
+            cx,
+            ids!(sidebar.menu_connections),
+            self.state.active_page == Page::Connections,
+            palette,
+        );
+        self.apply_menu_button_style(
+            cx,
+            ids!(sidebar.menu_logs),
+            self.state.active_page == Page::Logs,
+            palette,
+        );
         self.apply_menu_button_style(
             cx,
             ids!(sidebar.menu_settings),
@@ -2307,6 +3303,9 @@ impl App {
             return;
         }
         self.state.active_page = page;
+        if page == Page::Connections {
+            self.state.connections = self.core.connections();
+        }
         self.ui
             .view(ids!(dashboard.content_body))
             .set_scroll_pos(cx, dvec2(0.0, 0.0));
@@ -2321,6 +3320,7 @@ impl MatchEvent for App {
         self.silent_start_applied = false;
         info!("linkpad startup begin");
         self.load_persisted_settings();
+        self.recover_system_proxy(cx);
         let _ = self.core.configure_startup(
             self.state.auto_launch_enabled,
             self.state.silent_start_enabled,
@@ -2332,6 +3332,15 @@ impl MatchEvent for App {
         self.sync_from_core();
         self.persist_profiles();
         self.set_import_status_ready();
+        self.notify_subscription_warnings(cx);
+        self.core.start_refresh_scheduler();
+        self.core.start_geodata_updater();
+        self.core.start_traffic_monitor();
+        self.core.start_connection_tracker();
+        self.core.start_log_tail();
+        self.core.start_supervisor();
+        self.core.start_system_proxy_watchdog();
+        self.core_event_timer = cx.start_interval(1.0);
         self.install_shell_integrations();
         self.apply_silent_start_visibility(cx);
         info!(
@@ -2360,6 +3369,12 @@ impl MatchEvent for App {
         if self.core_task_timer.is_timer(event).is_some() {
             self.poll_core_task(cx);
         }
+        if self.core_event_timer.is_timer(event).is_some() {
+            self.poll_core_events(cx);
+            self.poll_traffic(cx);
+            self.poll_connections(cx);
+            self.poll_logs(cx);
+        }
     }
 
     fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions) {
@@ -2372,6 +3387,13 @@ impl MatchEvent for App {
             }
         }
 
+        if self
+            .ui
+            .mp_button(ids!(sidebar.menu_overview))
+            .clicked(actions)
+        {
+            self.switch_page(cx, Page::Overview);
+        }
         if self
             .ui
             .mp_button(ids!(sidebar.menu_profiles))
@@ -2389,6 +3411,16 @@ impl MatchEvent for App {
         if self.ui.mp_button(ids!(sidebar.menu_rules)).clicked(actions) {
             self.switch_page(cx, Page::Rules);
         }
+        if self
+            .ui
+            .mp_button(ids!(sidebar.menu_connections))
+            .clicked(actions)
+        {
+            self.switch_page(cx, Page::Connections);
+        }
+        if self.ui.mp_button(ids!(sidebar.menu_logs)).clicked(actions) {
+            self.switch_page(cx, Page::Logs);
+        }
         if self
             .ui
             .mp_button(ids!(sidebar.menu_settings))
@@ -2400,6 +3432,8 @@ impl MatchEvent for App {
         self.handle_profiles_actions(cx, actions);
         self.handle_proxy_groups_actions(cx, actions);
         self.handle_rules_actions(cx, actions);
+        self.handle_connections_actions(cx, actions);
+        self.handle_logs_actions(cx, actions);
         self.handle_settings_actions(cx, actions);
     }
 }
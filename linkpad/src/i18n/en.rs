@@ -2,14 +2,26 @@ use super::Strings;
 
 pub const EN: Strings = Strings {
     app_name: "Linkpad",
+    menu_overview: "Overview",
     menu_profiles: "Profiles",
     menu_proxy_groups: "Proxy Groups",
     menu_rules: "Rules",
+    menu_connections: "Connections",
+    menu_logs: "Logs",
     menu_settings: "Settings",
     tray_outbound_modes: "Outbound Modes",
     tray_profiles: "Profiles",
     tray_system_proxy: "System Proxy",
+    tray_copy_proxy_command: "Copy Proxy Command",
     tray_exit: "Exit",
+    overview_title: "Overview",
+    overview_desc: "Live traffic and memory use of the running kernel.",
+    overview_traffic_title: "Throughput",
+    overview_download_label: "Download",
+    overview_upload_label: "Upload",
+    overview_memory_label: "Memory",
+    overview_peak_label: "Peak (2 min)",
+    overview_disconnected: "Waiting for the kernel. Traffic appears once it is running.",
     profiles_title: "Profiles",
     profiles_desc: "Manage subscription profiles, local configs, and sync sources.",
     profiles_import_title: "Import Profile URL",
@@ -34,6 +46,29 @@ pub const EN: Strings = Strings {
     profiles_import_loading: "Importing profile URL...",
     profiles_import_success: "Profile imported successfully.",
     profiles_import_error: "Import failed: invalid profile URL.",
+    profiles_import_local_label: "Local File or Pasted Content",
+    profiles_import_file_placeholder: "/path/to/config.yaml",
+    profiles_import_file_button: "Import File",
+    profiles_import_file_error: "Import failed: enter a profile file path.",
+    profiles_import_text_name_placeholder: "Profile name (optional)",
+    profiles_import_text_placeholder: "Paste Clash YAML or share links",
+    profiles_import_text_button: "Import Text",
+    profiles_import_text_error: "Import failed: paste profile content first.",
+    profiles_source_inline: "Pasted content",
+    profiles_usage_used: "Used",
+    profiles_usage_unlimited: "Unlimited",
+    profiles_usage_expires: "Expires",
+    profiles_usage_expired: "Expired",
+    profiles_quota_warning_prefix: "Subscription is almost out of traffic",
+    profiles_expiry_warning_prefix: "Subscription expires soon",
+    profiles_expired_warning_prefix: "Subscription has expired",
+    profiles_activate_failed_prefix: "Failed to switch profile",
+    profiles_refresh_failed_prefix: "Failed to refresh profile",
+    profiles_auto_update_prefix: "Auto update",
+    profiles_auto_update_off: "Off",
+    profiles_interval_placeholder: "Hours",
+    profiles_scheduled_refresh_success_prefix: "Profile updated",
+    profiles_scheduled_refresh_failed_prefix: "Profile refresh failed",
     proxy_groups_title: "Proxy Groups",
     proxy_groups_desc: "Groups extracted from the active profile.",
     proxy_groups_empty: "No proxy groups in active profile.",
@@ -73,9 +108,53 @@ pub const EN: Strings = Strings {
     rules_count_prefix: "Total rules",
     rules_search_placeholder: "Search rules",
     rules_filter_all: "All",
-    rules_filter_domain: "Domain",
-    rules_filter_ip_cidr: "IP-CIDR",
-    rules_filter_process_name: "Process",
+    rules_filter_type_label: "Type",
+    rules_filter_target_label: "Policy",
+    rules_diagnostics_prefix: "Skipped invalid rules",
+    rules_match_placeholder: "Test a host or IP, e.g. example.com:443",
+    rules_match_process_placeholder: "Process (optional)",
+    rules_match_btn: "Test",
+    rules_matching_btn: "Testing...",
+    rules_match_prefix: "Matched rule",
+    rules_match_none: "No rule matches this host.",
+    rules_match_skipped_prefix: "Not evaluated locally",
+    rules_match_failed_prefix: "Rule test failed",
+    connections_title: "Connections",
+    connections_desc: "Connections going through the kernel right now.",
+    connections_search_placeholder: "Search host, process, rule or chain",
+    connections_sort_newest: "Newest",
+    connections_sort_download: "Most downloaded",
+    connections_sort_upload: "Most uploaded",
+    connections_sort_host: "Host",
+    connections_show_closed: "Closed",
+    connections_show_active: "Active",
+    connections_close_button: "Close",
+    connections_close_all_button: "Close All",
+    connections_summary_active: "Active",
+    connections_summary_closed: "Closed",
+    connections_summary_total: "Total",
+    connections_empty: "No connections.",
+    connections_closed_empty: "No closed connections yet.",
+    connections_disconnected: "Kernel is not running.",
+    connections_overflow_prefix: "Not shown",
+    connections_close_failed_prefix: "Failed to close connection",
+    logs_title: "Kernel Logs",
+    logs_desc: "Output of the mihomo kernel, newest last.",
+    logs_search_placeholder: "Filter by keyword",
+    logs_level_debug: "Debug and above",
+    logs_level_info: "Info and above",
+    logs_level_warning: "Warnings and errors",
+    logs_level_error: "Errors only",
+    logs_pause_button: "Pause",
+    logs_resume_button: "Resume",
+    logs_clear_button: "Clear",
+    logs_export_button: "Export",
+    logs_empty: "No log entries.",
+    logs_paused: "Paused",
+    logs_shown_prefix: "Shown",
+    logs_file_prefix: "Log file",
+    logs_export_success_prefix: "Log exported to",
+    logs_export_failed_prefix: "Failed to export log",
     settings_title: "Settings",
     settings_desc: "App preferences, network options, and system integration.",
     basic_setting_title: "Linkpad Basic Setting",
@@ -90,6 +169,8 @@ pub const EN: Strings = Strings {
     clash_port_label: "Port Config",
     clash_core_version_label: "Clash Core Version",
     clash_core_path_label: "Clash Core Path",
+    clash_override_label: "Config Overrides",
+    clash_override_hint: "Merged into the runtime config on the next kernel start.",
     clash_core_not_found: "Not Found",
     clash_core_installed_unknown_version: "Installed (version unknown)",
     clash_core_upgrade_button: "UPGRADE",
@@ -100,6 +181,39 @@ pub const EN: Strings = Strings {
     clash_port_update_success: "Clash port updated.",
     clash_port_update_invalid: "Invalid port. Use a value between 1 and 65535.",
     clash_port_update_failed_prefix: "Failed to update clash port",
+    clash_kernel_label: "Kernel",
+    clash_kernel_update_success: "Kernel switched.",
+    clash_kernel_update_failed_prefix: "Failed to switch kernel",
+    clash_channel_label: "Update Channel",
+    clash_core_previous_label: "Previous Version",
+    clash_core_rollback_button: "ROLLBACK",
+    clash_core_rolling_back_button: "ROLLING BACK...",
+    clash_core_rollback_success_prefix: "Kernel rolled back to",
+    clash_core_rollback_unmanaged: "the kernel found on this system",
+    clash_core_rollback_failed_prefix: "Failed to roll back kernel",
+    clash_mirrors_label: "Download Mirrors",
+    clash_mirrors_save_success: "Download mirrors saved.",
+    clash_core_file_label: "Install From File",
+    clash_core_install_file_button: "INSTALL",
+    clash_core_installing_button: "INSTALLING...",
+    clash_core_install_file_empty: "Enter the path of a downloaded kernel archive.",
+    clash_core_install_failed_prefix: "Failed to install kernel",
+    geodata_setting_title: "Geo Data",
+    geodata_hint: "Checked on startup and downloaded again once older than the update interval; the kernel reads new files after its next restart.",
+    geodata_missing: "Missing",
+    geodata_age_suffix: "ago",
+    geodata_update_button: "UPDATE",
+    geodata_updating_button: "UPDATING...",
+    geodata_interval_label: "Update Interval (hours)",
+    geodata_interval_invalid: "Invalid interval. Use whole hours, or leave it empty to only fetch missing files.",
+    geodata_settings_save_success: "Geo data settings saved.",
+    geodata_import_label: "Import File",
+    geodata_import_button: "IMPORT",
+    geodata_importing_button: "IMPORTING...",
+    geodata_import_file_empty: "Enter the path of a geodata file.",
+    geodata_update_success_prefix: "Geo data updated",
+    geodata_update_failed_prefix: "Failed to update geo data",
+    geodata_import_failed_prefix: "Failed to import geo data",
     auto_launch_update_failed_prefix: "Failed to update auto launch",
     silent_start_update_failed_prefix: "Failed to update silent start",
     clash_core_upgrade_success_prefix: "Clash core upgraded",
@@ -110,4 +224,27 @@ pub const EN: Strings = Strings {
     system_proxy_disable_success: "System proxy disabled globally.",
     system_proxy_enable_failed_prefix: "Failed to enable system proxy",
     system_proxy_disable_failed_prefix: "Failed to disable system proxy",
+    proxy_bypass_label: "Bypass",
+    proxy_ports_label: "Proxy Ports",
+    proxy_mode_label: "Proxy Mode",
+    proxy_pac_direct_label: "PAC Direct",
+    proxy_pac_hint: "PAC mode serves a script on the PAC port (empty for 7899) that sends the profile's DIRECT domain and IP rules, the bypass list and the PAC Direct entries straight out. It is rebuilt whenever the profile changes.",
+    proxy_restore_on_drift_label: "Restore when changed elsewhere",
+    proxy_shell_env_label: "Terminal proxy",
+    proxy_copy_command_button: "Copy Proxy Command",
+    proxy_command_copied: "Proxy command copied; paste it into a terminal",
+    shell_env_enabled: "Terminals opened from now on use the system proxy",
+    shell_env_disabled: "Terminal proxy removed from the shell startup files",
+    shell_env_failed_prefix: "Failed to update shell startup files",
+    system_proxy_recovered: "System proxy settings from the last unclean exit were restored",
+    system_proxy_drift_reapplied: "Another program changed the system proxy; it was set again",
+    system_proxy_drift_reported: "Another program changed the system proxy settings",
+    proxy_settings_hint: "Bypass takes comma-separated hosts, *.domains and CIDRs. Empty ports use the mixed port; HTTPS falls back to the HTTP port. A set port opens its own listener.",
+    proxy_settings_save_success: "System proxy settings saved.",
+    proxy_settings_invalid_port: "Invalid port. Use a value between 1 and 65535, or leave it empty.",
+    proxy_settings_save_failed_prefix: "Failed to save system proxy settings",
+    kernel_exited_prefix: "Kernel exited unexpectedly, restarting",
+    kernel_restarted: "Kernel restarted",
+    kernel_recovery_failed_prefix: "Kernel could not be restarted",
+    kernel_recovery_proxy_disabled: "System proxy has been disabled.",
 };
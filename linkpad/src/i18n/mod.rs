@@ -8,14 +8,26 @@ use zh::ZH;
 
 pub struct Strings {
     pub app_name: &'static str,
+    pub menu_overview: &'static str,
     pub menu_profiles: &'static str,
     pub menu_proxy_groups: &'static str,
     pub menu_rules: &'static str,
+    pub menu_connections: &'static str,
+    pub menu_logs: &'static str,
     pub menu_settings: &'static str,
     pub tray_outbound_modes: &'static str,
     pub tray_profiles: &'static str,
     pub tray_system_proxy: &'static str,
+    pub tray_copy_proxy_command: &'static str,
     pub tray_exit: &'static str,
+    pub overview_title: &'static str,
+    pub overview_desc: &'static str,
+    pub overview_traffic_title: &'static str,
+    pub overview_download_label: &'static str,
+    pub overview_upload_label: &'static str,
+    pub overview_memory_label: &'static str,
+    pub overview_peak_label: &'static str,
+    pub overview_disconnected: &'static str,
     pub profiles_title: &'static str,
     pub profiles_desc: &'static str,
     pub profiles_import_title: &'static str,
@@ -40,6 +52,29 @@ pub struct Strings {
     pub profiles_import_loading: &'static str,
     pub profiles_import_success: &'static str,
     pub profiles_import_error: &'static str,
+    pub profiles_import_local_label: &'static str,
+    pub profiles_import_file_placeholder: &'static str,
+    pub profiles_import_file_button: &'static str,
+    pub profiles_import_file_error: &'static str,
+    pub profiles_import_text_name_placeholder: &'static str,
+    pub profiles_import_text_placeholder: &'static str,
+    pub profiles_import_text_button: &'static str,
+    pub profiles_import_text_error: &'static str,
+    pub profiles_source_inline: &'static str,
+    pub profiles_usage_used: &'static str,
+    pub profiles_usage_unlimited: &'static str,
+    pub profiles_usage_expires: &'static str,
+    pub profiles_usage_expired: &'static str,
+    pub profiles_quota_warning_prefix: &'static str,
+    pub profiles_expiry_warning_prefix: &'static str,
+    pub profiles_expired_warning_prefix: &'static str,
+    pub profiles_activate_failed_prefix: &'static str,
+    pub profiles_refresh_failed_prefix: &'static str,
+    pub profiles_auto_update_prefix: &'static str,
+    pub profiles_auto_update_off: &'static str,
+    pub profiles_interval_placeholder: &'static str,
+    pub profiles_scheduled_refresh_success_prefix: &'static str,
+    pub profiles_scheduled_refresh_failed_prefix: &'static str,
     pub proxy_groups_title: &'static str,
     pub proxy_groups_desc: &'static str,
     pub proxy_groups_empty: &'static str,
@@ -79,9 +114,53 @@ pub struct Strings {
     pub rules_count_prefix: &'static str,
     pub rules_search_placeholder: &'static str,
     pub rules_filter_all: &'static str,
-    pub rules_filter_domain: &'static str,
-    pub rules_filter_ip_cidr: &'static str,
-    pub rules_filter_process_name: &'static str,
+    pub rules_filter_type_label: &'static str,
+    pub rules_filter_target_label: &'static str,
+    pub rules_diagnostics_prefix: &'static str,
+    pub rules_match_placeholder: &'static str,
+    pub rules_match_process_placeholder: &'static str,
+    pub rules_match_btn: &'static str,
+    pub rules_matching_btn: &'static str,
+    pub rules_match_prefix: &'static str,
+    pub rules_match_none: &'static str,
+    pub rules_match_skipped_prefix: &'static str,
+    pub rules_match_failed_prefix: &'static str,
+    pub connections_title: &'static str,
+    pub connections_desc: &'static str,
+    pub connections_search_placeholder: &'static str,
+    pub connections_sort_newest: &'static str,
+    pub connections_sort_download: &'static str,
+    pub connections_sort_upload: &'static str,
+    pub connections_sort_host: &'static str,
+    pub connections_show_closed: &'static str,
+    pub connections_show_active: &'static str,
+    pub connections_close_button: &'static str,
+    pub connections_close_all_button: &'static str,
+    pub connections_summary_active: &'static str,
+    pub connections_summary_closed: &'static str,
+    pub connections_summary_total: &'static str,
+    pub connections_empty: &'static str,
+    pub connections_closed_empty: &'static str,
+    pub connections_disconnected: &'static str,
+    pub connections_overflow_prefix: &'static str,
+    pub connections_close_failed_prefix: &'static str,
+    pub logs_title: &'static str,
+    pub logs_desc: &'static str,
+    pub logs_search_placeholder: &'static str,
+    pub logs_level_debug: &'static str,
+    pub logs_level_info: &'static str,
+    pub logs_level_warning: &'static str,
+    pub logs_level_error: &'static str,
+    pub logs_pause_button: &'static str,
+    pub logs_resume_button: &'static str,
+    pub logs_clear_button: &'static str,
+    pub logs_export_button: &'static str,
+    pub logs_empty: &'static str,
+    pub logs_paused: &'static str,
+    pub logs_shown_prefix: &'static str,
+    pub logs_file_prefix: &'static str,
+    pub logs_export_success_prefix: &'static str,
+    pub logs_export_failed_prefix: &'static str,
     pub settings_title: &'static str,
     pub settings_desc: &'static str,
     pub basic_setting_title: &'static str,
@@ -96,6 +175,8 @@ pub struct Strings {
     pub clash_port_label: &'static str,
     pub clash_core_version_label: &'static str,
     pub clash_core_path_label: &'static str,
+    pub clash_override_label: &'static str,
+    pub clash_override_hint: &'static str,
     pub clash_core_not_found: &'static str,
     pub clash_core_installed_unknown_version: &'static str,
     pub clash_core_upgrade_button: &'static str,
@@ -106,6 +187,39 @@ pub struct Strings {
     pub clash_port_update_success: &'static str,
     pub clash_port_update_invalid: &'static str,
     pub clash_port_update_failed_prefix: &'static str,
+    pub clash_kernel_label: &'static str,
+    pub clash_kernel_update_success: &'static str,
+    pub clash_kernel_update_failed_prefix: &'static str,
+    pub clash_channel_label: &'static str,
+    pub clash_core_previous_label: &'static str,
+    pub clash_core_rollback_button: &'static str,
+    pub clash_core_rolling_back_button: &'static str,
+    pub clash_core_rollback_success_prefix: &'static str,
+    pub clash_core_rollback_unmanaged: &'static str,
+    pub clash_core_rollback_failed_prefix: &'static str,
+    pub clash_mirrors_label: &'static str,
+    pub clash_mirrors_save_success: &'static str,
+    pub clash_core_file_label: &'static str,
+    pub clash_core_install_file_button: &'static str,
+    pub clash_core_installing_button: &'static str,
+    pub clash_core_install_file_empty: &'static str,
+    pub clash_core_install_failed_prefix: &'static str,
+    pub geodata_setting_title: &'static str,
+    pub geodata_hint: &'static str,
+    pub geodata_missing: &'static str,
+    pub geodata_age_suffix: &'static str,
+    pub geodata_update_button: &'static str,
+    pub geodata_updating_button: &'static str,
+    pub geodata_interval_label: &'static str,
+    pub geodata_interval_invalid: &'static str,
+    pub geodata_settings_save_success: &'static str,
+    pub geodata_import_label: &'static str,
+    pub geodata_import_button: &'static str,
+    pub geodata_importing_button: &'static str,
+    pub geodata_import_file_empty: &'static str,
+    pub geodata_update_success_prefix: &'static str,
+    pub geodata_update_failed_prefix: &'static str,
+    pub geodata_import_failed_prefix: &'static str,
     pub auto_launch_update_failed_prefix: &'static str,
     pub silent_start_update_failed_prefix: &'static str,
     pub clash_core_upgrade_success_prefix: &'static str,
@@ -116,6 +230,29 @@ pub struct Strings {
     pub system_proxy_disable_success: &'static str,
     pub system_proxy_enable_failed_prefix: &'static str,
     pub system_proxy_disable_failed_prefix: &'static str,
+    pub proxy_bypass_label: &'static str,
+    pub proxy_ports_label: &'static str,
+    pub proxy_mode_label: &'static str,
+    pub proxy_pac_direct_label: &'static str,
+    pub proxy_pac_hint: &'static str,
+    pub proxy_restore_on_drift_label: &'static str,
+    pub proxy_shell_env_label: &'static str,
+    pub proxy_copy_command_button: &'static str,
+    pub proxy_command_copied: &'static str,
+    pub shell_env_enabled: &'static str,
+    pub shell_env_disabled: &'static str,
+    pub shell_env_failed_prefix: &'static str,
+    pub system_proxy_recovered: &'static str,
+    pub system_proxy_drift_reapplied: &'static str,
+    pub system_proxy_drift_reported: &'static str,
+    pub proxy_settings_hint: &'static str,
+    pub proxy_settings_save_success: &'static str,
+    pub proxy_settings_invalid_port: &'static str,
+    pub proxy_settings_save_failed_prefix: &'static str,
+    pub kernel_exited_prefix: &'static str,
+    pub kernel_restarted: &'static str,
+    pub kernel_recovery_failed_prefix: &'static str,
+    pub kernel_recovery_proxy_disabled: &'static str,
 }
 
 pub fn strings(language: Language) -> &'static Strings {
@@ -127,18 +264,24 @@ pub fn strings(language: Language) -> &'static Strings {
 
 pub fn page_title(strings: &Strings, page: Page) -> &'static str {
     match page {
+        Page::Overview => strings.overview_title,
         Page::Profiles => strings.profiles_title,
         Page::ProxyGroups => strings.proxy_groups_title,
         Page::Rules => strings.rules_title,
+        Page::Connections => strings.connections_title,
+        Page::Logs => strings.logs_title,
         Page::Settings => strings.settings_title,
     }
 }
 
 pub fn page_description(strings: &Strings, page: Page) -> &'static str {
     match page {
+        Page::Overview => strings.overview_desc,
         Page::Profiles => strings.profiles_desc,
         Page::ProxyGroups => strings.proxy_groups_desc,
         Page::Rules => strings.rules_desc,
+        Page::Connections => strings.connections_desc,
+        Page::Logs => strings.logs_desc,
         Page::Settings => strings.settings_desc,
     }
 }
@@ -150,6 +293,28 @@ pub fn language_options(language: Language) -> Vec<String> {
     }
 }
 
+pub fn kernel_channel_options(language: Language) -> Vec<String> {
+    match language {
+        Language::English => vec![
+            "Stable".to_string(),
+            "Alpha".to_string(),
+            "Pinned".to_string(),
+        ],
+        Language::SimplifiedChinese => vec![
+            "稳定版".to_string(),
+            "预览版".to_string(),
+            "固定版本".to_string(),
+        ],
+    }
+}
+
+pub fn system_proxy_mode_options(language: Language) -> Vec<String> {
+    match language {
+        Language::English => vec!["Global".to_string(), "PAC".to_string()],
+        Language::SimplifiedChinese => vec!["全局".to_string(), "PAC".to_string()],
+    }
+}
+
 pub fn theme_options(language: Language) -> Vec<String> {
     match language {
         Language::English => vec![
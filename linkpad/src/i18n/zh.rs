@@ -2,14 +2,26 @@ use super::Strings;
 
 pub const ZH: Strings = Strings {
     app_name: "Linkpad",
+    menu_overview: "概览",
     menu_profiles: "配置",
     menu_proxy_groups: "策略组",
     menu_rules: "规则",
+    menu_connections: "连接",
+    menu_logs: "日志",
     menu_settings: "设置",
     tray_outbound_modes: "出站模式",
     tray_profiles: "配置",
     tray_system_proxy: "系统代理",
+    tray_copy_proxy_command: "复制代理命令",
     tray_exit: "退出",
+    overview_title: "概览",
+    overview_desc: "运行中内核的实时流量与内存占用。",
+    overview_traffic_title: "吞吐量",
+    overview_download_label: "下载",
+    overview_upload_label: "上传",
+    overview_memory_label: "内存",
+    overview_peak_label: "峰值（2 分钟）",
+    overview_disconnected: "等待内核。内核运行后将显示流量。",
     profiles_title: "配置",
     profiles_desc: "管理订阅配置、本地配置与同步来源。",
     profiles_import_title: "导入配置 URL",
@@ -34,6 +46,29 @@ pub const ZH: Strings = Strings {
     profiles_import_loading: "正在导入配置 URL...",
     profiles_import_success: "配置导入成功。",
     profiles_import_error: "导入失败：配置 URL 无效。",
+    profiles_import_local_label: "本地文件或粘贴内容",
+    profiles_import_file_placeholder: "/path/to/config.yaml",
+    profiles_import_file_button: "导入文件",
+    profiles_import_file_error: "导入失败：请输入配置文件路径。",
+    profiles_import_text_name_placeholder: "配置名称（可选）",
+    profiles_import_text_placeholder: "粘贴 Clash YAML 或分享链接",
+    profiles_import_text_button: "导入文本",
+    profiles_import_text_error: "导入失败：请先粘贴配置内容。",
+    profiles_source_inline: "粘贴的内容",
+    profiles_usage_used: "已用",
+    profiles_usage_unlimited: "不限",
+    profiles_usage_expires: "到期",
+    profiles_usage_expired: "已过期",
+    profiles_quota_warning_prefix: "订阅流量即将用尽",
+    profiles_expiry_warning_prefix: "订阅即将到期",
+    profiles_expired_warning_prefix: "订阅已过期",
+    profiles_activate_failed_prefix: "切换配置失败",
+    profiles_refresh_failed_prefix: "刷新配置失败",
+    profiles_auto_update_prefix: "自动更新",
+    profiles_auto_update_off: "关闭",
+    profiles_interval_placeholder: "小时",
+    profiles_scheduled_refresh_success_prefix: "配置已更新",
+    profiles_scheduled_refresh_failed_prefix: "配置更新失败",
     proxy_groups_title: "策略组",
     proxy_groups_desc: "来自当前激活配置的策略组。",
     proxy_groups_empty: "当前激活配置没有策略组。",
@@ -73,9 +108,53 @@ pub const ZH: Strings = Strings {
     rules_count_prefix: "规则总数",
     rules_search_placeholder: "搜索规则",
     rules_filter_all: "全部",
-    rules_filter_domain: "DOMAIN",
-    rules_filter_ip_cidr: "IP-CIDR",
-    rules_filter_process_name: "PROCESS",
+    rules_filter_type_label: "类型",
+    rules_filter_target_label: "策略",
+    rules_diagnostics_prefix: "已跳过的无效规则",
+    rules_match_placeholder: "测试域名或 IP，例如 example.com:443",
+    rules_match_process_placeholder: "进程（可选）",
+    rules_match_btn: "测试",
+    rules_matching_btn: "测试中...",
+    rules_match_prefix: "命中规则",
+    rules_match_none: "没有规则匹配该地址。",
+    rules_match_skipped_prefix: "无法在本地判断",
+    rules_match_failed_prefix: "规则测试失败",
+    connections_title: "连接",
+    connections_desc: "当前经过内核的连接。",
+    connections_search_placeholder: "搜索主机、进程、规则或代理链",
+    connections_sort_newest: "最新",
+    connections_sort_download: "下载最多",
+    connections_sort_upload: "上传最多",
+    connections_sort_host: "主机",
+    connections_show_closed: "已关闭",
+    connections_show_active: "活动",
+    connections_close_button: "关闭",
+    connections_close_all_button: "全部关闭",
+    connections_summary_active: "活动",
+    connections_summary_closed: "已关闭",
+    connections_summary_total: "总计",
+    connections_empty: "暂无连接。",
+    connections_closed_empty: "暂无已关闭的连接。",
+    connections_disconnected: "内核未运行。",
+    connections_overflow_prefix: "未显示",
+    connections_close_failed_prefix: "关闭连接失败",
+    logs_title: "内核日志",
+    logs_desc: "mihomo 内核输出，最新的在末尾。",
+    logs_search_placeholder: "按关键字筛选",
+    logs_level_debug: "Debug 及以上",
+    logs_level_info: "Info 及以上",
+    logs_level_warning: "警告和错误",
+    logs_level_error: "仅错误",
+    logs_pause_button: "暂停",
+    logs_resume_button: "继续",
+    logs_clear_button: "清空",
+    logs_export_button: "导出",
+    logs_empty: "暂无日志。",
+    logs_paused: "已暂停",
+    logs_shown_prefix: "显示",
+    logs_file_prefix: "日志文件",
+    logs_export_success_prefix: "日志已导出到",
+    logs_export_failed_prefix: "导出日志失败",
     settings_title: "设置",
     settings_desc: "应用偏好、网络选项与系统集成。",
     basic_setting_title: "Linkpad 基础设置",
@@ -90,6 +169,8 @@ pub const ZH: Strings = Strings {
     clash_port_label: "端口配置",
     clash_core_version_label: "Clash Core 版本",
     clash_core_path_label: "Clash Core 路径",
+    clash_override_label: "配置覆写",
+    clash_override_hint: "将在下次启动内核时合并到运行配置。",
     clash_core_not_found: "未找到",
     clash_core_installed_unknown_version: "已安装（版本未知）",
     clash_core_upgrade_button: "升级",
@@ -100,6 +181,39 @@ pub const ZH: Strings = Strings {
     clash_port_update_success: "Clash 端口已更新。",
     clash_port_update_invalid: "端口无效，请输入 1 到 65535。",
     clash_port_update_failed_prefix: "更新 Clash 端口失败",
+    clash_kernel_label: "内核",
+    clash_kernel_update_success: "内核已切换。",
+    clash_kernel_update_failed_prefix: "切换内核失败",
+    clash_channel_label: "更新通道",
+    clash_core_previous_label: "上一版本",
+    clash_core_rollback_button: "回滚",
+    clash_core_rolling_back_button: "回滚中...",
+    clash_core_rollback_success_prefix: "内核已回滚到",
+    clash_core_rollback_unmanaged: "系统中已有的内核",
+    clash_core_rollback_failed_prefix: "回滚内核失败",
+    clash_mirrors_label: "下载镜像",
+    clash_mirrors_save_success: "下载镜像已保存。",
+    clash_core_file_label: "从文件安装",
+    clash_core_install_file_button: "安装",
+    clash_core_installing_button: "安装中...",
+    clash_core_install_file_empty: "请输入已下载的内核压缩包路径。",
+    clash_core_install_failed_prefix: "安装内核失败",
+    geodata_setting_title: "地理数据",
+    geodata_hint: "启动时检查，超过更新间隔后重新下载；内核在下次重启后读取新文件。",
+    geodata_missing: "缺失",
+    geodata_age_suffix: "前",
+    geodata_update_button: "更新",
+    geodata_updating_button: "更新中...",
+    geodata_interval_label: "更新间隔（小时）",
+    geodata_interval_invalid: "更新间隔无效。请填写整数小时，留空则只下载缺失的文件。",
+    geodata_settings_save_success: "地理数据设置已保存。",
+    geodata_import_label: "导入文件",
+    geodata_import_button: "导入",
+    geodata_importing_button: "导入中...",
+    geodata_import_file_empty: "请输入地理数据文件路径。",
+    geodata_update_success_prefix: "地理数据已更新",
+    geodata_update_failed_prefix: "更新地理数据失败",
+    geodata_import_failed_prefix: "导入地理数据失败",
     auto_launch_update_failed_prefix: "更新开机自启失败",
     silent_start_update_failed_prefix: "更新静默启动失败",
     clash_core_upgrade_success_prefix: "Clash Core 已升级",
@@ -110,4 +224,27 @@ pub const ZH: Strings = Strings {
     system_proxy_disable_success: "系统代理已全局关闭。",
     system_proxy_enable_failed_prefix: "开启系统代理失败",
     system_proxy_disable_failed_prefix: "关闭系统代理失败",
+    proxy_bypass_label: "绕过",
+    proxy_ports_label: "代理端口",
+    proxy_mode_label: "代理模式",
+    proxy_pac_direct_label: "PAC 直连",
+    proxy_pac_hint: "PAC 模式会在 PAC 端口（留空为 7899）提供脚本，配置中的 DIRECT 域名与 IP 规则、绕过列表以及 PAC 直连条目都会直连。配置变化时脚本会自动重新生成。",
+    proxy_restore_on_drift_label: "被其他程序修改时恢复",
+    proxy_shell_env_label: "终端代理",
+    proxy_copy_command_button: "复制代理命令",
+    proxy_command_copied: "代理命令已复制,可粘贴到终端执行",
+    shell_env_enabled: "之后打开的终端将使用系统代理",
+    shell_env_disabled: "已从 Shell 启动文件中移除终端代理",
+    shell_env_failed_prefix: "更新 Shell 启动文件失败",
+    system_proxy_recovered: "已恢复上次异常退出前的系统代理设置",
+    system_proxy_drift_reapplied: "系统代理被其他程序修改,已重新设置",
+    system_proxy_drift_reported: "系统代理设置已被其他程序修改",
+    proxy_settings_hint: "绕过列表用逗号分隔主机、*.域名和 CIDR。端口留空则使用混合端口，HTTPS 留空则跟随 HTTP 端口；填写的端口会单独监听。",
+    proxy_settings_save_success: "系统代理设置已保存。",
+    proxy_settings_invalid_port: "端口无效。请填写 1 到 65535 之间的值，或留空。",
+    proxy_settings_save_failed_prefix: "保存系统代理设置失败",
+    kernel_exited_prefix: "内核意外退出，正在重启",
+    kernel_restarted: "内核已重启",
+    kernel_recovery_failed_prefix: "内核无法重启",
+    kernel_recovery_proxy_disabled: "已关闭系统代理。",
 };
@@ -1,4 +1,8 @@
-use linkpad_core::ProxyMode;
+use linkpad_core::{
+    ConnectionSort, ConnectionsView, GeoDataSettings, GeoDataStatus, KernelChannel, KernelKind,
+    LogEntry, LogLevel, ProfileSourceKind, ProxyMode, Rule, RuleDiagnostic, RuleKind, RuleMatch,
+    SubscriptionUserinfo, SystemProxyMode, SystemProxySettings, TrafficStats,
+};
 use std::collections::HashMap;
 
 #[derive(Clone, Debug)]
@@ -7,14 +11,36 @@ pub struct AppState {
     pub language: Language,
     pub theme: ThemePreference,
     pub profile_url_input: String,
+    pub profile_file_input: String,
+    pub profile_text_name_input: String,
+    pub profile_text_input: String,
     pub import_status: ImportStatus,
     pub profiles: Vec<ProfileSummary>,
     pub proxy_groups: Vec<ProxyGroupSummary>,
     pub proxy_nodes: Vec<ProxyNodeSummary>,
-    pub rules: Vec<String>,
+    pub rules: Vec<Rule>,
+    pub rule_diagnostics: Vec<RuleDiagnostic>,
     pub rules_query: String,
-    pub rules_filter: RuleFilter,
+    pub rules_kind_filter: Option<RuleKind>,
+    pub rules_target_filter: Option<String>,
     pub rules_visible_count: usize,
+    pub rules_match_input: String,
+    pub rules_match_process: String,
+    /// Result of the last rule test; the inner `None` means no rule matched.
+    pub rules_match: Option<Option<RuleMatch>>,
+    pub traffic: TrafficStats,
+    pub connections: ConnectionsView,
+    pub connections_query: String,
+    pub connections_sort: ConnectionSort,
+    /// List the closed-connections history instead of open connections.
+    pub connections_show_closed: bool,
+    /// Kernel log entries received since the last clear, oldest first.
+    pub logs: Vec<LogEntry>,
+    /// Sequence number of the newest entry fetched from the core.
+    pub logs_last_seq: Option<u64>,
+    pub logs_level: LogLevel,
+    pub logs_query: String,
+    pub logs_paused: bool,
     pub proxy_mode: ProxyMode,
     pub active_proxy_group: Option<String>,
     pub proxy_group_selected: HashMap<String, usize>,
@@ -22,17 +48,45 @@ pub struct AppState {
     pub close_to_tray_enabled: bool,
     pub auto_launch_enabled: bool,
     pub silent_start_enabled: bool,
+    pub system_proxy_settings: SystemProxySettings,
+    pub proxy_bypass_input: String,
+    /// HTTP, HTTPS and SOCKS port being edited; empty for the default.
+    pub proxy_port_inputs: [String; 3],
+    /// Mode picked in the dropdown, saved together with the inputs.
+    pub proxy_mode_input: SystemProxyMode,
+    pub proxy_pac_port_input: String,
+    pub proxy_pac_direct_input: String,
     pub clash_mixed_port: u16,
     pub clash_port_input: String,
+    pub kernel_kind: KernelKind,
+    pub kernel_channel: KernelChannel,
+    /// Tag typed for the pinned channel, kept while another channel is picked.
+    pub kernel_pinned_input: String,
+    pub kernel_mirrors: Vec<String>,
+    pub kernel_mirrors_input: String,
+    pub kernel_file_input: String,
+    pub geodata_settings: GeoDataSettings,
+    pub geodata_status: Vec<GeoDataStatus>,
+    /// URLs being edited, in [`linkpad_core::GeoDataKind::ALL`] order.
+    pub geodata_url_inputs: [String; 3],
+    pub geodata_interval_input: String,
+    /// Index into [`linkpad_core::GeoDataKind::ALL`] of the file IMPORT replaces.
+    pub geodata_import_index: usize,
+    pub geodata_file_input: String,
     pub clash_core_version: String,
+    pub clash_core_previous_version: String,
     pub clash_core_path: String,
+    pub clash_override_paths: String,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Page {
+    Overview,
     Profiles,
     ProxyGroups,
     Rules,
+    Connections,
+    Logs,
     Settings,
 }
 
@@ -49,14 +103,6 @@ pub enum ThemePreference {
     System,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum RuleFilter {
-    All,
-    Domain,
-    IpCidr,
-    ProcessName,
-}
-
 #[derive(Clone, Debug)]
 pub struct ImportStatus {
     pub message: String,
@@ -68,7 +114,12 @@ pub struct ProfileSummary {
     pub id: String,
     pub name: String,
     pub source: String,
+    pub source_kind: ProfileSourceKind,
     pub updated_at: String,
+    pub subscription: Option<SubscriptionUserinfo>,
+    /// Effective automatic refresh interval; `None` when it is off.
+    pub refresh_interval_hours: Option<u32>,
+    pub interval_override_hours: Option<u32>,
     pub node_count: usize,
     pub group_count: usize,
     pub rule_count: usize,
@@ -97,6 +148,9 @@ impl Default for AppState {
             language: Language::English,
             theme: ThemePreference::System,
             profile_url_input: String::new(),
+            profile_file_input: String::new(),
+            profile_text_name_input: String::new(),
+            profile_text_input: String::new(),
             import_status: ImportStatus {
                 message: "Ready to import profile URL.".to_string(),
                 is_error: false,
@@ -105,9 +159,24 @@ impl Default for AppState {
             proxy_groups: Vec::new(),
             proxy_nodes: Vec::new(),
             rules: Vec::new(),
+            rule_diagnostics: Vec::new(),
             rules_query: String::new(),
-            rules_filter: RuleFilter::All,
+            rules_kind_filter: None,
+            rules_target_filter: None,
             rules_visible_count: 50,
+            rules_match_input: String::new(),
+            rules_match_process: String::new(),
+            rules_match: None,
+            traffic: TrafficStats::default(),
+            connections: ConnectionsView::default(),
+            connections_query: String::new(),
+            connections_sort: ConnectionSort::default(),
+            connections_show_closed: false,
+            logs: Vec::new(),
+            logs_last_seq: None,
+            logs_level: LogLevel::default(),
+            logs_query: String::new(),
+            logs_paused: false,
             proxy_mode: ProxyMode::Rule,
             active_proxy_group: None,
             proxy_group_selected: HashMap::new(),
@@ -115,10 +184,30 @@ impl Default for AppState {
             close_to_tray_enabled: true,
             auto_launch_enabled: false,
             silent_start_enabled: false,
+            system_proxy_settings: SystemProxySettings::default(),
+            proxy_bypass_input: String::new(),
+            proxy_port_inputs: Default::default(),
+            proxy_mode_input: SystemProxyMode::default(),
+            proxy_pac_port_input: String::new(),
+            proxy_pac_direct_input: String::new(),
             clash_mixed_port: 7890,
             clash_port_input: "7890".to_string(),
+            kernel_kind: KernelKind::default(),
+            kernel_channel: KernelChannel::default(),
+            kernel_pinned_input: String::new(),
+            kernel_mirrors: Vec::new(),
+            kernel_mirrors_input: String::new(),
+            kernel_file_input: String::new(),
+            geodata_settings: GeoDataSettings::default(),
+            geodata_status: Vec::new(),
+            geodata_url_inputs: Default::default(),
+            geodata_interval_input: String::new(),
+            geodata_import_index: 0,
+            geodata_file_input: String::new(),
             clash_core_version: "Unknown".to_string(),
+            clash_core_previous_version: "-".to_string(),
             clash_core_path: "-".to_string(),
+            clash_override_paths: "-".to_string(),
         }
     }
 }
@@ -1,4 +1,5 @@
 use crate::state::{Language, ThemePreference};
+use linkpad_core::{GeoDataSettings, KernelChannel, KernelKind, SystemProxySettings};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -12,7 +13,12 @@ pub struct LoadedSettings {
     pub close_to_tray_enabled: bool,
     pub auto_launch_enabled: bool,
     pub silent_start_enabled: bool,
+    pub system_proxy: SystemProxySettings,
     pub clash_mixed_port: u16,
+    pub kernel_kind: KernelKind,
+    pub kernel_channel: KernelChannel,
+    pub kernel_mirrors: Vec<String>,
+    pub geodata: GeoDataSettings,
     pub proxy_group_selections: HashMap<String, String>,
 }
 
@@ -28,9 +34,19 @@ struct PersistedSettings {
     auto_launch_enabled: bool,
     #[serde(default)]
     silent_start_enabled: bool,
+    #[serde(default)]
+    system_proxy: SystemProxySettings,
     #[serde(default = "default_mixed_port")]
     clash_mixed_port: u16,
     #[serde(default)]
+    kernel_kind: KernelKind,
+    #[serde(default)]
+    kernel_channel: KernelChannel,
+    #[serde(default)]
+    kernel_mirrors: Vec<String>,
+    #[serde(default)]
+    geodata: GeoDataSettings,
+    #[serde(default)]
     proxy_group_selections: HashMap<String, String>,
 }
 
@@ -48,7 +64,12 @@ pub fn load() -> Option<LoadedSettings> {
         close_to_tray_enabled: persisted.close_to_tray_enabled,
         auto_launch_enabled: persisted.auto_launch_enabled,
         silent_start_enabled: persisted.silent_start_enabled,
+        system_proxy: persisted.system_proxy,
         clash_mixed_port: normalize_port(persisted.clash_mixed_port),
+        kernel_kind: persisted.kernel_kind,
+        kernel_channel: persisted.kernel_channel,
+        kernel_mirrors: persisted.kernel_mirrors,
+        geodata: persisted.geodata,
         proxy_group_selections: persisted.proxy_group_selections,
     })
 }
@@ -60,7 +81,12 @@ pub fn save(
     close_to_tray_enabled: bool,
     auto_launch_enabled: bool,
     silent_start_enabled: bool,
+    system_proxy: &SystemProxySettings,
     clash_mixed_port: u16,
+    kernel_kind: KernelKind,
+    kernel_channel: &KernelChannel,
+    kernel_mirrors: &[String],
+    geodata: &GeoDataSettings,
     proxy_group_selections: &HashMap<String, String>,
 ) -> std::io::Result<()> {
     let path = match settings_path() {
@@ -79,7 +105,12 @@ pub fn save(
         close_to_tray_enabled,
         auto_launch_enabled,
         silent_start_enabled,
+        system_proxy: system_proxy.clone(),
         clash_mixed_port: normalize_port(clash_mixed_port),
+        kernel_kind,
+        kernel_channel: kernel_channel.clone(),
+        kernel_mirrors: kernel_mirrors.to_vec(),
+        geodata: geodata.clone(),
         proxy_group_selections: proxy_group_selections.clone(),
     };
 
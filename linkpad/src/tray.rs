@@ -11,6 +11,7 @@ const TRAY_CMD_MODE_DIRECT: u64 = 10_003;
 const TRAY_CMD_PROFILE_BASE: u64 = 20_000;
 const TRAY_CMD_SYSTEM_PROXY_TOGGLE: u64 = 30_001;
 const TRAY_CMD_EXIT: u64 = 30_002;
+const TRAY_CMD_COPY_PROXY_COMMAND: u64 = 30_003;
 
 #[derive(Clone, Debug)]
 pub(super) struct ShellCommandAction(pub CommandId);
@@ -168,6 +169,10 @@ impl App {
             )),
             TrayMenuItem::Separator,
             TrayMenuItem::Command(system_proxy_item),
+            TrayMenuItem::Command(TrayCommandItem::new(
+                CommandId::new(TRAY_CMD_COPY_PROXY_COMMAND).expect("valid tray command id"),
+                strings.tray_copy_proxy_command,
+            )),
             TrayMenuItem::Separator,
             TrayMenuItem::Command(
                 TrayCommandItem::new(
@@ -196,7 +201,17 @@ impl App {
             TRAY_CMD_SYSTEM_PROXY_TOGGLE => {
                 self.set_system_proxy_enabled(cx, !self.state.system_proxy_enabled);
             }
-            TRAY_CMD_EXIT => cx.quit(),
+            TRAY_CMD_COPY_PROXY_COMMAND => self.copy_proxy_command(cx),
+            TRAY_CMD_EXIT => {
+                // Leave the OS proxy settings as they were found rather than
+                // pointing at a kernel that is about to stop.
+                if self.state.system_proxy_enabled
+                    && let Err(error) = self.core.disable_system_proxy()
+                {
+                    error!("failed to disable system proxy on exit: {error}");
+                }
+                cx.quit();
+            }
             _ => {
                 if raw_id >= TRAY_CMD_PROFILE_BASE {
                     let profile_index = (raw_id - TRAY_CMD_PROFILE_BASE) as usize;
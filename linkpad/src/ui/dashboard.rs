@@ -250,6 +250,40 @@ live_design! {
             }
         }
     }
+    ConnectionRow = <View> {
+        width: Fill,
+        height: Fit,
+        flow: Right,
+        align: {y: 0.5},
+        spacing: (SPACE_2),
+        padding: {left: (SPACE_2), right: (SPACE_2), top: (SPACE_2), bottom: (SPACE_2)},
+        show_bg: true,
+        draw_bg: {color: (PANEL_ACCENT_BG)},
+
+        <View> {
+            width: Fill,
+            height: Fit,
+            flow: Down,
+            spacing: (SPACE_1),
+
+            conn_target = <Label> {
+                text: "host:443"
+                draw_text: {text_style: <APP_FONT_BODY>{}}
+            }
+            conn_meta = <Label> {
+                width: Fill
+                text: "process | rule | chain"
+                draw_text: {text_style: <APP_FONT_CAPTION>{}, wrap: Word}
+            }
+        }
+
+        conn_traffic = <Label> {
+            text: ""
+            draw_text: {text_style: <APP_FONT_CAPTION>{}}
+        }
+        conn_close_btn = <MpButtonSmall> { text: "Close" }
+    }
+
     pub Dashboard = <View> {
         width: Fill,
         height: Fill,
@@ -275,6 +309,54 @@ live_design! {
                     show_scroll_y: true
                 }
 
+                overview_section = <View> {
+                    width: Fill,
+                    height: Fit,
+                    flow: Down,
+                    spacing: (SPACE_3),
+
+                    overview_card = <MpCard> {
+                        width: Fill,
+                        <MpCardHeader> {
+                            overview_traffic_title = <MpCardTitle> { text: "Throughput" }
+                        }
+                        <MpCardContent> {
+                            width: Fill,
+                            flow: Down,
+                            spacing: (SPACE_2),
+
+                            overview_status = <Label> {
+                                width: Fill
+                                text: ""
+                                draw_text: {text_style: <APP_FONT_CAPTION>{}, wrap: Word}
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                spacing: (SPACE_4),
+
+                                overview_download = <Label> { text: "Download: -" draw_text: {text_style: <APP_FONT_BODY>{}} }
+                                overview_upload = <Label> { text: "Upload: -" draw_text: {text_style: <APP_FONT_BODY>{}} }
+                                overview_memory = <Label> { text: "Memory: -" draw_text: {text_style: <APP_FONT_BODY>{}} }
+                                overview_peak = <Label> { text: "Peak: -" draw_text: {text_style: <APP_FONT_BODY>{}} }
+                            }
+
+                            overview_download_chart = <Label> {
+                                width: Fill
+                                text: ""
+                                draw_text: {text_style: <APP_FONT_BODY>{}}
+                            }
+                            overview_upload_chart = <Label> {
+                                width: Fill
+                                text: ""
+                                draw_text: {text_style: <APP_FONT_BODY>{}}
+                            }
+                        }
+                    }
+                }
+
                 profiles_section = <View> {
                     width: Fill,
                     height: Fit,
@@ -313,6 +395,44 @@ live_design! {
                                     text: "Validate & Import"
                                 }
                             }
+
+                            profile_local_label = <Label> {
+                                text: "Local File or Pasted Content"
+                                draw_text: {text_style: <APP_FONT_BODY>{}}
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_2),
+
+                                profile_file_input = <MpInput> {
+                                    width: Fill
+                                    empty_text: "/path/to/config.yaml"
+                                }
+                                profile_file_import_btn = <MpButtonSmall> { text: "Import File" }
+                            }
+
+                            profile_text_name_input = <MpInput> {
+                                width: Fill
+                                empty_text: "Profile name (optional)"
+                            }
+
+                            profile_text_input = <MpInput> {
+                                width: Fill
+                                empty_text: "Paste Clash YAML or share links"
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {x: 1.0, y: 0.5},
+
+                                profile_text_import_btn = <MpButtonSmall> { text: "Import Text" }
+                            }
                         }
                     }
 
@@ -368,6 +488,24 @@ live_design! {
                                         text: "source / updated"
                                         draw_text: {text_style: <APP_FONT_CAPTION>{}, wrap: Word}
                                     }
+                                    profile_row_1_usage = <Label> {
+                                        width: Fill
+                                        text: ""
+                                        draw_text: {text_style: <APP_FONT_CAPTION>{}, wrap: Word}
+                                    }
+                                    profile_row_1_usage_bar = <View> {
+                                        width: 240,
+                                        height: 6,
+                                        show_bg: true,
+                                        draw_bg: {color: (PANEL_BG)},
+
+                                        profile_row_1_usage_fill = <View> {
+                                            width: 0,
+                                            height: Fill,
+                                            show_bg: true,
+                                            draw_bg: {color: (MENU_ACTIVE_BG)}
+                                        }
+                                    }
                                     profile_row_1_status = <Label> { text: "Active" draw_text: {text_style: <APP_FONT_CAPTION>{}} }
                                 }
                                 <View> {
@@ -375,6 +513,10 @@ live_design! {
                                     height: Fit,
                                     flow: Right,
                                     spacing: (SPACE_1),
+                                    profile_row_1_interval_input = <MpInput> {
+                                        width: 72
+                                        empty_text: "Hours"
+                                    }
                                     profile_row_1_activate_btn = <MpButtonSmall> { text: "Activate" }
                                     profile_row_1_refresh_btn = <MpButtonSmall> { text: "Refresh" }
                                     profile_row_1_delete_btn = <MpButtonSmall> { text: "Delete" }
@@ -402,6 +544,24 @@ live_design! {
                                         text: "source / updated"
                                         draw_text: {text_style: <APP_FONT_CAPTION>{}, wrap: Word}
                                     }
+                                    profile_row_2_usage = <Label> {
+                                        width: Fill
+                                        text: ""
+                                        draw_text: {text_style: <APP_FONT_CAPTION>{}, wrap: Word}
+                                    }
+                                    profile_row_2_usage_bar = <View> {
+                                        width: 240,
+                                        height: 6,
+                                        show_bg: true,
+                                        draw_bg: {color: (PANEL_BG)},
+
+                                        profile_row_2_usage_fill = <View> {
+                                            width: 0,
+                                            height: Fill,
+                                            show_bg: true,
+                                            draw_bg: {color: (MENU_ACTIVE_BG)}
+                                        }
+                                    }
                                     profile_row_2_status = <Label> { text: "Inactive" draw_text: {text_style: <APP_FONT_CAPTION>{}} }
                                 }
                                 <View> {
@@ -409,6 +569,10 @@ live_design! {
                                     height: Fit,
                                     flow: Right,
                                     spacing: (SPACE_1),
+                                    profile_row_2_interval_input = <MpInput> {
+                                        width: 72
+                                        empty_text: "Hours"
+                                    }
                                     profile_row_2_activate_btn = <MpButtonSmall> { text: "Activate" }
                                     profile_row_2_refresh_btn = <MpButtonSmall> { text: "Refresh" }
                                     profile_row_2_delete_btn = <MpButtonSmall> { text: "Delete" }
@@ -436,6 +600,24 @@ live_design! {
                                         text: "source / updated"
                                         draw_text: {text_style: <APP_FONT_CAPTION>{}, wrap: Word}
                                     }
+                                    profile_row_3_usage = <Label> {
+                                        width: Fill
+                                        text: ""
+                                        draw_text: {text_style: <APP_FONT_CAPTION>{}, wrap: Word}
+                                    }
+                                    profile_row_3_usage_bar = <View> {
+                                        width: 240,
+                                        height: 6,
+                                        show_bg: true,
+                                        draw_bg: {color: (PANEL_BG)},
+
+                                        profile_row_3_usage_fill = <View> {
+                                            width: 0,
+                                            height: Fill,
+                                            show_bg: true,
+                                            draw_bg: {color: (MENU_ACTIVE_BG)}
+                                        }
+                                    }
                                     profile_row_3_status = <Label> { text: "Inactive" draw_text: {text_style: <APP_FONT_CAPTION>{}} }
                                 }
                                 <View> {
@@ -443,6 +625,10 @@ live_design! {
                                     height: Fit,
                                     flow: Right,
                                     spacing: (SPACE_1),
+                                    profile_row_3_interval_input = <MpInput> {
+                                        width: 72
+                                        empty_text: "Hours"
+                                    }
                                     profile_row_3_activate_btn = <MpButtonSmall> { text: "Activate" }
                                     profile_row_3_refresh_btn = <MpButtonSmall> { text: "Refresh" }
                                     profile_row_3_delete_btn = <MpButtonSmall> { text: "Delete" }
@@ -519,12 +705,44 @@ live_design! {
                                 width: Fill,
                                 height: Fit,
                                 flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_2),
+
+                                rules_kind_label = <Label> {text: "Type", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                rules_kind_dropdown = <MpDropdown> {
+                                    width: 200,
+                                    labels: ["All"],
+                                    selected_item: 0
+                                }
+                                rules_target_label = <Label> {text: "Policy", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                rules_target_dropdown = <MpDropdown> {
+                                    width: 200,
+                                    labels: ["All"],
+                                    selected_item: 0
+                                }
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
                                 spacing: (SPACE_2),
 
-                                rules_filter_all_btn = <MpButtonSmall> { text: "All" }
-                                rules_filter_domain_btn = <MpButtonSmall> { text: "Domain" }
-                                rules_filter_ip_cidr_btn = <MpButtonSmall> { text: "IP-CIDR" }
-                                rules_filter_process_btn = <MpButtonSmall> { text: "Process" }
+                                rules_match_input = <MpInput> {
+                                    width: Fill
+                                    empty_text: "Test a host or IP, e.g. example.com:443"
+                                }
+                                rules_match_process_input = <MpInput> {
+                                    width: 180
+                                    empty_text: "Process (optional)"
+                                }
+                                rules_match_btn = <MpButtonSmall> { text: "Test" }
+                            }
+                            rules_match_result = <Label> {
+                                width: Fill
+                                text: ""
+                                draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY), wrap: Word}
                             }
 
                             rules_count = <Label> {
@@ -535,6 +753,11 @@ live_design! {
                                 text: "No rules in active profile."
                                 draw_text: {text_style: <APP_FONT_CAPTION>{}}
                             }
+                            rules_diagnostics = <Label> {
+                                width: Fill
+                                text: ""
+                                draw_text: {text_style: <APP_FONT_CAPTION>{}, color: (TEXT_MUTED), wrap: Word}
+                            }
                             rules_list = <Label> {
                                 text: ""
                                 draw_text: {text_style: <APP_FONT_CAPTION>{}}
@@ -543,6 +766,144 @@ live_design! {
                     }
                 }
 
+                connections_section = <View> {
+                    width: Fill,
+                    height: Fit,
+                    flow: Down,
+                    spacing: (SPACE_3),
+
+                    connections_card = <MpCard> {
+                        width: Fill,
+                        <MpCardHeader> {
+                            connections_title = <MpCardTitle> { text: "Connections" }
+                            connections_desc = <MpCardDescription> { text: "Connections going through the kernel." }
+                        }
+                        <MpCardContent> {
+                            width: Fill,
+                            flow: Down,
+                            spacing: (SPACE_2),
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_2),
+
+                                connections_search_input = <MpInput> {
+                                    width: Fill
+                                    empty_text: "Search host, process, rule or chain"
+                                }
+                                connections_sort_dropdown = <MpDropdown> {
+                                    width: 160,
+                                    labels: ["Newest"],
+                                    selected_item: 0
+                                }
+                                connections_closed_btn = <MpButtonSmall> { text: "Closed" }
+                                connections_close_all_btn = <MpButtonSmall> { text: "Close All" }
+                            }
+
+                            connections_summary = <Label> {
+                                width: Fill
+                                text: ""
+                                draw_text: {text_style: <APP_FONT_CAPTION>{}, wrap: Word}
+                            }
+                            connections_empty = <Label> {
+                                text: "No connections."
+                                draw_text: {text_style: <APP_FONT_CAPTION>{}}
+                            }
+
+                            connection_row_1 = <ConnectionRow> {}
+                            connection_row_2 = <ConnectionRow> {}
+                            connection_row_3 = <ConnectionRow> {}
+                            connection_row_4 = <ConnectionRow> {}
+                            connection_row_5 = <ConnectionRow> {}
+                            connection_row_6 = <ConnectionRow> {}
+                            connection_row_7 = <ConnectionRow> {}
+                            connection_row_8 = <ConnectionRow> {}
+                            connection_row_9 = <ConnectionRow> {}
+                            connection_row_10 = <ConnectionRow> {}
+                            connection_row_11 = <ConnectionRow> {}
+                            connection_row_12 = <ConnectionRow> {}
+                            connection_row_13 = <ConnectionRow> {}
+                            connection_row_14 = <ConnectionRow> {}
+                            connection_row_15 = <ConnectionRow> {}
+                            connection_row_16 = <ConnectionRow> {}
+                            connection_row_17 = <ConnectionRow> {}
+                            connection_row_18 = <ConnectionRow> {}
+                            connection_row_19 = <ConnectionRow> {}
+                            connection_row_20 = <ConnectionRow> {}
+                            connection_row_21 = <ConnectionRow> {}
+                            connection_row_22 = <ConnectionRow> {}
+                            connection_row_23 = <ConnectionRow> {}
+                            connection_row_24 = <ConnectionRow> {}
+                            connection_row_25 = <ConnectionRow> {}
+                            connection_row_26 = <ConnectionRow> {}
+                            connection_row_27 = <ConnectionRow> {}
+                            connection_row_28 = <ConnectionRow> {}
+                            connection_row_29 = <ConnectionRow> {}
+                            connection_row_30 = <ConnectionRow> {}
+
+                            connections_overflow = <Label> {
+                                text: ""
+                                draw_text: {text_style: <APP_FONT_CAPTION>{}}
+                            }
+                        }
+                    }
+                }
+
+                logs_section = <View> {
+                    width: Fill,
+                    height: Fit,
+                    flow: Down,
+                    spacing: (SPACE_3),
+
+                    logs_card = <MpCard> {
+                        width: Fill,
+                        <MpCardHeader> {
+                            logs_title = <MpCardTitle> { text: "Kernel Logs" }
+                            logs_desc = <MpCardDescription> { text: "Output of the mihomo kernel." }
+                        }
+                        <MpCardContent> {
+                            width: Fill,
+                            flow: Down,
+                            spacing: (SPACE_2),
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_2),
+
+                                logs_search_input = <MpInput> {
+                                    width: Fill
+                                    empty_text: "Filter by keyword"
+                                }
+                                logs_level_dropdown = <MpDropdown> {
+                                    width: 140,
+                                    labels: ["Info"],
+                                    selected_item: 0
+                                }
+                                logs_pause_btn = <MpButtonSmall> { text: "Pause" }
+                                logs_clear_btn = <MpButtonSmall> { text: "Clear" }
+                                logs_export_btn = <MpButtonSmall> { text: "Export" }
+                            }
+
+                            logs_status = <Label> {
+                                width: Fill
+                                text: ""
+                                draw_text: {text_style: <APP_FONT_CAPTION>{}, wrap: Word}
+                            }
+                            logs_list = <Label> {
+                                width: Fill
+                                text: ""
+                                draw_text: {text_style: <APP_FONT_CAPTION>{}, wrap: Word}
+                            }
+                        }
+                    }
+                }
+
                 settings_section = <View> {
                     width: Fill,
                     height: Fit,
@@ -628,6 +989,119 @@ live_design! {
                                 system_proxy_switch = <MpSwitch> {}
                             }
 
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                proxy_bypass_label = <Label> {text: "Bypass", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                proxy_bypass_input = <MpInput> {
+                                    width: Fill
+                                    empty_text: "localhost, *.corp.example, 10.0.0.0/8"
+                                }
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                proxy_ports_label = <Label> {text: "Proxy Ports", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                <View> {width: Fill, height: Fit}
+                                proxy_http_port_input = <MpInput> {
+                                    width: 90
+                                    empty_text: "HTTP"
+                                }
+                                proxy_https_port_input = <MpInput> {
+                                    width: 90
+                                    empty_text: "HTTPS"
+                                }
+                                proxy_socks_port_input = <MpInput> {
+                                    width: 90
+                                    empty_text: "SOCKS"
+                                }
+                                proxy_settings_save_btn = <MpButtonPrimary> {
+                                    text: "Save"
+                                }
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                proxy_mode_label = <Label> {text: "Proxy Mode", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                <View> {width: Fill, height: Fit}
+                                proxy_mode_dropdown = <MpDropdown> {
+                                    width: 200,
+                                    labels: ["Global", "PAC"],
+                                    selected_item: 0
+                                }
+                                proxy_pac_port_input = <MpInput> {
+                                    width: 90
+                                    empty_text: "7899"
+                                }
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                proxy_pac_direct_label = <Label> {text: "PAC Direct", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                proxy_pac_direct_input = <MpInput> {
+                                    width: Fill
+                                    empty_text: "intranet.example, *.lan, 100.64.0.0/10"
+                                }
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                proxy_restore_on_drift_label = <Label> {text: "Restore when changed elsewhere", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                <View> {width: Fill, height: Fit}
+                                proxy_restore_on_drift_switch = <MpSwitch> {}
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                proxy_shell_env_label = <Label> {text: "Terminal proxy", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                <View> {width: Fill, height: Fit}
+                                proxy_copy_command_btn = <MpButtonSmall> {
+                                    text: "Copy Proxy Command"
+                                }
+                                proxy_shell_env_switch = <MpSwitch> {}
+                            }
+
+                            proxy_pac_hint = <Label> {
+                                width: Fill
+                                text: ""
+                                draw_text: {text_style: <APP_FONT_CAPTION>{}, color: (TEXT_MUTED), wrap: Word}
+                            }
+
+                            proxy_settings_hint = <Label> {
+                                width: Fill
+                                text: ""
+                                draw_text: {text_style: <APP_FONT_CAPTION>{}, color: (TEXT_MUTED), wrap: Word}
+                            }
+
                             <View> {
                                 width: Fill,
                                 height: Fit,
@@ -667,6 +1141,7 @@ live_design! {
                                 clash_setting_title = <MpCardTitle> { text: "Clash Setting" }
                                 <View> {width: Fill, height: Fit}
                                 clash_core_upgrade_btn = <MpButtonPrimary> { text: "UPGRADE" }
+                                clash_core_rollback_btn = <MpButtonPrimary> { text: "ROLLBACK" }
                                 clash_core_restart_btn = <MpButtonPrimary> { text: "RESTART" }
                             }
                         }
@@ -693,6 +1168,42 @@ live_design! {
                                 }
                             }
 
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                clash_kernel_label = <Label> {text: "Kernel", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                <View> {width: Fill, height: Fit}
+                                clash_kernel_dropdown = <MpDropdown> {
+                                    width: 200,
+                                    labels: ["mihomo", "sing-box"],
+                                    selected_item: 0
+                                }
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                clash_channel_label = <Label> {text: "Update Channel", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                <View> {width: Fill, height: Fit}
+                                clash_channel_pinned_input = <MpInput> {
+                                    width: 120
+                                    empty_text: "v1.19.2"
+                                }
+                                clash_channel_dropdown = <MpDropdown> {
+                                    width: 200,
+                                    labels: ["Stable", "Alpha", "Pinned"],
+                                    selected_item: 0
+                                }
+                            }
+
                             <View> {
                                 width: Fill,
                                 height: Fit,
@@ -708,6 +1219,55 @@ live_design! {
                                 }
                             }
 
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                clash_core_previous_label = <Label> {text: "Previous Version", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                <View> {width: Fill, height: Fit}
+                                clash_core_previous_value = <Label> {
+                                    text: "-"
+                                    draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_MUTED)}
+                                }
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                clash_mirrors_label = <Label> {text: "Download Mirrors", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                clash_mirrors_input = <MpInput> {
+                                    width: Fill
+                                    empty_text: "https://mirror.example"
+                                }
+                                clash_mirrors_save_btn = <MpButtonPrimary> {
+                                    text: "Save"
+                                }
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                clash_core_file_label = <Label> {text: "Install From File", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                clash_core_file_input = <MpInput> {
+                                    width: Fill
+                                    empty_text: "/path/to/mihomo-linux-amd64-v1.19.2.gz"
+                                }
+                                clash_core_install_file_btn = <MpButtonPrimary> {
+                                    text: "INSTALL"
+                                }
+                            }
+
                             <View> {
                                 width: Fill,
                                 height: Fit,
@@ -720,6 +1280,150 @@ live_design! {
                                     draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_MUTED)}
                                 }
                             }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Down,
+                                spacing: (SPACE_1),
+
+                                clash_override_label = <Label> {text: "Config Overrides", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                clash_override_hint = <Label> {
+                                    width: Fill
+                                    text: ""
+                                    draw_text: {text_style: <APP_FONT_CAPTION>{}, color: (TEXT_MUTED), wrap: Word}
+                                }
+                                clash_override_value = <Label> {
+                                    width: Fill
+                                    text: "-"
+                                    draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_MUTED), wrap: Word}
+                                }
+                            }
+                        }
+                    }
+
+                    geodata_card = <MpCard> {
+                        width: Fill,
+                        <MpCardHeader> {
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_2),
+
+                                geodata_setting_title = <MpCardTitle> { text: "Geo Data" }
+                                <View> {width: Fill, height: Fit}
+                                geodata_update_btn = <MpButtonPrimary> { text: "UPDATE" }
+                            }
+                        }
+                        <MpCardContent> {
+                            width: Fill,
+                            flow: Down,
+                            spacing: (SPACE_3),
+
+                            geodata_hint = <Label> {
+                                width: Fill
+                                text: ""
+                                draw_text: {text_style: <APP_FONT_CAPTION>{}, color: (TEXT_MUTED), wrap: Word}
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                geodata_geoip_label = <Label> {text: "geoip.metadb", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                <View> {width: Fill, height: Fit}
+                                geodata_geoip_value = <Label> {
+                                    text: "-"
+                                    draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_MUTED)}
+                                }
+                            }
+                            geodata_geoip_url_input = <MpInput> {
+                                width: Fill
+                                empty_text: "https://github.com/MetaCubeX/meta-rules-dat/releases/download/latest/geoip.metadb"
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                geodata_geosite_label = <Label> {text: "geosite.dat", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                <View> {width: Fill, height: Fit}
+                                geodata_geosite_value = <Label> {
+                                    text: "-"
+                                    draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_MUTED)}
+                                }
+                            }
+                            geodata_geosite_url_input = <MpInput> {
+                                width: Fill
+                                empty_text: "https://github.com/MetaCubeX/meta-rules-dat/releases/download/latest/geosite.dat"
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                geodata_asn_label = <Label> {text: "GeoLite2-ASN.mmdb", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                <View> {width: Fill, height: Fit}
+                                geodata_asn_value = <Label> {
+                                    text: "-"
+                                    draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_MUTED)}
+                                }
+                            }
+                            geodata_asn_url_input = <MpInput> {
+                                width: Fill
+                                empty_text: "https://github.com/MetaCubeX/meta-rules-dat/releases/download/latest/GeoLite2-ASN.mmdb"
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                geodata_interval_label = <Label> {text: "Update Interval (hours)", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                <View> {width: Fill, height: Fit}
+                                geodata_interval_input = <MpInput> {
+                                    width: 120
+                                    empty_text: "24"
+                                }
+                                geodata_save_btn = <MpButtonPrimary> {
+                                    text: "Save"
+                                }
+                            }
+
+                            <View> {
+                                width: Fill,
+                                height: Fit,
+                                flow: Right,
+                                align: {y: 0.5},
+                                spacing: (SPACE_3),
+
+                                geodata_import_label = <Label> {text: "Import File", draw_text: {text_style: <APP_FONT_BODY>{}, color: (TEXT_PRIMARY)}}
+                                geodata_import_dropdown = <MpDropdown> {
+                                    width: 200,
+                                    labels: ["geoip.metadb", "geosite.dat", "GeoLite2-ASN.mmdb"],
+                                    selected_item: 0
+                                }
+                                geodata_file_input = <MpInput> {
+                                    width: Fill
+                                    empty_text: "/path/to/geosite.dat"
+                                }
+                                geodata_import_btn = <MpButtonPrimary> {
+                                    text: "IMPORT"
+                                }
+                            }
                         }
                     }
                 }
@@ -21,5 +21,12 @@ live_design! {
             text: "Profiles",
             draw_text: {text_style: <APP_FONT_TITLE>{}, color: (TEXT_PRIMARY)},
         }
+
+        <View> { width: Fill, height: Fit }
+
+        traffic_label = <Label> {
+            text: "",
+            draw_text: {text_style: <APP_FONT_CAPTION>{}, color: (TEXT_MUTED)},
+        }
     }
 }
@@ -45,9 +45,12 @@ live_design! {
             flow: Down,
             spacing: (SPACE_2),
 
+            menu_overview = <MenuButton> { text: "Overview" }
             menu_profiles = <MenuButton> { text: "Profiles" }
             menu_proxy_groups = <MenuButton> { text: "Proxy Groups" }
             menu_rules = <MenuButton> { text: "Rules" }
+            menu_connections = <MenuButton> { text: "Connections" }
+            menu_logs = <MenuButton> { text: "Logs" }
             menu_settings = <MenuButton> { text: "Settings" }
         }
     }
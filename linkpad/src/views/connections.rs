@@ -0,0 +1,342 @@
+use super::*;
+use linkpad_core::{ConnectionSort, TrackedConnection};
+
+/// Rows declared for the connections list in the dashboard layout.
+const CONNECTION_ROWS: usize = 30;
+
+#[derive(Clone, Copy)]
+struct ConnectionRowIds {
+    row: [LiveId; 2],
+    target: [LiveId; 3],
+    meta: [LiveId; 3],
+    traffic: [LiveId; 3],
+    close_btn: [LiveId; 3],
+}
+
+impl App {
+    pub(super) fn handle_connections_actions(&mut self, cx: &mut Cx, actions: &Actions) {
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.connections_search_input))
+            .changed(actions)
+        {
+            self.state.connections_query = value;
+            self.refresh_ui(cx);
+        }
+        if let Some(index) = self
+            .ui
+            .drop_down(ids!(dashboard.connections_sort_dropdown))
+            .changed(actions)
+        {
+            self.state.connections_sort =
+                ConnectionSort::ALL.get(index).copied().unwrap_or_default();
+            self.refresh_ui(cx);
+        }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.connections_closed_btn))
+            .clicked(actions)
+        {
+            self.state.connections_show_closed = !self.state.connections_show_closed;
+            self.refresh_ui(cx);
+        }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.connections_close_all_btn))
+            .clicked(actions)
+        {
+            let result = self.core.close_all_connections();
+            self.finish_close_connection(cx, result);
+        }
+        if !self.state.connections_show_closed {
+            let visible = self.visible_connections();
+            for (index, tracked) in visible.iter().take(CONNECTION_ROWS).enumerate() {
+                let row_ids = Self::connection_row_ids(index);
+                if self.ui.mp_button(&row_ids.close_btn).clicked(actions) {
+                    let result = self.core.close_connection(&tracked.connection.id);
+                    self.finish_close_connection(cx, result);
+                }
+            }
+        }
+    }
+
+    pub(super) fn poll_connections(&mut self, cx: &mut Cx) {
+        if self.state.active_page != Page::Connections {
+            return;
+        }
+        let connections = self.core.connections();
+        if connections == self.state.connections {
+            return;
+        }
+        self.state.connections = connections;
+        let strings = i18n::strings(self.state.language);
+        self.apply_connections_state(cx, strings);
+        self.ui.redraw(cx);
+    }
+
+    fn finish_close_connection(&mut self, cx: &mut Cx, result: linkpad_core::CoreResult<()>) {
+        if let Err(error) = result {
+            let strings = i18n::strings(self.state.language);
+            error!("close connection failed: {error}");
+            self.push_notification(
+                cx,
+                NotificationLevel::Error,
+                format!("{}: {error}", strings.connections_close_failed_prefix),
+            );
+        }
+        self.state.connections = self.core.connections();
+        self.refresh_ui(cx);
+    }
+
+    /// The open connections, or the closed history, after search and sort.
+    fn visible_connections(&self) -> Vec<TrackedConnection> {
+        let source = if self.state.connections_show_closed {
+            &self.state.connections.closed
+        } else {
+            &self.state.connections.active
+        };
+        let mut visible = source
+            .iter()
+            .filter(|tracked| tracked.matches(&self.state.connections_query))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.state.connections_sort.sort(&mut visible);
+        visible
+    }
+
+    pub(super) fn apply_connections_state(&mut self, cx: &mut Cx, strings: &i18n::Strings) {
+        self.ui
+            .label(ids!(dashboard.connections_title))
+            .set_text(cx, strings.connections_title);
+        self.ui
+            .label(ids!(dashboard.connections_desc))
+            .set_text(cx, strings.connections_desc);
+        self.ui
+            .text_input(ids!(dashboard.connections_search_input))
+            .apply_over(
+                cx,
+                live! {
+                    empty_text: (strings.connections_search_placeholder)
+                },
+            );
+        self.ui
+            .text_input(ids!(dashboard.connections_search_input))
+            .set_text(cx, &self.state.connections_query);
+
+        let sort_labels = ConnectionSort::ALL
+            .iter()
+            .map(|sort| Self::connection_sort_label(strings, *sort).to_string())
+            .collect::<Vec<_>>();
+        let sort_index = ConnectionSort::ALL
+            .iter()
+            .position(|sort| *sort == self.state.connections_sort)
+            .unwrap_or(0);
+        let sort_dropdown = self.ui.drop_down(ids!(dashboard.connections_sort_dropdown));
+        sort_dropdown.set_labels(cx, sort_labels);
+        sort_dropdown.set_selected_item(cx, sort_index);
+
+        let show_closed = self.state.connections_show_closed;
+        self.ui
+            .mp_button(ids!(dashboard.connections_closed_btn))
+            .set_text(if show_closed {
+                strings.connections_show_active
+            } else {
+                strings.connections_show_closed
+            });
+        self.ui
+            .mp_button(ids!(dashboard.connections_close_all_btn))
+            .set_text(strings.connections_close_all_button);
+
+        let connections = &self.state.connections;
+        let summary = if connections.connected {
+            format!(
+                "{}: {} | {}: {} | {}: ↑ {} ↓ {}",
+                strings.connections_summary_active,
+                connections.active.len(),
+                strings.connections_summary_closed,
+                connections.closed.len(),
+                strings.connections_summary_total,
+                Self::format_bytes(connections.upload_total),
+                Self::format_bytes(connections.download_total)
+            )
+        } else {
+            strings.connections_disconnected.to_string()
+        };
+        self.ui
+            .label(ids!(dashboard.connections_summary))
+            .set_text(cx, &summary);
+
+        let visible = self.visible_connections();
+        self.ui
+            .label(ids!(dashboard.connections_empty))
+            .set_visible(cx, visible.is_empty());
+        self.ui.label(ids!(dashboard.connections_empty)).set_text(
+            cx,
+            if show_closed {
+                strings.connections_closed_empty
+            } else {
+                strings.connections_empty
+            },
+        );
+
+        let now = Self::now_unix_seconds();
+        for index in 0..CONNECTION_ROWS {
+            let row_ids = Self::connection_row_ids(index);
+            let Some(tracked) = visible.get(index) else {
+                self.ui.view(&row_ids.row).set_visible(cx, false);
+                continue;
+            };
+            self.ui.view(&row_ids.row).set_visible(cx, true);
+            self.ui
+                .label(&row_ids.target)
+                .set_text(cx, &tracked.target());
+            self.ui
+                .label(&row_ids.meta)
+                .set_text(cx, &Self::connection_meta_text(tracked));
+            let mut traffic = format!(
+                "↑ {}  ↓ {}",
+                Self::format_bytes(tracked.connection.upload),
+                Self::format_bytes(tracked.connection.download)
+            );
+            if let Some(duration) = tracked.duration_secs(now) {
+                traffic.push_str(&format!(" | {}", Self::format_duration(duration)));
+            }
+            self.ui.label(&row_ids.traffic).set_text(cx, &traffic);
+            self.ui
+                .mp_button(&row_ids.close_btn)
+                .set_text(strings.connections_close_button);
+            self.ui
+                .widget(&row_ids.close_btn)
+                .set_visible(cx, !show_closed);
+        }
+
+        let hidden = visible.len().saturating_sub(CONNECTION_ROWS);
+        self.ui
+            .label(ids!(dashboard.connections_overflow))
+            .set_text(
+                cx,
+                &if hidden > 0 {
+                    format!("{}: {hidden}", strings.connections_overflow_prefix)
+                } else {
+                    String::new()
+                },
+            );
+    }
+
+    pub(super) fn apply_connections_palette(&mut self, cx: &mut Cx, palette: ThemePalette) {
+        self.ui.widget(ids!(dashboard.connections_card)).apply_over(
+            cx,
+            live! {
+                draw_bg: { color: (palette.panel_alt_bg) }
+            },
+        );
+        self.apply_input_theme(cx, ids!(dashboard.connections_search_input), palette);
+        self.apply_dropdown_theme(cx, ids!(dashboard.connections_sort_dropdown), palette);
+        for id in [
+            ids!(dashboard.connections_title),
+            ids!(dashboard.connections_summary),
+        ] {
+            self.ui.label(id).apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+        }
+        for id in [
+            ids!(dashboard.connections_desc),
+            ids!(dashboard.connections_empty),
+            ids!(dashboard.connections_overflow),
+        ] {
+            self.ui.label(id).apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_muted) }
+                },
+            );
+        }
+        for index in 0..CONNECTION_ROWS {
+            let row_ids = Self::connection_row_ids(index);
+            self.ui.view(&row_ids.row).apply_over(
+                cx,
+                live! {
+                    draw_bg: { color: (palette.panel_accent_bg) }
+                },
+            );
+            self.ui.label(&row_ids.target).apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+            self.ui.label(&row_ids.meta).apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_muted) }
+                },
+            );
+            self.ui.label(&row_ids.traffic).apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_muted) }
+                },
+            );
+        }
+    }
+
+    fn connection_row_ids(index: usize) -> ConnectionRowIds {
+        let row_id = LiveId::from_str(&format!("connection_row_{}", index + 1));
+        ConnectionRowIds {
+            row: [live_id!(dashboard), row_id],
+            target: [live_id!(dashboard), row_id, live_id!(conn_target)],
+            meta: [live_id!(dashboard), row_id, live_id!(conn_meta)],
+            traffic: [live_id!(dashboard), row_id, live_id!(conn_traffic)],
+            close_btn: [live_id!(dashboard), row_id, live_id!(conn_close_btn)],
+        }
+    }
+
+    fn connection_sort_label(strings: &i18n::Strings, sort: ConnectionSort) -> &'static str {
+        match sort {
+            ConnectionSort::Newest => strings.connections_sort_newest,
+            ConnectionSort::Download => strings.connections_sort_download,
+            ConnectionSort::Upload => strings.connections_sort_upload,
+            ConnectionSort::Host => strings.connections_sort_host,
+        }
+    }
+
+    /// Process, network, matched rule and the proxy chain from the selected
+    /// group down to the node.
+    fn connection_meta_text(tracked: &TrackedConnection) -> String {
+        let connection = &tracked.connection;
+        let metadata = &connection.metadata;
+        let process = if metadata.process.is_empty() {
+            "-"
+        } else {
+            metadata.process.as_str()
+        };
+        let rule = if connection.rule_payload.is_empty() {
+            connection.rule.clone()
+        } else {
+            format!("{}({})", connection.rule, connection.rule_payload)
+        };
+        let chain = connection
+            .chains
+            .iter()
+            .rev()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" → ");
+        format!(
+            "{process} | {} | {rule} | {chain}",
+            metadata.network.to_ascii_uppercase()
+        )
+    }
+
+    fn format_duration(seconds: u64) -> String {
+        match seconds {
+            0..60 => format!("{seconds}s"),
+            60..3600 => format!("{}m {}s", seconds / 60, seconds % 60),
+            _ => format!("{}h {}m", seconds / 3600, (seconds % 3600) / 60),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use super::*;
+use linkpad_core::{LogEntry, LogLevel};
+use robius_directories::UserDirs;
+use std::path::Path;
+
+/// Entries kept by the page; older ones are dropped as new ones arrive.
+const LOG_HISTORY_LEN: usize = 2000;
+/// Newest matching entries rendered in the list.
+const LOG_LINES: usize = 200;
+
+impl App {
+    pub(super) fn handle_logs_actions(&mut self, cx: &mut Cx, actions: &Actions) {
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.logs_search_input))
+            .changed(actions)
+        {
+            self.state.logs_query = value;
+            self.refresh_ui(cx);
+        }
+        if let Some(index) = self
+            .ui
+            .drop_down(ids!(dashboard.logs_level_dropdown))
+            .changed(actions)
+        {
+            self.state.logs_level = LogLevel::ALL.get(index).copied().unwrap_or_default();
+            self.refresh_ui(cx);
+        }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.logs_pause_btn))
+            .clicked(actions)
+        {
+            self.state.logs_paused = !self.state.logs_paused;
+            self.poll_logs(cx);
+            self.refresh_ui(cx);
+        }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.logs_clear_btn))
+            .clicked(actions)
+        {
+            // Keep the sequence number so cleared entries are not fetched again.
+            self.state.logs.clear();
+            self.refresh_ui(cx);
+        }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.logs_export_btn))
+            .clicked(actions)
+        {
+            self.export_logs(cx);
+        }
+    }
+
+    pub(super) fn poll_logs(&mut self, cx: &mut Cx) {
+        if self.state.logs_paused {
+            return;
+        }
+        let entries = self.core.kernel_logs_since(self.state.logs_last_seq);
+        let Some(last) = entries.last() else {
+            return;
+        };
+        self.state.logs_last_seq = Some(last.seq);
+        self.state.logs.extend(entries);
+        let excess = self.state.logs.len().saturating_sub(LOG_HISTORY_LEN);
+        self.state.logs.drain(..excess);
+        if self.state.active_page != Page::Logs {
+            return;
+        }
+        let strings = i18n::strings(self.state.language);
+        self.apply_logs_state(cx, strings);
+        self.ui.redraw(cx);
+    }
+
+    fn export_logs(&mut self, cx: &mut Cx) {
+        let strings = i18n::strings(self.state.language);
+        let log_path = self.core.kernel_log_path();
+        let dest_dir = UserDirs::new()
+            .and_then(|dirs| dirs.download_dir().map(Path::to_path_buf))
+            .or_else(|| log_path.parent().map(Path::to_path_buf))
+            .unwrap_or_else(std::env::temp_dir);
+        match self.core.export_kernel_log(&dest_dir) {
+            Ok(path) => self.push_notification(
+                cx,
+                NotificationLevel::Success,
+                format!("{}: {}", strings.logs_export_success_prefix, path.display()),
+            ),
+            Err(error) => {
+                error!("export kernel log failed: {error}");
+                self.push_notification(
+                    cx,
+                    NotificationLevel::Error,
+                    format!("{}: {error}", strings.logs_export_failed_prefix),
+                );
+            }
+        }
+        self.refresh_ui(cx);
+    }
+
+    pub(super) fn apply_logs_state(&mut self, cx: &mut Cx, strings: &i18n::Strings) {
+        self.ui
+            .label(ids!(dashboard.logs_title))
+            .set_text(cx, strings.logs_title);
+        self.ui
+            .label(ids!(dashboard.logs_desc))
+            .set_text(cx, strings.logs_desc);
+        self.ui
+            .text_input(ids!(dashboard.logs_search_input))
+            .apply_over(
+                cx,
+                live! {
+                    empty_text: (strings.logs_search_placeholder)
+                },
+            );
+        self.ui
+            .text_input(ids!(dashboard.logs_search_input))
+            .set_text(cx, &self.state.logs_query);
+
+        let level_labels = LogLevel::ALL
+            .iter()
+            .map(|level| Self::log_level_label(strings, *level).to_string())
+            .collect::<Vec<_>>();
+        let level_index = LogLevel::ALL
+            .iter()
+            .position(|level| *level == self.state.logs_level)
+            .unwrap_or(0);
+        let level_dropdown = self.ui.drop_down(ids!(dashboard.logs_level_dropdown));
+        level_dropdown.set_labels(cx, level_labels);
+        level_dropdown.set_selected_item(cx, level_index);
+
+        self.ui
+            .mp_button(ids!(dashboard.logs_pause_btn))
+            .set_text(if self.state.logs_paused {
+                strings.logs_resume_button
+            } else {
+                strings.logs_pause_button
+            });
+        self.ui
+            .mp_button(ids!(dashboard.logs_clear_btn))
+            .set_text(strings.logs_clear_button);
+        self.ui
+            .mp_button(ids!(dashboard.logs_export_btn))
+            .set_text(strings.logs_export_button);
+
+        let matching = self
+            .state
+            .logs
+            .iter()
+            .filter(|entry| entry.matches(self.state.logs_level, &self.state.logs_query))
+            .collect::<Vec<_>>();
+        let shown = &matching[matching.len().saturating_sub(LOG_LINES)..];
+
+        let mut status = format!(
+            "{}: {} / {} | {}: {}",
+            strings.logs_shown_prefix,
+            shown.len(),
+            self.state.logs.len(),
+            strings.logs_file_prefix,
+            self.core.kernel_log_path().display()
+        );
+        if self.state.logs_paused {
+            status = format!("{} | {status}", strings.logs_paused);
+        }
+        self.ui
+            .label(ids!(dashboard.logs_status))
+            .set_text(cx, &status);
+
+        let list = if shown.is_empty() {
+            strings.logs_empty.to_string()
+        } else {
+            shown
+                .iter()
+                .map(|entry| Self::log_line(entry))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        self.ui.label(ids!(dashboard.logs_list)).set_text(cx, &list);
+    }
+
+    pub(super) fn apply_logs_palette(&mut self, cx: &mut Cx, palette: ThemePalette) {
+        self.ui.widget(ids!(dashboard.logs_card)).apply_over(
+            cx,
+            live! {
+                draw_bg: { color: (palette.panel_alt_bg) }
+            },
+        );
+        self.apply_input_theme(cx, ids!(dashboard.logs_search_input), palette);
+        self.apply_dropdown_theme(cx, ids!(dashboard.logs_level_dropdown), palette);
+        for id in [ids!(dashboard.logs_title), ids!(dashboard.logs_list)] {
+            self.ui.label(id).apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+        }
+        for id in [ids!(dashboard.logs_desc), ids!(dashboard.logs_status)] {
+            self.ui.label(id).apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_muted) }
+                },
+            );
+        }
+    }
+
+    fn log_level_label(strings: &i18n::Strings, level: LogLevel) -> &'static str {
+        match level {
+            LogLevel::Debug => strings.logs_level_debug,
+            LogLevel::Info => strings.logs_level_info,
+            LogLevel::Warning => strings.logs_level_warning,
+            LogLevel::Error => strings.logs_level_error,
+        }
+    }
+
+    /// `HH:MM:SS LEVEL message`, taking the clock time from the kernel's
+    /// RFC 3339 timestamp.
+    fn log_line(entry: &LogEntry) -> String {
+        let clock = entry
+            .time
+            .split_once('T')
+            .and_then(|(_, time)| time.get(..8))
+            .unwrap_or("--:--:--");
+        format!(
+            "{clock} {:<7} {}",
+            entry.level.as_str().to_ascii_uppercase(),
+            entry.message
+        )
+    }
+}
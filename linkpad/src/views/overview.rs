@@ -0,0 +1,158 @@
+use super::*;
+
+/// Bar heights of the throughput sparkline, from idle to the peak rate.
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Seconds of history drawn in the throughput chart.
+const SPARKLINE_WIDTH: usize = 60;
+
+impl App {
+    pub(super) fn poll_traffic(&mut self, cx: &mut Cx) {
+        let traffic = self.core.traffic_stats();
+        if traffic == self.state.traffic {
+            return;
+        }
+        self.state.traffic = traffic;
+        let strings = i18n::strings(self.state.language);
+        self.apply_traffic_state(cx, strings);
+        self.ui.redraw(cx);
+    }
+
+    pub(super) fn apply_traffic_state(&mut self, cx: &mut Cx, strings: &i18n::Strings) {
+        let traffic = &self.state.traffic;
+        let current = traffic.current();
+
+        let header_text = current
+            .map(|sample| {
+                format!(
+                    "↑ {}  ↓ {}",
+                    Self::format_rate(sample.up),
+                    Self::format_rate(sample.down)
+                )
+            })
+            .unwrap_or_default();
+        self.ui
+            .label(ids!(header.traffic_label))
+            .set_text(cx, &header_text);
+
+        self.ui
+            .label(ids!(dashboard.overview_traffic_title))
+            .set_text(cx, strings.overview_traffic_title);
+        self.ui.label(ids!(dashboard.overview_status)).set_text(
+            cx,
+            if traffic.connected {
+                ""
+            } else {
+                strings.overview_disconnected
+            },
+        );
+        let rate_text = |rate: Option<u64>| rate.map_or("-".to_string(), Self::format_rate);
+        self.ui.label(ids!(dashboard.overview_download)).set_text(
+            cx,
+            &format!(
+                "{}: {}",
+                strings.overview_download_label,
+                rate_text(current.map(|sample| sample.down))
+            ),
+        );
+        self.ui.label(ids!(dashboard.overview_upload)).set_text(
+            cx,
+            &format!(
+                "{}: {}",
+                strings.overview_upload_label,
+                rate_text(current.map(|sample| sample.up))
+            ),
+        );
+        self.ui.label(ids!(dashboard.overview_memory)).set_text(
+            cx,
+            &format!(
+                "{}: {}",
+                strings.overview_memory_label,
+                traffic
+                    .memory
+                    .filter(|_| traffic.connected)
+                    .map_or("-".to_string(), Self::format_bytes)
+            ),
+        );
+
+        let recent = &traffic.samples[traffic.samples.len().saturating_sub(SPARKLINE_WIDTH)..];
+        let peak = recent
+            .iter()
+            .map(|sample| sample.up.max(sample.down))
+            .max()
+            .unwrap_or(0);
+        self.ui.label(ids!(dashboard.overview_peak)).set_text(
+            cx,
+            &format!(
+                "{}: {}",
+                strings.overview_peak_label,
+                Self::format_rate(traffic.peak_rate())
+            ),
+        );
+        let download_chart = Self::sparkline(recent.iter().map(|sample| sample.down), peak);
+        let upload_chart = Self::sparkline(recent.iter().map(|sample| sample.up), peak);
+        self.ui
+            .label(ids!(dashboard.overview_download_chart))
+            .set_text(cx, &format!("↓ {download_chart}"));
+        self.ui
+            .label(ids!(dashboard.overview_upload_chart))
+            .set_text(cx, &format!("↑ {upload_chart}"));
+    }
+
+    pub(super) fn apply_overview_palette(&mut self, cx: &mut Cx, palette: ThemePalette) {
+        for id in [
+            ids!(dashboard.overview_traffic_title),
+            ids!(dashboard.overview_download),
+            ids!(dashboard.overview_upload),
+            ids!(dashboard.overview_memory),
+            ids!(dashboard.overview_peak),
+        ] {
+            self.ui.label(id).apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_primary) }
+                },
+            );
+        }
+        self.ui.label(ids!(dashboard.overview_status)).apply_over(
+            cx,
+            live! {
+                draw_text: { color: (palette.text_muted) }
+            },
+        );
+        self.ui
+            .label(ids!(dashboard.overview_download_chart))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.status_success) }
+                },
+            );
+        self.ui
+            .label(ids!(dashboard.overview_upload_chart))
+            .apply_over(
+                cx,
+                live! {
+                    draw_text: { color: (palette.text_muted) }
+                },
+            );
+    }
+
+    fn format_rate(bytes_per_second: u64) -> String {
+        format!("{}/s", Self::format_bytes(bytes_per_second))
+    }
+
+    /// One block character per sample, scaled so `peak` fills the cell.
+    fn sparkline(values: impl Iterator<Item = u64>, peak: u64) -> String {
+        let top = SPARKLINE_LEVELS.len() - 1;
+        values
+            .map(|value| {
+                let level = if peak == 0 {
+                    0
+                } else {
+                    ((u128::from(value) * top as u128).div_ceil(u128::from(peak)) as usize).min(top)
+                };
+                SPARKLINE_LEVELS[level]
+            })
+            .collect()
+    }
+}
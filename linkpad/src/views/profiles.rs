@@ -1,4 +1,6 @@
 use super::*;
+use linkpad_core::{CoreResult, Profile};
+use std::path::Path;
 
 impl App {
     pub(super) fn handle_profiles_actions(&mut self, cx: &mut Cx, actions: &Actions) {
@@ -16,6 +18,62 @@ impl App {
         {
             self.import_profile_from_input(cx);
         }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.profile_file_input))
+            .changed(actions)
+        {
+            self.state.profile_file_input = value;
+        }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.profile_file_import_btn))
+            .clicked(actions)
+        {
+            self.import_profile_from_file(cx);
+        }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.profile_text_name_input))
+            .changed(actions)
+        {
+            self.state.profile_text_name_input = value;
+        }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.profile_text_input))
+            .changed(actions)
+        {
+            self.state.profile_text_input = value;
+        }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.profile_text_import_btn))
+            .clicked(actions)
+        {
+            self.import_profile_from_text(cx);
+        }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.profile_row_1_interval_input))
+            .changed(actions)
+        {
+            self.update_profile_interval_row(cx, 0, &value);
+        }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.profile_row_2_interval_input))
+            .changed(actions)
+        {
+            self.update_profile_interval_row(cx, 1, &value);
+        }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.profile_row_3_interval_input))
+            .changed(actions)
+        {
+            self.update_profile_interval_row(cx, 2, &value);
+        }
         if self
             .ui
             .mp_button(ids!(dashboard.profile_row_1_activate_btn))
@@ -88,7 +146,7 @@ impl App {
         self.state.import_status.is_error = false;
     }
 
-    fn set_import_status_error(&mut self, message: String) {
+    pub(super) fn set_import_status_error(&mut self, message: String) {
         self.state.import_status.message = message;
         self.state.import_status.is_error = true;
     }
@@ -102,37 +160,73 @@ impl App {
     }
 
     fn import_profile_from_input(&mut self, cx: &mut Cx) {
-        if self.import_rx.is_some() {
-            warn!("skip profile import: previous import task still running");
-            return;
-        }
-
         let strings = i18n::strings(self.state.language);
         let url = self.state.profile_url_input.trim().to_string();
         if url.is_empty() {
             warn!("skip profile import: url is empty");
-            let message = strings.profiles_import_error.to_string();
-            self.set_import_status_error(message.clone());
-            self.push_notification(cx, NotificationLevel::Error, message);
-            self.refresh_ui(cx);
+            self.reject_profile_import(cx, strings.profiles_import_error.to_string());
             return;
         }
         info!("profile import requested");
+        self.start_profile_import(cx, move |core| core.import_profile_url(&url, true));
+    }
+
+    fn import_profile_from_file(&mut self, cx: &mut Cx) {
+        let strings = i18n::strings(self.state.language);
+        let path = self.state.profile_file_input.trim().to_string();
+        if path.is_empty() {
+            warn!("skip profile import: file path is empty");
+            self.reject_profile_import(cx, strings.profiles_import_file_error.to_string());
+            return;
+        }
+        info!("profile file import requested");
+        self.start_profile_import(cx, move |core| {
+            core.import_profile_file(Path::new(&path), true)
+        });
+    }
+
+    fn import_profile_from_text(&mut self, cx: &mut Cx) {
+        let strings = i18n::strings(self.state.language);
+        let content = self.state.profile_text_input.clone();
+        if content.trim().is_empty() {
+            warn!("skip profile import: pasted content is empty");
+            self.reject_profile_import(cx, strings.profiles_import_text_error.to_string());
+            return;
+        }
+        let name = self.state.profile_text_name_input.trim().to_string();
+        info!("profile text import requested");
+        self.start_profile_import(cx, move |core| {
+            core.import_profile_text(&name, &content, true)
+        });
+    }
+
+    fn reject_profile_import(&mut self, cx: &mut Cx, message: String) {
+        self.set_import_status_error(message.clone());
+        self.push_notification(cx, NotificationLevel::Error, message);
+        self.refresh_ui(cx);
+    }
+
+    fn start_profile_import<F>(&mut self, cx: &mut Cx, task: F)
+    where
+        F: FnOnce(&LinkpadCore) -> CoreResult<Profile> + Send + 'static,
+    {
+        if self.import_rx.is_some() {
+            warn!("skip profile import: previous import task still running");
+            return;
+        }
         if !self.import_poll_timer.is_empty() {
             cx.stop_timer(self.import_poll_timer);
             self.import_poll_timer = Timer::default();
         }
 
+        let strings = i18n::strings(self.state.language);
         self.state.import_status.message = strings.profiles_import_loading.to_string();
         self.state.import_status.is_error = false;
 
         let core = self.core.clone();
         let (tx, rx) = std::sync::mpsc::channel::<ImportTaskResult>();
         thread::spawn(move || {
-            let result = core
-                .import_profile_url(&url, true)
-                .map(|_| ())
-                .map_err(|error| error.to_string());
+            let result = task(&core).map(|_| ()).map_err(|error| error.to_string());
             let _ = tx.send(result);
         });
 
@@ -163,6 +257,9 @@ impl App {
                 self.persist_profiles();
                 self.sync_from_core();
                 self.state.profile_url_input.clear();
+                self.state.profile_file_input.clear();
+                self.state.profile_text_name_input.clear();
+                self.state.profile_text_input.clear();
                 self.state.import_status.message = strings.profiles_import_success.to_string();
                 self.state.import_status.is_error = false;
                 info!("profile import succeeded");
@@ -171,6 +268,7 @@ impl App {
                     NotificationLevel::Success,
                     strings.profiles_import_success.to_string(),
                 );
+                self.notify_subscription_warnings(cx);
             }
             Err(error) => {
                 let strings = i18n::strings(self.state.language);
@@ -193,17 +291,18 @@ impl App {
             return;
         };
 
-        match self.core.set_active_profile(&profile_id) {
-            Ok(()) => {
-                self.persist_profiles();
-                self.sync_from_core();
-                self.set_import_status_ready();
-            }
-            Err(error) => {
-                self.set_import_status_error(format!("{error}"));
-            }
-        }
-        self.refresh_ui(cx);
+        // The running kernel tests the profile and reloads or restarts on
+        // it, so the switch runs as a core task.
+        self.start_core_task(cx, CoreTaskKind::ActivatingProfile, move |core| {
+            core.set_active_profile(&profile_id)
+                .map(|()| CoreTaskOutput::ProfileActivated)
+        });
+    }
+
+    pub(super) fn apply_profile_activated(&mut self) {
+        self.persist_profiles();
+        self.sync_from_core();
+        self.set_import_status_ready();
     }
 
     fn refresh_profile_row(&mut self, cx: &mut Cx, row_index: usize) {
@@ -216,8 +315,31 @@ impl App {
             return;
         };
 
-        match self.core.refresh_profile(&profile_id) {
-            Ok(_) => {
+        self.start_core_task(cx, CoreTaskKind::RefreshingProfile, move |core| {
+            core.refresh_profile(&profile_id)
+                .map(|_| CoreTaskOutput::ProfileRefreshed)
+        });
+    }
+
+    pub(super) fn apply_profile_refreshed(&mut self, cx: &mut Cx) {
+        self.persist_profiles();
+        self.sync_from_core();
+        self.set_import_status_ready();
+        self.notify_subscription_warnings(cx);
+    }
+
+    fn delete_profile_row(&mut self, cx: &mut Cx, row_index: usize) {
+        let Some(profile_id) = self
+            .state
+            .profiles
+            .get(row_index)
+            .map(|profile| profile.id.clone())
+        else {
+            return;
+        };
+
+        match self.core.delete_profile(&profile_id) {
+            Ok(()) => {
                 self.persist_profiles();
                 self.sync_from_core();
                 self.set_import_status_ready();
@@ -229,7 +351,7 @@ impl App {
         self.refresh_ui(cx);
     }
 
-    fn delete_profile_row(&mut self, cx: &mut Cx, row_index: usize) {
+    fn update_profile_interval_row(&mut self, cx: &mut Cx, row_index: usize, value: &str) {
         let Some(profile_id) = self
             .state
             .profiles
@@ -238,12 +360,23 @@ impl App {
         else {
             return;
         };
+        let trimmed = value.trim();
+        let hours = if trimmed.is_empty() {
+            None
+        } else {
+            match trimmed.parse::<u32>() {
+                Ok(hours) => Some(hours),
+                Err(_) => {
+                    warn!("ignore invalid profile update interval: {trimmed}");
+                    return;
+                }
+            }
+        };
 
-        match self.core.delete_profile(&profile_id) {
+        match self.core.set_profile_update_interval(&profile_id, hours) {
             Ok(()) => {
                 self.persist_profiles();
                 self.sync_from_core();
-                self.set_import_status_ready();
             }
             Err(error) => {
                 self.set_import_status_error(format!("{error}"));
@@ -252,6 +385,97 @@ impl App {
         self.refresh_ui(cx);
     }
 
+    pub(super) fn handle_scheduled_profile_refresh(
+        &mut self,
+        cx: &mut Cx,
+        name: &str,
+        changed: bool,
+    ) {
+        self.persist_profiles();
+        self.sync_from_core();
+        if changed {
+            let strings = i18n::strings(self.state.language);
+            self.push_notification(
+                cx,
+                NotificationLevel::Success,
+                format!(
+                    "{}: {name}",
+                    strings.profiles_scheduled_refresh_success_prefix
+                ),
+            );
+        }
+        self.notify_subscription_warnings(cx);
+    }
+
+    pub(super) fn handle_scheduled_profile_refresh_failure(
+        &mut self,
+        cx: &mut Cx,
+        name: &str,
+        error: &str,
+    ) {
+        let strings = i18n::strings(self.state.language);
+        self.push_notification(
+            cx,
+            NotificationLevel::Error,
+            format!(
+                "{}: {name} ({error})",
+                strings.profiles_scheduled_refresh_failed_prefix
+            ),
+        );
+    }
+
+    /// Warns once per session about subscriptions close to their traffic quota
+    /// or expiry date.
+    pub(super) fn notify_subscription_warnings(&mut self, cx: &mut Cx) {
+        let strings = i18n::strings(self.state.language);
+        let now = Self::now_unix_seconds();
+        let mut warnings = Vec::new();
+        for profile in &self.state.profiles {
+            let Some(info) = profile.subscription else {
+                continue;
+            };
+            if info
+                .usage_ratio()
+                .is_some_and(|ratio| ratio >= SUBSCRIPTION_QUOTA_WARN_RATIO)
+            {
+                warnings.push((
+                    format!("{}:quota", profile.id),
+                    format!(
+                        "{}: {} ({} / {})",
+                        strings.profiles_quota_warning_prefix,
+                        profile.name,
+                        Self::format_bytes(info.used()),
+                        Self::format_bytes(info.total)
+                    ),
+                ));
+            }
+            if let Some(remaining) = info.seconds_until_expiry(now) {
+                let prefix = if remaining <= 0 {
+                    strings.profiles_expired_warning_prefix
+                } else if remaining <= SUBSCRIPTION_EXPIRY_WARN_SECS {
+                    strings.profiles_expiry_warning_prefix
+                } else {
+                    continue;
+                };
+                warnings.push((
+                    format!("{}:expiry", profile.id),
+                    format!(
+                        "{prefix}: {} ({})",
+                        profile.name,
+                        info.expire_date().unwrap_or_default()
+                    ),
+                ));
+            }
+        }
+
+        for (key, message) in warnings {
+            if self.subscription_warnings_sent.insert(key) {
+                warn!("{message}");
+                self.push_notification(cx, NotificationLevel::Info, message);
+            }
+        }
+    }
+
     pub(super) fn load_persisted_profiles(&mut self) {
         let profiles = profile_store::load();
         if profiles.is_empty() {
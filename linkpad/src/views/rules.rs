@@ -1,4 +1,6 @@
 use super::*;
+use linkpad_core::{Rule, RuleKind};
+use std::net::IpAddr;
 
 impl App {
     pub(super) fn handle_rules_actions(&mut self, cx: &mut Cx, actions: &Actions) {
@@ -11,57 +13,138 @@ impl App {
             self.reset_rules_pagination();
             self.refresh_ui(cx);
         }
-        if self
+        if let Some(index) = self
             .ui
-            .mp_button(ids!(dashboard.rules_filter_all_btn))
-            .clicked(actions)
+            .drop_down(ids!(dashboard.rules_kind_dropdown))
+            .changed(actions)
         {
-            self.state.rules_filter = RuleFilter::All;
+            self.state.rules_kind_filter = index
+                .checked_sub(1)
+                .and_then(|index| self.rule_kind_options().get(index).copied());
             self.reset_rules_pagination();
             self.refresh_ui(cx);
         }
-        if self
+        if let Some(index) = self
             .ui
-            .mp_button(ids!(dashboard.rules_filter_domain_btn))
-            .clicked(actions)
+            .drop_down(ids!(dashboard.rules_target_dropdown))
+            .changed(actions)
         {
-            self.state.rules_filter = RuleFilter::Domain;
+            self.state.rules_target_filter = index
+                .checked_sub(1)
+                .and_then(|index| self.rule_target_options().get(index).cloned());
             self.reset_rules_pagination();
             self.refresh_ui(cx);
         }
-        if self
+        if let Some(value) = self
             .ui
-            .mp_button(ids!(dashboard.rules_filter_ip_cidr_btn))
-            .clicked(actions)
+            .text_input(ids!(dashboard.rules_match_input))
+            .changed(actions)
         {
-            self.state.rules_filter = RuleFilter::IpCidr;
-            self.reset_rules_pagination();
-            self.refresh_ui(cx);
+            self.state.rules_match_input = value;
+        }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.rules_match_process_input))
+            .changed(actions)
+        {
+            self.state.rules_match_process = value;
         }
         if self
             .ui
-            .mp_button(ids!(dashboard.rules_filter_process_btn))
+            .mp_button(ids!(dashboard.rules_match_btn))
             .clicked(actions)
         {
-            self.state.rules_filter = RuleFilter::ProcessName;
-            self.reset_rules_pagination();
-            self.refresh_ui(cx);
+            self.run_rule_match(cx);
         }
     }
 
-    pub(super) fn filtered_rules(&self) -> Vec<String> {
+    /// Asks the core which rule handles the entered host. Matching may resolve
+    /// the host, so it runs as a core task; see [`App::apply_rule_match`].
+    fn run_rule_match(&mut self, cx: &mut Cx) {
+        let (host, port) = parse_match_target(&self.state.rules_match_input);
+        if host.is_empty() {
+            return;
+        }
+        let process = self.state.rules_match_process.trim();
+        let process = (!process.is_empty()).then(|| process.to_string());
+        self.state.rules_match = None;
+        self.start_core_task(cx, CoreTaskKind::MatchingRule, move |core| {
+            core.match_rule(&host, port, process.as_deref())
+                .map(CoreTaskOutput::RuleMatched)
+        });
+    }
+
+    /// Shows the result of a rule test and scrolls the rule list so the
+    /// winning rule is shown.
+    pub(super) fn apply_rule_match(&mut self, found: Option<RuleMatch>) {
+        if let Some(found) = found.as_ref() {
+            self.state.rules_query.clear();
+            self.state.rules_kind_filter = None;
+            self.state.rules_target_filter = None;
+            self.state.rules_visible_count = self
+                .state
+                .rules_visible_count
+                .max(found.index + 1)
+                .max(Self::RULES_PAGE_SIZE);
+        }
+        self.state.rules_match = Some(found);
+    }
+
+    /// Rule types present in the active profile, in mihomo's documented order.
+    pub(super) fn rule_kind_options(&self) -> Vec<RuleKind> {
+        let mut kinds = self
+            .state
+            .rules
+            .iter()
+            .map(|rule| rule.kind)
+            .collect::<Vec<_>>();
+        kinds.sort();
+        kinds.dedup();
+        kinds
+    }
+
+    /// Policies targeted by the active profile's rules, sorted by name.
+    pub(super) fn rule_target_options(&self) -> Vec<String> {
+        let mut targets = self
+            .state
+            .rules
+            .iter()
+            .map(|rule| rule.target.clone())
+            .collect::<Vec<_>>();
+        targets.sort();
+        targets.dedup();
+        targets
+    }
+
+    /// Rules passing the filters, with their position in the profile.
+    pub(super) fn filtered_rules(&self) -> Vec<(usize, Rule)> {
         let query = self.state.rules_query.trim().to_ascii_lowercase();
         self.state
             .rules
             .iter()
-            .filter(|rule| Self::rule_matches_filter(rule, self.state.rules_filter))
-            .filter(|rule| query.is_empty() || rule.to_ascii_lowercase().contains(&query))
-            .cloned()
+            .enumerate()
+            .filter(|(_, rule)| {
+                self.state
+                    .rules_kind_filter
+                    .is_none_or(|kind| rule.kind == kind)
+            })
+            .filter(|(_, rule)| {
+                self.state
+                    .rules_target_filter
+                    .as_ref()
+                    .is_none_or(|target| &rule.target == target)
+            })
+            .filter(|(_, rule)| {
+                query.is_empty() || rule.to_string().to_ascii_lowercase().contains(&query)
+            })
+            .map(|(index, rule)| (index, rule.clone()))
             .collect()
     }
 
     pub(super) fn should_paginate_rules(&self) -> bool {
-        self.state.rules_filter == RuleFilter::All && self.state.rules_query.trim().is_empty()
+        self.state.rules_kind_filter.is_none()
+            && self.state.rules_target_filter.is_none()
+            && self.state.rules_query.trim().is_empty()
     }
 
     pub(super) fn reset_rules_pagination(&mut self) {
@@ -106,13 +189,25 @@ impl App {
             (self.state.rules_visible_count + Self::RULES_PAGE_SIZE).min(filtered_count);
         self.refresh_ui(cx);
     }
+}
 
-    fn rule_matches_filter(rule: &str, filter: RuleFilter) -> bool {
-        match filter {
-            RuleFilter::All => true,
-            RuleFilter::Domain => rule.starts_with("DOMAIN"),
-            RuleFilter::IpCidr => rule.starts_with("IP-CIDR"),
-            RuleFilter::ProcessName => rule.starts_with("PROCESS-NAME"),
-        }
+/// Splits `host:port`, `[v6]:port` or a bare host or IP address.
+fn parse_match_target(input: &str) -> (String, Option<u16>) {
+    let input = input.trim();
+    if input.parse::<IpAddr>().is_ok() {
+        return (input.to_string(), None);
+    }
+    if let Some(rest) = input.strip_prefix('[')
+        && let Some((host, port)) = rest.split_once(']')
+    {
+        let port = port.strip_prefix(':').and_then(|port| port.parse().ok());
+        return (host.to_string(), port);
+    }
+    match input.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => match port.parse() {
+            Ok(port) => (host.to_string(), Some(port)),
+            Err(_) => (input.to_string(), None),
+        },
+        _ => (input.to_string(), None),
     }
 }
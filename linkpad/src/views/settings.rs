@@ -1,4 +1,5 @@
 use super::*;
+use std::path::Path;
 
 impl App {
     pub(super) fn handle_settings_actions(&mut self, cx: &mut Cx, actions: &Actions) {
@@ -9,6 +10,18 @@ impl App {
         {
             self.state.clash_port_input = value;
         }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.clash_channel_pinned_input))
+            .changed(actions)
+        {
+            self.state.kernel_pinned_input = value;
+            if matches!(self.state.kernel_channel, KernelChannel::Pinned(_)) {
+                self.set_kernel_channel(KernelChannel::Pinned(
+                    self.state.kernel_pinned_input.trim().to_string(),
+                ));
+            }
+        }
         if let Some(on) = self
             .ui
             .mp_switch(ids!(dashboard.system_proxy_switch))
@@ -16,6 +29,78 @@ impl App {
         {
             self.set_system_proxy_enabled(cx, on);
         }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.proxy_bypass_input))
+            .changed(actions)
+        {
+            self.state.proxy_bypass_input = value;
+        }
+        for (index, port_input) in [
+            ids!(dashboard.proxy_http_port_input),
+            ids!(dashboard.proxy_https_port_input),
+            ids!(dashboard.proxy_socks_port_input),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            if let Some(value) = self.ui.text_input(port_input).changed(actions) {
+                self.state.proxy_port_inputs[index] = value;
+            }
+        }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.proxy_pac_port_input))
+            .changed(actions)
+        {
+            self.state.proxy_pac_port_input = value;
+        }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.proxy_pac_direct_input))
+            .changed(actions)
+        {
+            self.state.proxy_pac_direct_input = value;
+        }
+        if let Some(index) = self
+            .ui
+            .drop_down(ids!(dashboard.proxy_mode_dropdown))
+            .changed(actions)
+        {
+            self.state.proxy_mode_input = match index {
+                1 => SystemProxyMode::Pac,
+                _ => SystemProxyMode::Global,
+            };
+            self.save_system_proxy_settings(cx);
+        }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.proxy_settings_save_btn))
+            .clicked(actions)
+        {
+            self.save_system_proxy_settings(cx);
+        }
+        if let Some(on) = self
+            .ui
+            .mp_switch(ids!(dashboard.proxy_restore_on_drift_switch))
+            .changed(actions)
+        {
+            self.set_proxy_restore_on_drift(cx, on);
+        }
+        if let Some(on) = self
+            .ui
+            .mp_switch(ids!(dashboard.proxy_shell_env_switch))
+            .changed(actions)
+        {
+            self.set_proxy_shell_env(cx, on);
+        }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.proxy_copy_command_btn))
+            .clicked(actions)
+        {
+            self.copy_proxy_command(cx);
+        }
         if let Some(on) = self
             .ui
             .mp_switch(ids!(dashboard.close_to_tray_switch))
@@ -93,6 +178,27 @@ impl App {
             self.persist_settings();
             self.refresh_ui(cx);
         }
+        if let Some(index) = self
+            .ui
+            .drop_down(ids!(dashboard.clash_kernel_dropdown))
+            .changed(actions)
+        {
+            let kind = KernelKind::ALL.get(index).copied().unwrap_or_default();
+            self.set_kernel_kind(cx, kind);
+        }
+        if let Some(index) = self
+            .ui
+            .drop_down(ids!(dashboard.clash_channel_dropdown))
+            .changed(actions)
+        {
+            let channel = match index {
+                1 => KernelChannel::Alpha,
+                2 => KernelChannel::Pinned(self.state.kernel_pinned_input.trim().to_string()),
+                _ => KernelChannel::Stable,
+            };
+            self.set_kernel_channel(channel);
+            self.refresh_ui(cx);
+        }
         if self
             .ui
             .mp_button(ids!(dashboard.clash_core_upgrade_btn))
@@ -100,6 +206,52 @@ impl App {
         {
             self.start_core_upgrade(cx);
         }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.clash_core_rollback_btn))
+            .clicked(actions)
+        {
+            self.start_core_rollback(cx);
+        }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.clash_core_file_input))
+            .changed(actions)
+        {
+            self.state.kernel_file_input = value;
+        }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.clash_core_install_file_btn))
+            .clicked(actions)
+        {
+            self.start_core_install_from_file(cx);
+        }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.clash_mirrors_input))
+            .changed(actions)
+        {
+            self.state.kernel_mirrors_input = value;
+        }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.clash_mirrors_save_btn))
+            .clicked(actions)
+        {
+            let mirrors = parse_mirrors(&self.state.kernel_mirrors_input);
+            self.state.kernel_mirrors_input = mirrors.join(", ");
+            self.state.kernel_mirrors = mirrors.clone();
+            self.core.set_kernel_mirrors(mirrors);
+            self.persist_settings();
+            let strings = i18n::strings(self.state.language);
+            self.push_notification(
+                cx,
+                NotificationLevel::Success,
+                strings.clash_mirrors_save_success.to_string(),
+            );
+            self.refresh_ui(cx);
+        }
         if self
             .ui
             .mp_button(ids!(dashboard.clash_core_restart_btn))
@@ -107,6 +259,60 @@ impl App {
         {
             self.start_core_restart(cx);
         }
+        for (index, url_input) in [
+            ids!(dashboard.geodata_geoip_url_input),
+            ids!(dashboard.geodata_geosite_url_input),
+            ids!(dashboard.geodata_asn_url_input),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            if let Some(value) = self.ui.text_input(url_input).changed(actions) {
+                self.state.geodata_url_inputs[index] = value;
+            }
+        }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.geodata_interval_input))
+            .changed(actions)
+        {
+            self.state.geodata_interval_input = value;
+        }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.geodata_save_btn))
+            .clicked(actions)
+        {
+            self.save_geodata_settings(cx);
+        }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.geodata_update_btn))
+            .clicked(actions)
+        {
+            self.start_geodata_update(cx);
+        }
+        if let Some(index) = self
+            .ui
+            .drop_down(ids!(dashboard.geodata_import_dropdown))
+            .changed(actions)
+        {
+            self.state.geodata_import_index = index.min(GeoDataKind::ALL.len() - 1);
+        }
+        if let Some(value) = self
+            .ui
+            .text_input(ids!(dashboard.geodata_file_input))
+            .changed(actions)
+        {
+            self.state.geodata_file_input = value;
+        }
+        if self
+            .ui
+            .mp_button(ids!(dashboard.geodata_import_btn))
+            .clicked(actions)
+        {
+            self.start_geodata_import(cx);
+        }
         if self
             .ui
             .mp_button(ids!(dashboard.clash_port_save_btn))
@@ -116,78 +322,447 @@ impl App {
             let trimmed = self.state.clash_port_input.trim();
             let parsed = trimmed.parse::<u16>().ok().filter(|port| *port > 0);
             if let Some(port) = parsed {
-                let mut config = self.core.config();
-                config.mixed_port = port;
-                match self.core.update_config(config) {
-                    Ok(()) => {
-                        self.state.clash_mixed_port = port;
-                        self.state.clash_port_input = port.to_string();
-                        self.persist_settings();
-                        self.push_notification(
-                            cx,
-                            NotificationLevel::Success,
-                            strings.clash_port_update_success.to_string(),
-                        );
-                    }
-                    Err(error) => {
-                        self.push_notification(
-                            cx,
-                            NotificationLevel::Error,
-                            format!("{}: {error}", strings.clash_port_update_failed_prefix),
-                        );
-                    }
-                }
+                // A running kernel validates and restarts on the new port,
+                // which can take a while.
+                self.start_core_task(cx, CoreTaskKind::SavingPort, move |core| {
+                    let mut config = core.config();
+                    config.mixed_port = port;
+                    core.update_config(config)
+                        .map(|()| CoreTaskOutput::PortSaved(port))
+                });
             } else {
                 self.push_notification(
                     cx,
                     NotificationLevel::Error,
                     strings.clash_port_update_invalid.to_string(),
                 );
+                self.refresh_ui(cx);
             }
-            self.refresh_ui(cx);
         }
     }
 
-    pub(super) fn set_system_proxy_enabled(&mut self, cx: &mut Cx, on: bool) {
+    /// Takes the mixed port a [`CoreTaskKind::SavingPort`] task applied.
+    pub(super) fn apply_saved_port(&mut self, cx: &mut Cx, port: u16) {
         let strings = i18n::strings(self.state.language);
-        info!("system proxy toggle requested: on={on}");
-        let result = if on {
-            self.core.enable_system_proxy()
+        self.state.clash_mixed_port = port;
+        self.state.clash_port_input = port.to_string();
+        self.persist_settings();
+        self.push_notification(
+            cx,
+            NotificationLevel::Success,
+            strings.clash_port_update_success.to_string(),
+        );
+    }
+
+    /// Switches the kernel the core runs; a running kernel is replaced right
+    /// away, and the switch is refused when the new kernel rejects the config.
+    fn set_kernel_kind(&mut self, cx: &mut Cx, kind: KernelKind) {
+        if kind == self.state.kernel_kind {
+            return;
+        }
+        self.start_core_task(cx, CoreTaskKind::SwitchingKernel, move |core| {
+            let mut config = core.config();
+            config.kernel = kind;
+            core.update_config(config)
+                .map(|()| CoreTaskOutput::KernelSwitched(kind))
+        });
+    }
+
+    /// Takes the kernel a [`CoreTaskKind::SwitchingKernel`] task switched to.
+    pub(super) fn apply_kernel_switch(&mut self, cx: &mut Cx, kind: KernelKind) {
+        let strings = i18n::strings(self.state.language);
+        info!("kernel switched to {}", kind.as_str());
+        self.state.kernel_kind = kind;
+        self.sync_from_core();
+        self.persist_settings();
+        self.push_notification(
+            cx,
+            NotificationLevel::Success,
+            strings.clash_kernel_update_success.to_string(),
+        );
+    }
+
+    /// Installs a kernel archive downloaded by hand, for machines that cannot
+    /// reach GitHub or a mirror.
+    fn start_core_install_from_file(&mut self, cx: &mut Cx) {
+        let path = self.state.kernel_file_input.trim().to_string();
+        if path.is_empty() {
+            let strings = i18n::strings(self.state.language);
+            self.push_notification(
+                cx,
+                NotificationLevel::Error,
+                strings.clash_core_install_file_empty.to_string(),
+            );
+            return;
+        }
+        info!("core install from file requested: {path}");
+        self.start_core_task(cx, CoreTaskKind::Installing, move |core| {
+            core.install_kernel_from_file(Path::new(&path))
+                .map(CoreTaskOutput::Upgraded)
+        });
+    }
+
+    /// Applies the picked mode and the typed bypass list and ports; an enabled
+    /// system proxy and a running kernel pick them up right away.
+    fn save_system_proxy_settings(&mut self, cx: &mut Cx) {
+        let strings = i18n::strings(self.state.language);
+        let mut ports = [None; 3];
+        for (port, input) in ports.iter_mut().zip(&self.state.proxy_port_inputs) {
+            let input = input.trim();
+            if input.is_empty() {
+                continue;
+            }
+            match input.parse::<u16>() {
+                Ok(value) if value > 0 => *port = Some(value),
+                _ => {
+                    self.push_notification(
+                        cx,
+                        NotificationLevel::Error,
+                        strings.proxy_settings_invalid_port.to_string(),
+                    );
+                    return;
+                }
+            }
+        }
+        let [http_port, https_port, socks_port] = ports;
+        let pac_port = self.state.proxy_pac_port_input.trim();
+        let pac_port = if pac_port.is_empty() {
+            SystemProxySettings::default().pac_port
         } else {
-            self.core.disable_system_proxy()
+            match pac_port.parse::<u16>() {
+                Ok(value) if value > 0 => value,
+                _ => {
+                    self.push_notification(
+                        cx,
+                        NotificationLevel::Error,
+                        strings.proxy_settings_invalid_port.to_string(),
+                    );
+                    return;
+                }
+            }
         };
-
-        match result {
+        let settings = SystemProxySettings {
+            mode: self.state.proxy_mode_input,
+            bypass: parse_bypass(&self.state.proxy_bypass_input),
+            http_port,
+            https_port,
+            socks_port,
+            pac_port,
+            pac_direct: parse_bypass(&self.state.proxy_pac_direct_input),
+            restore_on_drift: self.state.system_proxy_settings.restore_on_drift,
+            shell_env: self.state.system_proxy_settings.shell_env,
+        };
+        let mut config = self.core.config();
+        config.system_proxy = settings.clone();
+        match self.core.update_config(config) {
             Ok(()) => {
-                self.state.system_proxy_enabled = on;
-                if on {
-                    self.apply_saved_proxy_group_selections_to_core();
-                    self.sync_from_core();
-                    self.snapshot_proxy_group_selections();
+                info!("system proxy settings saved: {settings:?}");
+                self.state.proxy_bypass_input = settings.bypass.join(", ");
+                self.state.proxy_pac_port_input = settings.pac_port.to_string();
+                self.state.proxy_pac_direct_input = settings.pac_direct.join(", ");
+                self.state.system_proxy_settings = settings;
+                self.persist_settings();
+                self.push_notification(
+                    cx,
+                    NotificationLevel::Success,
+                    strings.proxy_settings_save_success.to_string(),
+                );
+            }
+            Err(error) => {
+                error!("system proxy settings rejected: {error}");
+                self.state.proxy_mode_input = self.state.system_proxy_settings.mode;
+                self.push_notification(
+                    cx,
+                    NotificationLevel::Error,
+                    format!("{}: {error}", strings.proxy_settings_save_failed_prefix),
+                );
+            }
+        }
+        self.refresh_ui(cx);
+    }
+
+    /// Applies the typed geodata URLs and update interval; the URLs reach the
+    /// kernel's `geox-url` on its next restart.
+    fn save_geodata_settings(&mut self, cx: &mut Cx) {
+        let strings = i18n::strings(self.state.language);
+        let interval = self.state.geodata_interval_input.trim();
+        let update_interval_hours = if interval.is_empty() {
+            None
+        } else {
+            match interval.parse::<u32>() {
+                Ok(hours) if hours > 0 => Some(hours),
+                _ => {
+                    self.push_notification(
+                        cx,
+                        NotificationLevel::Error,
+                        strings.geodata_interval_invalid.to_string(),
+                    );
+                    return;
                 }
+            }
+        };
+        let [geoip_url, geosite_url, asn_url] = self
+            .state
+            .geodata_url_inputs
+            .clone()
+            .map(|url| url.trim().to_string());
+        let settings = GeoDataSettings {
+            geoip_url,
+            geosite_url,
+            asn_url,
+            update_interval_hours,
+        };
+        self.core.set_geodata_settings(settings.clone());
+        self.state.geodata_settings = settings;
+        self.persist_settings();
+        self.push_notification(
+            cx,
+            NotificationLevel::Success,
+            strings.geodata_settings_save_success.to_string(),
+        );
+        self.refresh_ui(cx);
+    }
+
+    /// Downloads every geodata file from its configured URL.
+    fn start_geodata_update(&mut self, cx: &mut Cx) {
+        info!("geodata update requested");
+        self.start_core_task(cx, CoreTaskKind::UpdatingGeoData, |core| {
+            GeoDataKind::ALL
+                .into_iter()
+                .map(|kind| core.update_geodata(kind))
+                .collect::<CoreResult<Vec<_>>>()
+                .map(CoreTaskOutput::GeoDataUpdated)
+        });
+    }
+
+    /// Replaces the geodata file picked in the dropdown with one downloaded by
+    /// hand.
+    fn start_geodata_import(&mut self, cx: &mut Cx) {
+        let path = self.state.geodata_file_input.trim().to_string();
+        if path.is_empty() {
+            let strings = i18n::strings(self.state.language);
+            self.push_notification(
+                cx,
+                NotificationLevel::Error,
+                strings.geodata_import_file_empty.to_string(),
+            );
+            return;
+        }
+        let kind = GeoDataKind::ALL[self.state.geodata_import_index];
+        info!("geodata import requested: {} from {path}", kind.file_name());
+        self.start_core_task(cx, CoreTaskKind::ImportingGeoData, move |core| {
+            core.import_geodata_file(kind, Path::new(&path))
+                .map(|status| CoreTaskOutput::GeoDataUpdated(vec![status]))
+        });
+    }
+
+    pub(super) fn handle_geodata_update_failed(
+        &mut self,
+        cx: &mut Cx,
+        kind: GeoDataKind,
+        error: &str,
+    ) {
+        let strings = i18n::strings(self.state.language);
+        self.push_notification(
+            cx,
+            NotificationLevel::Error,
+            format!(
+                "{}: {} ({error})",
+                strings.geodata_update_failed_prefix,
+                kind.file_name()
+            ),
+        );
+    }
+
+    /// One line per geodata file: version, size and age, or that it is missing.
+    pub(super) fn format_geodata_status(
+        status: Option<&GeoDataStatus>,
+        strings: &i18n::Strings,
+    ) -> String {
+        let Some(status) = status.filter(|status| status.present) else {
+            return strings.geodata_missing.to_string();
+        };
+        let mut parts = Vec::new();
+        if let Some(version) = &status.version {
+            parts.push(version.clone());
+        }
+        parts.push(Self::format_bytes(status.size));
+        if let Some(age) = status.age_secs {
+            let age = match age {
+                0..3600 => format!("{}m", age / 60),
+                3600..86400 => format!("{}h", age / 3600),
+                _ => format!("{}d", age / 86400),
+            };
+            parts.push(format!("{age} {}", strings.geodata_age_suffix));
+        }
+        parts.join(" · ")
+    }
+
+    /// Picks the releases UPGRADE installs; takes effect on the next upgrade.
+    fn set_kernel_channel(&mut self, channel: KernelChannel) {
+        self.core.set_kernel_channel(channel.clone());
+        self.state.kernel_channel = channel;
+        self.persist_settings();
+    }
+
+    /// Enabling may start the kernel and wait for it to become ready, so the
+    /// toggle runs as a core task; see [`App::apply_system_proxy_toggle`].
+    pub(super) fn set_system_proxy_enabled(&mut self, cx: &mut Cx, on: bool) {
+        info!("system proxy toggle requested: on={on}");
+        if on {
+            self.start_core_task(cx, CoreTaskKind::EnablingSystemProxy, |core| {
+                core.enable_system_proxy()
+                    .map(|()| CoreTaskOutput::SystemProxyToggled(true))
+            });
+        } else {
+            self.start_core_task(cx, CoreTaskKind::DisablingSystemProxy, |core| {
+                core.disable_system_proxy()
+                    .map(|()| CoreTaskOutput::SystemProxyToggled(false))
+            });
+        }
+    }
+
+    pub(super) fn apply_system_proxy_toggle(&mut self, cx: &mut Cx, on: bool) {
+        let strings = i18n::strings(self.state.language);
+        self.state.system_proxy_enabled = on;
+        if on {
+            self.apply_saved_proxy_group_selections_to_core();
+            self.sync_from_core();
+            self.snapshot_proxy_group_selections();
+        }
+        let message = if on {
+            strings.system_proxy_enable_success
+        } else {
+            strings.system_proxy_disable_success
+        };
+        info!("system proxy toggle succeeded: on={on}");
+        self.push_notification(cx, NotificationLevel::Success, message.to_string());
+        self.persist_settings();
+    }
+
+    pub(super) fn set_proxy_restore_on_drift(&mut self, cx: &mut Cx, on: bool) {
+        self.core.set_proxy_restore_on_drift(on);
+        self.state.system_proxy_settings.restore_on_drift = on;
+        self.persist_settings();
+        self.refresh_ui(cx);
+    }
+
+    /// Hooks the env files into the shell rc files and lets the core keep
+    /// them in step with the system proxy, or undoes both.
+    pub(super) fn set_proxy_shell_env(&mut self, cx: &mut Cx, on: bool) {
+        let strings = i18n::strings(self.state.language);
+        let hooks = if on {
+            self.core.install_shell_hooks()
+        } else {
+            self.core.uninstall_shell_hooks()
+        };
+        match hooks {
+            Ok(_) => {
+                self.core.set_proxy_shell_env(on);
+                self.state.system_proxy_settings.shell_env = on;
+                self.persist_settings();
                 let message = if on {
-                    strings.system_proxy_enable_success
+                    strings.shell_env_enabled
                 } else {
-                    strings.system_proxy_disable_success
+                    strings.shell_env_disabled
                 };
-                info!("system proxy toggle succeeded: on={on}");
                 self.push_notification(cx, NotificationLevel::Success, message.to_string());
             }
             Err(error) => {
-                self.state.system_proxy_enabled = self.core.is_system_proxy_enabled();
-                let message = if on {
-                    format!("{}: {error}", strings.system_proxy_enable_failed_prefix)
-                } else {
-                    format!("{}: {error}", strings.system_proxy_disable_failed_prefix)
-                };
-                error!("system proxy toggle failed: {error}");
-                self.push_notification(cx, NotificationLevel::Error, message);
+                error!("terminal proxy toggle failed: {error}");
+                self.push_notification(
+                    cx,
+                    NotificationLevel::Error,
+                    format!("{}: {error}", strings.shell_env_failed_prefix),
+                );
             }
         }
-        self.persist_settings();
         self.refresh_ui(cx);
     }
 
+    /// Copies the `export` lines for the current ports, in the syntax of the
+    /// user's shell.
+    pub(super) fn copy_proxy_command(&mut self, cx: &mut Cx) {
+        let strings = i18n::strings(self.state.language);
+        let commands = self.core.shell_proxy_commands(ShellSyntax::current());
+        cx.copy_to_clipboard(&commands);
+        self.push_notification(
+            cx,
+            NotificationLevel::Success,
+            strings.proxy_command_copied.to_string(),
+        );
+    }
+
+    /// Puts back proxy settings an earlier run left behind when it ended
+    /// without disabling the system proxy.
+    pub(super) fn recover_system_proxy(&mut self, cx: &mut Cx) {
+        match self.core.recover_system_proxy() {
+            Ok(true) => {
+                let strings = i18n::strings(self.state.language);
+                self.push_notification(
+                    cx,
+                    NotificationLevel::Info,
+                    strings.system_proxy_recovered.to_string(),
+                );
+            }
+            Ok(false) => {}
+            Err(error) => error!("system proxy recovery failed: {error}"),
+        }
+    }
+
+    pub(super) fn handle_system_proxy_drifted(&mut self, cx: &mut Cx, reapplied: bool) {
+        let strings = i18n::strings(self.state.language);
+        warn!("system proxy settings drifted: reapplied={reapplied}");
+        self.state.system_proxy_enabled = self.core.is_system_proxy_enabled();
+        if reapplied {
+            let message = strings.system_proxy_drift_reapplied.to_string();
+            self.push_notification(cx, NotificationLevel::Info, message);
+        } else {
+            let message = strings.system_proxy_drift_reported.to_string();
+            self.push_notification(cx, NotificationLevel::Error, message);
+        }
+    }
+
+    /// Reports a kernel crash with the last line it logged; the core's
+    /// supervisor restarts it on its own.
+    pub(super) fn handle_kernel_exited(&mut self, cx: &mut Cx, status: &str, log_tail: &[String]) {
+        let strings = i18n::strings(self.state.language);
+        let mut message = format!("{}: {status}", strings.kernel_exited_prefix);
+        if let Some(line) = log_tail.iter().rev().find(|line| !line.trim().is_empty()) {
+            message.push_str(&format!(" | {}", line.trim()));
+        }
+        error!("kernel exited unexpectedly: {status}");
+        self.push_notification(cx, NotificationLevel::Error, message);
+    }
+
+    pub(super) fn handle_kernel_restarted(&mut self, cx: &mut Cx, attempts: u32) {
+        let strings = i18n::strings(self.state.language);
+        info!("kernel restarted by supervisor: attempts={attempts}");
+        self.sync_from_core();
+        self.push_notification(
+            cx,
+            NotificationLevel::Success,
+            strings.kernel_restarted.to_string(),
+        );
+    }
+
+    pub(super) fn handle_kernel_recovery_failed(
+        &mut self,
+        cx: &mut Cx,
+        error: &str,
+        system_proxy_disabled: bool,
+    ) {
+        let strings = i18n::strings(self.state.language);
+        error!("kernel recovery failed: {error}");
+        self.state.system_proxy_enabled = self.core.is_system_proxy_enabled();
+        self.persist_settings();
+        let mut message = format!("{}: {error}", strings.kernel_recovery_failed_prefix);
+        if system_proxy_disabled {
+            message.push_str(&format!(" | {}", strings.kernel_recovery_proxy_disabled));
+        }
+        self.push_notification(cx, NotificationLevel::Error, message);
+    }
+
     pub(super) fn load_persisted_settings(&mut self) {
         if let Some(loaded) = settings_store::load() {
             self.state.language = loaded.language;
@@ -196,8 +771,17 @@ impl App {
             self.state.close_to_tray_enabled = loaded.close_to_tray_enabled;
             self.state.auto_launch_enabled = loaded.auto_launch_enabled;
             self.state.silent_start_enabled = loaded.silent_start_enabled;
+            self.state.system_proxy_settings = loaded.system_proxy;
             self.state.clash_mixed_port = loaded.clash_mixed_port;
             self.state.clash_port_input = loaded.clash_mixed_port.to_string();
+            self.state.kernel_kind = loaded.kernel_kind;
+            if let KernelChannel::Pinned(tag) = &loaded.kernel_channel {
+                self.state.kernel_pinned_input = tag.clone();
+            }
+            self.state.kernel_channel = loaded.kernel_channel;
+            self.state.kernel_mirrors_input = loaded.kernel_mirrors.join(", ");
+            self.state.kernel_mirrors = loaded.kernel_mirrors;
+            self.state.geodata_settings = loaded.geodata;
             self.saved_proxy_group_selections = loaded.proxy_group_selections;
             info!("loaded persisted settings");
         } else {
@@ -222,7 +806,12 @@ impl App {
             self.state.close_to_tray_enabled,
             self.state.auto_launch_enabled,
             self.state.silent_start_enabled,
+            &self.state.system_proxy_settings,
             self.state.clash_mixed_port,
+            self.state.kernel_kind,
+            &self.state.kernel_channel,
+            &self.state.kernel_mirrors,
+            &self.state.geodata_settings,
             &self.saved_proxy_group_selections,
         );
     }
@@ -230,6 +819,31 @@ impl App {
     pub(super) fn apply_clash_config_to_core(&mut self) {
         let mut config = self.core.config();
         config.mixed_port = self.state.clash_mixed_port;
+        config.kernel = self.state.kernel_kind;
+        config.kernel_channel = self.state.kernel_channel.clone();
+        config.kernel_mirrors = self.state.kernel_mirrors.clone();
+        config.geodata = self.state.geodata_settings.clone();
+        config.system_proxy = self.state.system_proxy_settings.clone();
         let _ = self.core.update_config(config);
     }
 }
+
+/// Bypass entries typed as a comma- or space-separated list.
+fn parse_bypass(input: &str) -> Vec<String> {
+    input
+        .split(|ch: char| ch == ',' || ch == ';' || ch.is_whitespace())
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Mirror base URLs typed as a comma- or space-separated list.
+fn parse_mirrors(input: &str) -> Vec<String> {
+    input
+        .split(|ch: char| ch == ',' || ch.is_whitespace())
+        .map(|mirror| mirror.trim().trim_end_matches('/'))
+        .filter(|mirror| !mirror.is_empty())
+        .map(str::to_string)
+        .collect()
+}